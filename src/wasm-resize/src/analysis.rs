@@ -0,0 +1,871 @@
+//! Image analysis alongside the resize pipeline
+//! Cheap measurements computed from resized output, such as edge maps for UI overlays
+//! and focus stacking, and quality metrics for comparing results against a reference
+
+#[cfg(not(any(target_arch = "wasm32", test)))]
+compile_error!("This module only supports wasm32 target");
+
+use std::f32::consts::PI;
+
+use crate::formats::median_cut;
+use crate::gamma_simd::{
+    gamma_area_core, init_gamma_luts, linear_to_srgb_fast, resize_rgba_gamma_bilinear,
+    srgb_to_linear_lut,
+};
+use crate::{
+    intermediate_pass, lanczos_kernel, mark_rows_completed, resize_slices, resolve_algorithm,
+    set_last_error, validate_buffer, validate_out_ptr, validate_params, LANCZOS_A,
+    RESIZE_ALGO_BILINEAR, RESIZE_ALGO_LANCZOS, RESIZE_ALGO_NEAREST, RESIZE_ERR_INVALID_PARAM,
+    RESIZE_OK,
+};
+
+// Floats written by resize_rgba_channel_stats: (mean, stddev) for R, G, B, A
+const CHANNEL_STATS_LEN: usize = 8;
+
+// Longest side of the nearest-sampled image resize_rgba_with_palette quantizes
+const PALETTE_SAMPLE_SIZE: u32 = 64;
+
+// PSNR reported for identical buffers, where the true value is infinite
+const PSNR_IDENTICAL_DB: f32 = 99.0;
+
+// Half-width of the window over which resize_rgba_focus_stack sums edge strength
+const FOCUS_WINDOW_RADIUS: usize = 2;
+
+// 64-bit FNV-1a parameters used by resize_rgba_hashed
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+// Edge of the grayscale thumbnail transformed by phash_rgba
+const PHASH_SIZE: usize = 32;
+// Edge of the low-frequency DCT block reduced to the 64-bit hash
+const PHASH_LOW_FREQ: usize = 8;
+
+/// Integer luma of an sRGB pixel (Rec. 709 weights in Q8, alpha ignored)
+#[inline(always)]
+pub(crate) fn luma(p: &[u8]) -> i32 {
+    (54 * p[0] as i32 + 183 * p[1] as i32 + 19 * p[2] as i32 + 128) >> 8
+}
+
+/// Sobel gradient magnitude of the luma of an RGBA image, one byte per pixel
+/// Borders replicate the edge pixels; the magnitude is divided by 4 so a full
+/// black-to-white step maps to 255
+fn sobel_edges(rgba: &[u8], w: u32, h: u32, edges: &mut [u8]) {
+    let w = w as usize;
+    let h = h as usize;
+    let lum: Vec<i32> = rgba.chunks_exact(4).map(luma).collect();
+    let at = |x: usize, y: usize| lum[y * w + x];
+
+    for y in 0..h {
+        let (up, down) = (y.saturating_sub(1), (y + 1).min(h - 1));
+        for x in 0..w {
+            let (left, right) = (x.saturating_sub(1), (x + 1).min(w - 1));
+            let gx = (at(right, up) + 2 * at(right, y) + at(right, down))
+                - (at(left, up) + 2 * at(left, y) + at(left, down));
+            let gy = (at(left, down) + 2 * at(x, down) + at(right, down))
+                - (at(left, up) + 2 * at(x, up) + at(right, up));
+            let magnitude = ((gx * gx + gy * gy) as f32).sqrt() / 4.0;
+            edges[y * w + x] = (magnitude + 0.5).min(255.0) as u8;
+        }
+    }
+}
+
+/// Resize and compute an edge map of the result in one call
+/// `dst_ptr` receives the regular resize with `algorithm`; `edge_ptr` receives the
+/// Sobel gradient magnitude of the resized image's luma (dst_w * dst_h bytes, one per
+/// pixel), e.g. for focus peaking overlays on a preview
+/// Returns error code: 0 = success, non-zero = error
+///
+/// # Safety
+/// src_ptr/dst_ptr must point to valid RGBA buffers of the given dimensions and
+/// edge_ptr to dst_w * dst_h writable bytes; no two buffers may overlap
+#[no_mangle]
+pub unsafe extern "C" fn resize_rgba_with_edges(
+    src_ptr: *const u8,
+    src_w: u32,
+    src_h: u32,
+    dst_ptr: *mut u8,
+    edge_ptr: *mut u8,
+    dst_w: u32,
+    dst_h: u32,
+    algorithm: u32,
+) -> i32 {
+    let (src_size, dst_size) = match validate_params(src_ptr, src_w, src_h, dst_ptr, dst_w, dst_h) {
+        Ok(sizes) => sizes,
+        Err(code) => return code,
+    };
+
+    let edge_size = dst_size / 4;
+    let buffers = [(src_ptr, src_size), (dst_ptr as *const u8, dst_size)];
+    if let Err(code) = validate_out_ptr(edge_ptr, edge_size, &buffers) {
+        return code;
+    }
+
+    let src = std::slice::from_raw_parts(src_ptr, src_size);
+    let dst = std::slice::from_raw_parts_mut(dst_ptr, dst_size);
+    let code = resize_slices(src, src_w, src_h, dst, dst_w, dst_h, algorithm);
+    if code != RESIZE_OK {
+        return code;
+    }
+
+    let edges = std::slice::from_raw_parts_mut(edge_ptr, edge_size);
+    sobel_edges(dst, dst_w, dst_h, edges);
+
+    RESIZE_OK
+}
+
+/// Sum of an edge map over the (2r + 1)^2 window around every pixel, clipped at the borders
+fn window_sums(edges: &[u8], w: usize, h: usize, r: usize) -> Vec<u32> {
+    let mut rows = vec![0u32; w * h];
+    for y in 0..h {
+        let line = &edges[y * w..(y + 1) * w];
+        for x in 0..w {
+            let (x0, x1) = (x.saturating_sub(r), (x + r).min(w - 1));
+            rows[y * w + x] = line[x0..=x1].iter().map(|&e| e as u32).sum();
+        }
+    }
+
+    let mut sums = vec![0u32; w * h];
+    for y in 0..h {
+        let (y0, y1) = (y.saturating_sub(r), (y + r).min(h - 1));
+        for x in 0..w {
+            sums[y * w + x] = (y0..=y1).map(|sy| rows[sy * w + x]).sum();
+        }
+    }
+    sums
+}
+
+/// Focus-stack two shots of the same scene at the destination resolution
+/// Both sources are resized with `algorithm`; each destination pixel is then taken from
+/// whichever result has the larger Sobel gradient energy in the surrounding 5x5 window,
+/// so in-focus detail wins over its blurred counterpart. Ties keep `a`
+/// Returns error code: 0 = success, non-zero = error
+///
+/// # Safety
+/// a_ptr/b_ptr must point to valid RGBA buffers of src_w x src_h pixels and dst_ptr to
+/// a valid RGBA buffer of the destination dimensions overlapping neither
+#[no_mangle]
+pub unsafe extern "C" fn resize_rgba_focus_stack(
+    a_ptr: *const u8,
+    b_ptr: *const u8,
+    src_w: u32,
+    src_h: u32,
+    dst_ptr: *mut u8,
+    dst_w: u32,
+    dst_h: u32,
+    algorithm: u32,
+) -> i32 {
+    let (src_size, dst_size) = match validate_params(a_ptr, src_w, src_h, dst_ptr, dst_w, dst_h) {
+        Ok(sizes) => sizes,
+        Err(code) => return code,
+    };
+    if let Err(code) = validate_buffer(b_ptr, src_w, src_h) {
+        return code;
+    }
+
+    if let Err(code) = validate_out_ptr(dst_ptr, dst_size, &[(b_ptr, src_size)]) {
+        return code;
+    }
+
+    let a = std::slice::from_raw_parts(a_ptr, src_size);
+    let b = std::slice::from_raw_parts(b_ptr, src_size);
+    let dst = std::slice::from_raw_parts_mut(dst_ptr, dst_size);
+
+    let code = intermediate_pass(|| resize_slices(a, src_w, src_h, dst, dst_w, dst_h, algorithm));
+    if code != RESIZE_OK {
+        return code;
+    }
+    let mut resized_b = vec![0u8; dst_size];
+    let code = intermediate_pass(|| resize_slices(b, src_w, src_h, &mut resized_b, dst_w, dst_h, algorithm));
+    if code != RESIZE_OK {
+        return code;
+    }
+
+    let (w, h) = (dst_w as usize, dst_h as usize);
+    let mut edges_a = vec![0u8; w * h];
+    let mut edges_b = vec![0u8; w * h];
+    sobel_edges(dst, dst_w, dst_h, &mut edges_a);
+    sobel_edges(&resized_b, dst_w, dst_h, &mut edges_b);
+    let sharp_a = window_sums(&edges_a, w, h, FOCUS_WINDOW_RADIUS);
+    let sharp_b = window_sums(&edges_b, w, h, FOCUS_WINDOW_RADIUS);
+
+    for (i, (sa, sb)) in sharp_a.iter().zip(&sharp_b).enumerate() {
+        if sb > sa {
+            dst[i * 4..i * 4 + 4].copy_from_slice(&resized_b[i * 4..i * 4 + 4]);
+        }
+    }
+    mark_rows_completed(dst_h);
+
+    RESIZE_OK
+}
+
+/// Fraction of filter weight that falls on real source pixels, per destination coordinate
+/// Mirrors the full tap windows of the resampling cores: bilinear reads floor(c) and
+/// floor(c) + 1, Lanczos floor(c) - 2 ..= floor(c) + 3 with c = (d + 0.5) * scale - 0.5.
+/// Bilinear replaces taps outside the source with the edge pixel; Lanczos
+/// (precompute_lanczos_weights) drops them and renormalizes the remaining weights. Either
+/// way the output rests only on the in-range taps, and the result is their share of the
+/// window's absolute weight. Nearest always samples a real pixel
+fn axis_coverage(src_size: u32, dst_size: u32, algorithm: u32) -> Vec<f32> {
+    let scale = src_size as f32 / dst_size as f32;
+    let a = LANCZOS_A as i32;
+    (0..dst_size)
+        .map(|d| {
+            let c = (d as f32 + 0.5) * scale - 0.5;
+            let base = c.floor() as i32;
+            let (first, last) = match algorithm {
+                RESIZE_ALGO_NEAREST => return 1.0,
+                RESIZE_ALGO_BILINEAR => (base, base + 1),
+                _ => (base - a + 1, base + a),
+            };
+            let weight = |i: i32| {
+                if algorithm == RESIZE_ALGO_BILINEAR {
+                    let f = c - base as f32;
+                    if i == base { 1.0 - f } else { f }
+                } else {
+                    lanczos_kernel((i as f32 - c) / scale, LANCZOS_A).abs()
+                }
+            };
+            let total: f32 = (first..=last).map(weight).sum();
+            let inside: f32 = (first.max(0)..=last.min(src_size as i32 - 1)).map(weight).sum();
+            if total > 0.0 { inside / total } else { 1.0 }
+        })
+        .collect()
+}
+
+/// Resize and write a per-pixel coverage map alongside the result
+/// `dst_ptr` receives the regular resize with `algorithm`; `coverage_ptr` receives one
+/// byte per destination pixel, 255 when every filter tap of the pixel read a real source
+/// pixel and lower in proportion to the filter weight that fell outside the source
+/// (product of both axes). Bilinear fills that weight by edge clamping and Lanczos
+/// renormalizes it onto the remaining taps, so either way it is extrapolated. Useful for
+/// compositing near borders
+/// Returns error code: 0 = success, non-zero = error
+///
+/// # Safety
+/// src_ptr/dst_ptr must point to valid RGBA buffers of the given dimensions and
+/// coverage_ptr to dst_w * dst_h writable bytes; no two buffers may overlap
+#[no_mangle]
+pub unsafe extern "C" fn resize_rgba_coverage(
+    src_ptr: *const u8,
+    src_w: u32,
+    src_h: u32,
+    dst_ptr: *mut u8,
+    coverage_ptr: *mut u8,
+    dst_w: u32,
+    dst_h: u32,
+    algorithm: u32,
+) -> i32 {
+    let (src_size, dst_size) = match validate_params(src_ptr, src_w, src_h, dst_ptr, dst_w, dst_h) {
+        Ok(sizes) => sizes,
+        Err(code) => return code,
+    };
+
+    let coverage_size = dst_size / 4;
+    let buffers = [(src_ptr, src_size), (dst_ptr as *const u8, dst_size)];
+    if let Err(code) = validate_out_ptr(coverage_ptr, coverage_size, &buffers) {
+        return code;
+    }
+
+    // Resolved here too so the coverage describes the algorithm that actually ran
+    let algorithm = match resolve_algorithm(algorithm, src_w, src_h, dst_w, dst_h) {
+        Ok(a) => a,
+        Err(code) => return code,
+    };
+
+    let src = std::slice::from_raw_parts(src_ptr, src_size);
+    let dst = std::slice::from_raw_parts_mut(dst_ptr, dst_size);
+    let code = resize_slices(src, src_w, src_h, dst, dst_w, dst_h, algorithm);
+    if code != RESIZE_OK {
+        return code;
+    }
+
+    let x_coverage = axis_coverage(src_w, dst_w, algorithm);
+    let y_coverage = axis_coverage(src_h, dst_h, algorithm);
+    let coverage = std::slice::from_raw_parts_mut(coverage_ptr, coverage_size);
+    for (row, &cy) in coverage.chunks_exact_mut(dst_w as usize).zip(&y_coverage) {
+        for (out, &cx) in row.iter_mut().zip(&x_coverage) {
+            *out = (cx * cy * 255.0 + 0.5) as u8;
+        }
+    }
+
+    RESIZE_OK
+}
+
+/// Peak signal-to-noise ratio between two RGBA images of the same dimensions
+/// The mean squared error is taken over all four channels, and
+/// `10 * log10(255^2 / mse)` dB is written to `out`. Identical buffers report
+/// PSNR_IDENTICAL_DB (99 dB) instead of infinity. `a_ptr` and `b_ptr` are only read
+/// and may alias; `out` must not overlap either of them
+/// Returns error code: 0 = success, non-zero = error
+///
+/// # Safety
+/// a_ptr/b_ptr must point to valid RGBA buffers of w x h pixels and `out` to a
+/// writable, 4-byte aligned f32
+#[no_mangle]
+pub unsafe extern "C" fn psnr_rgba(a_ptr: *const u8, b_ptr: *const u8, w: u32, h: u32, out: *mut f32) -> i32 {
+    let size = match validate_buffer(a_ptr, w, h) {
+        Ok(size) => size,
+        Err(code) => return code,
+    };
+    if let Err(code) = validate_buffer(b_ptr, w, h) {
+        return code;
+    }
+
+    if let Err(code) = validate_out_ptr(out, 1, &[(a_ptr, size), (b_ptr, size)]) {
+        return code;
+    }
+
+    let a = std::slice::from_raw_parts(a_ptr, size);
+    let b = std::slice::from_raw_parts(b_ptr, size);
+
+    // Exact integer sum: at most 255^2 per byte, far below u64 range for MAX_PIXELS
+    let squared_error: u64 = a
+        .iter()
+        .zip(b)
+        .map(|(&x, &y)| {
+            let d = x.abs_diff(y) as u64;
+            d * d
+        })
+        .sum();
+
+    *out = if squared_error == 0 {
+        PSNR_IDENTICAL_DB
+    } else {
+        let mse = squared_error as f64 / size as f64;
+        (10.0 * (255.0f64 * 255.0 / mse).log10()) as f32
+    };
+
+    RESIZE_OK
+}
+
+/// Resize and report per-channel statistics of the source in one call
+/// `dst_ptr` receives the regular resize with `algorithm`; `out_stats` receives eight
+/// floats, (mean, stddev) for R, G, B and A in that order, taken over every source pixel.
+/// Color channels are measured in linear light (sRGB-decoded, 0..1), alpha as 0..1.
+/// Useful for detecting color casts (e.g. a red mean well above green and blue)
+/// Returns error code: 0 = success, non-zero = error
+///
+/// # Safety
+/// src_ptr/dst_ptr must point to valid RGBA buffers of the given dimensions and
+/// `out_stats` to 8 writable, 4-byte aligned f32 values not overlapping either buffer
+#[no_mangle]
+pub unsafe extern "C" fn resize_rgba_channel_stats(
+    src_ptr: *const u8,
+    src_w: u32,
+    src_h: u32,
+    dst_ptr: *mut u8,
+    dst_w: u32,
+    dst_h: u32,
+    algorithm: u32,
+    out_stats: *mut f32,
+) -> i32 {
+    let (src_size, dst_size) = match validate_params(src_ptr, src_w, src_h, dst_ptr, dst_w, dst_h) {
+        Ok(sizes) => sizes,
+        Err(code) => return code,
+    };
+
+    let buffers = [(src_ptr, src_size), (dst_ptr as *const u8, dst_size)];
+    if let Err(code) = validate_out_ptr(out_stats, CHANNEL_STATS_LEN, &buffers) {
+        return code;
+    }
+
+    let src = std::slice::from_raw_parts(src_ptr, src_size);
+    let dst = std::slice::from_raw_parts_mut(dst_ptr, dst_size);
+    let code = resize_slices(src, src_w, src_h, dst, dst_w, dst_h, algorithm);
+    if code != RESIZE_OK {
+        return code;
+    }
+
+    init_gamma_luts();
+    let mut sum = [0.0f64; 4];
+    let mut sum_sq = [0.0f64; 4];
+    for p in src.chunks_exact(4) {
+        let values = [
+            srgb_to_linear_lut(p[0]),
+            srgb_to_linear_lut(p[1]),
+            srgb_to_linear_lut(p[2]),
+            p[3] as f32 / 255.0,
+        ];
+        for c in 0..4 {
+            let v = values[c] as f64;
+            sum[c] += v;
+            sum_sq[c] += v * v;
+        }
+    }
+
+    let n = (src_size / 4) as f64;
+    let stats = std::slice::from_raw_parts_mut(out_stats, CHANNEL_STATS_LEN);
+    for c in 0..4 {
+        let mean = sum[c] / n;
+        // Rounding can leave a tiny negative variance for constant channels
+        let variance = (sum_sq[c] / n - mean * mean).max(0.0);
+        stats[c * 2] = mean as f32;
+        stats[c * 2 + 1] = variance.sqrt() as f32;
+    }
+
+    RESIZE_OK
+}
+
+/// Center-weighted average color of an RGBA image
+/// Every pixel is weighted by a Gaussian centered on the image with a standard deviation
+/// of `sigma_frac` times the smaller dimension, so the subject in the middle dominates
+/// over backgrounds and borders. Color is averaged in linear light and additionally
+/// weighted by alpha (transparent pixels contribute no color); the result's alpha is
+/// the Gaussian-weighted mean alpha. The sRGB result is written to `out_rgba`
+/// Returns error code: 0 = success, non-zero = error
+///
+/// # Safety
+/// src_ptr must point to a valid RGBA buffer of the given dimensions and `out_rgba` to
+/// 4 writable bytes not overlapping it
+#[no_mangle]
+pub unsafe extern "C" fn weighted_average_color_rgba(
+    src_ptr: *const u8,
+    src_w: u32,
+    src_h: u32,
+    sigma_frac: f32,
+    out_rgba: *mut u8,
+) -> i32 {
+    let src_size = match validate_buffer(src_ptr, src_w, src_h) {
+        Ok(size) => size,
+        Err(code) => return code,
+    };
+
+    if let Err(code) = validate_out_ptr(out_rgba, 4, &[(src_ptr, src_size)]) {
+        return code;
+    }
+
+    if !sigma_frac.is_finite() || sigma_frac <= 0.0 {
+        set_last_error(RESIZE_ERR_INVALID_PARAM);
+        return RESIZE_ERR_INVALID_PARAM;
+    }
+
+    // Separable Gaussian: weight(x, y) = gx[x] * gy[y]
+    let sigma = sigma_frac as f64 * src_w.min(src_h) as f64;
+    let gaussian = |n: u32| -> Vec<f64> {
+        (0..n)
+            .map(|i| {
+                let d = (i as f64 + 0.5 - n as f64 / 2.0) / sigma;
+                (-0.5 * d * d).exp()
+            })
+            .collect()
+    };
+    let (gx, gy) = (gaussian(src_w), gaussian(src_h));
+
+    init_gamma_luts();
+    let src = std::slice::from_raw_parts(src_ptr, src_size);
+    let mut color = [0.0f64; 3];
+    let (mut alpha_weight, mut weight_sum) = (0.0f64, 0.0f64);
+    for (row, &wy) in src.chunks_exact(src_w as usize * 4).zip(&gy) {
+        for (p, &wx) in row.chunks_exact(4).zip(&gx) {
+            let w = wx * wy;
+            let wa = w * p[3] as f64 / 255.0;
+            for (c, &v) in color.iter_mut().zip(p) {
+                *c += srgb_to_linear_lut(v) as f64 * wa;
+            }
+            alpha_weight += wa;
+            weight_sum += w;
+        }
+    }
+
+    let out = std::slice::from_raw_parts_mut(out_rgba, 4);
+    if alpha_weight > 0.0 {
+        for (o, c) in out.iter_mut().zip(color) {
+            *o = (linear_to_srgb_fast((c / alpha_weight) as f32) * 255.0 + 0.5) as u8;
+        }
+    } else {
+        out[..3].fill(0);
+    }
+    out[3] = (alpha_weight / weight_sum * 255.0 + 0.5).min(255.0) as u8;
+
+    RESIZE_OK
+}
+
+/// Resize and extract the image's dominant colors as a palette sidecar (UI theming)
+/// After resizing with `algorithm`, the source is nearest-sampled down to at most
+/// PALETTE_SAMPLE_SIZE pixels per side (so only real source colors are seen) and
+/// median-cut over its non-transparent pixels into up to `palette_size` (1..=256)
+/// colors. `palette_ptr` receives them as RGBA, most populous first; entries beyond
+/// the number of colors found are zero
+/// Returns error code: 0 = success, non-zero = error
+///
+/// # Safety
+/// src_ptr/dst_ptr must point to valid RGBA buffers of the given dimensions and
+/// palette_ptr to palette_size * 4 writable bytes overlapping neither
+#[no_mangle]
+pub unsafe extern "C" fn resize_rgba_with_palette(
+    src_ptr: *const u8,
+    src_w: u32,
+    src_h: u32,
+    dst_ptr: *mut u8,
+    dst_w: u32,
+    dst_h: u32,
+    palette_ptr: *mut u8,
+    palette_size: u32,
+    algorithm: u32,
+) -> i32 {
+    let (src_size, dst_size) = match validate_params(src_ptr, src_w, src_h, dst_ptr, dst_w, dst_h) {
+        Ok(sizes) => sizes,
+        Err(code) => return code,
+    };
+
+    if !(1..=256).contains(&palette_size) {
+        set_last_error(RESIZE_ERR_INVALID_PARAM);
+        return RESIZE_ERR_INVALID_PARAM;
+    }
+
+    let palette_len = palette_size as usize * 4;
+    let buffers = [(src_ptr, src_size), (dst_ptr as *const u8, dst_size)];
+    if let Err(code) = validate_out_ptr(palette_ptr, palette_len, &buffers) {
+        return code;
+    }
+
+    let src = std::slice::from_raw_parts(src_ptr, src_size);
+    let dst = std::slice::from_raw_parts_mut(dst_ptr, dst_size);
+    let code = resize_slices(src, src_w, src_h, dst, dst_w, dst_h, algorithm);
+    if code != RESIZE_OK {
+        return code;
+    }
+
+    let scale = (PALETTE_SAMPLE_SIZE as f32 / src_w.max(src_h) as f32).min(1.0);
+    let sample_w = ((src_w as f32 * scale) as u32).max(1);
+    let sample_h = ((src_h as f32 * scale) as u32).max(1);
+    let mut sample = vec![0u8; sample_w as usize * sample_h as usize * 4];
+    let code = intermediate_pass(|| {
+        resize_slices(src, src_w, src_h, &mut sample, sample_w, sample_h, RESIZE_ALGO_NEAREST)
+    });
+    if code != RESIZE_OK {
+        return code;
+    }
+
+    let mut pixels: Vec<[u8; 4]> = sample
+        .chunks_exact(4)
+        .filter(|p| p[3] > 0)
+        .map(|p| [p[0], p[1], p[2], p[3]])
+        .collect();
+    let mut colors = if pixels.is_empty() {
+        Vec::new()
+    } else {
+        median_cut(&mut pixels, palette_size as usize)
+    };
+    colors.sort_by_key(|&(_, population)| std::cmp::Reverse(population));
+
+    let palette = std::slice::from_raw_parts_mut(palette_ptr, palette_len);
+    palette.fill(0);
+    for (entry, (color, _)) in palette.chunks_exact_mut(4).zip(&colors) {
+        entry.copy_from_slice(color);
+    }
+
+    RESIZE_OK
+}
+
+/// Low-frequency 2D DCT-II coefficients of a PHASH_SIZE x PHASH_SIZE grayscale block
+/// Only the top-left PHASH_LOW_FREQ x PHASH_LOW_FREQ coefficients are computed,
+/// returned row-major (vertical frequency major). Normalization is omitted since the
+/// hash only compares coefficients against each other
+fn dct_low_freq(gray: &[f32]) -> [f32; PHASH_LOW_FREQ * PHASH_LOW_FREQ] {
+    let mut basis = [[0.0f32; PHASH_SIZE]; PHASH_LOW_FREQ];
+    for (k, row) in basis.iter_mut().enumerate() {
+        for (n, b) in row.iter_mut().enumerate() {
+            *b = (PI * (2 * n + 1) as f32 * k as f32 / (2 * PHASH_SIZE) as f32).cos();
+        }
+    }
+
+    // Horizontal pass: PHASH_SIZE rows reduced to PHASH_LOW_FREQ coefficients each
+    let mut rows = [[0.0f32; PHASH_LOW_FREQ]; PHASH_SIZE];
+    for (row, line) in rows.iter_mut().zip(gray.chunks_exact(PHASH_SIZE)) {
+        for (coef, b) in row.iter_mut().zip(basis.iter()) {
+            *coef = line.iter().zip(b.iter()).map(|(v, c)| v * c).sum();
+        }
+    }
+
+    let mut out = [0.0f32; PHASH_LOW_FREQ * PHASH_LOW_FREQ];
+    for (v, b) in basis.iter().enumerate() {
+        for u in 0..PHASH_LOW_FREQ {
+            out[v * PHASH_LOW_FREQ + u] = rows.iter().zip(b.iter()).map(|(row, c)| row[u] * c).sum();
+        }
+    }
+    out
+}
+
+/// Perceptual hash (pHash) of an RGBA image for near-duplicate detection
+/// The image is downscaled gamma-correctly (linear-light area averaging) to 32x32,
+/// reduced to linear Rec. 709 luminance and transformed with a DCT. Each of the 8x8
+/// lowest-frequency coefficients sets one bit when it is above their median; bit
+/// `v * 8 + u` (from the least significant bit) holds vertical frequency `v` and
+/// horizontal frequency `u`. Compare hashes by Hamming distance
+/// Returns error code: 0 = success, non-zero = error
+///
+/// # Safety
+/// src_ptr must point to a valid RGBA buffer of the given dimensions and out_hash
+/// to a writable, 8-byte aligned u64 that does not overlap the source
+#[no_mangle]
+pub unsafe extern "C" fn phash_rgba(src_ptr: *const u8, src_w: u32, src_h: u32, out_hash: *mut u64) -> i32 {
+    let src_size = match validate_buffer(src_ptr, src_w, src_h) {
+        Ok(size) => size,
+        Err(code) => return code,
+    };
+
+    if let Err(code) = validate_out_ptr(out_hash, 1, &[(src_ptr, src_size)]) {
+        return code;
+    }
+
+    init_gamma_luts();
+    let src = std::slice::from_raw_parts(src_ptr, src_size);
+    let mut thumb = vec![0u8; PHASH_SIZE * PHASH_SIZE * 4];
+    let code = gamma_area_core(src, src_w, src_h, &mut thumb, PHASH_SIZE as u32, PHASH_SIZE as u32);
+    if code != RESIZE_OK {
+        return code;
+    }
+
+    let gray: Vec<f32> = thumb
+        .chunks_exact(4)
+        .map(|p| {
+            0.2126 * srgb_to_linear_lut(p[0]) + 0.7152 * srgb_to_linear_lut(p[1]) + 0.0722 * srgb_to_linear_lut(p[2])
+        })
+        .collect();
+
+    let coefficients = dct_low_freq(&gray);
+    let mut sorted = coefficients;
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let mid = sorted.len() / 2;
+    let median = (sorted[mid - 1] + sorted[mid]) / 2.0;
+
+    let hash = coefficients
+        .iter()
+        .enumerate()
+        .filter(|(_, &c)| c > median)
+        .fold(0u64, |hash, (bit, _)| hash | (1 << bit));
+
+    *out_hash = hash;
+    RESIZE_OK
+}
+
+/// Resize and hash the output for content-addressed caching
+/// After the regular resize with `algorithm`, `out_hash` receives the 64-bit FNV-1a hash
+/// of dst_w and dst_h (little-endian u32 each) followed by the destination bytes, so
+/// equal pixels at different shapes still hash differently. The hash is exact, not
+/// perceptual: pair it with set_deterministic_accumulation when keys must match across
+/// builds. `out_hash` is only written on success
+/// Returns error code: 0 = success, non-zero = error
+///
+/// # Safety
+/// src_ptr/dst_ptr must point to valid RGBA buffers of the given dimensions and out_hash
+/// to a writable, 8-byte aligned u64 that overlaps neither
+#[no_mangle]
+pub unsafe extern "C" fn resize_rgba_hashed(
+    src_ptr: *const u8,
+    src_w: u32,
+    src_h: u32,
+    dst_ptr: *mut u8,
+    dst_w: u32,
+    dst_h: u32,
+    algorithm: u32,
+    out_hash: *mut u64,
+) -> i32 {
+    let (src_size, dst_size) = match validate_params(src_ptr, src_w, src_h, dst_ptr, dst_w, dst_h) {
+        Ok(sizes) => sizes,
+        Err(code) => return code,
+    };
+
+    let buffers = [(src_ptr, src_size), (dst_ptr as *const u8, dst_size)];
+    if let Err(code) = validate_out_ptr(out_hash, 1, &buffers) {
+        return code;
+    }
+
+    let src = std::slice::from_raw_parts(src_ptr, src_size);
+    let dst = std::slice::from_raw_parts_mut(dst_ptr, dst_size);
+    let code = resize_slices(src, src_w, src_h, dst, dst_w, dst_h, algorithm);
+    if code != RESIZE_OK {
+        return code;
+    }
+
+    let header = [dst_w.to_le_bytes(), dst_h.to_le_bytes()];
+    *out_hash = header
+        .iter()
+        .flatten()
+        .chain(dst.iter())
+        .fold(FNV_OFFSET_BASIS, |hash, &b| (hash ^ b as u64).wrapping_mul(FNV_PRIME));
+
+    RESIZE_OK
+}
+
+/// Resize and report how much the result differs from a nearest-neighbor resize
+/// `dst_ptr` receives the regular resize with `algorithm`; the same destination is
+/// also resampled with nearest neighbor and the percentage (0..100) of pixels with any
+/// differing channel is written to `out_diff_pct`. A value near 0 means the costlier
+/// filter bought nothing for this image, e.g. flat-color artwork
+/// Returns error code: 0 = success, non-zero = error
+///
+/// # Safety
+/// src_ptr/dst_ptr must point to valid RGBA buffers of the given dimensions and
+/// `out_diff_pct` to a writable, 4-byte aligned f32 not overlapping either buffer
+#[no_mangle]
+pub unsafe extern "C" fn resize_rgba_compare_nearest(
+    src_ptr: *const u8,
+    src_w: u32,
+    src_h: u32,
+    dst_ptr: *mut u8,
+    dst_w: u32,
+    dst_h: u32,
+    algorithm: u32,
+    out_diff_pct: *mut f32,
+) -> i32 {
+    let (src_size, dst_size) = match validate_params(src_ptr, src_w, src_h, dst_ptr, dst_w, dst_h) {
+        Ok(sizes) => sizes,
+        Err(code) => return code,
+    };
+
+    let buffers = [(src_ptr, src_size), (dst_ptr as *const u8, dst_size)];
+    if let Err(code) = validate_out_ptr(out_diff_pct, 1, &buffers) {
+        return code;
+    }
+
+    let src = std::slice::from_raw_parts(src_ptr, src_size);
+    let dst = std::slice::from_raw_parts_mut(dst_ptr, dst_size);
+    let code = resize_slices(src, src_w, src_h, dst, dst_w, dst_h, algorithm);
+    if code != RESIZE_OK {
+        return code;
+    }
+
+    let mut nearest = vec![0u8; dst_size];
+    let code = intermediate_pass(|| resize_slices(src, src_w, src_h, &mut nearest, dst_w, dst_h, RESIZE_ALGO_NEAREST));
+    if code != RESIZE_OK {
+        return code;
+    }
+
+    let differing = dst
+        .chunks_exact(4)
+        .zip(nearest.chunks_exact(4))
+        .filter(|(a, b)| a != b)
+        .count();
+    *out_diff_pct = (differing as f64 * 100.0 / (dst_size / 4) as f64) as f32;
+
+    RESIZE_OK
+}
+
+/// Debug resize that cross-checks three algorithms against each other
+/// Runs bilinear, Lanczos and gamma-correct bilinear on the same input, writes the
+/// Lanczos result to `dst_ptr` and the percentage (0..100) of pixels where any channel
+/// differs by more than `tolerance` between any two of the results to
+/// `out_disagree_pct`. Smooth content agrees closely; a high value flags fine detail
+/// the filters treat differently, or an algorithm-specific bug
+/// Returns error code: 0 = success, non-zero = error
+///
+/// # Safety
+/// src_ptr/dst_ptr must point to valid RGBA buffers of the given dimensions and
+/// `out_disagree_pct` to a writable, 4-byte aligned f32 not overlapping either buffer
+#[no_mangle]
+pub unsafe extern "C" fn resize_rgba_ensemble(
+    src_ptr: *const u8,
+    src_w: u32,
+    src_h: u32,
+    dst_ptr: *mut u8,
+    dst_w: u32,
+    dst_h: u32,
+    tolerance: u8,
+    out_disagree_pct: *mut f32,
+) -> i32 {
+    let (src_size, dst_size) = match validate_params(src_ptr, src_w, src_h, dst_ptr, dst_w, dst_h) {
+        Ok(sizes) => sizes,
+        Err(code) => return code,
+    };
+
+    let buffers = [(src_ptr, src_size), (dst_ptr as *const u8, dst_size)];
+    if let Err(code) = validate_out_ptr(out_disagree_pct, 1, &buffers) {
+        return code;
+    }
+
+    let src = std::slice::from_raw_parts(src_ptr, src_size);
+    let dst = std::slice::from_raw_parts_mut(dst_ptr, dst_size);
+    let code = resize_slices(src, src_w, src_h, dst, dst_w, dst_h, RESIZE_ALGO_LANCZOS);
+    if code != RESIZE_OK {
+        return code;
+    }
+
+    let mut bilinear = vec![0u8; dst_size];
+    let code = intermediate_pass(|| {
+        resize_slices(src, src_w, src_h, &mut bilinear, dst_w, dst_h, RESIZE_ALGO_BILINEAR)
+    });
+    if code != RESIZE_OK {
+        return code;
+    }
+
+    // u32 storage keeps the RGBA buffer 4-byte aligned for the pointer-based entry point
+    let mut gamma = vec![0u32; dst_size / 4];
+    let code = intermediate_pass(|| {
+        resize_rgba_gamma_bilinear(src_ptr, src_w, src_h, gamma.as_mut_ptr() as *mut u8, dst_w, dst_h)
+    });
+    if code != RESIZE_OK {
+        return code;
+    }
+    let gamma = std::slice::from_raw_parts(gamma.as_ptr() as *const u8, dst_size);
+
+    let disagreeing = dst
+        .chunks_exact(4)
+        .zip(bilinear.chunks_exact(4))
+        .zip(gamma.chunks_exact(4))
+        .filter(|((a, b), g)| {
+            (0..4).any(|c| {
+                let lo = a[c].min(b[c]).min(g[c]);
+                let hi = a[c].max(b[c]).max(g[c]);
+                hi - lo > tolerance
+            })
+        })
+        .count();
+    *out_disagree_pct = (disagreeing as f64 * 100.0 / (dst_size / 4) as f64) as f32;
+
+    RESIZE_OK
+}
+
+/// Residual between an image and its downscale -> upscale round trip
+/// The source is downscaled by `factor` (to max(1, src / factor) on each axis) and
+/// upscaled back to src_w x src_h with `algorithm`; `dst_residual_ptr` receives
+/// 128 + clamp(original - reconstructed, -128, 127) per channel, so 128 means the round
+/// trip was exact. Adding (residual - 128) to the reconstruction recovers the original
+/// wherever the difference fits the range, e.g. as a training target for super-resolution
+/// Returns error code: 0 = success, non-zero = error
+///
+/// # Safety
+/// src_ptr must point to a valid RGBA buffer of the given dimensions and
+/// dst_residual_ptr to a valid RGBA buffer of the same dimensions
+#[no_mangle]
+pub unsafe extern "C" fn resize_residual_rgba(
+    src_ptr: *const u8,
+    src_w: u32,
+    src_h: u32,
+    factor: u32,
+    dst_residual_ptr: *mut u8,
+    algorithm: u32,
+) -> i32 {
+    let (src_size, dst_size) = match validate_params(src_ptr, src_w, src_h, dst_residual_ptr, src_w, src_h) {
+        Ok(sizes) => sizes,
+        Err(code) => return code,
+    };
+
+    if factor == 0 {
+        set_last_error(RESIZE_ERR_INVALID_PARAM);
+        return RESIZE_ERR_INVALID_PARAM;
+    }
+
+    let (small_w, small_h) = ((src_w / factor).max(1), (src_h / factor).max(1));
+    let src = std::slice::from_raw_parts(src_ptr, src_size);
+    let mut small = vec![0u8; small_w as usize * small_h as usize * 4];
+    let code = intermediate_pass(|| resize_slices(src, src_w, src_h, &mut small, small_w, small_h, algorithm));
+    if code != RESIZE_OK {
+        return code;
+    }
+
+    let dst = std::slice::from_raw_parts_mut(dst_residual_ptr, dst_size);
+    let code = intermediate_pass(|| resize_slices(&small, small_w, small_h, dst, src_w, src_h, algorithm));
+    if code != RESIZE_OK {
+        return code;
+    }
+
+    // dst holds the reconstruction; replace it with the biased residual
+    for (r, &o) in dst.iter_mut().zip(src) {
+        *r = (128 + (o as i32 - *r as i32).clamp(-128, 127)) as u8;
+    }
+    mark_rows_completed(src_h);
+
+    RESIZE_OK
+}
@@ -0,0 +1,486 @@
+//! Color adjustment operations applied around the resize pipeline
+//! Operates on RGBA buffers in place; color math is done in linear light
+//! using the gamma conversion helpers from the gamma module, or in OKLab where
+//! perceptual uniformity matters
+
+#[cfg(not(any(target_arch = "wasm32", test)))]
+compile_error!("This module only supports wasm32 target");
+
+use crate::gamma_simd::{init_gamma_luts, linear_to_srgb_fast, srgb_to_linear_lut};
+use crate::separable::{algorithm_taps, convolve};
+use crate::{
+    mark_rows_completed, resize_for_post_pass, resolve_algorithm, set_last_error, validate_buffer,
+    validate_intermediate, validate_params, RESIZE_ERR_ALIGNMENT, RESIZE_ERR_INVALID_PARAM,
+    RESIZE_ERR_NULL_PTR, RESIZE_OK,
+};
+
+// Floats read by resize_rgba_colormatrix: three rows of [r, g, b, offset]
+const COLOR_MATRIX_LEN: usize = 12;
+
+/// Build a u8 -> u8 table applying `f` in linear light for one channel
+/// Turns a per-pixel powf into a single table lookup
+#[inline(always)]
+fn linear_channel_table(f: impl Fn(f32) -> f32) -> [u8; 256] {
+    let mut table = [0u8; 256];
+    for (v, out) in table.iter_mut().enumerate() {
+        let linear = f(srgb_to_linear_lut(v as u8));
+        *out = (linear_to_srgb_fast(linear) * 255.0 + 0.5) as u8;
+    }
+    table
+}
+
+/// Gray-world auto white balance, applied in place
+/// Computes per-channel linear-light means over non-transparent pixels, derives
+/// scale factors that equalize them to their overall mean, and applies those in
+/// linear space before re-encoding to sRGB. Alpha is left untouched.
+/// Returns error code: 0 = success, non-zero = error
+///
+/// # Safety
+/// ptr must point to a valid RGBA buffer of w * h * 4 bytes
+#[no_mangle]
+pub unsafe extern "C" fn auto_white_balance_rgba(ptr: *mut u8, w: u32, h: u32) -> i32 {
+    let size = match validate_buffer(ptr, w, h) {
+        Ok(size) => size,
+        Err(code) => return code,
+    };
+    
+    init_gamma_luts();
+    let data = std::slice::from_raw_parts_mut(ptr, size);
+    
+    // Histogram first so the linear decode runs 256 times per channel, not per pixel
+    let mut histograms = [[0u64; 256]; 3];
+    for p in data.chunks_exact(4) {
+        if p[3] == 0 {
+            continue;
+        }
+        histograms[0][p[0] as usize] += 1;
+        histograms[1][p[1] as usize] += 1;
+        histograms[2][p[2] as usize] += 1;
+    }
+    
+    let mut means = [0.0f64; 3];
+    let mut count = 0u64;
+    for (c, histogram) in histograms.iter().enumerate() {
+        let mut sum = 0.0f64;
+        count = 0;
+        for (v, &n) in histogram.iter().enumerate() {
+            sum += srgb_to_linear_lut(v as u8) as f64 * n as f64;
+            count += n;
+        }
+        means[c] = if count > 0 { sum / count as f64 } else { 0.0 };
+    }
+    
+    // Nothing visible, or a channel with no signal at all: leave the image alone
+    if count == 0 || means.iter().any(|&m| m <= 1e-6) {
+        return RESIZE_OK;
+    }
+    
+    let gray = (means[0] + means[1] + means[2]) / 3.0;
+    let tables = [
+        linear_channel_table(|v| v * (gray / means[0]) as f32),
+        linear_channel_table(|v| v * (gray / means[1]) as f32),
+        linear_channel_table(|v| v * (gray / means[2]) as f32),
+    ];
+    
+    for p in data.chunks_exact_mut(4) {
+        p[0] = tables[0][p[0] as usize];
+        p[1] = tables[1][p[1] as usize];
+        p[2] = tables[2][p[2] as usize];
+    }
+    mark_rows_completed(h);
+    
+    RESIZE_OK
+}
+
+/// Dilate color into fully transparent pixels, applied in place
+/// Each iteration gives every transparent pixel that has a colored 8-neighbor (a
+/// pixel with alpha > 0, or one filled by an earlier iteration) the average RGB of
+/// those neighbors, so color spreads one ring per iteration. Alpha stays 0, so the
+/// image looks the same, but filtering and mipmapping no longer pull in the black of
+/// transparent pixels at sprite edges. Stops early once nothing is left to fill
+/// Returns error code: 0 = success, non-zero = error
+///
+/// # Safety
+/// ptr must point to a valid RGBA buffer of w * h * 4 bytes
+#[no_mangle]
+pub unsafe extern "C" fn alpha_bleed_rgba(ptr: *mut u8, w: u32, h: u32, iterations: u32) -> i32 {
+    let size = match validate_buffer(ptr, w, h) {
+        Ok(size) => size,
+        Err(code) => return code,
+    };
+
+    let data = std::slice::from_raw_parts_mut(ptr, size);
+    let (w, h) = (w as usize, h as usize);
+    let mut colored: Vec<bool> = data.chunks_exact(4).map(|p| p[3] > 0).collect();
+    let mut filled = Vec::new();
+
+    for _ in 0..iterations {
+        // Decided against the previous ring only, so each iteration grows by one pixel
+        filled.clear();
+        for y in 0..h {
+            for x in 0..w {
+                if colored[y * w + x] {
+                    continue;
+                }
+                let mut sum = [0u32; 3];
+                let mut count = 0u32;
+                for ny in y.saturating_sub(1)..(y + 2).min(h) {
+                    for nx in x.saturating_sub(1)..(x + 2).min(w) {
+                        let n = ny * w + nx;
+                        if colored[n] {
+                            sum[0] += data[n * 4] as u32;
+                            sum[1] += data[n * 4 + 1] as u32;
+                            sum[2] += data[n * 4 + 2] as u32;
+                            count += 1;
+                        }
+                    }
+                }
+                if count > 0 {
+                    filled.push((y * w + x, sum.map(|s| ((s + count / 2) / count) as u8)));
+                }
+            }
+        }
+
+        if filled.is_empty() {
+            break;
+        }
+        for &(i, rgb) in &filled {
+            data[i * 4..i * 4 + 3].copy_from_slice(&rgb);
+            colored[i] = true;
+        }
+    }
+    mark_rows_completed(h as u32);
+
+    RESIZE_OK
+}
+
+/// Resize and apply a 3x4 color matrix to each output pixel
+/// `matrix_ptr` holds 12 floats, three row-major rows of `[r, g, b, offset]`, so
+/// `out.r = m[0] * r + m[1] * g + m[2] * b + m[3]` and likewise for G and B. The
+/// matrix is applied in linear light after resizing with `algorithm`; results are
+/// clamped to [0, 1] and alpha is left untouched. Non-finite entries are rejected
+/// Returns error code: 0 = success, non-zero = error
+///
+/// # Safety
+/// src_ptr/dst_ptr must point to valid RGBA buffers of the given dimensions and
+/// matrix_ptr to 12 readable, 4-byte aligned f32 values
+#[no_mangle]
+pub unsafe extern "C" fn resize_rgba_colormatrix(
+    src_ptr: *const u8,
+    src_w: u32,
+    src_h: u32,
+    dst_ptr: *mut u8,
+    dst_w: u32,
+    dst_h: u32,
+    matrix_ptr: *const f32,
+    algorithm: u32,
+) -> i32 {
+    if matrix_ptr.is_null() {
+        set_last_error(RESIZE_ERR_NULL_PTR);
+        return RESIZE_ERR_NULL_PTR;
+    }
+
+    if !(matrix_ptr as usize).is_multiple_of(std::mem::align_of::<f32>()) {
+        set_last_error(RESIZE_ERR_ALIGNMENT);
+        return RESIZE_ERR_ALIGNMENT;
+    }
+
+    // Copied before resizing, so a matrix stored inside the destination is still valid
+    let mut matrix = [0.0f32; COLOR_MATRIX_LEN];
+    matrix.copy_from_slice(std::slice::from_raw_parts(matrix_ptr, COLOR_MATRIX_LEN));
+    if !matrix.iter().all(|m| m.is_finite()) {
+        set_last_error(RESIZE_ERR_INVALID_PARAM);
+        return RESIZE_ERR_INVALID_PARAM;
+    }
+
+    let code = resize_for_post_pass(src_ptr, src_w, src_h, dst_ptr, dst_w, dst_h, algorithm);
+    if code != RESIZE_OK {
+        return code;
+    }
+
+    init_gamma_luts();
+    let dst = std::slice::from_raw_parts_mut(dst_ptr, dst_w as usize * dst_h as usize * 4);
+    for p in dst.chunks_exact_mut(4) {
+        let rgb = [srgb_to_linear_lut(p[0]), srgb_to_linear_lut(p[1]), srgb_to_linear_lut(p[2])];
+        for (channel, row) in p[..3].iter_mut().zip(matrix.chunks_exact(4)) {
+            let linear = row[0] * rgb[0] + row[1] * rgb[1] + row[2] * rgb[2] + row[3];
+            *channel = (linear_to_srgb_fast(linear) * 255.0 + 0.5) as u8;
+        }
+    }
+    mark_rows_completed(dst_h);
+
+    RESIZE_OK
+}
+
+/// Resize and apply per-channel 256-entry curves to the output
+/// After resizing with `algorithm`, each output R, G and B value is replaced by
+/// `lut[value]` from its channel's table (e.g. tone curves or LUT-based grades); a
+/// null table leaves that channel unchanged. Alpha is never remapped
+/// Returns error code: 0 = success, non-zero = error
+///
+/// # Safety
+/// src_ptr/dst_ptr must point to valid RGBA buffers of the given dimensions and each
+/// non-null LUT pointer to 256 readable bytes
+#[no_mangle]
+pub unsafe extern "C" fn resize_rgba_curves(
+    src_ptr: *const u8,
+    src_w: u32,
+    src_h: u32,
+    dst_ptr: *mut u8,
+    dst_w: u32,
+    dst_h: u32,
+    r_lut: *const u8,
+    g_lut: *const u8,
+    b_lut: *const u8,
+    algorithm: u32,
+) -> i32 {
+    // Copied before resizing, so tables stored inside the destination are still valid
+    let table = |lut: *const u8| -> Option<[u8; 256]> {
+        if lut.is_null() {
+            return None;
+        }
+        let mut table = [0u8; 256];
+        table.copy_from_slice(std::slice::from_raw_parts(lut, 256));
+        Some(table)
+    };
+    let tables = [table(r_lut), table(g_lut), table(b_lut)];
+
+    let code = resize_for_post_pass(src_ptr, src_w, src_h, dst_ptr, dst_w, dst_h, algorithm);
+    if code != RESIZE_OK {
+        return code;
+    }
+
+    let dst = std::slice::from_raw_parts_mut(dst_ptr, dst_w as usize * dst_h as usize * 4);
+    for p in dst.chunks_exact_mut(4) {
+        for (channel, table) in p[..3].iter_mut().zip(&tables) {
+            if let Some(table) = table {
+                *channel = table[*channel as usize];
+            }
+        }
+    }
+    mark_rows_completed(dst_h);
+
+    RESIZE_OK
+}
+
+/// Resize and snap nearly opaque / nearly transparent output alpha
+/// After resizing with `algorithm`, alpha above `opaque_threshold` becomes 255 and alpha
+/// below `transparent_threshold` becomes 0, so antialiasing residue (e.g. 253 on a solid
+/// edge) can't build up over repeated resizes; alpha in between and color are kept.
+/// `transparent_threshold` must not exceed `opaque_threshold`
+/// Returns error code: 0 = success, non-zero = error
+///
+/// # Safety
+/// src_ptr/dst_ptr must point to valid RGBA buffers of the given dimensions
+#[no_mangle]
+pub unsafe extern "C" fn resize_rgba_alpha_snap(
+    src_ptr: *const u8,
+    src_w: u32,
+    src_h: u32,
+    dst_ptr: *mut u8,
+    dst_w: u32,
+    dst_h: u32,
+    opaque_threshold: u8,
+    transparent_threshold: u8,
+    algorithm: u32,
+) -> i32 {
+    if transparent_threshold > opaque_threshold {
+        set_last_error(RESIZE_ERR_INVALID_PARAM);
+        return RESIZE_ERR_INVALID_PARAM;
+    }
+
+    let code = resize_for_post_pass(src_ptr, src_w, src_h, dst_ptr, dst_w, dst_h, algorithm);
+    if code != RESIZE_OK {
+        return code;
+    }
+
+    let dst = std::slice::from_raw_parts_mut(dst_ptr, dst_w as usize * dst_h as usize * 4);
+    for p in dst.chunks_exact_mut(4) {
+        if p[3] > opaque_threshold {
+            p[3] = 255;
+        } else if p[3] < transparent_threshold {
+            p[3] = 0;
+        }
+    }
+    mark_rows_completed(dst_h);
+
+    RESIZE_OK
+}
+
+/// Resize an image keyed from a solid matte color without matte fringing
+/// Semi-transparent pixels are assumed to be the foreground mixed over the matte
+/// (`observed = color * a + matte * (1 - a)`), so before resizing the matte share is
+/// removed: `color = (observed - matte * (1 - a)) / a`. The recovered colors are
+/// resampled premultiplied with the taps of `algorithm` and written as straight
+/// alpha, so edges carry the foreground color instead of a tint of the matte
+/// Returns error code: 0 = success, non-zero = error
+///
+/// # Safety
+/// src_ptr/dst_ptr must point to valid RGBA buffers of the given dimensions
+#[no_mangle]
+pub unsafe extern "C" fn resize_rgba_dematte(
+    src_ptr: *const u8,
+    src_w: u32,
+    src_h: u32,
+    dst_ptr: *mut u8,
+    dst_w: u32,
+    dst_h: u32,
+    matte_r: u8,
+    matte_g: u8,
+    matte_b: u8,
+    algorithm: u32,
+) -> i32 {
+    let (src_size, dst_size) = match validate_params(src_ptr, src_w, src_h, dst_ptr, dst_w, dst_h) {
+        Ok(sizes) => sizes,
+        Err(code) => return code,
+    };
+
+    let algorithm = match resolve_algorithm(algorithm, src_w, src_h, dst_w, dst_h) {
+        Ok(a) => a,
+        Err(code) => return code,
+    };
+
+    if let Err(code) = validate_intermediate(dst_w, src_h) {
+        return code;
+    }
+
+    let src = std::slice::from_raw_parts(src_ptr, src_size);
+    let dst = std::slice::from_raw_parts_mut(dst_ptr, dst_size);
+
+    // The premultiplied foreground is the observed color minus the matte's share,
+    // which is clamped to [0, a] so noisy keys can't go negative or exceed coverage
+    let matte = [matte_r as f32 / 255.0, matte_g as f32 / 255.0, matte_b as f32 / 255.0];
+    let premultiplied: Vec<f32> = src
+        .chunks_exact(4)
+        .flat_map(|p| {
+            let a = p[3] as f32 / 255.0;
+            let unmix = |c: usize| (p[c] as f32 / 255.0 - matte[c] * (1.0 - a)).clamp(0.0, a);
+            [unmix(0), unmix(1), unmix(2), a]
+        })
+        .collect();
+
+    let x_taps = algorithm_taps(src_w, dst_w, algorithm, 0.0);
+    let y_taps = algorithm_taps(src_h, dst_h, algorithm, 0.0);
+    let out = match convolve(&premultiplied, src_w, src_h, dst_w, dst_h, &x_taps, &y_taps) {
+        Ok(out) => out,
+        Err(code) => return code,
+    };
+
+    for (d, v) in dst.chunks_exact_mut(4).zip(out.chunks_exact(4)) {
+        let a = v[3].clamp(0.0, 1.0);
+        if a <= 0.0 {
+            d.fill(0);
+            continue;
+        }
+        for c in 0..3 {
+            d[c] = ((v[c] / a).clamp(0.0, 1.0) * 255.0 + 0.5) as u8;
+        }
+        d[3] = (a * 255.0 + 0.5) as u8;
+    }
+    mark_rows_completed(dst_h);
+
+    RESIZE_OK
+}
+
+/// Convert linear-light sRGB to OKLab `[L, a, b]` (Björn Ottosson's matrices)
+#[inline(always)]
+fn linear_to_oklab(rgb: [f32; 3]) -> [f32; 3] {
+    let [r, g, b] = rgb;
+    let l = (0.412_221_46 * r + 0.536_332_55 * g + 0.051_445_995 * b).cbrt();
+    let m = (0.211_903_5 * r + 0.680_699_5 * g + 0.107_396_96 * b).cbrt();
+    let s = (0.088_302_46 * r + 0.281_718_85 * g + 0.629_978_7 * b).cbrt();
+    [
+        0.210_454_26 * l + 0.793_617_8 * m - 0.004_072_047 * s,
+        1.977_998_5 * l - 2.428_592_2 * m + 0.450_593_7 * s,
+        0.025_904_037 * l + 0.782_771_77 * m - 0.808_675_77 * s,
+    ]
+}
+
+/// Convert OKLab `[L, a, b]` back to linear-light sRGB (may leave the [0, 1] gamut)
+#[inline(always)]
+fn oklab_to_linear(lab: [f32; 3]) -> [f32; 3] {
+    let [l, a, b] = lab;
+    let l_ = l + 0.396_337_78 * a + 0.215_803_76 * b;
+    let m_ = l - 0.105_561_346 * a - 0.063_854_17 * b;
+    let s_ = l - 0.089_484_18 * a - 1.291_485_5 * b;
+    let (l, m, s) = (l_ * l_ * l_, m_ * m_ * m_, s_ * s_ * s_);
+    [
+        4.076_741_7 * l - 3.307_711_6 * m + 0.230_969_94 * s,
+        -1.268_438 * l + 2.609_757_4 * m - 0.341_319_38 * s,
+        -0.004_196_086_3 * l - 0.703_418_6 * m + 1.707_614_7 * s,
+    ]
+}
+
+/// Resize with interpolation in the OKLab perceptual color space
+/// Pixels are decoded sRGB -> linear -> OKLab, premultiplied by alpha and resampled
+/// with the taps of `algorithm`, then divided by alpha and converted back, so color
+/// ramps (e.g. blue to yellow) pass through perceptually even hues instead of gray.
+/// Colors that land outside the sRGB gamut are clamped
+/// Returns error code: 0 = success, non-zero = error
+///
+/// # Safety
+/// src_ptr/dst_ptr must point to valid RGBA buffers of the given dimensions
+#[no_mangle]
+pub unsafe extern "C" fn resize_rgba_oklab(
+    src_ptr: *const u8,
+    src_w: u32,
+    src_h: u32,
+    dst_ptr: *mut u8,
+    dst_w: u32,
+    dst_h: u32,
+    algorithm: u32,
+) -> i32 {
+    let (src_size, dst_size) = match validate_params(src_ptr, src_w, src_h, dst_ptr, dst_w, dst_h) {
+        Ok(sizes) => sizes,
+        Err(code) => return code,
+    };
+
+    let algorithm = match resolve_algorithm(algorithm, src_w, src_h, dst_w, dst_h) {
+        Ok(a) => a,
+        Err(code) => return code,
+    };
+
+    if let Err(code) = validate_intermediate(dst_w, src_h) {
+        return code;
+    }
+
+    let src = std::slice::from_raw_parts(src_ptr, src_size);
+    let dst = std::slice::from_raw_parts_mut(dst_ptr, dst_size);
+
+    init_gamma_luts();
+    let premultiplied: Vec<f32> = src
+        .chunks_exact(4)
+        .flat_map(|p| {
+            let a = p[3] as f32 / 255.0;
+            let [l, ca, cb] = linear_to_oklab([
+                srgb_to_linear_lut(p[0]),
+                srgb_to_linear_lut(p[1]),
+                srgb_to_linear_lut(p[2]),
+            ]);
+            [l * a, ca * a, cb * a, a]
+        })
+        .collect();
+
+    let x_taps = algorithm_taps(src_w, dst_w, algorithm, 0.0);
+    let y_taps = algorithm_taps(src_h, dst_h, algorithm, 0.0);
+    let out = match convolve(&premultiplied, src_w, src_h, dst_w, dst_h, &x_taps, &y_taps) {
+        Ok(out) => out,
+        Err(code) => return code,
+    };
+
+    for (d, v) in dst.chunks_exact_mut(4).zip(out.chunks_exact(4)) {
+        let a = v[3].clamp(0.0, 1.0);
+        if a <= 0.0 {
+            d.fill(0);
+            continue;
+        }
+        let rgb = oklab_to_linear([v[0] / a, v[1] / a, v[2] / a]);
+        for (channel, linear) in d[..3].iter_mut().zip(rgb) {
+            *channel = (linear_to_srgb_fast(linear.clamp(0.0, 1.0)) * 255.0 + 0.5) as u8;
+        }
+        d[3] = (a * 255.0 + 0.5) as u8;
+    }
+    mark_rows_completed(dst_h);
+
+    RESIZE_OK
+}
@@ -0,0 +1,368 @@
+//! Spatial filters applied to resized output
+//! Neighborhood operations (smoothing, sharpening, ...) run on the destination buffer after
+//! resampling, e.g. to prepare thumbnails for lossy encoders
+
+#[cfg(not(any(target_arch = "wasm32", test)))]
+compile_error!("This module only supports wasm32 target");
+
+use crate::analysis::luma;
+use crate::gamma_simd::{encode_linear_rgba, linear_to_srgb_fast, linearize_rgba};
+use crate::separable::{convolve, soft_clip_value, AxisTaps};
+use crate::{
+    mark_row_completed, mark_rows_completed, resize_for_post_pass, set_last_error, validate_buffer,
+    RESIZE_ERR_INVALID_PARAM, RESIZE_OK,
+};
+
+// Luma step (in 8-bit levels) at which resize_rgba_compress_prep smoothing falls to half
+// strength; gradients well above it are treated as edges and left untouched
+const COMPRESS_PREP_EDGE: f32 = 12.0;
+
+// Mean squared luma detail (in 8-bit levels^2) at which resize_rgba_adaptive_sharpen
+// falls to half strength; grain well above it is left almost untouched
+const SHARPEN_NOISE_VARIANCE: f32 = 25.0;
+// Largest base_amount accepted by resize_rgba_adaptive_sharpen
+const SHARPEN_MAX_AMOUNT: f32 = 4.0;
+
+// Largest blur_sigma accepted by resize_rgba_scrim (destination pixels)
+// The Gaussian is truncated at 3 sigma, so this bounds the taps per pixel
+const SCRIM_MAX_SIGMA: f32 = 64.0;
+
+/// Resize, then smooth low-gradient areas to help lossy/near-lossless encoders
+/// After the regular resize with `algorithm`, every pixel is blended toward a 3x3
+/// binomial blur of its neighborhood. The blend factor is `strength` (0..=1) scaled by
+/// 1 / (1 + (g / 12)^2), where g is the largest luma difference to the 8 neighbors, so
+/// flat areas lose fine noise while edges keep their full contrast. 0 leaves the
+/// resize unchanged
+/// Returns error code: 0 = success, non-zero = error
+///
+/// # Safety
+/// src_ptr/dst_ptr must point to valid RGBA buffers of the given dimensions
+#[no_mangle]
+pub unsafe extern "C" fn resize_rgba_compress_prep(
+    src_ptr: *const u8,
+    src_w: u32,
+    src_h: u32,
+    dst_ptr: *mut u8,
+    dst_w: u32,
+    dst_h: u32,
+    strength: f32,
+    algorithm: u32,
+) -> i32 {
+    if !(0.0..=1.0).contains(&strength) {
+        set_last_error(RESIZE_ERR_INVALID_PARAM);
+        return RESIZE_ERR_INVALID_PARAM;
+    }
+
+    let code = resize_for_post_pass(src_ptr, src_w, src_h, dst_ptr, dst_w, dst_h, algorithm);
+    if code != RESIZE_OK {
+        return code;
+    }
+    if strength == 0.0 {
+        mark_rows_completed(dst_h);
+        return RESIZE_OK;
+    }
+
+    let (w, h) = (dst_w as usize, dst_h as usize);
+    let dst = std::slice::from_raw_parts_mut(dst_ptr, w * h * 4);
+    let resized = dst.to_vec();
+    let lum: Vec<i32> = resized.chunks_exact(4).map(luma).collect();
+
+    for y in 0..h {
+        let rows = [y.saturating_sub(1), y, (y + 1).min(h - 1)];
+        for x in 0..w {
+            let cols = [x.saturating_sub(1), x, (x + 1).min(w - 1)];
+            let center = lum[y * w + x];
+
+            let mut gradient = 0;
+            let mut blur = [0u32; 4];
+            for (ry, &sy) in rows.iter().enumerate() {
+                for (rx, &sx) in cols.iter().enumerate() {
+                    let i = sy * w + sx;
+                    gradient = gradient.max((lum[i] - center).abs());
+                    // Binomial [1 2 1] x [1 2 1] weights, total 16
+                    let weight = (1 + (ry & 1) as u32) * (1 + (rx & 1) as u32);
+                    for (b, &v) in blur.iter_mut().zip(&resized[i * 4..i * 4 + 4]) {
+                        *b += v as u32 * weight;
+                    }
+                }
+            }
+
+            let g = gradient as f32 / COMPRESS_PREP_EDGE;
+            let t = strength / (1.0 + g * g);
+            let i = (y * w + x) * 4;
+            for (c, &b) in blur.iter().enumerate() {
+                let v = resized[i + c] as f32;
+                dst[i + c] = (v + (b as f32 / 16.0 - v) * t + 0.5) as u8;
+            }
+        }
+        mark_row_completed(y as u32);
+    }
+
+    RESIZE_OK
+}
+
+/// 3x3 binomial blur of an RGBA image plus the luma detail (luma minus its blur)
+/// Borders replicate the edge pixels
+fn binomial_blur(rgba: &[u8], w: usize, h: usize) -> (Vec<f32>, Vec<f32>) {
+    let mut blur = vec![0.0f32; w * h * 4];
+    let mut detail = vec![0.0f32; w * h];
+
+    for y in 0..h {
+        let rows = [y.saturating_sub(1), y, (y + 1).min(h - 1)];
+        for x in 0..w {
+            let cols = [x.saturating_sub(1), x, (x + 1).min(w - 1)];
+            let mut sum = [0u32; 4];
+            for (ry, &sy) in rows.iter().enumerate() {
+                for (rx, &sx) in cols.iter().enumerate() {
+                    let i = (sy * w + sx) * 4;
+                    // Binomial [1 2 1] x [1 2 1] weights, total 16
+                    let weight = (1 + (ry & 1) as u32) * (1 + (rx & 1) as u32);
+                    for (b, &v) in sum.iter_mut().zip(&rgba[i..i + 4]) {
+                        *b += v as u32 * weight;
+                    }
+                }
+            }
+
+            let i = y * w + x;
+            for (b, s) in blur[i * 4..i * 4 + 4].iter_mut().zip(sum) {
+                *b = s as f32 / 16.0;
+            }
+            // Same weights as analysis::luma, without its rounding
+            let p = &rgba[i * 4..i * 4 + 4];
+            let pixel_sum = 54 * p[0] as u32 + 183 * p[1] as u32 + 19 * p[2] as u32;
+            let pixel_luma = pixel_sum as f32 / 256.0;
+            let blur_luma = (54 * sum[0] + 183 * sum[1] + 19 * sum[2]) as f32 / (256.0 * 16.0);
+            detail[i] = pixel_luma - blur_luma;
+        }
+    }
+
+    (blur, detail)
+}
+
+/// 3x3 binomial blur of linear-light RGBA, with the weights and borders of binomial_blur
+fn binomial_blur_linear(linear: &[f32], w: usize, h: usize) -> Vec<f32> {
+    let mut blur = vec![0.0f32; w * h * 4];
+    for y in 0..h {
+        let rows = [y.saturating_sub(1), y, (y + 1).min(h - 1)];
+        for x in 0..w {
+            let cols = [x.saturating_sub(1), x, (x + 1).min(w - 1)];
+            let out = &mut blur[(y * w + x) * 4..(y * w + x) * 4 + 4];
+            for (ry, &sy) in rows.iter().enumerate() {
+                for (rx, &sx) in cols.iter().enumerate() {
+                    let i = (sy * w + sx) * 4;
+                    let weight = ((1 + (ry & 1)) * (1 + (rx & 1))) as f32 / 16.0;
+                    for (b, &v) in out.iter_mut().zip(&linear[i..i + 4]) {
+                        *b += v * weight;
+                    }
+                }
+            }
+        }
+    }
+    blur
+}
+
+/// Resize, then unsharp-mask with a strength adapted to the local noise level
+/// After the regular resize with `algorithm`, each color channel is pushed away from
+/// its 3x3 binomial blur by `base_amount` scaled by 1 / (1 + v / 25), where v is the
+/// mean squared luma detail (luma minus its blur) over the surrounding 5x5 window.
+/// Soft edges and smooth gradients carry little fine detail and get close to the full
+/// amount, while grainy areas are spared instead of having their noise amplified.
+/// `base_amount` must be in 0..=4; 0 leaves the resize unchanged and alpha is never
+/// modified. With `soft_clip` = 0 the mask works on the sRGB-encoded channels and hard
+/// clamps; with `soft_clip` = 1 it works in linear light and overshoot is rolled off by
+/// soft_clip_value before encoding, so sharpened highlights keep their gradient
+/// Returns error code: 0 = success, non-zero = error
+///
+/// # Safety
+/// src_ptr/dst_ptr must point to valid RGBA buffers of the given dimensions
+#[no_mangle]
+pub unsafe extern "C" fn resize_rgba_adaptive_sharpen(
+    src_ptr: *const u8,
+    src_w: u32,
+    src_h: u32,
+    dst_ptr: *mut u8,
+    dst_w: u32,
+    dst_h: u32,
+    base_amount: f32,
+    soft_clip: u32,
+    algorithm: u32,
+) -> i32 {
+    if !(0.0..=SHARPEN_MAX_AMOUNT).contains(&base_amount) || soft_clip > 1 {
+        set_last_error(RESIZE_ERR_INVALID_PARAM);
+        return RESIZE_ERR_INVALID_PARAM;
+    }
+
+    let code = resize_for_post_pass(src_ptr, src_w, src_h, dst_ptr, dst_w, dst_h, algorithm);
+    if code != RESIZE_OK {
+        return code;
+    }
+    if base_amount == 0.0 {
+        mark_rows_completed(dst_h);
+        return RESIZE_OK;
+    }
+
+    let (w, h) = (dst_w as usize, dst_h as usize);
+    let dst = std::slice::from_raw_parts_mut(dst_ptr, w * h * 4);
+    let resized = dst.to_vec();
+    let (blur, detail) = binomial_blur(&resized, w, h);
+    // Linear-light copy and its blur, only needed for the soft-clip mode
+    let (linear, linear_blur) = if soft_clip == 1 {
+        let linear = linearize_rgba(&resized, false);
+        let linear_blur = binomial_blur_linear(&linear, w, h);
+        (linear, linear_blur)
+    } else {
+        (Vec::new(), Vec::new())
+    };
+
+    for y in 0..h {
+        let (y0, y1) = (y.saturating_sub(2), (y + 2).min(h - 1));
+        for x in 0..w {
+            let (x0, x1) = (x.saturating_sub(2), (x + 2).min(w - 1));
+
+            let mut energy = 0.0f32;
+            for sy in y0..=y1 {
+                for &d in &detail[sy * w + x0..=sy * w + x1] {
+                    energy += d * d;
+                }
+            }
+            let variance = energy / ((y1 - y0 + 1) * (x1 - x0 + 1)) as f32;
+            let amount = base_amount / (1.0 + variance / SHARPEN_NOISE_VARIANCE);
+
+            let i = (y * w + x) * 4;
+            for c in 0..3 {
+                dst[i + c] = if soft_clip == 1 {
+                    let v = linear[i + c];
+                    let sharpened = v + (v - linear_blur[i + c]) * amount;
+                    (linear_to_srgb_fast(soft_clip_value(sharpened)) * 255.0 + 0.5) as u8
+                } else {
+                    let v = resized[i + c] as f32;
+                    let sharpened = v + (v - blur[i + c]) * amount;
+                    (sharpened.clamp(0.0, 255.0) + 0.5) as u8
+                };
+            }
+        }
+        mark_row_completed(y as u32);
+    }
+
+    RESIZE_OK
+}
+
+/// Resize into a darkened, blurred backdrop ("scrim") for overlaid text
+/// After the regular resize with `algorithm`, RGB is multiplied by `1 - darken` in
+/// linear light and the result is blurred with a Gaussian of standard deviation
+/// `blur_sigma` destination pixels (truncated at 3 sigma, edges replicated),
+/// premultiplied so transparent areas do not bleed color. `darken` must be in 0..=1
+/// and `blur_sigma` in 0..=64; 0 skips the blur
+/// Returns error code: 0 = success, non-zero = error
+///
+/// # Safety
+/// src_ptr/dst_ptr must point to valid RGBA buffers of the given dimensions
+#[no_mangle]
+pub unsafe extern "C" fn resize_rgba_scrim(
+    src_ptr: *const u8,
+    src_w: u32,
+    src_h: u32,
+    dst_ptr: *mut u8,
+    dst_w: u32,
+    dst_h: u32,
+    darken: f32,
+    blur_sigma: f32,
+    algorithm: u32,
+) -> i32 {
+    if !(0.0..=1.0).contains(&darken) || !(0.0..=SCRIM_MAX_SIGMA).contains(&blur_sigma) {
+        set_last_error(RESIZE_ERR_INVALID_PARAM);
+        return RESIZE_ERR_INVALID_PARAM;
+    }
+
+    let code = resize_for_post_pass(src_ptr, src_w, src_h, dst_ptr, dst_w, dst_h, algorithm);
+    if code != RESIZE_OK {
+        return code;
+    }
+    if darken == 0.0 && blur_sigma == 0.0 {
+        mark_rows_completed(dst_h);
+        return RESIZE_OK;
+    }
+
+    let dst = std::slice::from_raw_parts_mut(dst_ptr, dst_w as usize * dst_h as usize * 4);
+    let mut linear = linearize_rgba(dst, true);
+    let keep = 1.0 - darken;
+    for p in linear.chunks_exact_mut(4) {
+        p[0] *= keep;
+        p[1] *= keep;
+        p[2] *= keep;
+    }
+
+    if blur_sigma > 0.0 {
+        let inv = -0.5 / (blur_sigma * blur_sigma);
+        let gaussian = |x: f32| (x * x * inv).exp();
+        let x_taps = AxisTaps::new(dst_w, dst_w, 3.0 * blur_sigma, &gaussian);
+        let y_taps = AxisTaps::new(dst_h, dst_h, 3.0 * blur_sigma, &gaussian);
+        linear = match convolve(&linear, dst_w, dst_h, dst_w, dst_h, &x_taps, &y_taps) {
+            Ok(out) => out,
+            Err(code) => return code,
+        };
+    }
+
+    encode_linear_rgba(&linear, dst, true);
+    mark_rows_completed(dst_h);
+
+    RESIZE_OK
+}
+
+/// Box-blur an RGBA image in place in constant time per pixel, whatever the radius
+/// Per-channel integral images (summed-area tables with u64 sums) are built once, then
+/// each pixel becomes the rounded mean of the (2 * radius + 1)^2 window around it from
+/// four lookups. The window is clamped to the image, so edge pixels average only the
+/// pixels that exist, and radii beyond the image size act as max(w, h). Channels are
+/// blurred independently (straight alpha); radius 0 leaves the image unchanged
+/// Returns error code: 0 = success, non-zero = error
+///
+/// # Safety
+/// ptr must point to a valid, writable RGBA buffer of the given dimensions
+#[no_mangle]
+pub unsafe extern "C" fn box_blur_rgba_integral(ptr: *mut u8, w: u32, h: u32, radius: u32) -> i32 {
+    let size = match validate_buffer(ptr, w, h) {
+        Ok(size) => size,
+        Err(code) => return code,
+    };
+    if radius == 0 {
+        return RESIZE_OK;
+    }
+
+    // Clamped so window bounds cannot overflow (usize is 32-bit on wasm32)
+    let (w, h, r) = (w as usize, h as usize, radius.min(w.max(h)) as usize);
+    let pixels = std::slice::from_raw_parts_mut(ptr, size);
+
+    // integral[(y * (w + 1) + x) * 4 + c] = sum of channel c over rows < y, columns < x
+    let stride = (w + 1) * 4;
+    let mut integral = vec![0u64; stride * (h + 1)];
+    for y in 0..h {
+        let mut row_sum = [0u64; 4];
+        for x in 0..w {
+            let p = &pixels[(y * w + x) * 4..(y * w + x) * 4 + 4];
+            let above = y * stride + (x + 1) * 4;
+            for c in 0..4 {
+                row_sum[c] += p[c] as u64;
+                integral[above + stride + c] = integral[above + c] + row_sum[c];
+            }
+        }
+    }
+
+    for y in 0..h {
+        let (top, bottom) = (y.saturating_sub(r), (y + r + 1).min(h));
+        for x in 0..w {
+            let (left, right) = (x.saturating_sub(r), (x + r + 1).min(w));
+            let count = ((bottom - top) * (right - left)) as u64;
+            let d = (y * w + x) * 4;
+            for c in 0..4 {
+                let sum = integral[bottom * stride + right * 4 + c] + integral[top * stride + left * 4 + c]
+                    - integral[top * stride + right * 4 + c]
+                    - integral[bottom * stride + left * 4 + c];
+                pixels[d + c] = ((sum + count / 2) / count) as u8;
+            }
+        }
+        mark_row_completed(y as u32);
+    }
+
+    RESIZE_OK
+}
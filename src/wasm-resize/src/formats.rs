@@ -0,0 +1,525 @@
+//! Output pixel format conversion
+//! Resizes that produce packed or reduced-depth destination formats instead of
+//! RGBA8, converting each resized pixel in the output stage
+
+#[cfg(not(any(target_arch = "wasm32", test)))]
+compile_error!("This module only supports wasm32 target");
+
+use crate::gamma_simd::linearize_rgba;
+use crate::separable::{algorithm_taps, convolve, tent_kernel, AxisTaps};
+use crate::{
+    intermediate_pass, mark_row_completed, mark_rows_completed, resize_slices, resolve_algorithm,
+    set_last_error, validate_buffer, validate_intermediate, MAX_DIMENSION, MAX_PIXELS,
+    RESIZE_ERR_ALIGNMENT, RESIZE_ERR_INVALID_PARAM, RESIZE_ERR_INVALID_SIZE, RESIZE_ERR_NULL_PTR,
+    RESIZE_ERR_OVERLAP, RESIZE_OK,
+};
+
+// Squared distance standing in for "no feature pixel on this line" in the distance transform
+const EDT_INFINITY: f64 = 1e20;
+
+// 4x4 Bayer matrix (values 0..16) used for ordered dithering
+const BAYER_4X4: [[u32; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+/// Validate a destination buffer of `bytes_per_pixel`-byte pixels and compute its size
+/// Same null/dimension limits as validate_buffer; alignment is checked by the caller
+#[inline(always)]
+fn validate_packed(ptr: *const u8, w: u32, h: u32, bytes_per_pixel: u64) -> Result<usize, i32> {
+    if ptr.is_null() {
+        set_last_error(RESIZE_ERR_NULL_PTR);
+        return Err(RESIZE_ERR_NULL_PTR);
+    }
+
+    let pixels = w as u64 * h as u64;
+    if w == 0 || h == 0 || w > MAX_DIMENSION || h > MAX_DIMENSION || pixels > MAX_PIXELS {
+        set_last_error(RESIZE_ERR_INVALID_SIZE);
+        return Err(RESIZE_ERR_INVALID_SIZE);
+    }
+
+    set_last_error(RESIZE_OK);
+    Ok((pixels * bytes_per_pixel) as usize)
+}
+
+/// Quantize an 8-bit channel to `bits` bits
+/// `bias` in [0, 255) is added before truncation: 127 rounds to nearest, an ordered
+/// dither threshold spreads the rounding error spatially
+#[inline(always)]
+fn quantize(value: u8, bits: u32, bias: u32) -> u16 {
+    let max = (1u32 << bits) - 1;
+    ((value as u32 * max + bias) / 255) as u16
+}
+
+/// Resize and pack the output as RGB565
+/// The source is resized in RGBA with `algorithm`, then each pixel is packed into a
+/// little-endian u16 (red in the top 5 bits, green in the middle 6, blue in the low 5);
+/// alpha is dropped. With `dither` = 1 a 4x4 ordered (Bayer) dither is applied before
+/// the bit-depth reduction to break up banding in gradients; 0 rounds to nearest
+/// Returns error code: 0 = success, non-zero = error
+///
+/// # Safety
+/// src_ptr must point to a valid RGBA buffer of the given dimensions and dst_ptr to
+/// dst_w * dst_h * 2 writable, 2-byte aligned bytes that do not overlap the source
+#[no_mangle]
+pub unsafe extern "C" fn resize_rgba_to_rgb565(
+    src_ptr: *const u8,
+    src_w: u32,
+    src_h: u32,
+    dst_ptr: *mut u8,
+    dst_w: u32,
+    dst_h: u32,
+    algorithm: u32,
+    dither: u32,
+) -> i32 {
+    let src_size = match validate_buffer(src_ptr, src_w, src_h) {
+        Ok(size) => size,
+        Err(code) => return code,
+    };
+    let dst_size = match validate_packed(dst_ptr, dst_w, dst_h, 2) {
+        Ok(size) => size,
+        Err(code) => return code,
+    };
+
+    if !(dst_ptr as usize).is_multiple_of(std::mem::align_of::<u16>()) {
+        set_last_error(RESIZE_ERR_ALIGNMENT);
+        return RESIZE_ERR_ALIGNMENT;
+    }
+
+    let src_start = src_ptr as usize;
+    let src_end = src_start.saturating_add(src_size);
+    let dst_start = dst_ptr as usize;
+    let dst_end = dst_start.saturating_add(dst_size);
+    if src_start < dst_end && dst_start < src_end {
+        set_last_error(RESIZE_ERR_OVERLAP);
+        return RESIZE_ERR_OVERLAP;
+    }
+
+    if dither > 1 {
+        set_last_error(RESIZE_ERR_INVALID_PARAM);
+        return RESIZE_ERR_INVALID_PARAM;
+    }
+
+    let src = std::slice::from_raw_parts(src_ptr, src_size);
+    // Four bytes per RGBA pixel against two per packed pixel
+    let mut rgba = vec![0u8; dst_size * 2];
+    let code = intermediate_pass(|| resize_slices(src, src_w, src_h, &mut rgba, dst_w, dst_h, algorithm));
+    if code != RESIZE_OK {
+        return code;
+    }
+
+    let dst = std::slice::from_raw_parts_mut(dst_ptr, dst_size);
+    let dst_w = dst_w as usize;
+    for (i, (out, p)) in dst.chunks_exact_mut(2).zip(rgba.chunks_exact(4)).enumerate() {
+        // Threshold at the center of each Bayer cell, scaled to [0, 255)
+        let bias = if dither == 1 {
+            (2 * BAYER_4X4[(i / dst_w) & 3][(i % dst_w) & 3] + 1) * 255 / 32
+        } else {
+            127
+        };
+        let packed = (quantize(p[0], 5, bias) << 11) | (quantize(p[1], 6, bias) << 5) | quantize(p[2], 5, bias);
+        out.copy_from_slice(&packed.to_le_bytes());
+    }
+    mark_rows_completed(dst_h);
+
+    RESIZE_OK
+}
+
+/// Convert an f32 to IEEE 754 half-float bits, rounding to nearest even
+/// Magnitudes below 2^-14 become subnormals (or signed zero), magnitudes from the
+/// largest finite half (65504) upward clamp to it, and NaN stays a quiet NaN
+#[inline(always)]
+fn f32_to_f16(value: f32) -> u16 {
+    let bits = value.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let abs = bits & 0x7fff_ffff;
+
+    if abs > 0x7f80_0000 {
+        return sign | 0x7e00;
+    }
+    if abs >= 0x477f_e000 {
+        return sign | 0x7bff;
+    }
+
+    // Round the dropped low `shift` bits of `mantissa` to nearest, ties to even
+    let round = |mantissa: u32, shift: u32| -> u32 {
+        let kept = mantissa >> shift;
+        let rest = mantissa & ((1 << shift) - 1);
+        let half = 1 << (shift - 1);
+        if rest > half || (rest == half && kept & 1 == 1) {
+            kept + 1
+        } else {
+            kept
+        }
+    };
+
+    if abs < 0x3880_0000 {
+        // Below the smallest normal half: subnormal in units of 2^-24, or zero
+        if abs < 0x3300_0000 {
+            return sign;
+        }
+        let exponent = abs >> 23;
+        let mantissa = (abs & 0x007f_ffff) | 0x0080_0000;
+        return sign | round(mantissa, 126 - exponent) as u16;
+    }
+
+    // Rebias the exponent (127 -> 15); a mantissa carry correctly bumps the exponent
+    sign | round(abs - 0x3800_0000, 13) as u16
+}
+
+/// Resize to half-float (f16) linear-light RGBA, e.g. for WebGPU rgba16float textures
+/// The source is decoded to linear light, filtered premultiplied with the taps of
+/// `algorithm` in f32, and written with straight alpha as four IEEE half floats per
+/// pixel (stored as u16) in 0..=1; 0.0 and 1.0 are exact. The destination holds
+/// dst_w * dst_h * 4 values (* 2 bytes)
+/// Returns error code: 0 = success, non-zero = error
+///
+/// # Safety
+/// src_ptr must point to a valid RGBA buffer of the given dimensions and dst_ptr_f16 to
+/// dst_w * dst_h * 4 writable, 2-byte aligned u16 values not overlapping it
+#[no_mangle]
+pub unsafe extern "C" fn resize_rgba_to_f16(
+    src_ptr: *const u8,
+    src_w: u32,
+    src_h: u32,
+    dst_ptr_f16: *mut u16,
+    dst_w: u32,
+    dst_h: u32,
+    algorithm: u32,
+) -> i32 {
+    let src_size = match validate_buffer(src_ptr, src_w, src_h) {
+        Ok(size) => size,
+        Err(code) => return code,
+    };
+    let dst_size = match validate_packed(dst_ptr_f16 as *const u8, dst_w, dst_h, 8) {
+        Ok(size) => size,
+        Err(code) => return code,
+    };
+
+    if !(dst_ptr_f16 as usize).is_multiple_of(std::mem::align_of::<u16>()) {
+        set_last_error(RESIZE_ERR_ALIGNMENT);
+        return RESIZE_ERR_ALIGNMENT;
+    }
+
+    let src_start = src_ptr as usize;
+    let src_end = src_start.saturating_add(src_size);
+    let dst_start = dst_ptr_f16 as usize;
+    let dst_end = dst_start.saturating_add(dst_size);
+    if src_start < dst_end && dst_start < src_end {
+        set_last_error(RESIZE_ERR_OVERLAP);
+        return RESIZE_ERR_OVERLAP;
+    }
+
+    let algorithm = match resolve_algorithm(algorithm, src_w, src_h, dst_w, dst_h) {
+        Ok(a) => a,
+        Err(code) => return code,
+    };
+
+    if let Err(code) = validate_intermediate(dst_w, src_h) {
+        return code;
+    }
+
+    let src = std::slice::from_raw_parts(src_ptr, src_size);
+    let linear = linearize_rgba(src, true);
+    let x_taps = algorithm_taps(src_w, dst_w, algorithm, 0.0);
+    let y_taps = algorithm_taps(src_h, dst_h, algorithm, 0.0);
+    let out = match convolve(&linear, src_w, src_h, dst_w, dst_h, &x_taps, &y_taps) {
+        Ok(out) => out,
+        Err(code) => return code,
+    };
+
+    let dst = std::slice::from_raw_parts_mut(dst_ptr_f16, dst_size / 2);
+    for (d, v) in dst.chunks_exact_mut(4).zip(out.chunks_exact(4)) {
+        let alpha = v[3].clamp(0.0, 1.0);
+        let unweight = if alpha > 1e-6 { 1.0 / alpha } else { 0.0 };
+        for c in 0..3 {
+            d[c] = f32_to_f16((v[c] * unweight).clamp(0.0, 1.0));
+        }
+        d[3] = f32_to_f16(alpha);
+    }
+    mark_rows_completed(dst_h);
+
+    RESIZE_OK
+}
+
+/// Median-cut quantization of RGBA pixels to at most `max_colors` palette entries
+/// Repeatedly splits the box with the widest channel range at the median of that
+/// channel (moved to the nearest value boundary so equal colors stay together); each
+/// final box contributes its mean color and its pixel count
+pub(crate) fn median_cut(pixels: &mut [[u8; 4]], max_colors: usize) -> Vec<([u8; 4], usize)> {
+    // Widest channel of a box and its range
+    let widest = |b: &[[u8; 4]]| -> (usize, u8) {
+        (0..4)
+            .map(|c| {
+                let (lo, hi) = b.iter().fold((u8::MAX, 0), |(lo, hi), p| (lo.min(p[c]), hi.max(p[c])));
+                (c, hi - lo)
+            })
+            .max_by_key(|&(_, range)| range)
+            .unwrap_or((0, 0))
+    };
+
+    let mut boxes = vec![(0, pixels.len())];
+    while boxes.len() < max_colors {
+        let candidate = boxes
+            .iter()
+            .enumerate()
+            .map(|(i, &(start, end))| (i, widest(&pixels[start..end])))
+            .filter(|&(_, (_, range))| range > 0)
+            .max_by_key(|&(_, (_, range))| range);
+        let Some((i, (channel, _))) = candidate else {
+            break;
+        };
+
+        let (start, end) = boxes[i];
+        let slice = &mut pixels[start..end];
+        slice.sort_unstable_by_key(|p| p[channel]);
+        let mid = slice.len() / 2;
+        let value = slice[mid][channel];
+        let below = slice.partition_point(|p| p[channel] < value);
+        let through = slice.partition_point(|p| p[channel] <= value);
+        // The range is non-zero, so at least one boundary lies strictly inside
+        let split = if below > 0 && (mid - below <= through - mid || through == slice.len()) {
+            below
+        } else {
+            through
+        };
+
+        boxes[i] = (start, start + split);
+        boxes.push((start + split, end));
+    }
+
+    boxes
+        .iter()
+        .map(|&(start, end)| {
+            let mut sum = [0u64; 4];
+            for p in &pixels[start..end] {
+                for (s, &v) in sum.iter_mut().zip(p) {
+                    *s += v as u64;
+                }
+            }
+            let n = (end - start) as u64;
+            (sum.map(|s| ((s + n / 2) / n) as u8), end - start)
+        })
+        .collect()
+}
+
+/// Resize and quantize the output to an indexed (paletted) image
+/// The source is resized in RGBA with `algorithm`, a palette of up to `palette_size`
+/// (2..=256) colors is built with median-cut over the resized pixels, and every pixel
+/// is replaced by the index of its nearest palette entry (squared RGBA distance).
+/// `palette_ptr` receives `palette_size` RGBA entries; entries beyond the number of
+/// distinct colors found are zero
+/// Returns error code: 0 = success, non-zero = error
+///
+/// # Safety
+/// src_ptr must point to a valid RGBA buffer of the given dimensions, dst_ptr to
+/// dst_w * dst_h writable bytes and palette_ptr to palette_size * 4 writable bytes;
+/// no two buffers may overlap
+#[no_mangle]
+pub unsafe extern "C" fn resize_rgba_to_indexed(
+    src_ptr: *const u8,
+    src_w: u32,
+    src_h: u32,
+    dst_ptr: *mut u8,
+    palette_ptr: *mut u8,
+    dst_w: u32,
+    dst_h: u32,
+    palette_size: u32,
+    algorithm: u32,
+) -> i32 {
+    let src_size = match validate_buffer(src_ptr, src_w, src_h) {
+        Ok(size) => size,
+        Err(code) => return code,
+    };
+    let dst_size = match validate_packed(dst_ptr, dst_w, dst_h, 1) {
+        Ok(size) => size,
+        Err(code) => return code,
+    };
+
+    if palette_ptr.is_null() {
+        set_last_error(RESIZE_ERR_NULL_PTR);
+        return RESIZE_ERR_NULL_PTR;
+    }
+
+    if !(2..=256).contains(&palette_size) {
+        set_last_error(RESIZE_ERR_INVALID_PARAM);
+        return RESIZE_ERR_INVALID_PARAM;
+    }
+
+    let palette_len = palette_size as usize * 4;
+    let regions = [(src_ptr as usize, src_size), (dst_ptr as usize, dst_size), (palette_ptr as usize, palette_len)];
+    for (i, &(a, a_len)) in regions.iter().enumerate() {
+        for &(b, b_len) in &regions[i + 1..] {
+            if a < b.saturating_add(b_len) && b < a.saturating_add(a_len) {
+                set_last_error(RESIZE_ERR_OVERLAP);
+                return RESIZE_ERR_OVERLAP;
+            }
+        }
+    }
+
+    let src = std::slice::from_raw_parts(src_ptr, src_size);
+    let mut rgba = vec![0u8; dst_size * 4];
+    let code = intermediate_pass(|| resize_slices(src, src_w, src_h, &mut rgba, dst_w, dst_h, algorithm));
+    if code != RESIZE_OK {
+        return code;
+    }
+
+    let mut pixels: Vec<[u8; 4]> = rgba.chunks_exact(4).map(|p| [p[0], p[1], p[2], p[3]]).collect();
+    let colors: Vec<[u8; 4]> = median_cut(&mut pixels, palette_size as usize).into_iter().map(|(c, _)| c).collect();
+
+    let palette = std::slice::from_raw_parts_mut(palette_ptr, palette_len);
+    palette.fill(0);
+    for (entry, color) in palette.chunks_exact_mut(4).zip(&colors) {
+        entry.copy_from_slice(color);
+    }
+
+    // Neighboring pixels often repeat, so remember the last lookup
+    let dst = std::slice::from_raw_parts_mut(dst_ptr, dst_size);
+    let mut last: Option<(&[u8], u8)> = None;
+    for (d, p) in dst.iter_mut().zip(rgba.chunks_exact(4)) {
+        if let Some((color, index)) = last {
+            if color == p {
+                *d = index;
+                continue;
+            }
+        }
+        let distance = |c: &[u8; 4]| -> u32 { c.iter().zip(p).map(|(&a, &b)| (a as i32 - b as i32).pow(2) as u32).sum() };
+        let index = (0..colors.len()).min_by_key(|&i| distance(&colors[i])).unwrap_or(0) as u8;
+        *d = index;
+        last = Some((p, index));
+    }
+    mark_rows_completed(dst_h);
+
+    RESIZE_OK
+}
+
+/// 1D squared Euclidean distance transform (Felzenszwalb & Huttenlocher)
+/// `f` holds per-sample costs (0 at feature pixels, EDT_INFINITY elsewhere); `out[q]` receives
+/// `min over p of (q - p)^2 + f[p]`. `hull` and `bounds` are scratch of f.len() and
+/// f.len() + 1 entries
+fn distance_transform_1d(f: &[f64], out: &mut [f64], hull: &mut [usize], bounds: &mut [f64]) {
+    let n = f.len();
+    let parabola = |q: usize, p: usize| ((f[q] + (q * q) as f64) - (f[p] + (p * p) as f64)) / (2.0 * q as f64 - 2.0 * p as f64);
+
+    let mut k = 0;
+    hull[0] = 0;
+    bounds[0] = f64::NEG_INFINITY;
+    bounds[1] = f64::INFINITY;
+    for q in 1..n {
+        let mut s = parabola(q, hull[k]);
+        while s <= bounds[k] {
+            k -= 1;
+            s = parabola(q, hull[k]);
+        }
+        k += 1;
+        hull[k] = q;
+        bounds[k] = s;
+        bounds[k + 1] = f64::INFINITY;
+    }
+
+    k = 0;
+    for (q, o) in out.iter_mut().enumerate() {
+        while bounds[k + 1] < q as f64 {
+            k += 1;
+        }
+        let d = q as f64 - hull[k] as f64;
+        *o = d * d + f[hull[k]];
+    }
+}
+
+/// Squared distance from every pixel to the nearest pixel where `feature` is true
+/// Separable: columns first, then rows
+fn distance_transform(feature: &[bool], w: usize, h: usize) -> Vec<f64> {
+    let mut grid: Vec<f64> = feature.iter().map(|&f| if f { 0.0 } else { EDT_INFINITY }).collect();
+    let n = w.max(h);
+    let (mut line, mut out) = (vec![0.0f64; n], vec![0.0f64; n]);
+    let (mut hull, mut bounds) = (vec![0usize; n], vec![0.0f64; n + 1]);
+
+    for x in 0..w {
+        for y in 0..h {
+            line[y] = grid[y * w + x];
+        }
+        distance_transform_1d(&line[..h], &mut out[..h], &mut hull, &mut bounds);
+        for y in 0..h {
+            grid[y * w + x] = out[y];
+        }
+    }
+    for row in grid.chunks_exact_mut(w) {
+        line[..w].copy_from_slice(row);
+        distance_transform_1d(&line[..w], row, &mut hull, &mut bounds);
+    }
+
+    grid
+}
+
+/// Resize an alpha mask into a single-channel signed distance field (scalable glyphs)
+/// Pixels with alpha >= 128 are inside. At source resolution each pixel gets its
+/// Euclidean distance to the 50% contour (half a pixel short of the nearest pixel on
+/// the other side), positive inside and negative outside; the field is downscaled with
+/// a widened tent filter and stored as `128 + clamp(dist / spread_px * 127, -128, 127)`,
+/// so 128 is the edge. `spread_px` is in source pixels and must be finite and positive
+/// Returns error code: 0 = success, non-zero = error
+///
+/// # Safety
+/// src_ptr must point to a valid RGBA buffer of the given dimensions and dst_ptr to
+/// dst_w * dst_h writable bytes not overlapping it
+#[no_mangle]
+pub unsafe extern "C" fn resize_mask_to_sdf(
+    src_ptr: *const u8,
+    src_w: u32,
+    src_h: u32,
+    dst_ptr: *mut u8,
+    dst_w: u32,
+    dst_h: u32,
+    spread_px: f32,
+) -> i32 {
+    let src_size = match validate_buffer(src_ptr, src_w, src_h) {
+        Ok(size) => size,
+        Err(code) => return code,
+    };
+    let dst_size = match validate_packed(dst_ptr, dst_w, dst_h, 1) {
+        Ok(size) => size,
+        Err(code) => return code,
+    };
+
+    if !spread_px.is_finite() || spread_px <= 0.0 {
+        set_last_error(RESIZE_ERR_INVALID_PARAM);
+        return RESIZE_ERR_INVALID_PARAM;
+    }
+
+    let (src_start, dst_start) = (src_ptr as usize, dst_ptr as usize);
+    if src_start < dst_start.saturating_add(dst_size) && dst_start < src_start.saturating_add(src_size) {
+        set_last_error(RESIZE_ERR_OVERLAP);
+        return RESIZE_ERR_OVERLAP;
+    }
+
+    let src = std::slice::from_raw_parts(src_ptr, src_size);
+    let (w, h) = (src_w as usize, src_h as usize);
+    let inside: Vec<bool> = src.chunks_exact(4).map(|p| p[3] >= 128).collect();
+    let outside: Vec<bool> = inside.iter().map(|&i| !i).collect();
+    let to_outside = distance_transform(&outside, w, h);
+    let to_inside = distance_transform(&inside, w, h);
+    let field: Vec<f32> = inside
+        .iter()
+        .zip(to_outside.iter().zip(&to_inside))
+        .map(|(&i, (&out_d, &in_d))| if i { out_d.sqrt() as f32 - 0.5 } else { 0.5 - in_d.sqrt() as f32 })
+        .collect();
+
+    // Separable tent downscale of the single-channel field
+    let x_taps = AxisTaps::new(src_w, dst_w, 1.0, &tent_kernel);
+    let y_taps = AxisTaps::new(src_h, dst_h, 1.0, &tent_kernel);
+    let mut temp = vec![0.0f32; dst_w as usize * h];
+    for (row, temp_row) in field.chunks_exact(w).zip(temp.chunks_exact_mut(dst_w as usize)) {
+        for (x, t) in temp_row.iter_mut().enumerate() {
+            *t = x_taps.taps(x).map(|(sx, weight)| row[sx] * weight).sum();
+        }
+    }
+
+    let dst = std::slice::from_raw_parts_mut(dst_ptr, dst_size);
+    for (y, dst_row) in dst.chunks_exact_mut(dst_w as usize).enumerate() {
+        for (x, d) in dst_row.iter_mut().enumerate() {
+            let dist: f32 = y_taps.taps(y).map(|(sy, weight)| temp[sy * dst_w as usize + x] * weight).sum();
+            *d = (128.0 + (dist / spread_px * 127.0).clamp(-128.0, 127.0)).round() as u8;
+        }
+        mark_row_completed(y as u32);
+    }
+
+    RESIZE_OK
+}
@@ -0,0 +1,127 @@
+//! Floating-point (HDR) image paths
+//! Resizes that take linear-light f32 RGBA input, resample it in linear light and
+//! map the result into 8-bit sRGB output
+
+#[cfg(not(any(target_arch = "wasm32", test)))]
+compile_error!("This module only supports wasm32 target");
+
+use crate::gamma_simd::{init_gamma_luts, linear_to_srgb_fast};
+use crate::separable::{algorithm_taps, convolve};
+use crate::{
+    mark_rows_completed, resolve_algorithm, set_last_error, validate_buffer, validate_intermediate,
+    RESIZE_ERR_ALIGNMENT, RESIZE_ERR_INVALID_PARAM, RESIZE_ERR_OVERLAP, RESIZE_OK, RESIZE_TONE_CLIP,
+    RESIZE_TONE_FILMIC, RESIZE_TONE_REINHARD,
+};
+
+/// Map an exposure-scaled linear value (>= 0) into [0, 1] with the selected tone curve
+#[inline(always)]
+fn tone_map(x: f32, tone: u32) -> f32 {
+    match tone {
+        RESIZE_TONE_CLIP => x.min(1.0),
+        RESIZE_TONE_REINHARD => x / (1.0 + x),
+        _ => {
+            // RESIZE_TONE_FILMIC: ACES filmic fit (Narkowicz 2015)
+            let mapped = (x * (2.51 * x + 0.03)) / (x * (2.43 * x + 0.59) + 0.14);
+            mapped.clamp(0.0, 1.0)
+        }
+    }
+}
+
+/// Resize linear-light f32 RGBA to 8-bit sRGB with tone mapping
+/// The source holds straight-alpha linear RGB (values above 1.0 allowed) and alpha in
+/// [0, 1]; non-finite and negative samples are treated as 0. Color is premultiplied,
+/// resampled in linear light with the taps of `algorithm`, then scaled by `exposure`,
+/// compressed into [0, 1] by `tone` (0 = clip, 1 = Reinhard, 2 = filmic) and sRGB-encoded
+/// Returns error code: 0 = success, non-zero = error
+///
+/// # Safety
+/// src_ptr_f32 must point to src_w * src_h * 4 f32-aligned f32 values and dst_ptr_u8 to
+/// a valid RGBA buffer of the given dimensions; the buffers must not overlap
+#[no_mangle]
+pub unsafe extern "C" fn resize_rgbaf32_to_srgb_u8(
+    src_ptr_f32: *const f32,
+    src_w: u32,
+    src_h: u32,
+    dst_ptr_u8: *mut u8,
+    dst_w: u32,
+    dst_h: u32,
+    tone: u32,
+    exposure: f32,
+    algorithm: u32,
+) -> i32 {
+    // Each source pixel is 16 bytes; validate_buffer counts the 4 channels
+    let src_len = match validate_buffer(src_ptr_f32 as *const u8, src_w, src_h) {
+        Ok(channels) => channels,
+        Err(code) => return code,
+    };
+    // The source is read as an f32 slice, so check its alignment as f32 explicitly
+    // rather than relying on the RGBA byte rule above
+    if !(src_ptr_f32 as usize).is_multiple_of(std::mem::align_of::<f32>()) {
+        set_last_error(RESIZE_ERR_ALIGNMENT);
+        return RESIZE_ERR_ALIGNMENT;
+    }
+    let dst_size = match validate_buffer(dst_ptr_u8, dst_w, dst_h) {
+        Ok(size) => size,
+        Err(code) => return code,
+    };
+
+    let src_start = src_ptr_f32 as usize;
+    let src_end = src_start.saturating_add(src_len * std::mem::size_of::<f32>());
+    let dst_start = dst_ptr_u8 as usize;
+    let dst_end = dst_start.saturating_add(dst_size);
+    if src_start < dst_end && dst_start < src_end {
+        set_last_error(RESIZE_ERR_OVERLAP);
+        return RESIZE_ERR_OVERLAP;
+    }
+
+    if tone > RESIZE_TONE_FILMIC || !exposure.is_finite() || exposure < 0.0 {
+        set_last_error(RESIZE_ERR_INVALID_PARAM);
+        return RESIZE_ERR_INVALID_PARAM;
+    }
+
+    let algorithm = match resolve_algorithm(algorithm, src_w, src_h, dst_w, dst_h) {
+        Ok(a) => a,
+        Err(code) => return code,
+    };
+
+    if let Err(code) = validate_intermediate(dst_w, src_h) {
+        return code;
+    }
+
+    let src = std::slice::from_raw_parts(src_ptr_f32, src_len);
+    let dst = std::slice::from_raw_parts_mut(dst_ptr_u8, dst_size);
+
+    let sanitize = |v: f32| if v.is_finite() { v.max(0.0) } else { 0.0 };
+    let premultiplied: Vec<f32> = src
+        .chunks_exact(4)
+        .flat_map(|p| {
+            let a = sanitize(p[3]).min(1.0);
+            [sanitize(p[0]) * a, sanitize(p[1]) * a, sanitize(p[2]) * a, a]
+        })
+        .collect();
+
+    let x_taps = algorithm_taps(src_w, dst_w, algorithm, 0.0);
+    let y_taps = algorithm_taps(src_h, dst_h, algorithm, 0.0);
+    let out = match convolve(&premultiplied, src_w, src_h, dst_w, dst_h, &x_taps, &y_taps) {
+        Ok(out) => out,
+        Err(code) => return code,
+    };
+
+    init_gamma_luts();
+    for (d, v) in dst.chunks_exact_mut(4).zip(out.chunks_exact(4)) {
+        let a = v[3].clamp(0.0, 1.0);
+        if a <= 0.0 {
+            d.fill(0);
+            continue;
+        }
+        for c in 0..3 {
+            // Lanczos lobes can dip below zero next to bright highlights
+            let linear = (v[c] / a).max(0.0) * exposure;
+            d[c] = (linear_to_srgb_fast(tone_map(linear, tone)) * 255.0 + 0.5) as u8;
+        }
+        d[3] = (a * 255.0 + 0.5) as u8;
+    }
+    mark_rows_completed(dst_h);
+
+    RESIZE_OK
+}
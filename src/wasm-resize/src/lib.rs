@@ -4,26 +4,146 @@
 // Compile-time assertion: This crate only supports wasm32 target
 // This ensures the code is only compiled for WebAssembly, preventing accidental
 // compilation for other targets where the code may not work correctly.
-#[cfg(not(target_arch = "wasm32"))]
+// Host builds are allowed for the unit tests only (see test.sh)
+#[cfg(not(any(target_arch = "wasm32", test)))]
 compile_error!("This crate only supports wasm32 target");
 
 use std::alloc::{alloc, dealloc, Layout};
 use std::cell::{Cell, RefCell};
 
 // Gamma-correct SIMD module
-#[cfg(target_arch = "wasm32")]
+#[cfg(any(target_arch = "wasm32", test))]
 mod gamma_simd;
 
 // SIMD optimization helpers for regular resampling
-#[cfg(target_arch = "wasm32")]
+#[cfg(any(target_arch = "wasm32", test))]
 mod simd_helpers;
 
-#[cfg(target_arch = "wasm32")]
-use simd_helpers::{copy_4_pixels_simd, bilinear_interp_4_pixels};
+// Generic separable resampling with arbitrary kernels
+#[cfg(any(target_arch = "wasm32", test))]
+mod separable;
+
+// Region-aware resize variants (borders, clip rectangles, sub-rectangles)
+#[cfg(any(target_arch = "wasm32", test))]
+mod regions;
+
+// Color adjustments (white balance, ...) applied around the resize pipeline
+#[cfg(any(target_arch = "wasm32", test))]
+mod color;
+
+// Geometric transforms (rotation, transpose) combined with resampling
+#[cfg(any(target_arch = "wasm32", test))]
+mod transform;
+
+// Floating-point (HDR) input paths with tone mapping
+#[cfg(any(target_arch = "wasm32", test))]
+mod hdr;
+
+// Image analysis (edge maps, ...) computed alongside resizing
+#[cfg(any(target_arch = "wasm32", test))]
+mod analysis;
+
+// Planar (single-plane, one byte per sample) resampling
+#[cfg(any(target_arch = "wasm32", test))]
+mod planar;
+
+// Packed, reduced-depth and indexed output formats (RGB565, paletted, f16, SDF, ...)
+#[cfg(any(target_arch = "wasm32", test))]
+mod formats;
+
+// Spatial post-filters (smoothing, sharpening, scrims, box blur, ...) applied to resized output
+#[cfg(any(target_arch = "wasm32", test))]
+mod filters;
+
+// Per-phase timing of the resampling cores (requires a host clock import)
+#[cfg(all(any(target_arch = "wasm32", test), feature = "timing"))]
+mod profiling;
+
+#[cfg(any(target_arch = "wasm32", test))]
+use separable::{FIXED_TEMP_BITS, FIXED_WEIGHT_BITS};
+
+#[cfg(any(target_arch = "wasm32", test))]
+use simd_helpers::{bilinear_interp_4_pixels, bilinear_interp_4_pixels_scalar, pack_f32x4_to_u8};
+
+#[cfg(target_feature = "simd128")]
+use simd_helpers::copy_4_pixels_simd;
 
 // Export gamma-correct resize function for JavaScript
-#[cfg(target_arch = "wasm32")]
-pub use gamma_simd::resize_rgba_gamma_bilinear;
+#[cfg(any(target_arch = "wasm32", test))]
+pub use gamma_simd::{
+    alpha_range, build_laplacian_pyramid_rgba, cache_source, free_cached_source,
+    generate_mipchain_rgba, resize_from_cache, resize_rgba_auto_alpha, resize_rgba_gamma_area,
+    resize_rgba_gamma_bilinear, resize_rgba_gamma_per_channel, resize_rgba_text_aware,
+    set_gamma_approx, CachedSource,
+};
+
+// Export separable-kernel resize functions for JavaScript
+#[cfg(any(target_arch = "wasm32", test))]
+pub use separable::{
+    describe_sample, eval_kernel, resize_normalmap_rgba, resize_rgba_bicubic_limited,
+    resize_rgba_custom_kernel, resize_rgba_dithered_seeded, resize_rgba_fixedpoint,
+    resize_rgba_lanczos_blur, resize_rgba_lanczos_overshoot, resize_rgba_monotonic,
+    resize_rgba_prefilter_bilinear, resize_rgba_weighted, set_intermediate_precision,
+};
+
+// Export region-aware resize functions for JavaScript
+#[cfg(any(target_arch = "wasm32", test))]
+pub use regions::{
+    autocrop_alpha_bounds, get_transparent_skip_count, resize_rgba_atlas_slot,
+    resize_rgba_circle_avatar, resize_rgba_clip, resize_rgba_crop, resize_rgba_deletterbox,
+    resize_rgba_grid_overlay, resize_rgba_keep_corners, resize_rgba_mosaic, resize_rgba_over_dst,
+    resize_rgba_preserve_border, resize_rgba_roi_quality, resize_rgba_skip_transparent,
+    resize_rgba_tile_fill, resize_rgba_update, resize_rgba_watermark, resize_rgba_window,
+    MosaicSource,
+};
+
+// Export color adjustment functions for JavaScript
+#[cfg(any(target_arch = "wasm32", test))]
+pub use color::{
+    alpha_bleed_rgba, auto_white_balance_rgba, resize_rgba_alpha_snap, resize_rgba_colormatrix,
+    resize_rgba_curves, resize_rgba_dematte, resize_rgba_oklab,
+};
+
+// Export rotation functions for JavaScript
+#[cfg(any(target_arch = "wasm32", test))]
+pub use transform::{
+    resize_rgba_flipped, resize_rgba_warp, resize_rotate_rgba, resize_transpose_rgba, rotate_rgba_90,
+};
+
+// Export HDR resize functions for JavaScript
+#[cfg(any(target_arch = "wasm32", test))]
+pub use hdr::resize_rgbaf32_to_srgb_u8;
+
+// Export image analysis functions for JavaScript
+#[cfg(any(target_arch = "wasm32", test))]
+pub use analysis::{
+    phash_rgba, psnr_rgba, resize_residual_rgba, resize_rgba_channel_stats,
+    resize_rgba_compare_nearest, resize_rgba_coverage, resize_rgba_ensemble, resize_rgba_focus_stack,
+    resize_rgba_hashed, resize_rgba_with_edges, resize_rgba_with_palette,
+    weighted_average_color_rgba,
+};
+
+// Export planar resize functions for JavaScript
+#[cfg(any(target_arch = "wasm32", test))]
+pub use planar::{
+    resize_alpha_edge_aware, resize_plane_phase, resize_rle_mask, resize_yuv420_to_rgba,
+};
+
+// Export packed output format functions for JavaScript
+#[cfg(any(target_arch = "wasm32", test))]
+pub use formats::{
+    resize_mask_to_sdf, resize_rgba_to_f16, resize_rgba_to_indexed, resize_rgba_to_rgb565,
+};
+
+// Export spatial filter functions for JavaScript
+#[cfg(any(target_arch = "wasm32", test))]
+pub use filters::{
+    box_blur_rgba_integral, resize_rgba_adaptive_sharpen, resize_rgba_compress_prep, resize_rgba_scrim,
+};
+
+// Export profiling functions for JavaScript
+#[cfg(all(any(target_arch = "wasm32", test), feature = "timing"))]
+pub use profiling::resize_rgba_lanczos_profiled;
 
 // Error codes returned by resize functions
 // 0 = success, non-zero = error
@@ -34,13 +154,65 @@ pub const RESIZE_ERR_OVERFLOW: i32 = 3;
 pub const RESIZE_ERR_MEMORY: i32 = 4;
 pub const RESIZE_ERR_ALIGNMENT: i32 = 5;
 pub const RESIZE_ERR_OVERLAP: i32 = 6;
+pub const RESIZE_ERR_INVALID_PARAM: i32 = 7;
+pub const RESIZE_ERR_CANCELLED: i32 = 8;
+
+// Algorithm identifiers accepted by resize variants taking an explicit `algorithm` parameter
+// NEAREST/BILINEAR/LANCZOS match the values returned by select_resize_algorithm,
+// AUTO defers to that heuristic
+pub const RESIZE_ALGO_NEAREST: u32 = 0;
+pub const RESIZE_ALGO_BILINEAR: u32 = 1;
+pub const RESIZE_ALGO_LANCZOS: u32 = 2;
+pub const RESIZE_ALGO_AUTO: u32 = 3;
+
+// Intermediate precision modes for set_intermediate_precision
+pub const RESIZE_PRECISION_F32: u32 = 0;
+pub const RESIZE_PRECISION_FIXED: u32 = 1;
+pub const RESIZE_PRECISION_F64: u32 = 2;
+
+// Gamma transfer curves for set_gamma_approx
+pub const RESIZE_GAMMA_SRGB: u32 = 0;
+pub const RESIZE_GAMMA_POW22: u32 = 1;
+
+// Tone curves for resize_rgbaf32_to_srgb_u8
+pub const RESIZE_TONE_CLIP: u32 = 0;
+pub const RESIZE_TONE_REINHARD: u32 = 1;
+pub const RESIZE_TONE_FILMIC: u32 = 2;
+
+// YUV -> RGB conversion matrices for resize_yuv420_to_rgba
+// LIMITED expects video-range samples (Y 16..235, UV 16..240), FULL the whole 0..255
+pub const RESIZE_YUV_BT601_LIMITED: u32 = 0;
+pub const RESIZE_YUV_BT709_LIMITED: u32 = 1;
+pub const RESIZE_YUV_BT601_FULL: u32 = 2;
+pub const RESIZE_YUV_BT709_FULL: u32 = 3;
+
+// Kernel shapes for eval_kernel
+pub const RESIZE_KERNEL_LANCZOS: u32 = 0;
+pub const RESIZE_KERNEL_TRIANGLE: u32 = 1;
+pub const RESIZE_KERNEL_CATMULL_ROM: u32 = 2;
+pub const RESIZE_KERNEL_MITCHELL: u32 = 3;
+pub const RESIZE_KERNEL_GAUSSIAN: u32 = 4;
+
+// Reported by get_last_completed_row before any destination row has been written
+pub const RESIZE_NO_ROW_COMPLETED: u32 = u32::MAX;
 
 // Thread-local storage for last error code (wasm32 is effectively single-threaded,
 // but this keeps the API future-proof and explicit)
 thread_local! {
     static LAST_ERROR_CODE: Cell<i32> = Cell::new(RESIZE_OK);
+    // Last destination row the current resize finished writing
+    static LAST_COMPLETED_ROW: Cell<u32> = const { Cell::new(RESIZE_NO_ROW_COMPLETED) };
+    // Nesting depth of intermediate passes whose rows are reworked before they are final
+    static INTERMEDIATE_DEPTH: Cell<u32> = const { Cell::new(0) };
+    // Whether the current resize has taken a SIMD path (see last_resize_used_simd)
+    static SIMD_PATH_TAKEN: Cell<bool> = const { Cell::new(false) };
+    // Set by request_cancel, polled by the row loops through cancel_due
+    static CANCEL_REQUESTED: Cell<bool> = const { Cell::new(false) };
 }
 
+// Rows between cancellation checks in the row loops
+const CANCEL_POLL_ROWS: u32 = 32;
+
 // Thread-local reusable buffers for LUT computation
 // These buffers are reused across resize calls to avoid repeated heap allocations
 thread_local! {
@@ -48,12 +220,199 @@ thread_local! {
     static X0_INDICES_BILINEAR: RefCell<Vec<usize>> = RefCell::new(Vec::new());
     static X1_INDICES_BILINEAR: RefCell<Vec<usize>> = RefCell::new(Vec::new());
     static FX_VALUES_BILINEAR: RefCell<Vec<f32>> = RefCell::new(Vec::new());
-    // Lanczos separable convolution buffers
-    static LANCZOS_X_WEIGHTS: RefCell<Vec<Vec<f32>>> = RefCell::new(Vec::new()); // Precomputed X-axis weights
-    static LANCZOS_Y_WEIGHTS: RefCell<Vec<Vec<f32>>> = RefCell::new(Vec::new()); // Precomputed Y-axis weights
-    static LANCZOS_X_INDICES: RefCell<Vec<Vec<i32>>> = RefCell::new(Vec::new()); // Source pixel indices for X
-    static LANCZOS_Y_INDICES: RefCell<Vec<Vec<i32>>> = RefCell::new(Vec::new()); // Source pixel indices for Y
-    static LANCZOS_TEMP_BUFFER: RefCell<Vec<f32>> = RefCell::new(Vec::new()); // Intermediate buffer for separable convolution
+    // Lanczos separable convolution buffers (tap tables + intermediate buffer)
+    static LANCZOS_STORAGE: RefCell<LanczosStorage> = const { RefCell::new(LanczosStorage::new()) };
+    // LUTs, tap tables and Pass-1 ring of the streaming exports (see RowStream)
+    static ROW_STREAM_STORAGE: RefCell<RowStreamStorage> = RefCell::new(RowStreamStorage::default());
+    // Single destination row handed to the resize_rgba_row_sink callback
+    static ROW_SINK_BUFFER: RefCell<Vec<u8>> = const { RefCell::new(Vec::new()) };
+    // Tile band plus packed tile handed to the resize_rgba_tile_sink callback
+    static TILE_SINK_BUFFER: RefCell<Vec<u8>> = const { RefCell::new(Vec::new()) };
+    // Contiguous frame the strided-destination exports resize into before copying rows out
+    static STRIDED_FRAME: RefCell<Vec<u8>> = const { RefCell::new(Vec::new()) };
+}
+
+// Maximum number of taps per destination coordinate for the 3-lobed Lanczos kernel
+// The window spans center - 2 ..= center + 3, i.e. 2 * LANCZOS_A source pixels
+const LANCZOS_A: f32 = 3.0;
+const LANCZOS_MAX_TAPS: usize = 6;
+
+/// Owned Lanczos working memory kept in thread-local storage between calls
+/// Each intermediate precision keeps its own Pass-1 buffer, so switching precision
+/// reuses what the earlier calls at that precision grew
+#[derive(Default)]
+struct LanczosStorage {
+    x_weights: Vec<f32>,
+    x_indices: Vec<i32>,
+    x_counts: Vec<u32>,
+    y_weights: Vec<f32>,
+    y_indices: Vec<i32>,
+    y_counts: Vec<u32>,
+    temp: Vec<f32>,
+    temp_f64: Vec<f64>,
+    temp_fixed: Vec<i32>,
+    fixed_weights: Vec<i32>,
+}
+
+impl LanczosStorage {
+    const fn new() -> Self {
+        LanczosStorage {
+            x_weights: Vec::new(),
+            x_indices: Vec::new(),
+            x_counts: Vec::new(),
+            y_weights: Vec::new(),
+            y_indices: Vec::new(),
+            y_counts: Vec::new(),
+            temp: Vec::new(),
+            temp_f64: Vec::new(),
+            temp_fixed: Vec::new(),
+            fixed_weights: Vec::new(),
+        }
+    }
+
+    /// Resize the owned vectors for the given dimensions (reusing capacity) and borrow them
+    /// with a `temp` of `temp_len` values of the sample type of `precision`
+    /// (see set_intermediate_precision)
+    fn work(&mut self, dst_w: u32, dst_h: u32, temp_len: usize, precision: u32) -> LanczosWork<'_> {
+        let fixed = precision == RESIZE_PRECISION_FIXED;
+        let (tables, (temp, temp_f64, temp_fixed)) = self.tables(dst_w, dst_h, fixed);
+        match precision {
+            RESIZE_PRECISION_F64 => {
+                temp_f64.resize(temp_len, 0.0);
+                LanczosWork::F64(tables.with_temp(temp_f64))
+            }
+            RESIZE_PRECISION_FIXED => {
+                temp_fixed.resize(temp_len, 0);
+                LanczosWork::Fixed(tables.with_temp(temp_fixed))
+            }
+            _ => {
+                temp.resize(temp_len, 0.0);
+                LanczosWork::F32(tables.with_temp(temp))
+            }
+        }
+    }
+
+    /// Convert the filled X and Y tap tables into `fixed_weights`, as lanczos_core does per
+    /// call; no-op unless the table was sized for the fixed-point precision
+    fn fill_fixed_weights(&mut self) {
+        let x_taps = self.x_counts.len() * LANCZOS_MAX_TAPS;
+        if self.fixed_weights.len() < x_taps + self.y_counts.len() * LANCZOS_MAX_TAPS {
+            return;
+        }
+        let (x_fixed, y_fixed) = self.fixed_weights.split_at_mut(x_taps);
+        i32::tap_weights(&self.x_counts, &self.x_weights, x_fixed);
+        i32::tap_weights(&self.y_counts, &self.y_weights, y_fixed);
+    }
+
+    /// Resize and borrow the tap tables (the fixed-point weight table only when `fixed`
+    /// is set), handing back the per-precision temp vectors untouched
+    #[allow(clippy::type_complexity)]
+    fn tables(
+        &mut self,
+        dst_w: u32,
+        dst_h: u32,
+        fixed: bool,
+    ) -> (LanczosBuffers<'_, ()>, (&mut Vec<f32>, &mut Vec<f64>, &mut Vec<i32>)) {
+        let x_taps = dst_w as usize * LANCZOS_MAX_TAPS;
+        let y_taps = dst_h as usize * LANCZOS_MAX_TAPS;
+
+        self.x_weights.resize(x_taps, 0.0);
+        self.x_indices.resize(x_taps, 0);
+        self.x_counts.resize(dst_w as usize, 0);
+        self.y_weights.resize(y_taps, 0.0);
+        self.y_indices.resize(y_taps, 0);
+        self.y_counts.resize(dst_h as usize, 0);
+        self.fixed_weights.resize(if fixed { x_taps + y_taps } else { 0 }, 0);
+
+        let LanczosStorage {
+            x_weights,
+            x_indices,
+            x_counts,
+            y_weights,
+            y_indices,
+            y_counts,
+            temp,
+            temp_f64,
+            temp_fixed,
+            fixed_weights,
+        } = self;
+        let tables = LanczosBuffers {
+            x_weights,
+            x_indices,
+            x_counts,
+            y_weights,
+            y_indices,
+            y_counts,
+            fixed_weights,
+            temp: &mut [],
+        };
+        (tables, (temp, temp_f64, temp_fixed))
+    }
+}
+
+/// Borrowed working memory for the separable Lanczos passes
+/// Tap tables use a fixed stride of LANCZOS_MAX_TAPS entries per destination coordinate,
+/// with the number of valid taps stored in the matching `*_counts` entry. `temp` holds
+/// the Pass-1 results as LanczosSample values (f32 unless another precision is selected).
+/// `fixed_weights` receives the X then Y weights in fixed point; it is only used, and
+/// only needs room for both tables, with the fixed-point precision
+struct LanczosBuffers<'a, T = f32> {
+    x_weights: &'a mut [f32],
+    x_indices: &'a mut [i32],
+    x_counts: &'a mut [u32],
+    y_weights: &'a mut [f32],
+    y_indices: &'a mut [i32],
+    y_counts: &'a mut [u32],
+    fixed_weights: &'a mut [i32],
+    temp: &'a mut [T],
+}
+
+impl<'a, T> LanczosBuffers<'a, T> {
+    /// The same tap tables with a different intermediate buffer
+    fn with_temp<U>(self, temp: &'a mut [U]) -> LanczosBuffers<'a, U> {
+        let LanczosBuffers { x_weights, x_indices, x_counts, y_weights, y_indices, y_counts, fixed_weights, .. } =
+            self;
+        LanczosBuffers { x_weights, x_indices, x_counts, y_weights, y_indices, y_counts, fixed_weights, temp }
+    }
+}
+
+/// Lanczos working memory with the Pass-1 buffer in the selected intermediate precision
+enum LanczosWork<'a> {
+    F32(LanczosBuffers<'a, f32>),
+    F64(LanczosBuffers<'a, f64>),
+    Fixed(LanczosBuffers<'a, i32>),
+}
+
+/// Destination sub-rectangle written by the resampling cores
+/// Pixels outside the rectangle are left untouched
+/// `origin_y` is the destination row stored first in the output slice; it is 0 when
+/// the slice holds the whole frame, and equals `y` when it holds only the rectangle's rows
+#[derive(Clone, Copy)]
+struct DstRect {
+    x: u32,
+    y: u32,
+    w: u32,
+    h: u32,
+    origin_y: u32,
+}
+
+impl DstRect {
+    /// Rectangle covering the whole destination
+    #[inline(always)]
+    const fn full(w: u32, h: u32) -> Self {
+        DstRect { x: 0, y: 0, w, h, origin_y: 0 }
+    }
+}
+
+/// Storage row holding logical (top-down) source row `y`
+/// Bottom-up sources store the top of the image last
+#[inline(always)]
+fn src_row(y: u32, src_h: u32, bottom_up: bool) -> u32 {
+    if bottom_up {
+        src_h - 1 - y
+    } else {
+        y
+    }
 }
 
 #[inline(always)]
@@ -61,6 +420,63 @@ fn set_last_error(code: i32) {
     LAST_ERROR_CODE.with(|c| c.set(code));
 }
 
+/// Record that destination row `y` holds its final output
+/// Ignored inside intermediate_pass, whose rows are overwritten again later
+#[inline(always)]
+fn mark_row_completed(y: u32) {
+    if INTERMEDIATE_DEPTH.with(|d| d.get()) == 0 {
+        LAST_COMPLETED_ROW.with(|r| r.set(y));
+    }
+}
+
+/// Record that all `rows` destination rows hold their final output, for stores that
+/// write the whole frame at once
+#[inline(always)]
+fn mark_rows_completed(rows: u32) {
+    if let Some(last) = rows.checked_sub(1) {
+        mark_row_completed(last);
+    }
+}
+
+/// Reset the per-resize row and SIMD tracking when a resize starts
+/// Resizes nested in an intermediate_pass are part of the outer one and keep its state
+#[inline(always)]
+fn begin_resize() {
+    if INTERMEDIATE_DEPTH.with(|d| d.get()) == 0 {
+        LAST_COMPLETED_ROW.with(|r| r.set(RESIZE_NO_ROW_COMPLETED));
+        SIMD_PATH_TAKEN.with(|t| t.set(false));
+    }
+}
+
+/// Run a pass whose output is post-processed afterwards without reporting its rows as
+/// completed, so each row is reported once, when its final value is stored
+fn intermediate_pass<R>(pass: impl FnOnce() -> R) -> R {
+    INTERMEDIATE_DEPTH.with(|d| d.set(d.get() + 1));
+    let result = pass();
+    INTERMEDIATE_DEPTH.with(|d| d.set(d.get() - 1));
+    result
+}
+
+/// Whether a cancellation check is due after finishing row `i` of a loop over `rows`
+/// rows and the flag is set; records RESIZE_ERR_CANCELLED when it is
+/// The first and last rows are always checked, so short loops can be cancelled too
+#[inline(always)]
+pub(crate) fn cancel_due(i: u32, rows: u32) -> bool {
+    let poll = i == 0 || i + 1 == rows || (i + 1).is_multiple_of(CANCEL_POLL_ROWS);
+    if !poll || !CANCEL_REQUESTED.with(|c| c.get()) {
+        return false;
+    }
+    set_last_error(RESIZE_ERR_CANCELLED);
+    true
+}
+
+/// Record that the current resize ran a SIMD branch
+#[cfg(target_feature = "simd128")]
+#[inline(always)]
+pub(crate) fn mark_simd_used() {
+    SIMD_PATH_TAKEN.with(|t| t.set(true));
+}
+
 /// Allocate memory (exported for JavaScript to allocate buffers)
 /// Returns null pointer on failure
 /// 
@@ -114,6 +530,53 @@ pub unsafe extern "C" fn dealloc_memory(ptr: *mut u8, size: usize) {
     dealloc(ptr, layout);
 }
 
+/// Deallocate several buffers in one call (avoids one JS boundary crossing per buffer)
+/// `ptrs` and `sizes` are parallel arrays of `count` memory offsets and byte sizes;
+/// null entries are skipped. Returns the number of buffers freed, or 0 with the
+/// last error set when either array pointer is null or misaligned
+///
+/// # Safety
+/// Each non-null entry must be a live allocation from alloc_memory with the matching size
+#[no_mangle]
+pub unsafe extern "C" fn dealloc_many(ptrs: *const u32, sizes: *const usize, count: u32) -> i32 {
+    if ptrs.is_null() || sizes.is_null() {
+        set_last_error(RESIZE_ERR_NULL_PTR);
+        return 0;
+    }
+
+    if !(ptrs as usize).is_multiple_of(std::mem::align_of::<u32>())
+        || !(sizes as usize).is_multiple_of(std::mem::align_of::<usize>())
+    {
+        set_last_error(RESIZE_ERR_ALIGNMENT);
+        return 0;
+    }
+
+    let ptrs = std::slice::from_raw_parts(ptrs, count as usize);
+    let sizes = std::slice::from_raw_parts(sizes, count as usize);
+    let freed = dealloc_entries(ptrs.iter().map(|&ptr| ptr as usize).zip(sizes.iter().copied()));
+
+    set_last_error(RESIZE_OK);
+    freed
+}
+
+/// Free path of dealloc_many over (address, size) entries, skipping null or empty ones
+/// Returns the number of buffers freed. Addresses are full usize values here, so the
+/// loop can be exercised on 64-bit test hosts, where u32 offsets cannot hold a pointer
+///
+/// # Safety
+/// Each non-null entry must be a live allocation from alloc_memory with the matching size
+unsafe fn dealloc_entries(entries: impl Iterator<Item = (usize, usize)>) -> i32 {
+    let mut freed = 0i32;
+    for (ptr, size) in entries {
+        if ptr == 0 || size == 0 {
+            continue;
+        }
+        dealloc_memory(ptr as *mut u8, size);
+        freed += 1;
+    }
+    freed
+}
+
 /// Get last error message (for debugging)
 /// Returns a pointer to a static string, or null
 #[no_mangle]
@@ -126,6 +589,8 @@ pub extern "C" fn get_last_error() -> *const u8 {
     static ERR_MEMORY_MSG: &[u8] = b"Memory error\0";
     static ERR_ALIGNMENT_MSG: &[u8] = b"Pointer alignment error\0";
     static ERR_OVERLAP_MSG: &[u8] = b"Memory regions overlap\0";
+    static ERR_INVALID_PARAM_MSG: &[u8] = b"Invalid parameter\0";
+    static ERR_CANCELLED_MSG: &[u8] = b"Cancelled\0";
     static ERR_UNKNOWN_MSG: &[u8] = b"Unknown error\0";
 
     let code = LAST_ERROR_CODE.with(|c| c.get());
@@ -137,10 +602,171 @@ pub extern "C" fn get_last_error() -> *const u8 {
         RESIZE_ERR_MEMORY => ERR_MEMORY_MSG.as_ptr(),
         RESIZE_ERR_ALIGNMENT => ERR_ALIGNMENT_MSG.as_ptr(),
         RESIZE_ERR_OVERLAP => ERR_OVERLAP_MSG.as_ptr(),
+        RESIZE_ERR_INVALID_PARAM => ERR_INVALID_PARAM_MSG.as_ptr(),
+        RESIZE_ERR_CANCELLED => ERR_CANCELLED_MSG.as_ptr(),
         _ => ERR_UNKNOWN_MSG.as_ptr(),
     }
 }
 
+/// Get the last destination row written by the most recent resize (for debugging)
+/// Reset to RESIZE_NO_ROW_COMPLETED when a resize starts; after a failure or a
+/// cancellation the rows up to and including the returned one hold valid output. Rows
+/// are reported once, when their final value is stored, so intermediate and
+/// post-processing passes never report a row early; operations writing several outputs
+/// report the one written last
+#[no_mangle]
+pub extern "C" fn get_last_completed_row() -> u32 {
+    LAST_COMPLETED_ROW.with(|r| r.get())
+}
+
+/// Ask running and later resizes to stop (e.g. when the user navigates away)
+/// The shared row loops (nearest, bilinear, both Lanczos passes, the separable
+/// convolution and the gamma paths), the transform loops and the single-plane
+/// convolutions check the flag on their first and last row and
+/// every CANCEL_POLL_ROWS rows between, then return RESIZE_ERR_CANCELLED. Rows already
+/// stored stay in the destination (see get_last_completed_row); a resize cancelled in
+/// an intermediate pass leaves it untouched. The flag stays set until clear_cancel
+#[no_mangle]
+pub extern "C" fn request_cancel() {
+    CANCEL_REQUESTED.with(|c| c.set(true));
+}
+
+/// Clear the flag set by request_cancel so resizes run to completion again
+#[no_mangle]
+pub extern "C" fn clear_cancel() {
+    CANCEL_REQUESTED.with(|c| c.set(false));
+}
+
+/// Whether the most recent resize took a SIMD path (1) or stayed scalar (0)
+/// Set by the aligned nearest copy, the f32x4 pixel pack (Lanczos, separable and
+/// linear-light output) and the gamma area prefix sums. The nearest copy needs 16-byte
+/// aligned buffers, so a `+simd128` nearest resize of unaligned buffers stays scalar.
+/// Reset when a resize starts; always 0 in builds without simd128
+#[no_mangle]
+pub extern "C" fn last_resize_used_simd() -> i32 {
+    SIMD_PATH_TAKEN.with(|t| t.get()) as i32
+}
+
+// Image size limits (prevent excessive memory allocation)
+// MAX_DIMENSION: 65535 is the maximum value for u16, chosen to:
+// - Prevent excessive memory allocation (65535^2 * 4 bytes = ~17GB for a single image)
+// - Stay within WASM memory constraints (WASM linear memory is limited, typically 2-4GB)
+// - Provide a reasonable upper bound for practical image processing scenarios
+// - Avoid potential integer overflow issues in intermediate calculations
+const MAX_DIMENSION: u32 = 65535;
+
+// MAX_PIXELS: 268,435,456 pixels = 256 megapixels (256MP)
+// This limit ensures:
+// - Reasonable memory usage (256MP * 4 bytes = 1GB for RGBA)
+// - Prevents processing of unreasonably large images that would cause performance issues
+// - Aligns with common high-resolution camera formats (e.g., 16K at 16:9 ≈ 132MP)
+const MAX_PIXELS: u64 = 268_435_456;
+
+// Limit on separable intermediate buffers (dst_w * src_h pixels of 4 x f32 = 16 bytes each)
+// Both images can pass MAX_PIXELS while the intermediate does not (a wide downscale of a
+// tall image), so it is checked separately before allocating.
+// Defaults to MAX_PIXELS; adjustable with set_max_intermediate_pixels
+thread_local! {
+    static MAX_INTERMEDIATE_PIXELS: Cell<u64> = const { Cell::new(MAX_PIXELS) };
+}
+
+/// Set the largest intermediate buffer (in pixels, dst_w * src_h) the separable
+/// resize paths may allocate; larger requests fail with RESIZE_ERR_MEMORY.
+/// Passing 0 restores the default (MAX_PIXELS)
+#[no_mangle]
+pub extern "C" fn set_max_intermediate_pixels(limit: u32) -> i32 {
+    let limit = if limit == 0 { MAX_PIXELS } else { limit as u64 };
+    MAX_INTERMEDIATE_PIXELS.with(|l| l.set(limit));
+    set_last_error(RESIZE_OK);
+    RESIZE_OK
+}
+
+// When set, floating-point paths use the same operation order in SIMD and scalar builds
+// Toggled with set_deterministic_accumulation
+thread_local! {
+    static DETERMINISTIC_ACCUMULATION: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Force a fixed floating-point evaluation order so output is bit-identical between
+/// `+simd128` and scalar builds (for golden tests)
+/// 0 = off (default): SIMD paths may reorder arithmetic, e.g. the bilinear blend.
+/// 1 = on: such paths use the scalar order. Separable taps are always summed left to
+/// right, one lane per channel, so only the reordered paths change
+/// Returns error code: 0 = success, RESIZE_ERR_INVALID_PARAM for any other value
+#[no_mangle]
+pub extern "C" fn set_deterministic_accumulation(on: u32) -> i32 {
+    match on {
+        0 | 1 => {
+            DETERMINISTIC_ACCUMULATION.with(|d| d.set(on == 1));
+            set_last_error(RESIZE_OK);
+            RESIZE_OK
+        }
+        _ => {
+            set_last_error(RESIZE_ERR_INVALID_PARAM);
+            RESIZE_ERR_INVALID_PARAM
+        }
+    }
+}
+
+/// Reject separable resizes whose Pass 1 buffer (dst_w * src_h pixels) exceeds the limit
+#[inline(always)]
+fn validate_intermediate(dst_w: u32, src_h: u32) -> Result<(), i32> {
+    let pixels = dst_w as u64 * src_h as u64;
+    if pixels > MAX_INTERMEDIATE_PIXELS.with(|l| l.get()) {
+        set_last_error(RESIZE_ERR_MEMORY);
+        return Err(RESIZE_ERR_MEMORY);
+    }
+    Ok(())
+}
+
+/// Pre-grow the thread-local working buffers for a resize of the given dimensions
+/// Sizes the index/weight LUTs (and the Lanczos intermediate, in the current intermediate
+/// precision) exactly as a real resize with `algorithm` would, and builds the gamma LUTs
+/// used by the gamma-correct variants, so the first real call does no buffer allocation.
+/// No pixel data is touched
+/// Returns error code: 0 = success, non-zero = error
+#[no_mangle]
+pub extern "C" fn warm_buffers(src_w: u32, src_h: u32, dst_w: u32, dst_h: u32, algorithm: u32) -> i32 {
+    let dims = [src_w, src_h, dst_w, dst_h];
+    if dims.iter().any(|&d| d == 0 || d > MAX_DIMENSION)
+        || src_w as u64 * src_h as u64 > MAX_PIXELS
+        || dst_w as u64 * dst_h as u64 > MAX_PIXELS
+    {
+        set_last_error(RESIZE_ERR_INVALID_SIZE);
+        return RESIZE_ERR_INVALID_SIZE;
+    }
+
+    let algorithm = match resolve_algorithm(algorithm, src_w, src_h, dst_w, dst_h) {
+        Ok(a) => a,
+        Err(code) => return code,
+    };
+
+    let dst_w_usize = dst_w as usize;
+    match algorithm {
+        RESIZE_ALGO_NEAREST => X_INDICES_NEAREST.with(|cell| cell.borrow_mut().resize(dst_w_usize, 0)),
+        RESIZE_ALGO_LANCZOS => {
+            if let Err(code) = validate_intermediate(dst_w, src_h) {
+                return code;
+            }
+            let temp_len = dst_w_usize * src_h as usize * 4;
+            let precision = separable::intermediate_precision();
+            LANCZOS_STORAGE.with(|cell| {
+                cell.borrow_mut().work(dst_w, dst_h, temp_len, precision);
+            });
+        }
+        _ => {
+            X0_INDICES_BILINEAR.with(|cell| cell.borrow_mut().resize(dst_w_usize, 0));
+            X1_INDICES_BILINEAR.with(|cell| cell.borrow_mut().resize(dst_w_usize, 0));
+            FX_VALUES_BILINEAR.with(|cell| cell.borrow_mut().resize(dst_w_usize, 0.0));
+        }
+    }
+
+    gamma_simd::init_gamma_luts();
+
+    set_last_error(RESIZE_OK);
+    RESIZE_OK
+}
+
 /// Validate resize parameters and compute safe buffer sizes
 #[inline(always)]
 fn validate_params(
@@ -151,6 +777,9 @@ fn validate_params(
     dst_w: u32,
     dst_h: u32,
 ) -> Result<(usize, usize), i32> {
+    // Every resize validates first, so this marks the start of a new resize
+    begin_resize();
+    
     // Check null pointers
     if src_ptr.is_null() || dst_ptr.is_null() {
         set_last_error(RESIZE_ERR_NULL_PTR);
@@ -203,20 +832,6 @@ fn validate_params(
     };
     
     // Check reasonable limits (prevent excessive memory allocation)
-    // MAX_DIMENSION: 65535 is the maximum value for u16, chosen to:
-    // - Prevent excessive memory allocation (65535^2 * 4 bytes = ~17GB for a single image)
-    // - Stay within WASM memory constraints (WASM linear memory is limited, typically 2-4GB)
-    // - Provide a reasonable upper bound for practical image processing scenarios
-    // - Avoid potential integer overflow issues in intermediate calculations
-    const MAX_DIMENSION: u32 = 65535;
-    
-    // MAX_PIXELS: 268,435,456 pixels = 256 megapixels (256MP)
-    // This limit ensures:
-    // - Reasonable memory usage (256MP * 4 bytes = 1GB for RGBA)
-    // - Prevents processing of unreasonably large images that would cause performance issues
-    // - Aligns with common high-resolution camera formats (e.g., 16K at 16:9 ≈ 132MP)
-    const MAX_PIXELS: u64 = 268_435_456;
-    
     if src_w > MAX_DIMENSION
         || src_h > MAX_DIMENSION
         || dst_w > MAX_DIMENSION
@@ -257,6 +872,62 @@ fn validate_params(
     Ok((src_size, dst_size))
 }
 
+/// Validate a single in-place RGBA buffer and compute its size in bytes
+/// Applies the same null/alignment/dimension limits as validate_params
+#[inline(always)]
+fn validate_buffer(ptr: *const u8, w: u32, h: u32) -> Result<usize, i32> {
+    begin_resize();
+    
+    if ptr.is_null() {
+        set_last_error(RESIZE_ERR_NULL_PTR);
+        return Err(RESIZE_ERR_NULL_PTR);
+    }
+    
+    if !(ptr as usize).is_multiple_of(4) {
+        set_last_error(RESIZE_ERR_ALIGNMENT);
+        return Err(RESIZE_ERR_ALIGNMENT);
+    }
+    
+    if w == 0 || h == 0 || w > MAX_DIMENSION || h > MAX_DIMENSION {
+        set_last_error(RESIZE_ERR_INVALID_SIZE);
+        return Err(RESIZE_ERR_INVALID_SIZE);
+    }
+    
+    let pixels = (w as u64) * (h as u64);
+    if pixels > MAX_PIXELS {
+        set_last_error(RESIZE_ERR_INVALID_SIZE);
+        return Err(RESIZE_ERR_INVALID_SIZE);
+    }
+    
+    set_last_error(RESIZE_OK);
+    Ok((pixels * 4) as usize)
+}
+
+/// Validate an out-pointer of `count` values of T written alongside a resize
+/// Checks null, T's alignment and that the `count * size_of::<T>()` bytes overlap none of
+/// `buffers` ((pointer, byte length) pairs, already validated by validate_params/_buffer)
+#[inline(always)]
+fn validate_out_ptr<T>(out: *const T, count: usize, buffers: &[(*const u8, usize)]) -> Result<(), i32> {
+    if out.is_null() {
+        set_last_error(RESIZE_ERR_NULL_PTR);
+        return Err(RESIZE_ERR_NULL_PTR);
+    }
+    
+    if !(out as usize).is_multiple_of(std::mem::align_of::<T>()) {
+        set_last_error(RESIZE_ERR_ALIGNMENT);
+        return Err(RESIZE_ERR_ALIGNMENT);
+    }
+    
+    let out_start = out as usize;
+    let out_end = out_start.saturating_add(count.saturating_mul(std::mem::size_of::<T>()));
+    if buffers.iter().any(|&(ptr, size)| (ptr as usize) < out_end && out_start < (ptr as usize).saturating_add(size)) {
+        set_last_error(RESIZE_ERR_OVERLAP);
+        return Err(RESIZE_ERR_OVERLAP);
+    }
+    
+    Ok(())
+}
+
 /// Check if the resize operation uses integer scaling ratios
 /// Returns (is_integer_x, is_integer_y) where true means the scale factor is an integer
 /// 
@@ -298,7 +969,7 @@ fn is_integer_scaling(src_w: u32, src_h: u32, dst_w: u32, dst_h: u32) -> (bool,
 /// Lanczos kernel function (3-lobed, high-quality resampling)
 /// L(x) = sinc(x) * sinc(x/3) for |x| < 3, 0 otherwise
 #[inline(always)]
-fn lanczos_kernel(x: f32, a: f32) -> f32 {
+pub(crate) fn lanczos_kernel(x: f32, a: f32) -> f32 {
     if x.abs() >= a {
         return 0.0;
     }
@@ -371,6 +1042,48 @@ fn select_resize_algorithm(src_w: u32, src_h: u32, dst_w: u32, dst_h: u32) -> u3
     }
 }
 
+/// Frozen algorithm selection backing resize_rgba_v1
+/// Snapshot of select_resize_algorithm as of the v1 API. Do not tune this function:
+/// heuristic changes belong in select_resize_algorithm; a new behavior gets a new
+/// versioned entry point instead.
+/// - Upscaling on both axes: Lanczos
+/// - Downscaling by more than 8x on either axis: nearest
+/// - Otherwise, with (bilinear, lanczos) ratio limits of (8, 4) below 1MP source
+///   pixels, (4, 2) below 10MP and (2, 1) above: Lanczos when both axes are within the
+///   Lanczos limit, bilinear when both are within the bilinear limit, else nearest
+#[inline(always)]
+fn select_resize_algorithm_v1(src_w: u32, src_h: u32, dst_w: u32, dst_h: u32) -> u32 {
+    let down_x = src_w > dst_w;
+    let down_y = src_h > dst_h;
+    if !down_x && !down_y {
+        return RESIZE_ALGO_LANCZOS;
+    }
+    
+    let exceeds = |limit: u32| {
+        (down_x && src_w > dst_w.saturating_mul(limit)) || (down_y && src_h > dst_h.saturating_mul(limit))
+    };
+    if exceeds(8) {
+        return RESIZE_ALGO_NEAREST;
+    }
+    
+    let src_pixels = (src_w as u64) * (src_h as u64);
+    let (bilinear_limit, lanczos_limit) = if src_pixels < 1_000_000 {
+        (8, 4)
+    } else if src_pixels < 10_000_000 {
+        (4, 2)
+    } else {
+        (2, 1)
+    };
+    
+    if !exceeds(lanczos_limit) {
+        RESIZE_ALGO_LANCZOS
+    } else if !exceeds(bilinear_limit) {
+        RESIZE_ALGO_BILINEAR
+    } else {
+        RESIZE_ALGO_NEAREST
+    }
+}
+
 /// Legacy function for backward compatibility
 #[allow(dead_code)]
 #[inline(always)]
@@ -378,28 +1091,164 @@ fn should_use_nearest_neighbor(src_w: u32, src_h: u32, dst_w: u32, dst_h: u32) -
     select_resize_algorithm(src_w, src_h, dst_w, dst_h) == 0
 }
 
-/// Fast nearest neighbor resize (for downscaling large images)
+/// Report the per-axis scale factors the resamplers actually use
+/// Writes `out[0] = src_w / dst_w` and `out[1] = src_h / dst_h` (source pixels per
+/// destination pixel, computed exactly as the resize paths do)
 /// Returns error code: 0 = success, non-zero = error
+///
+/// # Safety
+/// `out` must point to 2 writable, 4-byte aligned f32 values
 #[no_mangle]
-pub unsafe extern "C" fn resize_rgba_nearest(
-    src_ptr: *const u8,
+pub unsafe extern "C" fn effective_scale(src_w: u32, src_h: u32, dst_w: u32, dst_h: u32, out: *mut f32) -> i32 {
+    if src_w == 0 || src_h == 0 || dst_w == 0 || dst_h == 0 {
+        set_last_error(RESIZE_ERR_INVALID_SIZE);
+        return RESIZE_ERR_INVALID_SIZE;
+    }
+
+    write_scale(src_w, src_h, dst_w, dst_h, out)
+}
+
+/// Scale factors for an aspect-preserving fit of the source inside a box_w x box_h box
+/// The limiting axis is scaled to the box exactly and the other axis to the nearest
+/// whole pixel (at least 1), so the factors can differ slightly from each other.
+/// Writes the same `out[0..2]` as effective_scale for those fitted dimensions
+/// Returns error code: 0 = success, non-zero = error
+///
+/// # Safety
+/// `out` must point to 2 writable, 4-byte aligned f32 values
+#[no_mangle]
+pub unsafe extern "C" fn effective_scale_contain(
     src_w: u32,
     src_h: u32,
-    dst_ptr: *mut u8,
-    dst_w: u32,
-    dst_h: u32,
+    box_w: u32,
+    box_h: u32,
+    out: *mut f32,
 ) -> i32 {
-    let (src_size, dst_size) = match validate_params(src_ptr, src_w, src_h, dst_ptr, dst_w, dst_h) {
-        Ok(sizes) => sizes,
-        Err(code) => return code,
+    if src_w == 0 || src_h == 0 || box_w == 0 || box_h == 0 {
+        set_last_error(RESIZE_ERR_INVALID_SIZE);
+        return RESIZE_ERR_INVALID_SIZE;
+    }
+
+    // Width-limited when box_w / src_w <= box_h / src_h
+    let (fit_w, fit_h) = if box_w as u64 * src_h as u64 <= box_h as u64 * src_w as u64 {
+        (box_w, scaled_dimension(src_h, box_w, src_w))
+    } else {
+        (scaled_dimension(src_w, box_h, src_h), box_h)
     };
-    
-    let src = match std::slice::from_raw_parts(src_ptr, src_size).get(..) {
-        Some(s) => s,
-        None => {
-            set_last_error(RESIZE_ERR_MEMORY);
-            return RESIZE_ERR_MEMORY;
-        }
+    write_scale(src_w, src_h, fit_w, fit_h, out)
+}
+
+/// Scale factors for an aspect-preserving fill of a box_w x box_h box (overflow cropped)
+/// Mirrors effective_scale_contain with the larger of the two box ratios, so the scaled
+/// image covers the box and the overflowing axis extends past it before cropping
+/// Returns error code: 0 = success, non-zero = error
+///
+/// # Safety
+/// `out` must point to 2 writable, 4-byte aligned f32 values
+#[no_mangle]
+pub unsafe extern "C" fn effective_scale_cover(
+    src_w: u32,
+    src_h: u32,
+    box_w: u32,
+    box_h: u32,
+    out: *mut f32,
+) -> i32 {
+    if src_w == 0 || src_h == 0 || box_w == 0 || box_h == 0 {
+        set_last_error(RESIZE_ERR_INVALID_SIZE);
+        return RESIZE_ERR_INVALID_SIZE;
+    }
+
+    // Height-limited (width overflows) when box_w / src_w <= box_h / src_h
+    let (fill_w, fill_h) = if box_w as u64 * src_h as u64 <= box_h as u64 * src_w as u64 {
+        (scaled_dimension(src_w, box_h, src_h), box_h)
+    } else {
+        (box_w, scaled_dimension(src_h, box_w, src_w))
+    };
+    write_scale(src_w, src_h, fill_w, fill_h, out)
+}
+
+/// Scale factors for converting non-square source pixels to square output pixels
+/// Source pixels are `par_num / par_den` times as wide as they are tall; the output is
+/// dst_w wide and its height is chosen (nearest whole pixel, at least 1) so the displayed
+/// aspect ratio is kept: `dst_h = dst_w * src_h * par_den / (src_w * par_num)`
+/// Returns error code: 0 = success, non-zero = error
+///
+/// # Safety
+/// `out` must point to 2 writable, 4-byte aligned f32 values
+#[no_mangle]
+pub unsafe extern "C" fn effective_scale_par(
+    src_w: u32,
+    src_h: u32,
+    par_num: u32,
+    par_den: u32,
+    dst_w: u32,
+    out: *mut f32,
+) -> i32 {
+    if src_w == 0 || src_h == 0 || dst_w == 0 {
+        set_last_error(RESIZE_ERR_INVALID_SIZE);
+        return RESIZE_ERR_INVALID_SIZE;
+    }
+    if par_num == 0 || par_den == 0 {
+        set_last_error(RESIZE_ERR_INVALID_PARAM);
+        return RESIZE_ERR_INVALID_PARAM;
+    }
+
+    let num = dst_w as u128 * src_h as u128 * par_den as u128;
+    let den = src_w as u128 * par_num as u128;
+    let dst_h = ((num + den / 2) / den).clamp(1, MAX_DIMENSION as u128) as u32;
+    write_scale(src_w, src_h, dst_w, dst_h, out)
+}
+
+/// `size * num / den` rounded to the nearest whole pixel, clamped to [1, MAX_DIMENSION]
+#[inline(always)]
+fn scaled_dimension(size: u32, num: u32, den: u32) -> u32 {
+    let scaled = (size as u64 * num as u64 + den as u64 / 2) / den as u64;
+    scaled.clamp(1, MAX_DIMENSION as u64) as u32
+}
+
+/// Write `[src_w / dst_w, src_h / dst_h]` to `out` after checking the pointer
+/// Shared tail of the effective_scale* exports; dimensions are already validated
+unsafe fn write_scale(src_w: u32, src_h: u32, dst_w: u32, dst_h: u32, out: *mut f32) -> i32 {
+    if out.is_null() {
+        set_last_error(RESIZE_ERR_NULL_PTR);
+        return RESIZE_ERR_NULL_PTR;
+    }
+
+    if !(out as usize).is_multiple_of(std::mem::align_of::<f32>()) {
+        set_last_error(RESIZE_ERR_ALIGNMENT);
+        return RESIZE_ERR_ALIGNMENT;
+    }
+
+    let out = std::slice::from_raw_parts_mut(out, 2);
+    out[0] = src_w as f32 / dst_w as f32;
+    out[1] = src_h as f32 / dst_h as f32;
+
+    set_last_error(RESIZE_OK);
+    RESIZE_OK
+}
+
+/// Fast nearest neighbor resize (for downscaling large images)
+/// Returns error code: 0 = success, non-zero = error
+#[no_mangle]
+pub unsafe extern "C" fn resize_rgba_nearest(
+    src_ptr: *const u8,
+    src_w: u32,
+    src_h: u32,
+    dst_ptr: *mut u8,
+    dst_w: u32,
+    dst_h: u32,
+) -> i32 {
+    let (src_size, dst_size) = match validate_params(src_ptr, src_w, src_h, dst_ptr, dst_w, dst_h) {
+        Ok(sizes) => sizes,
+        Err(code) => return code,
+    };
+    
+    let src = match std::slice::from_raw_parts(src_ptr, src_size).get(..) {
+        Some(s) => s,
+        None => {
+            set_last_error(RESIZE_ERR_MEMORY);
+            return RESIZE_ERR_MEMORY;
+        }
     };
     
     let dst = match std::slice::from_raw_parts_mut(dst_ptr, dst_size).get_mut(..) {
@@ -410,125 +1259,237 @@ pub unsafe extern "C" fn resize_rgba_nearest(
         }
     };
     
-    let scale_x = src_w as f32 / dst_w as f32;
-    let scale_y = src_h as f32 / dst_h as f32;
+    nearest_with_thread_locals(src, src_w, src_h, dst, dst_w, dst_h, DstRect::full(dst_w, dst_h), false)
+}
 
-    // Precompute X mapping: for each destination x, which source pixel (byte index) to sample
-    // This avoids recomputing float math inside the inner loop
+/// Nearest neighbor resize using the thread-local X-mapping buffer
+#[allow(clippy::too_many_arguments)]
+fn nearest_with_thread_locals(
+    src: &[u8],
+    src_w: u32,
+    src_h: u32,
+    dst: &mut [u8],
+    dst_w: u32,
+    dst_h: u32,
+    rect: DstRect,
+    src_bottom_up: bool,
+) -> i32 {
     // Reuse thread-local buffer to avoid heap allocation on every call
     X_INDICES_NEAREST.with(|x_indices_cell| {
         let mut x_indices = x_indices_cell.borrow_mut();
-        let dst_w_usize = dst_w as usize;
-        
-        // Clear and reserve capacity if needed (reuses existing capacity)
-        x_indices.clear();
-        let x_cap = x_indices.capacity();
-        if x_cap < dst_w_usize {
-            x_indices.reserve(dst_w_usize.saturating_sub(x_cap));
+        // Resize reuses existing capacity
+        x_indices.resize(dst_w as usize, 0);
+        nearest_core(src, src_w, src_h, dst, dst_w, dst_h, rect, src_bottom_up, &mut x_indices)
+    })
+}
+
+/// Nearest neighbor resize for color-keyed sprites
+/// Every output pixel is an exact copy of one source pixel (no blending, so the key
+/// color is never approximated); output pixels whose sampled source RGB equals
+/// (`key_r`, `key_g`, `key_b`) are written as fully transparent black instead
+/// Returns error code: 0 = success, non-zero = error
+///
+/// # Safety
+/// src_ptr/dst_ptr must point to valid RGBA buffers of the given dimensions
+#[no_mangle]
+pub unsafe extern "C" fn resize_rgba_nearest_keyed(
+    src_ptr: *const u8,
+    src_w: u32,
+    src_h: u32,
+    dst_ptr: *mut u8,
+    dst_w: u32,
+    dst_h: u32,
+    key_r: u8,
+    key_g: u8,
+    key_b: u8,
+) -> i32 {
+    let (src_size, dst_size) = match validate_params(src_ptr, src_w, src_h, dst_ptr, dst_w, dst_h) {
+        Ok(sizes) => sizes,
+        Err(code) => return code,
+    };
+    
+    let src = std::slice::from_raw_parts(src_ptr, src_size);
+    let dst = std::slice::from_raw_parts_mut(dst_ptr, dst_size);
+    
+    let code = intermediate_pass(|| {
+        nearest_with_thread_locals(src, src_w, src_h, dst, dst_w, dst_h, DstRect::full(dst_w, dst_h), false)
+    });
+    if code != RESIZE_OK {
+        return code;
+    }
+    
+    // Nearest output pixels are verbatim source pixels, so keying the output is
+    // equivalent to keying the sampled source
+    for p in dst.chunks_exact_mut(4) {
+        if p[..3] == [key_r, key_g, key_b] {
+            p.fill(0);
         }
-        
-        // Precompute X indices
-        for x in 0..dst_w {
-            let src_x = ((x as f32 + 0.5) * scale_x) as u32;
-            let src_x = src_x.min(src_w - 1);
-            x_indices.push((src_x as usize) * 4);
+    }
+    mark_rows_completed(dst_h);
+    
+    RESIZE_OK
+}
+
+/// Nearest neighbor resampling core, writing only destination pixels inside `rect`
+/// `x_indices` must hold `dst_w` entries; it is overwritten with the X-mapping LUT
+#[allow(clippy::too_many_arguments)]
+fn nearest_core(
+    src: &[u8],
+    src_w: u32,
+    src_h: u32,
+    dst: &mut [u8],
+    dst_w: u32,
+    dst_h: u32,
+    rect: DstRect,
+    src_bottom_up: bool,
+    x_indices: &mut [usize],
+) -> i32 {
+    if x_indices.len() < dst_w as usize {
+        set_last_error(RESIZE_ERR_MEMORY);
+        return RESIZE_ERR_MEMORY;
+    }
+    
+    fill_nearest_lut(src_w, dst_w, x_indices);
+    
+    for y in rect.y..rect.y + rect.h {
+        let dst_row = match dst_row_mut(dst, dst_w, y - rect.origin_y) {
+            Some(row) => row,
+            None => {
+                set_last_error(RESIZE_ERR_OVERFLOW);
+                return RESIZE_ERR_OVERFLOW;
+            }
+        };
+        let code = nearest_row(src, src_w, src_h, dst_h, y, src_bottom_up, rect.x..rect.x + rect.w, x_indices, dst_row);
+        if code != RESIZE_OK {
+            return code;
+        }
+        mark_row_completed(y);
+        if cancel_due(y - rect.y, rect.h) {
+            return RESIZE_ERR_CANCELLED;
+        }
+    }
+    
+    RESIZE_OK
+}
+
+/// Precompute the nearest X mapping: for each destination x, which source pixel (byte
+/// index) to sample. This avoids recomputing float math inside the inner loop
+/// `x_indices` must hold `dst_w` entries
+fn fill_nearest_lut(src_w: u32, dst_w: u32, x_indices: &mut [usize]) {
+    let scale_x = src_w as f32 / dst_w as f32;
+    for x in 0..dst_w {
+        let src_x = ((x as f32 + 0.5) * scale_x) as u32;
+        let src_x = src_x.min(src_w - 1);
+        x_indices[x as usize] = (src_x as usize) * 4;
+    }
+}
+
+/// The slice of `dst` starting at row `row` of a `dst_w`-pixel-wide image
+/// Empty when `dst` is shorter; None if the offset overflows
+#[inline(always)]
+fn dst_row_mut(dst: &mut [u8], dst_w: u32, row: u32) -> Option<&mut [u8]> {
+    let offset = (row as usize).checked_mul(dst_w as usize)?.checked_mul(4)?;
+    Some(dst.get_mut(offset..).unwrap_or_default())
+}
+
+/// Nearest neighbor sampling of destination row `y`, columns `xs`, into `dst_row`
+/// (whose pixel 0 is destination column 0) using the LUT from fill_nearest_lut
+#[allow(clippy::too_many_arguments)]
+#[inline(always)]
+fn nearest_row(
+    src: &[u8],
+    src_w: u32,
+    src_h: u32,
+    dst_h: u32,
+    y: u32,
+    src_bottom_up: bool,
+    xs: std::ops::Range<u32>,
+    x_indices: &[usize],
+    dst_row: &mut [u8],
+) -> i32 {
+    // Clip to the last column in `xs`, so the 16-byte SIMD copy never spills past it
+    let row_end = (xs.end as usize * 4).min(dst_row.len());
+    let dst_row = &mut dst_row[..row_end];
+    let scale_y = src_h as f32 / dst_h as f32;
+    let src_y = ((y as f32 + 0.5) * scale_y) as u32;
+    let src_y = src_row(src_y.min(src_h - 1), src_h, src_bottom_up);
+    
+    // Check for integer overflow in offset calculation
+    let src_y_offset = match (src_y as usize)
+        .checked_mul(src_w as usize)
+        .and_then(|x| x.checked_mul(4))
+    {
+        Some(offset) => offset,
+        None => {
+            set_last_error(RESIZE_ERR_OVERFLOW);
+            return RESIZE_ERR_OVERFLOW;
         }
+    };
+    
+    // Validate offset is within source buffer bounds
+    if src_y_offset >= src.len() {
+        set_last_error(RESIZE_ERR_INVALID_SIZE);
+        return RESIZE_ERR_INVALID_SIZE;
+    }
+    
+    for x in xs {
+        let x_idx = x as usize;
         
-        // Optimized nearest neighbor with pre-calculated indices
-        // Enhanced bounds checking to prevent buffer overflows
-        for y in 0..dst_h {
-            let src_y = ((y as f32 + 0.5) * scale_y) as u32;
-            let src_y = src_y.min(src_h - 1);
-            
-            // Check for integer overflow in offset calculation
-            let src_y_offset = match (src_y as usize)
-                .checked_mul(src_w as usize)
-                .and_then(|x| x.checked_mul(4))
-            {
-                Some(offset) => offset,
-                None => {
-                    set_last_error(RESIZE_ERR_OVERFLOW);
-                    return RESIZE_ERR_OVERFLOW;
-                }
-            };
-            
-            // Validate offset is within source buffer bounds
-            if src_y_offset >= src.len() {
-                set_last_error(RESIZE_ERR_INVALID_SIZE);
-                return RESIZE_ERR_INVALID_SIZE;
+        let src_idx = match src_y_offset.checked_add(x_indices[x_idx]) {
+            Some(idx) => idx,
+            None => {
+                set_last_error(RESIZE_ERR_OVERFLOW);
+                return RESIZE_ERR_OVERFLOW;
             }
-            
-            for x in 0..dst_w {
-                // Validate LUT index is within bounds
-                let x_idx = x as usize;
-                if x_idx >= x_indices.len() {
-                    set_last_error(RESIZE_ERR_INVALID_SIZE);
-                    return RESIZE_ERR_INVALID_SIZE;
-                }
-                
-                let src_idx = match src_y_offset.checked_add(x_indices[x_idx]) {
-                    Some(idx) => idx,
-                    None => {
-                        set_last_error(RESIZE_ERR_OVERFLOW);
-                        return RESIZE_ERR_OVERFLOW;
-                    }
-                };
-                
-                // Check for integer overflow in destination index calculation
-                let dst_idx = match (y as usize)
-                    .checked_mul(dst_w as usize)
-                    .and_then(|x| x.checked_add(x_idx))
-                    .and_then(|x| x.checked_mul(4))
+        };
+        let dst_idx = x_idx * 4;
+        
+        // Enhanced bounds checking: ensure we can safely access 4 bytes
+        if src_idx.saturating_add(3) < src.len() && dst_idx + 3 < dst_row.len() {
+            // Use SIMD-optimized copy for aligned memory (4 pixels = 16 bytes)
+            // Check if both pointers are 16-byte aligned and a full 16 bytes are
+            // available on both sides for optimal SIMD performance
+            #[cfg(target_feature = "simd128")]
+            {
+                if (src.as_ptr() as usize + src_idx).is_multiple_of(16)
+                    && (dst_row.as_ptr() as usize + dst_idx).is_multiple_of(16)
+                    && src_idx + 16 <= src.len()
+                    && dst_idx + 16 <= dst_row.len()
                 {
-                    Some(idx) => idx,
-                    None => {
-                        set_last_error(RESIZE_ERR_OVERFLOW);
-                        return RESIZE_ERR_OVERFLOW;
-                    }
-                };
-                
-                // Enhanced bounds checking: ensure we can safely access 4 bytes
-                if src_idx.saturating_add(3) < src.len() && dst_idx.saturating_add(3) < dst.len() {
-                    // Additional safety check: ensure indices are within valid range
-                    if src_idx < src.len() && dst_idx < dst.len() {
-                        // Use SIMD-optimized copy for aligned memory (4 pixels = 16 bytes)
-                        // Check if both pointers are 16-byte aligned for optimal SIMD performance
-                        #[cfg(target_feature = "simd128")]
-                        {
-                            if (src.as_ptr().add(src_idx) as usize) % 16 == 0
-                                && (dst.as_mut_ptr().add(dst_idx) as usize) % 16 == 0
-                            {
-                                copy_4_pixels_simd(src.as_ptr().add(src_idx), dst.as_mut_ptr().add(dst_idx));
-                            } else {
-                                // Unaligned: use scalar copy
-                                dst[dst_idx] = src[src_idx];
-                                dst[dst_idx + 1] = src[src_idx + 1];
-                                dst[dst_idx + 2] = src[src_idx + 2];
-                                dst[dst_idx + 3] = src[src_idx + 3];
-                            }
-                        }
-                        #[cfg(not(target_feature = "simd128"))]
-                        {
-                            dst[dst_idx] = src[src_idx];
-                            dst[dst_idx + 1] = src[src_idx + 1];
-                            dst[dst_idx + 2] = src[src_idx + 2];
-                            dst[dst_idx + 3] = src[src_idx + 3];
-                        }
+                    unsafe {
+                        copy_4_pixels_simd(src.as_ptr().add(src_idx), dst_row.as_mut_ptr().add(dst_idx));
                     }
+                    mark_simd_used();
+                } else {
+                    // Unaligned: use scalar copy
+                    dst_row[dst_idx..dst_idx + 4].copy_from_slice(&src[src_idx..src_idx + 4]);
                 }
             }
+            #[cfg(not(target_feature = "simd128"))]
+            {
+                dst_row[dst_idx..dst_idx + 4].copy_from_slice(&src[src_idx..src_idx + 4]);
+            }
         }
-        
-        RESIZE_OK
-    })
+    }
+    
+    RESIZE_OK
+}
+
+/// The single color of a solid-color RGBA image, or None as soon as a pixel differs
+/// Cheap for typical images, which differ within the first few pixels
+#[inline(always)]
+fn uniform_color(pixels: &[u8]) -> Option<[u8; 4]> {
+    let (first, rest) = pixels.split_first_chunk::<4>()?;
+    rest.chunks_exact(4).all(|p| p == first).then_some(*first)
 }
 
 /// Resize RGBA image data with automatic algorithm selection
 /// 
 /// This function serves as the main entry point and handles:
 /// 1. Parameter validation
-/// 2. Algorithm selection (nearest neighbor vs bilinear interpolation)
-/// 3. Delegation to the appropriate resize implementation
+/// 2. Solid-color sources, which are filled directly without resampling
+/// 3. Algorithm selection (nearest neighbor vs bilinear interpolation)
+/// 4. Delegation to the appropriate resize implementation
 /// 
 /// The algorithm is automatically chosen based on:
 /// - Scale factor (large downscaling uses nearest neighbor for performance)
@@ -573,6 +1534,15 @@ pub unsafe extern "C" fn resize_rgba(
         }
     };
     
+    // Any filter maps a constant image to the same constant
+    if let Some(color) = uniform_color(src) {
+        for pixel in dst.chunks_exact_mut(4) {
+            pixel.copy_from_slice(&color);
+        }
+        mark_row_completed(dst_h - 1);
+        return RESIZE_OK;
+    }
+    
     // Select optimal algorithm based on scale factor and image size
     let algorithm = select_resize_algorithm(src_w, src_h, dst_w, dst_h);
     match algorithm {
@@ -581,19 +1551,22 @@ pub unsafe extern "C" fn resize_rgba(
         _ => {} // Continue with bilinear (algorithm == 1)
     }
 
-    let scale_x = src_w as f32 / dst_w as f32;
-    let scale_y = src_h as f32 / dst_h as f32;
+    bilinear_with_thread_locals(src, src_w, src_h, dst, dst_w, dst_h, DstRect::full(dst_w, dst_h), false)
+}
 
-    // ==================== Precompute interpolation parameters along X ====================
-    //
-    // For each destination x:
-    // - Precompute the corresponding source coordinate src_x
-    // - Derive x0 / x1 (neighboring source pixel indices)
-    // - And the interpolation weight fx
-    //
-    // This avoids repeated floating point division / floor / clamp in the inner loop.
+/// Bilinear resize using the thread-local X-direction LUT buffers
+#[allow(clippy::too_many_arguments)]
+fn bilinear_with_thread_locals(
+    src: &[u8],
+    src_w: u32,
+    src_h: u32,
+    dst: &mut [u8],
+    dst_w: u32,
+    dst_h: u32,
+    rect: DstRect,
+    src_bottom_up: bool,
+) -> i32 {
     // Reuse thread-local buffers to avoid heap allocation on every call
-
     let dst_w_usize = dst_w as usize;
     
     X0_INDICES_BILINEAR.with(|x0_cell| {
@@ -603,213 +1576,267 @@ pub unsafe extern "C" fn resize_rgba(
                 let mut x1_indices = x1_cell.borrow_mut();
                 let mut fx_values = fx_cell.borrow_mut();
                 
-                // Clear and reserve capacity if needed (reuses existing capacity)
-                x0_indices.clear();
-                x1_indices.clear();
-                fx_values.clear();
-                
-                // Calculate required capacity before reserving to avoid borrow conflicts
-                let x0_cap = x0_indices.capacity();
-                let x1_cap = x1_indices.capacity();
-                let fx_cap = fx_values.capacity();
-                
-                if x0_cap < dst_w_usize {
-                    x0_indices.reserve(dst_w_usize.saturating_sub(x0_cap));
-                }
-                if x1_cap < dst_w_usize {
-                    x1_indices.reserve(dst_w_usize.saturating_sub(x1_cap));
-                }
-                if fx_cap < dst_w_usize {
-                    fx_values.reserve(dst_w_usize.saturating_sub(fx_cap));
-                }
-                
-                // Precompute X-direction LUT
-        for x in 0..dst_w {
-            let src_x = (x as f32 + 0.5) * scale_x - 0.5;
-                    let x0 = src_x.floor() as i32;
-                    let x1 = (x0 + 1).min(src_w as i32 - 1);
-                    let fx = (src_x - x0 as f32).max(0.0).min(1.0);
-
-                    let x0_clamped = x0.clamp(0, src_w as i32 - 1) as usize * 4;
-                    let x1_clamped = x1.clamp(0, src_w as i32 - 1) as usize * 4;
-
-                    x0_indices.push(x0_clamped);
-                    x1_indices.push(x1_clamped);
-                    fx_values.push(fx);
-                }
-                
-                // Optimized bilinear interpolation with bounds checking
-                for y in 0..dst_h {
-            let src_y = (y as f32 + 0.5) * scale_y - 0.5;
-            let y0 = src_y.floor() as i32;
-            let y1 = (y0 + 1).min(src_h as i32 - 1);
-                    let fy = (src_y - y0 as f32).max(0.0).min(1.0);
-                    
-                    // Pre-calculate y offsets with clamping to valid range
-                    // Enhanced overflow checking for safety
-                    let y0_clamped = y0.clamp(0, src_h as i32 - 1) as usize;
-                    let y1_clamped = y1.clamp(0, src_h as i32 - 1) as usize;
-                    
-                    // Check for integer overflow in offset calculations
-                    let y0_offset = match y0_clamped
-                        .checked_mul(src_w as usize)
-                        .and_then(|x| x.checked_mul(4))
-                    {
-                        Some(offset) => offset,
-                        None => {
-                            set_last_error(RESIZE_ERR_OVERFLOW);
-                            return RESIZE_ERR_OVERFLOW;
-                        }
-                    };
-                    
-                    let y1_offset = match y1_clamped
-                        .checked_mul(src_w as usize)
-                        .and_then(|x| x.checked_mul(4))
-                    {
-                        Some(offset) => offset,
-                        None => {
-                            set_last_error(RESIZE_ERR_OVERFLOW);
-                            return RESIZE_ERR_OVERFLOW;
-                        }
-                    };
-                    
-                    // Validate offsets are within source buffer bounds
-                    if y0_offset >= src.len() || y1_offset >= src.len() {
-                        set_last_error(RESIZE_ERR_INVALID_SIZE);
-                        return RESIZE_ERR_INVALID_SIZE;
-                    }
-                    
-                    for x in 0..dst_w {
-                        // Fetch X-direction parameters from the precomputed LUT
-                        // Enhanced bounds checking for LUT access
-                        let lut_index = x as usize;
-                        
-                        // Validate LUT indices are within bounds
-                        if lut_index >= x0_indices.len()
-                            || lut_index >= x1_indices.len()
-                            || lut_index >= fx_values.len()
-                        {
-                            set_last_error(RESIZE_ERR_INVALID_SIZE);
-                            return RESIZE_ERR_INVALID_SIZE;
-                        }
-                        
-                        let x0_clamped = x0_indices[lut_index];
-                        let x1_clamped = x1_indices[lut_index];
-                        let fx = fx_values[lut_index];
-                        
-                        // Get four neighboring pixels with clamped edge handling
-                        // Enhanced bounds checking with overflow protection
-                        let get_pixel_safe = |offset: usize, idx: usize| -> [u8; 4] {
-                            // Check for integer overflow in position calculation
-                            let mut pos = match offset.checked_add(idx) {
-                                Some(p) => p,
-                                None => {
-                                    // Overflow occurred, return transparent pixel
-                                    return [0, 0, 0, 0];
-                                }
-                            };
-                            
-                            // Enhanced bounds checking: ensure we can safely access 4 bytes
-                            // Clamp to last full pixel within bounds (replicate edge pixel)
-                            if pos.saturating_add(3) >= src.len() {
-                                if src.len() >= 4 {
-                                    // Clamp to the last complete pixel
-                                    pos = src.len().saturating_sub(4);
-                                } else {
-                                    // Source buffer too small, return transparent pixel
-                                    return [0, 0, 0, 0];
-                                }
-                            }
-                            
-                            // Final safety check: ensure position is within bounds
-                            if pos >= src.len() {
-                                return [0, 0, 0, 0];
-                            }
-                            
-                            // Safe to access 4 bytes
-                            [
-                                src[pos],
-                                src[pos + 1],
-                                src[pos + 2],
-                                src[pos + 3],
-                            ]
-                        };
-                        
-                        let p00 = get_pixel_safe(y0_offset, x0_clamped);
-                        let p10 = get_pixel_safe(y0_offset, x1_clamped);
-                        let p01 = get_pixel_safe(y1_offset, x0_clamped);
-                        let p11 = get_pixel_safe(y1_offset, x1_clamped);
-                        
-                        // Use SIMD-optimized bilinear interpolation
-                        // This function uses optimized scalar code with SIMD-ready structure
-                        // Future: Full SIMD implementation for 4x speedup
-                        let result = bilinear_interp_4_pixels(p00, p10, p01, p11, fx, fy);
-                        
-                        // Write to destination with enhanced bounds checking
-                        // Check for integer overflow in destination index calculation
-                        let dst_idx = match (y as usize)
-                            .checked_mul(dst_w as usize)
-                            .and_then(|x| x.checked_add(lut_index))
-                            .and_then(|x| x.checked_mul(4))
-                        {
-                            Some(idx) => idx,
-                            None => {
-                                set_last_error(RESIZE_ERR_OVERFLOW);
-                                return RESIZE_ERR_OVERFLOW;
-                            }
-                        };
-                        
-                        // Enhanced bounds checking: ensure we can safely write 4 bytes
-                        if dst_idx.saturating_add(3) < dst.len() && dst_idx < dst.len() {
-                            dst[dst_idx] = result[0];
-                            dst[dst_idx + 1] = result[1];
-                            dst[dst_idx + 2] = result[2];
-                            dst[dst_idx + 3] = result[3];
-                        }
-                    }
-                }
+                // Resize reuses existing capacity
+                x0_indices.resize(dst_w_usize, 0);
+                x1_indices.resize(dst_w_usize, 0);
+                fx_values.resize(dst_w_usize, 0.0);
                 
-                RESIZE_OK
+                bilinear_core(
+                    src, src_w, src_h, dst, dst_w, dst_h,
+                    rect, src_bottom_up, &mut x0_indices, &mut x1_indices, &mut fx_values,
+                )
             })
         })
     })
 }
 
+/// Bilinear resampling core, writing only destination pixels inside `rect`
+/// `x0_indices`, `x1_indices` and `fx_values` must hold `dst_w` entries;
+/// they are overwritten with the X-direction LUT
+#[allow(clippy::too_many_arguments)]
+fn bilinear_core(
+    src: &[u8],
+    src_w: u32,
+    src_h: u32,
+    dst: &mut [u8],
+    dst_w: u32,
+    dst_h: u32,
+    rect: DstRect,
+    src_bottom_up: bool,
+    x0_indices: &mut [usize],
+    x1_indices: &mut [usize],
+    fx_values: &mut [f32],
+) -> i32 {
+    let dst_w_usize = dst_w as usize;
+    if x0_indices.len() < dst_w_usize || x1_indices.len() < dst_w_usize || fx_values.len() < dst_w_usize {
+        set_last_error(RESIZE_ERR_MEMORY);
+        return RESIZE_ERR_MEMORY;
+    }
+    
+    fill_bilinear_lut(src_w, dst_w, x0_indices, x1_indices, fx_values);
+    
+    let deterministic = DETERMINISTIC_ACCUMULATION.with(|d| d.get());
+    let lut = BilinearLut { x0_indices, x1_indices, fx_values, deterministic };
+    
+    for y in rect.y..rect.y + rect.h {
+        let dst_row = match dst_row_mut(dst, dst_w, y - rect.origin_y) {
+            Some(row) => row,
+            None => {
+                set_last_error(RESIZE_ERR_OVERFLOW);
+                return RESIZE_ERR_OVERFLOW;
+            }
+        };
+        let code = bilinear_row(src, src_w, src_h, dst_h, y, src_bottom_up, rect.x..rect.x + rect.w, &lut, dst_row);
+        if code != RESIZE_OK {
+            return code;
+        }
+        mark_row_completed(y);
+        if cancel_due(y - rect.y, rect.h) {
+            return RESIZE_ERR_CANCELLED;
+        }
+    }
+    
+    RESIZE_OK
+}
+
+/// Bilinear X-direction LUT as filled by fill_bilinear_lut
+struct BilinearLut<'a> {
+    x0_indices: &'a [usize],
+    x1_indices: &'a [usize],
+    fx_values: &'a [f32],
+    // Use the scalar interpolation (see DETERMINISTIC_ACCUMULATION)
+    deterministic: bool,
+}
+
+/// Precompute the bilinear interpolation parameters along X
+///
+/// For each destination x:
+/// - Precompute the corresponding source coordinate src_x
+/// - Derive x0 / x1 (neighboring source pixel byte indices)
+/// - And the interpolation weight fx
+///
+/// This avoids repeated floating point division / floor / clamp in the inner loop.
+/// All three slices must hold `dst_w` entries
+fn fill_bilinear_lut(src_w: u32, dst_w: u32, x0_indices: &mut [usize], x1_indices: &mut [usize], fx_values: &mut [f32]) {
+    let scale_x = src_w as f32 / dst_w as f32;
+    for x in 0..dst_w {
+        let src_x = (x as f32 + 0.5) * scale_x - 0.5;
+        let x0 = src_x.floor() as i32;
+        let x1 = (x0 + 1).min(src_w as i32 - 1);
+        let fx = (src_x - x0 as f32).clamp(0.0, 1.0);
+
+        let x0_clamped = x0.clamp(0, src_w as i32 - 1) as usize * 4;
+        let x1_clamped = x1.clamp(0, src_w as i32 - 1) as usize * 4;
+
+        x0_indices[x as usize] = x0_clamped;
+        x1_indices[x as usize] = x1_clamped;
+        fx_values[x as usize] = fx;
+    }
+}
+
+/// Bilinear interpolation of destination row `y`, columns `xs`, into `dst_row`
+/// (whose pixel 0 is destination column 0)
+#[allow(clippy::too_many_arguments)]
+#[inline(always)]
+fn bilinear_row(
+    src: &[u8],
+    src_w: u32,
+    src_h: u32,
+    dst_h: u32,
+    y: u32,
+    src_bottom_up: bool,
+    xs: std::ops::Range<u32>,
+    lut: &BilinearLut<'_>,
+    dst_row: &mut [u8],
+) -> i32 {
+    let scale_y = src_h as f32 / dst_h as f32;
+    let src_y = (y as f32 + 0.5) * scale_y - 0.5;
+    let y0 = src_y.floor() as i32;
+    let y1 = (y0 + 1).min(src_h as i32 - 1);
+    let fy = (src_y - y0 as f32).clamp(0.0, 1.0);
+    
+    // Pre-calculate y offsets with clamping to valid range
+    // Enhanced overflow checking for safety
+    let y0_clamped = src_row(y0.clamp(0, src_h as i32 - 1) as u32, src_h, src_bottom_up) as usize;
+    let y1_clamped = src_row(y1.clamp(0, src_h as i32 - 1) as u32, src_h, src_bottom_up) as usize;
+    
+    // Check for integer overflow in offset calculations
+    let y0_offset = match y0_clamped
+        .checked_mul(src_w as usize)
+        .and_then(|x| x.checked_mul(4))
+    {
+        Some(offset) => offset,
+        None => {
+            set_last_error(RESIZE_ERR_OVERFLOW);
+            return RESIZE_ERR_OVERFLOW;
+        }
+    };
+    
+    let y1_offset = match y1_clamped
+        .checked_mul(src_w as usize)
+        .and_then(|x| x.checked_mul(4))
+    {
+        Some(offset) => offset,
+        None => {
+            set_last_error(RESIZE_ERR_OVERFLOW);
+            return RESIZE_ERR_OVERFLOW;
+        }
+    };
+    
+    // Validate offsets are within source buffer bounds
+    if y0_offset >= src.len() || y1_offset >= src.len() {
+        set_last_error(RESIZE_ERR_INVALID_SIZE);
+        return RESIZE_ERR_INVALID_SIZE;
+    }
+    
+    for x in xs {
+        // Fetch X-direction parameters from the precomputed LUT
+        let lut_index = x as usize;
+        let x0_clamped = lut.x0_indices[lut_index];
+        let x1_clamped = lut.x1_indices[lut_index];
+        let fx = lut.fx_values[lut_index];
+        
+        let p00 = get_pixel_clamped(src, y0_offset, x0_clamped);
+        let p10 = get_pixel_clamped(src, y0_offset, x1_clamped);
+        let p01 = get_pixel_clamped(src, y1_offset, x0_clamped);
+        let p11 = get_pixel_clamped(src, y1_offset, x1_clamped);
+        
+        // Use SIMD-optimized bilinear interpolation
+        // This function uses optimized scalar code with SIMD-ready structure
+        // Future: Full SIMD implementation for 4x speedup
+        let result = if lut.deterministic {
+            bilinear_interp_4_pixels_scalar(p00, p10, p01, p11, fx, fy)
+        } else {
+            unsafe { bilinear_interp_4_pixels(p00, p10, p01, p11, fx, fy) }
+        };
+        
+        // Enhanced bounds checking: ensure we can safely write 4 bytes
+        let dst_idx = lut_index * 4;
+        if dst_idx + 3 < dst_row.len() {
+            dst_row[dst_idx..dst_idx + 4].copy_from_slice(&result);
+        }
+    }
+    
+    RESIZE_OK
+}
+
+/// Read one RGBA pixel at `offset + idx`, replicating the last complete pixel
+/// when the position runs past the end of the buffer
+/// Returns a transparent pixel on overflow or when the buffer holds no full pixel
+#[inline(always)]
+fn get_pixel_clamped(src: &[u8], offset: usize, idx: usize) -> [u8; 4] {
+    // Check for integer overflow in position calculation
+    let mut pos = match offset.checked_add(idx) {
+        Some(p) => p,
+        None => return [0, 0, 0, 0],
+    };
+    
+    // Clamp to last full pixel within bounds (replicate edge pixel)
+    if pos.saturating_add(3) >= src.len() {
+        if src.len() >= 4 {
+            pos = src.len() - 4;
+        } else {
+            return [0, 0, 0, 0];
+        }
+    }
+    
+    [src[pos], src[pos + 1], src[pos + 2], src[pos + 3]]
+}
+
 /// Anti-ringing clamp: prevents overshoot/undershoot artifacts
 /// Clamps result to min/max of contributing source pixels
 #[inline(always)]
-fn anti_ringing_clamp(value: f32, min_val: f32, max_val: f32) -> f32 {
-    value.max(min_val).min(max_val)
+fn anti_ringing_clamp<S: PartialOrd>(value: S, min_val: S, max_val: S) -> S {
+    let value = if value < min_val { min_val } else { value };
+    if value > max_val {
+        max_val
+    } else {
+        value
+    }
 }
 
 /// Precompute Lanczos weights and indices for separable convolution
-/// Returns (weights, indices) for a single destination coordinate
+/// Writes the taps for a single destination coordinate into `weights` / `indices`
+/// (each at least LANCZOS_MAX_TAPS long) and returns the number of taps written
 #[inline(always)]
 fn precompute_lanczos_weights(
     dst_coord: f32,
     src_size: u32,
     scale: f32,
     a: f32,
-) -> (Vec<f32>, Vec<i32>) {
+    weights: &mut [f32],
+    indices: &mut [i32],
+) -> usize {
     let src_coord = (dst_coord + 0.5) * scale - 0.5;
     let center = src_coord.floor() as i32;
     let start = (center - a as i32 + 1).max(0);
     let end = (center + a as i32).min(src_size as i32 - 1);
     
-    let mut weights = Vec::new();
-    let mut indices = Vec::new();
+    let mut count = 0;
     
     for i in start..=end {
         let dist = (i as f32 - src_coord) / scale;
         let weight = lanczos_kernel(dist, a);
         
-        if weight.abs() >= 1e-6 {
-            weights.push(weight);
-            indices.push(i);
+        if weight.abs() >= 1e-6 && count < weights.len() && count < indices.len() {
+            weights[count] = weight;
+            indices[count] = i;
+            count += 1;
         }
     }
     
-    (weights, indices)
+    count
+}
+
+/// Fill a tap table with one LANCZOS_MAX_TAPS stride per destination coordinate
+/// `counts` selects how many coordinates are computed
+fn fill_lanczos_taps(src_size: u32, scale: f32, counts: &mut [u32], weights: &mut [f32], indices: &mut [i32]) {
+    let taps = weights
+        .chunks_exact_mut(LANCZOS_MAX_TAPS)
+        .zip(indices.chunks_exact_mut(LANCZOS_MAX_TAPS));
+    for (coord, (count, (w, i))) in counts.iter_mut().zip(taps).enumerate() {
+        *count = precompute_lanczos_weights(coord as f32, src_size, scale, LANCZOS_A, w, i) as u32;
+    }
 }
 
 /// High-quality Lanczos resampling using separable convolution
@@ -847,267 +1874,1800 @@ pub unsafe extern "C" fn resize_rgba_lanczos(
         }
     };
     
-    const LANCZOS_A: f32 = 3.0; // 3-lobed Lanczos kernel
-    let scale_x = src_w as f32 / dst_w as f32;
-    let scale_y = src_h as f32 / dst_h as f32;
-    
-    // Use thread-local buffers for intermediate results and precomputed weights
-    LANCZOS_X_WEIGHTS.with(|x_weights_cell| {
-        LANCZOS_X_INDICES.with(|x_indices_cell| {
-            LANCZOS_Y_WEIGHTS.with(|y_weights_cell| {
-                LANCZOS_Y_INDICES.with(|y_indices_cell| {
-                    LANCZOS_TEMP_BUFFER.with(|temp_cell| {
-                        let mut x_weights = x_weights_cell.borrow_mut();
-                        let mut x_indices = x_indices_cell.borrow_mut();
-                        let mut y_weights = y_weights_cell.borrow_mut();
-                        let mut y_indices = y_indices_cell.borrow_mut();
-                        let mut temp_buffer = temp_cell.borrow_mut();
-                        
-                        // Clear and resize buffers
-                        x_weights.clear();
-                        x_indices.clear();
-                        y_weights.clear();
-                        y_indices.clear();
-                        
-                        // Precompute X-axis weights and indices (Pass 1 preparation)
-                        x_weights.reserve(dst_w as usize);
-                        x_indices.reserve(dst_w as usize);
-                        for x in 0..dst_w {
-                            let (weights, indices) = precompute_lanczos_weights(x as f32, src_w, scale_x, LANCZOS_A);
-                            x_weights.push(weights);
-                            x_indices.push(indices);
-                        }
-                        
-                        // Precompute Y-axis weights and indices (Pass 2 preparation)
-                        y_weights.reserve(dst_h as usize);
-                        y_indices.reserve(dst_h as usize);
-                        for y in 0..dst_h {
-                            let (weights, indices) = precompute_lanczos_weights(y as f32, src_h, scale_y, LANCZOS_A);
-                            y_weights.push(weights);
-                            y_indices.push(indices);
-                        }
-                        
-                        // Allocate intermediate buffer for Pass 1 result (dst_w × src_h × 4 channels)
-                        let temp_size = (dst_w as usize) * (src_h as usize) * 4;
-                        temp_buffer.clear();
-                        temp_buffer.reserve(temp_size);
-                        temp_buffer.resize(temp_size, 0.0f32);
-                        
-                        // ==================== Pass 1: Horizontal (X-axis) resampling ====================
-                        // Resize from src_w × src_h to dst_w × src_h
-                        for y in 0..src_h {
-                            let y_offset_src = match (y as usize)
-                                .checked_mul(src_w as usize)
-                                .and_then(|x| x.checked_mul(4))
-                            {
-                                Some(offset) => offset,
-                                None => {
-                                    set_last_error(RESIZE_ERR_OVERFLOW);
-                                    return RESIZE_ERR_OVERFLOW;
-                                }
-                            };
-                            
-                            if y_offset_src >= src.len() {
-                                continue;
-                            }
-                            
-                            for x in 0..dst_w {
-                                let x_idx = x as usize;
-                                if x_idx >= x_weights.len() || x_idx >= x_indices.len() {
-                                    continue;
-                                }
-                                
-                                let weights = &x_weights[x_idx];
-                                let indices = &x_indices[x_idx];
-                                
-                                // Accumulate weighted samples
-                                let mut r_sum = 0.0f32;
-                                let mut g_sum = 0.0f32;
-                                let mut b_sum = 0.0f32;
-                                let mut a_sum = 0.0f32;
-                                let mut weight_sum = 0.0f32;
-                                
-                                // Track min/max for anti-ringing
-                                let mut r_min = 255.0f32;
-                                let mut r_max = 0.0f32;
-                                let mut g_min = 255.0f32;
-                                let mut g_max = 0.0f32;
-                                let mut b_min = 255.0f32;
-                                let mut b_max = 0.0f32;
-                                let mut a_min = 255.0f32;
-                                let mut a_max = 0.0f32;
-                                
-                                for (weight, &sx) in weights.iter().zip(indices.iter()) {
-                                    let sx_clamped = sx.clamp(0, src_w as i32 - 1) as usize;
-                                    let src_idx = match y_offset_src.checked_add(sx_clamped * 4) {
-                                        Some(idx) => idx,
-                                        None => continue,
-                                    };
-                                    
-                                    if src_idx.saturating_add(3) >= src.len() {
-                                        continue;
-                                    }
-                                    
-                                    let r = src[src_idx] as f32;
-                                    let g = src[src_idx + 1] as f32;
-                                    let b = src[src_idx + 2] as f32;
-                                    let a = src[src_idx + 3] as f32;
-                                    
-                                    r_sum += r * weight;
-                                    g_sum += g * weight;
-                                    b_sum += b * weight;
-                                    a_sum += a * weight;
-                                    weight_sum += weight;
-                                    
-                                    // Track min/max for anti-ringing
-                                    r_min = r_min.min(r);
-                                    r_max = r_max.max(r);
-                                    g_min = g_min.min(g);
-                                    g_max = g_max.max(g);
-                                    b_min = b_min.min(b);
-                                    b_max = b_max.max(b);
-                                    a_min = a_min.min(a);
-                                    a_max = a_max.max(a);
-                                }
-                                
-                                // Normalize
-                                if weight_sum.abs() > 1e-6 {
-                                    r_sum /= weight_sum;
-                                    g_sum /= weight_sum;
-                                    b_sum /= weight_sum;
-                                    a_sum /= weight_sum;
-                                }
-                                
-                                // Anti-ringing clamp: prevent overshoot/undershoot
-                                r_sum = anti_ringing_clamp(r_sum, r_min, r_max);
-                                g_sum = anti_ringing_clamp(g_sum, g_min, g_max);
-                                b_sum = anti_ringing_clamp(b_sum, b_min, b_max);
-                                a_sum = anti_ringing_clamp(a_sum, a_min, a_max);
-                                
-                                // Store in temp buffer (dst_w × src_h)
-                                let temp_idx = ((y as usize) * (dst_w as usize) + x_idx) * 4;
-                                if temp_idx + 3 < temp_buffer.len() {
-                                    temp_buffer[temp_idx] = r_sum;
-                                    temp_buffer[temp_idx + 1] = g_sum;
-                                    temp_buffer[temp_idx + 2] = b_sum;
-                                    temp_buffer[temp_idx + 3] = a_sum;
-                                }
-                            }
-                        }
-                        
-                        // ==================== Pass 2: Vertical (Y-axis) resampling ====================
-                        // Resize from dst_w × src_h to dst_w × dst_h
-                        for y in 0..dst_h {
-                            let y_idx = y as usize;
-                            if y_idx >= y_weights.len() || y_idx >= y_indices.len() {
-                                continue;
-                            }
-                            
-                            let weights = &y_weights[y_idx];
-                            let indices = &y_indices[y_idx];
-                            
-                            for x in 0..dst_w {
-                                let x_idx = x as usize;
-                                
-                                // Accumulate weighted samples from temp buffer
-                                let mut r_sum = 0.0f32;
-                                let mut g_sum = 0.0f32;
-                                let mut b_sum = 0.0f32;
-                                let mut a_sum = 0.0f32;
-                                let mut weight_sum = 0.0f32;
-                                
-                                // Track min/max for anti-ringing
-                                let mut r_min = 255.0f32;
-                                let mut r_max = 0.0f32;
-                                let mut g_min = 255.0f32;
-                                let mut g_max = 0.0f32;
-                                let mut b_min = 255.0f32;
-                                let mut b_max = 0.0f32;
-                                let mut a_min = 255.0f32;
-                                let mut a_max = 0.0f32;
-                                
-                                for (weight, &sy) in weights.iter().zip(indices.iter()) {
-                                    let sy_clamped = sy.clamp(0, src_h as i32 - 1) as usize;
-                                    let temp_idx = (sy_clamped * (dst_w as usize) + x_idx) * 4;
-                                    
-                                    if temp_idx + 3 >= temp_buffer.len() {
-                                        continue;
-                                    }
-                                    
-                                    let r = temp_buffer[temp_idx];
-                                    let g = temp_buffer[temp_idx + 1];
-                                    let b = temp_buffer[temp_idx + 2];
-                                    let a = temp_buffer[temp_idx + 3];
-                                    
-                                    r_sum += r * weight;
-                                    g_sum += g * weight;
-                                    b_sum += b * weight;
-                                    a_sum += a * weight;
-                                    weight_sum += weight;
-                                    
-                                    // Track min/max for anti-ringing
-                                    r_min = r_min.min(r);
-                                    r_max = r_max.max(r);
-                                    g_min = g_min.min(g);
-                                    g_max = g_max.max(g);
-                                    b_min = b_min.min(b);
-                                    b_max = b_max.max(b);
-                                    a_min = a_min.min(a);
-                                    a_max = a_max.max(a);
-                                }
-                                
-                                // Normalize
-                                if weight_sum.abs() > 1e-6 {
-                                    r_sum /= weight_sum;
-                                    g_sum /= weight_sum;
-                                    b_sum /= weight_sum;
-                                    a_sum /= weight_sum;
-                                }
-                                
-                                // Anti-ringing clamp
-                                r_sum = anti_ringing_clamp(r_sum, r_min, r_max);
-                                g_sum = anti_ringing_clamp(g_sum, g_min, g_max);
-                                b_sum = anti_ringing_clamp(b_sum, b_min, b_max);
-                                a_sum = anti_ringing_clamp(a_sum, a_min, a_max);
-                                
-                                // Clamp to valid u8 range
-            let result = [
-                                    r_sum.max(0.0).min(255.0) as u8,
-                                    g_sum.max(0.0).min(255.0) as u8,
-                                    b_sum.max(0.0).min(255.0) as u8,
-                                    a_sum.max(0.0).min(255.0) as u8,
-            ];
-
-            // Write to destination
-                                let dst_idx = match (y as usize)
-                                    .checked_mul(dst_w as usize)
-                                    .and_then(|row| row.checked_add(x_idx))
-                                    .and_then(|pixel| pixel.checked_mul(4))
-                                {
-                                    Some(idx) => idx,
-                                    None => {
-                                        set_last_error(RESIZE_ERR_OVERFLOW);
-                                        return RESIZE_ERR_OVERFLOW;
-                                    }
-                                };
-                                
-                                if dst_idx.saturating_add(3) < dst.len() && dst_idx < dst.len() {
-            dst[dst_idx] = result[0];
-            dst[dst_idx + 1] = result[1];
-            dst[dst_idx + 2] = result[2];
-            dst[dst_idx + 3] = result[3];
-        }
-    }
-}
-
-                        RESIZE_OK
-                    })
-                })
-            })
-        })
-    })
+    lanczos_with_thread_locals(src, src_w, src_h, dst, dst_w, dst_h, DstRect::full(dst_w, dst_h), false)
 }
 
+/// Lanczos resize using the thread-local tap tables and intermediate buffer
+#[allow(clippy::too_many_arguments)]
+fn lanczos_with_thread_locals(
+    src: &[u8],
+    src_w: u32,
+    src_h: u32,
+    dst: &mut [u8],
+    dst_w: u32,
+    dst_h: u32,
+    rect: DstRect,
+    src_bottom_up: bool,
+) -> i32 {
+    if let Err(code) = validate_intermediate(dst_w, src_h) {
+        return code;
+    }
+    
+    // Intermediate buffer for Pass 1 result (dst_w × src_h × 4 channels)
+    let temp_len = (dst_w as usize) * (src_h as usize) * 4;
+    
+    LANCZOS_STORAGE.with(|storage_cell| {
+        let mut storage = storage_cell.borrow_mut();
+        let work = storage.work(dst_w, dst_h, temp_len, separable::intermediate_precision());
+        lanczos_core(src, src_w, src_h, dst, dst_w, dst_h, rect, src_bottom_up, work)
+    })
+}
+
+/// Separable Lanczos resampling core operating on borrowed working memory
+/// Only destination pixels inside `rect` are computed; Pass 1 is limited to the
+/// columns and source rows those pixels depend on. The Pass-1 sample type is the one
+/// `work` was borrowed for (see LanczosStorage::work)
+#[allow(clippy::too_many_arguments)]
+fn lanczos_core(
+    src: &[u8],
+    src_w: u32,
+    src_h: u32,
+    dst: &mut [u8],
+    dst_w: u32,
+    dst_h: u32,
+    rect: DstRect,
+    src_bottom_up: bool,
+    work: LanczosWork<'_>,
+) -> i32 {
+    match work {
+        LanczosWork::F32(buffers) => lanczos_core_as(src, src_w, src_h, dst, dst_w, dst_h, rect, src_bottom_up, buffers),
+        LanczosWork::F64(buffers) => lanczos_core_as(src, src_w, src_h, dst, dst_w, dst_h, rect, src_bottom_up, buffers),
+        LanczosWork::Fixed(buffers) => lanczos_core_as(src, src_w, src_h, dst, dst_w, dst_h, rect, src_bottom_up, buffers),
+    }
+}
+
+/// lanczos_core with `S` as the Pass-1 sample and accumulator type
+#[allow(clippy::too_many_arguments)]
+fn lanczos_core_as<S: LanczosSample>(
+    src: &[u8],
+    src_w: u32,
+    src_h: u32,
+    dst: &mut [u8],
+    dst_w: u32,
+    dst_h: u32,
+    rect: DstRect,
+    src_bottom_up: bool,
+    buffers: LanczosBuffers<'_, S>,
+) -> i32 {
+    let LanczosBuffers {
+        x_weights,
+        x_indices,
+        x_counts,
+        y_weights,
+        y_indices,
+        y_counts,
+        fixed_weights,
+        temp: temp_buffer,
+    } = buffers;
+    
+    let x_taps = dst_w as usize * LANCZOS_MAX_TAPS;
+    let y_taps = dst_h as usize * LANCZOS_MAX_TAPS;
+    let temp_size = (dst_w as usize) * (src_h as usize) * 4;
+    if x_weights.len() < x_taps
+        || x_indices.len() < x_taps
+        || x_counts.len() < dst_w as usize
+        || y_weights.len() < y_taps
+        || y_indices.len() < y_taps
+        || y_counts.len() < dst_h as usize
+        || (S::FIXED_WEIGHTS && fixed_weights.len() < x_taps + y_taps)
+        || temp_buffer.len() < temp_size
+    {
+        set_last_error(RESIZE_ERR_MEMORY);
+        return RESIZE_ERR_MEMORY;
+    }
+    
+    #[cfg(feature = "timing")]
+    let precompute_start = profiling::phase_clock();
+    
+    let scale_x = src_w as f32 / dst_w as f32;
+    let scale_y = src_h as f32 / dst_h as f32;
+    
+    // Precompute X-axis weights and indices (Pass 1 preparation)
+    fill_lanczos_taps(src_w, scale_x, &mut x_counts[..dst_w as usize], x_weights, x_indices);
+    
+    // Precompute Y-axis weights and indices (Pass 2 preparation)
+    fill_lanczos_taps(src_h, scale_y, &mut y_counts[..dst_h as usize], y_weights, y_indices);
+    
+    // Weights in the sample type's form (normalized Q14 for fixed point)
+    let (x_fixed, y_fixed) = fixed_weights.split_at_mut(if S::FIXED_WEIGHTS { x_taps } else { 0 });
+    let x_sample_weights = S::tap_weights(&x_counts[..dst_w as usize], x_weights, x_fixed);
+    let y_sample_weights = S::tap_weights(&y_counts[..dst_h as usize], y_weights, y_fixed);
+    
+    #[cfg(feature = "timing")]
+    let pass1_start = profiling::phase_clock();
+    
+    // Source rows referenced by the destination rows being written
+    // (the whole source for a full-size rectangle)
+    let mut row_first = src_h;
+    let mut row_last = 0u32;
+    for y in rect.y as usize..(rect.y + rect.h) as usize {
+        let base = y * LANCZOS_MAX_TAPS;
+        for &sy in &y_indices[base..base + y_counts[y] as usize] {
+            let sy = sy.clamp(0, src_h as i32 - 1) as u32;
+            row_first = row_first.min(sy);
+            row_last = row_last.max(sy);
+        }
+    }
+    
+    let x_taps = LanczosTaps { counts: x_counts, weights: x_sample_weights, indices: x_indices };
+    let y_taps = LanczosTaps { counts: y_counts, weights: y_sample_weights, indices: y_indices };
+    let row_len = dst_w as usize * 4;
+    let temp_buffer = &mut temp_buffer[..temp_size];
+    
+    // ==================== Pass 1: Horizontal (X-axis) resampling ====================
+    // Resize from src_w × src_h to dst_w × src_h
+    let pass1_last = row_last.min(src_h - 1);
+    for y in row_first..=pass1_last {
+        let temp_row = &mut temp_buffer[y as usize * row_len..(y as usize + 1) * row_len];
+        let code = lanczos_pass1_row(src, src_w, src_h, y, src_bottom_up, rect.x..rect.x + rect.w, &x_taps, temp_row);
+        if code != RESIZE_OK {
+            return code;
+        }
+        if cancel_due(y - row_first, pass1_last - row_first + 1) {
+            return RESIZE_ERR_CANCELLED;
+        }
+    }
+    
+    #[cfg(feature = "timing")]
+    let pass2_start = profiling::phase_clock();
+    
+    // ==================== Pass 2: Vertical (Y-axis) resampling ====================
+    // Resize from dst_w × src_h to dst_w × dst_h
+    for y in rect.y..rect.y + rect.h {
+        let dst_row = match dst_row_mut(dst, dst_w, y - rect.origin_y) {
+            Some(row) => row,
+            None => {
+                set_last_error(RESIZE_ERR_OVERFLOW);
+                return RESIZE_ERR_OVERFLOW;
+            }
+        };
+        // temp holds every source row at its own index
+        lanczos_pass2_row(temp_buffer, |sy| sy, dst_w, src_h, y, rect.x..rect.x + rect.w, &y_taps, dst_row);
+        mark_row_completed(y);
+        if cancel_due(y - rect.y, rect.h) {
+            return RESIZE_ERR_CANCELLED;
+        }
+    }
+
+    #[cfg(feature = "timing")]
+    profiling::record_lanczos_phases([precompute_start, pass1_start, pass2_start, profiling::phase_clock()]);
+
+    RESIZE_OK
+}
+
+/// One axis of a Lanczos tap table (see LanczosBuffers)
+/// `W` is the weight type of the sample type in use (see LanczosSample)
+struct LanczosTaps<'a, W = f32> {
+    counts: &'a [u32],
+    weights: &'a [W],
+    indices: &'a [i32],
+}
+
+impl<W> LanczosTaps<'_, W> {
+    /// Weights and source indices of destination coordinate `coord`
+    #[inline(always)]
+    fn get(&self, coord: u32) -> (&[W], &[i32]) {
+        let base = coord as usize * LANCZOS_MAX_TAPS;
+        let count = self.counts[coord as usize] as usize;
+        (&self.weights[base..base + count], &self.indices[base..base + count])
+    }
+}
+
+/// Pass-1 sample type of the Lanczos passes, chosen by set_intermediate_precision:
+/// f32 (default), f64, or i32 fixed point holding FIXED_TEMP_BITS fractional bits with
+/// Q(FIXED_WEIGHT_BITS) weights and i64 sums, so the fixed-point passes do no
+/// floating-point work per tap
+trait LanczosSample: Copy + PartialOrd {
+    /// Tap weight type
+    type Weight: Copy;
+    /// Weighted-sum accumulator type
+    type Acc: Copy + Default;
+    const ZERO: Self;
+    /// Whether tap_weights converts into its `fixed` table rather than borrowing `weights`
+    const FIXED_WEIGHTS: bool = false;
+    
+    /// Convert a tap table's f32 weights (first `counts.len()` coordinates), writing
+    /// them to `fixed` when FIXED_WEIGHTS is set
+    fn tap_weights<'a>(counts: &[u32], weights: &'a [f32], fixed: &'a mut [i32]) -> &'a [Self::Weight];
+    /// Sample value of a source byte
+    fn from_u8(v: u8) -> Self;
+    /// `acc + v * w`
+    fn mul_add(acc: Self::Acc, v: Self, w: Self::Weight) -> Self::Acc;
+    /// `sum + w`, in the units `normalize` divides by
+    fn add_weight(sum: Self::Acc, w: Self::Weight) -> Self::Acc;
+    /// Divide a weighted sum by its total weight (left as is when that is ~0)
+    fn normalize(sum: Self::Acc, weight_sum: Self::Acc) -> Self;
+    /// Clamp four channels to [0, 255] and truncate them to bytes
+    fn pack(v: [Self; 4]) -> [u8; 4];
+}
+
+impl LanczosSample for f32 {
+    type Weight = f32;
+    type Acc = f32;
+    const ZERO: Self = 0.0;
+    
+    fn tap_weights<'a>(_counts: &[u32], weights: &'a [f32], _fixed: &'a mut [i32]) -> &'a [f32] {
+        weights
+    }
+    #[inline(always)]
+    fn from_u8(v: u8) -> Self {
+        v as f32
+    }
+    #[inline(always)]
+    fn mul_add(acc: Self, v: Self, w: f32) -> Self {
+        acc + v * w
+    }
+    #[inline(always)]
+    fn add_weight(sum: Self, w: f32) -> Self {
+        sum + w
+    }
+    #[inline(always)]
+    fn normalize(sum: Self, weight_sum: Self) -> Self {
+        if weight_sum.abs() > 1e-6 {
+            sum / weight_sum
+        } else {
+            sum
+        }
+    }
+    #[inline(always)]
+    fn pack(v: [Self; 4]) -> [u8; 4] {
+        pack_f32x4_to_u8(v)
+    }
+}
+
+impl LanczosSample for f64 {
+    type Weight = f32;
+    type Acc = f64;
+    const ZERO: Self = 0.0;
+    
+    fn tap_weights<'a>(_counts: &[u32], weights: &'a [f32], _fixed: &'a mut [i32]) -> &'a [f32] {
+        weights
+    }
+    #[inline(always)]
+    fn from_u8(v: u8) -> Self {
+        v as f64
+    }
+    #[inline(always)]
+    fn mul_add(acc: Self, v: Self, w: f32) -> Self {
+        acc + v * w as f64
+    }
+    #[inline(always)]
+    fn add_weight(sum: Self, w: f32) -> Self {
+        sum + w as f64
+    }
+    #[inline(always)]
+    fn normalize(sum: Self, weight_sum: Self) -> Self {
+        if weight_sum.abs() > 1e-6 {
+            sum / weight_sum
+        } else {
+            sum
+        }
+    }
+    #[inline(always)]
+    fn pack(v: [Self; 4]) -> [u8; 4] {
+        v.map(|c| c.clamp(0.0, 255.0) as u8)
+    }
+}
+
+impl LanczosSample for i32 {
+    type Weight = i32;
+    type Acc = i64;
+    const ZERO: Self = 0;
+    const FIXED_WEIGHTS: bool = true;
+    
+    /// Normalize each coordinate's weights and round them so they sum to exactly one;
+    /// the rounding residual goes to the dominant tap so flat areas stay exact
+    fn tap_weights<'a>(counts: &[u32], weights: &'a [f32], fixed: &'a mut [i32]) -> &'a [i32] {
+        let one = 1i32 << FIXED_WEIGHT_BITS;
+        let fixed = &mut fixed[..counts.len() * LANCZOS_MAX_TAPS];
+        fixed.fill(0);
+        let coords = weights.chunks_exact(LANCZOS_MAX_TAPS).zip(fixed.chunks_exact_mut(LANCZOS_MAX_TAPS));
+        for (&count, (weights, fixed)) in counts.iter().zip(coords) {
+            let (weights, fixed) = (&weights[..count as usize], &mut fixed[..count as usize]);
+            let total = weights.iter().sum::<f32>();
+            if total.abs() <= 1e-6 {
+                continue;
+            }
+            for (f, &w) in fixed.iter_mut().zip(weights) {
+                *f = (w / total * one as f32).round() as i32;
+            }
+            let residual = one - fixed.iter().sum::<i32>();
+            let dominant = (0..weights.len()).max_by(|&a, &b| weights[a].abs().total_cmp(&weights[b].abs()));
+            if let Some(d) = dominant {
+                fixed[d] += residual;
+            }
+        }
+        fixed
+    }
+    #[inline(always)]
+    fn from_u8(v: u8) -> Self {
+        (v as i32) << FIXED_TEMP_BITS
+    }
+    #[inline(always)]
+    fn mul_add(acc: i64, v: Self, w: i32) -> i64 {
+        acc + v as i64 * w as i64
+    }
+    #[inline(always)]
+    fn add_weight(sum: i64, w: i32) -> i64 {
+        sum + w as i64
+    }
+    /// Q(temp + weight) sum over a Q(weight) total, rounded half up to Q(temp)
+    /// (saturated to i32; the anti-ringing clamp follows)
+    #[inline(always)]
+    fn normalize(sum: i64, weight_sum: i64) -> Self {
+        let v = if weight_sum > 0 {
+            (sum + weight_sum / 2).div_euclid(weight_sum)
+        } else {
+            sum >> FIXED_WEIGHT_BITS
+        };
+        v.clamp(i32::MIN as i64, i32::MAX as i64) as i32
+    }
+    #[inline(always)]
+    fn pack(v: [Self; 4]) -> [u8; 4] {
+        v.map(|c| (c >> FIXED_TEMP_BITS).clamp(0, 255) as u8)
+    }
+}
+
+/// Lanczos Pass 1 for one source row: resample logical (top-down) source row `y`
+/// horizontally into `temp_row` (dst_w RGBA pixels of `S`), destination columns `xs` only
+#[allow(clippy::too_many_arguments)]
+#[inline(always)]
+fn lanczos_pass1_row<S: LanczosSample>(
+    src: &[u8],
+    src_w: u32,
+    src_h: u32,
+    y: u32,
+    src_bottom_up: bool,
+    xs: std::ops::Range<u32>,
+    x_taps: &LanczosTaps<'_, S::Weight>,
+    temp_row: &mut [S],
+) -> i32 {
+    // temp keeps logical (top-down) row order; only the source read is remapped
+    let y_offset_src = match (src_row(y, src_h, src_bottom_up) as usize)
+        .checked_mul(src_w as usize)
+        .and_then(|x| x.checked_mul(4))
+    {
+        Some(offset) => offset,
+        None => {
+            set_last_error(RESIZE_ERR_OVERFLOW);
+            return RESIZE_ERR_OVERFLOW;
+        }
+    };
+    
+    if y_offset_src >= src.len() {
+        temp_row[xs.start as usize * 4..xs.end as usize * 4].fill(S::ZERO);
+        return RESIZE_OK;
+    }
+    
+    for x in xs {
+        let x_idx = x as usize;
+        let (weights, indices) = x_taps.get(x);
+        
+        // Accumulate weighted samples, tracking each channel's min/max for anti-ringing
+        let mut sums = [S::Acc::default(); 4];
+        let mut weight_sum = S::Acc::default();
+        let mut mins = [S::from_u8(255); 4];
+        let mut maxs = [S::from_u8(0); 4];
+        
+        for (&weight, &sx) in weights.iter().zip(indices.iter()) {
+            let sx_clamped = sx.clamp(0, src_w as i32 - 1) as usize;
+            let src_idx = match y_offset_src.checked_add(sx_clamped * 4) {
+                Some(idx) => idx,
+                None => continue,
+            };
+            
+            if src_idx.saturating_add(3) >= src.len() {
+                continue;
+            }
+            
+            for c in 0..4 {
+                let v = S::from_u8(src[src_idx + c]);
+                sums[c] = S::mul_add(sums[c], v, weight);
+                mins[c] = if v < mins[c] { v } else { mins[c] };
+                maxs[c] = if v > maxs[c] { v } else { maxs[c] };
+            }
+            weight_sum = S::add_weight(weight_sum, weight);
+        }
+        
+        // Normalize, then clamp to prevent overshoot/undershoot
+        let temp_idx = x_idx * 4;
+        for c in 0..4 {
+            temp_row[temp_idx + c] = anti_ringing_clamp(S::normalize(sums[c], weight_sum), mins[c], maxs[c]);
+        }
+    }
+    
+    RESIZE_OK
+}
+
+/// Lanczos Pass 2 for one destination row: resample destination row `y` vertically
+/// from the Pass-1 rows in `temp` (dst_w RGBA pixels of `S` each) into `dst_row`,
+/// columns `xs` only. `slot` maps a source row to the row of `temp` holding its Pass-1
+/// result
+#[allow(clippy::too_many_arguments)]
+#[inline(always)]
+fn lanczos_pass2_row<S: LanczosSample>(
+    temp: &[S],
+    slot: impl Fn(usize) -> usize,
+    dst_w: u32,
+    src_h: u32,
+    y: u32,
+    xs: std::ops::Range<u32>,
+    y_taps: &LanczosTaps<'_, S::Weight>,
+    dst_row: &mut [u8],
+) {
+    let (weights, indices) = y_taps.get(y);
+    
+    for x in xs {
+        let x_idx = x as usize;
+        
+        // Accumulate weighted samples from temp buffer, tracking min/max for anti-ringing
+        let mut sums = [S::Acc::default(); 4];
+        let mut weight_sum = S::Acc::default();
+        let mut mins = [S::from_u8(255); 4];
+        let mut maxs = [S::from_u8(0); 4];
+        
+        for (&weight, &sy) in weights.iter().zip(indices.iter()) {
+            let sy_clamped = sy.clamp(0, src_h as i32 - 1) as usize;
+            let temp_idx = (slot(sy_clamped) * (dst_w as usize) + x_idx) * 4;
+            
+            if temp_idx + 3 >= temp.len() {
+                continue;
+            }
+            
+            for c in 0..4 {
+                let v = temp[temp_idx + c];
+                sums[c] = S::mul_add(sums[c], v, weight);
+                mins[c] = if v < mins[c] { v } else { mins[c] };
+                maxs[c] = if v > maxs[c] { v } else { maxs[c] };
+            }
+            weight_sum = S::add_weight(weight_sum, weight);
+        }
+        
+        // Normalize and apply the anti-ringing clamp
+        let mut out = [S::ZERO; 4];
+        for c in 0..4 {
+            out[c] = anti_ringing_clamp(S::normalize(sums[c], weight_sum), mins[c], maxs[c]);
+        }
+        
+        // Clamp to valid u8 range
+        let result = S::pack(out);
+
+        // Write to destination
+        let dst_idx = x_idx * 4;
+        if dst_idx + 3 < dst_row.len() {
+            dst_row[dst_idx..dst_idx + 4].copy_from_slice(&result);
+        }
+    }
+}
+
+/// Map an explicit `algorithm` parameter to a concrete algorithm
+/// RESIZE_ALGO_AUTO is resolved with select_resize_algorithm; unknown values are rejected
+#[inline(always)]
+fn resolve_algorithm(algorithm: u32, src_w: u32, src_h: u32, dst_w: u32, dst_h: u32) -> Result<u32, i32> {
+    match algorithm {
+        RESIZE_ALGO_NEAREST | RESIZE_ALGO_BILINEAR | RESIZE_ALGO_LANCZOS => Ok(algorithm),
+        RESIZE_ALGO_AUTO => Ok(select_resize_algorithm(src_w, src_h, dst_w, dst_h)),
+        _ => {
+            set_last_error(RESIZE_ERR_INVALID_PARAM);
+            Err(RESIZE_ERR_INVALID_PARAM)
+        }
+    }
+}
+
+/// Number of scratch bytes required by a concrete algorithm
+/// Lanczos sizes its Pass-1 buffer (and fixed-point weight tables) for the current
+/// intermediate precision. Slices are carved in decreasing alignment; only the f64
+/// buffer can need padding past the usize-aligned start, which is included here
+fn scratch_bytes(src_h: u32, dst_w: u32, dst_h: u32, algorithm: u32) -> Option<u64> {
+    let dst_w = dst_w as u64;
+    let dst_h = dst_h as u64;
+    let index_size = std::mem::size_of::<usize>() as u64;
+    match algorithm {
+        // X-mapping LUT
+        RESIZE_ALGO_NEAREST => dst_w.checked_mul(index_size),
+        // x0/x1 index LUTs + fx weights
+        RESIZE_ALGO_BILINEAR => dst_w.checked_mul(index_size * 2 + 4),
+        // Tap tables (weight + index per tap, one count per coordinate, plus a fixed-point
+        // weight per tap in that precision) + Pass 1 buffer
+        _ => {
+            let (sample_size, align, fixed) = match separable::intermediate_precision() {
+                RESIZE_PRECISION_F64 => (8, std::mem::align_of::<f64>(), false),
+                RESIZE_PRECISION_FIXED => (4, 4, true),
+                _ => (4, 4, false),
+            };
+            let taps_per_coord = LANCZOS_MAX_TAPS as u64 * if fixed { 3 } else { 2 };
+            let tables = dst_w.checked_add(dst_h)?.checked_mul((taps_per_coord + 1) * 4)?;
+            let temp = dst_w.checked_mul(src_h as u64)?.checked_mul(4 * sample_size)?;
+            let padding = align.saturating_sub(std::mem::align_of::<usize>()) as u64;
+            tables.checked_add(temp)?.checked_add(padding)
+        }
+    }
+}
+
+/// Compute the scratch size in bytes required by resize_rgba_scratch
+/// For Lanczos the size depends on the intermediate precision (see
+/// set_intermediate_precision), so query it after selecting the precision
+/// Returns 0 on error (check get_last_error)
+#[no_mangle]
+pub extern "C" fn scratch_size_needed(src_w: u32, src_h: u32, dst_w: u32, dst_h: u32, algorithm: u32) -> usize {
+    let dims = [src_w, src_h, dst_w, dst_h];
+    if dims.iter().any(|&d| d == 0 || d > MAX_DIMENSION)
+        || src_w as u64 * src_h as u64 > MAX_PIXELS
+        || dst_w as u64 * dst_h as u64 > MAX_PIXELS
+    {
+        set_last_error(RESIZE_ERR_INVALID_SIZE);
+        return 0;
+    }
+    
+    let algorithm = match resolve_algorithm(algorithm, src_w, src_h, dst_w, dst_h) {
+        Ok(a) => a,
+        Err(_) => return 0,
+    };
+    
+    let bytes = scratch_bytes(src_h, dst_w, dst_h, algorithm)
+        .and_then(|n| usize::try_from(n).ok());
+    
+    match bytes {
+        Some(b) => {
+            set_last_error(RESIZE_OK);
+            b
+        }
+        None => {
+            set_last_error(RESIZE_ERR_OVERFLOW);
+            0
+        }
+    }
+}
+
+/// Carve a typed slice of `len` elements from scratch memory, advancing `offset`
+/// The start is padded up to T's alignment. The caller guarantees the scratch region is
+/// large enough, including that padding; slices are carved in decreasing alignment
+/// (f64 and usize before the 4-byte types)
+#[inline(always)]
+unsafe fn carve_scratch<'a, T>(base: *mut u8, offset: &mut usize, len: usize) -> &'a mut [T] {
+    let start = base.add(*offset);
+    let padding = start.align_offset(std::mem::align_of::<T>());
+    let ptr = start.add(padding) as *mut T;
+    *offset += padding + len * std::mem::size_of::<T>();
+    std::slice::from_raw_parts_mut(ptr, len)
+}
+
+/// Lanczos working memory carved from scratch, for the current intermediate precision
+/// Mirrors the layout counted by scratch_bytes
+unsafe fn carve_lanczos_scratch<'a>(base: *mut u8, src_h: u32, dst_w: u32, dst_h: u32) -> LanczosWork<'a> {
+    let mut offset = 0usize;
+    let precision = separable::intermediate_precision();
+    let temp_len = dst_w as usize * src_h as usize * 4;
+    let x_taps = dst_w as usize * LANCZOS_MAX_TAPS;
+    let y_taps = dst_h as usize * LANCZOS_MAX_TAPS;
+    // The f64 buffer goes first, where its padding is bounded by scratch_bytes
+    let temp_f64: &mut [f64] = match precision {
+        RESIZE_PRECISION_F64 => carve_scratch(base, &mut offset, temp_len),
+        _ => &mut [],
+    };
+    let fixed_len = if precision == RESIZE_PRECISION_FIXED { x_taps + y_taps } else { 0 };
+    let tables: LanczosBuffers<()> = LanczosBuffers {
+        x_weights: carve_scratch(base, &mut offset, x_taps),
+        x_indices: carve_scratch(base, &mut offset, x_taps),
+        x_counts: carve_scratch(base, &mut offset, dst_w as usize),
+        y_weights: carve_scratch(base, &mut offset, y_taps),
+        y_indices: carve_scratch(base, &mut offset, y_taps),
+        y_counts: carve_scratch(base, &mut offset, dst_h as usize),
+        fixed_weights: carve_scratch(base, &mut offset, fixed_len),
+        temp: &mut [],
+    };
+    match precision {
+        RESIZE_PRECISION_F64 => LanczosWork::F64(tables.with_temp(temp_f64)),
+        RESIZE_PRECISION_FIXED => LanczosWork::Fixed(tables.with_temp(carve_scratch(base, &mut offset, temp_len))),
+        _ => LanczosWork::F32(tables.with_temp(carve_scratch(base, &mut offset, temp_len))),
+    }
+}
+
+/// Resize using caller-provided scratch memory instead of thread-local buffers
+/// Useful when several logical instances share one worker and must not share state.
+/// `scratch_ptr` must be usize-aligned (4 bytes on wasm32), must not overlap src/dst, and
+/// must hold at least scratch_size_needed(src_w, src_h, dst_w, dst_h, algorithm) bytes,
+/// queried under the same intermediate precision
+/// Returns RESIZE_ERR_MEMORY if the scratch is too small
+///
+/// # Safety
+/// src_ptr/dst_ptr must point to valid RGBA buffers of the given dimensions and
+/// scratch_ptr to `scratch_size` writable bytes
+#[no_mangle]
+pub unsafe extern "C" fn resize_rgba_scratch(
+    src_ptr: *const u8,
+    src_w: u32,
+    src_h: u32,
+    dst_ptr: *mut u8,
+    dst_w: u32,
+    dst_h: u32,
+    algorithm: u32,
+    scratch_ptr: *mut u8,
+    scratch_size: usize,
+) -> i32 {
+    let (src_size, dst_size) = match validate_params(src_ptr, src_w, src_h, dst_ptr, dst_w, dst_h) {
+        Ok(sizes) => sizes,
+        Err(code) => return code,
+    };
+    
+    if scratch_ptr.is_null() {
+        set_last_error(RESIZE_ERR_NULL_PTR);
+        return RESIZE_ERR_NULL_PTR;
+    }
+    
+    // The index LUTs are carved as usize, which is 8-byte aligned on 64-bit hosts
+    if !(scratch_ptr as usize).is_multiple_of(std::mem::align_of::<usize>()) {
+        set_last_error(RESIZE_ERR_ALIGNMENT);
+        return RESIZE_ERR_ALIGNMENT;
+    }
+    
+    let algorithm = match resolve_algorithm(algorithm, src_w, src_h, dst_w, dst_h) {
+        Ok(a) => a,
+        Err(code) => return code,
+    };
+    
+    let needed = match scratch_bytes(src_h, dst_w, dst_h, algorithm)
+        .and_then(|n| usize::try_from(n).ok())
+    {
+        Some(n) => n,
+        None => {
+            set_last_error(RESIZE_ERR_OVERFLOW);
+            return RESIZE_ERR_OVERFLOW;
+        }
+    };
+    
+    if scratch_size < needed {
+        set_last_error(RESIZE_ERR_MEMORY);
+        return RESIZE_ERR_MEMORY;
+    }
+    
+    // Scratch must not alias either image buffer
+    let scratch_start = scratch_ptr as usize;
+    let scratch_end = scratch_start.saturating_add(needed);
+    for (start, len) in [(src_ptr as usize, src_size), (dst_ptr as usize, dst_size)] {
+        if scratch_start < start.saturating_add(len) && start < scratch_end {
+            set_last_error(RESIZE_ERR_OVERLAP);
+            return RESIZE_ERR_OVERLAP;
+        }
+    }
+    
+    let src = std::slice::from_raw_parts(src_ptr, src_size);
+    let dst = std::slice::from_raw_parts_mut(dst_ptr, dst_size);
+    let dst_w_usize = dst_w as usize;
+    let mut offset = 0usize;
+    
+    match algorithm {
+        RESIZE_ALGO_NEAREST => {
+            let x_indices = carve_scratch::<usize>(scratch_ptr, &mut offset, dst_w_usize);
+            nearest_core(src, src_w, src_h, dst, dst_w, dst_h, DstRect::full(dst_w, dst_h), false, x_indices)
+        }
+        RESIZE_ALGO_BILINEAR => {
+            let x0_indices = carve_scratch::<usize>(scratch_ptr, &mut offset, dst_w_usize);
+            let x1_indices = carve_scratch::<usize>(scratch_ptr, &mut offset, dst_w_usize);
+            let fx_values = carve_scratch::<f32>(scratch_ptr, &mut offset, dst_w_usize);
+            bilinear_core(
+                src, src_w, src_h, dst, dst_w, dst_h,
+                DstRect::full(dst_w, dst_h), false, x0_indices, x1_indices, fx_values,
+            )
+        }
+        _ => {
+            let work = carve_lanczos_scratch(scratch_ptr, src_h, dst_w, dst_h);
+            lanczos_core(src, src_w, src_h, dst, dst_w, dst_h, DstRect::full(dst_w, dst_h), false, work)
+        }
+    }
+}
+
+/// Resize between two RGBA slices with an explicit algorithm using the thread-local buffers
+/// Shared entry point for variants that resize internal buffers rather than raw pointers
+fn resize_slices(
+    src: &[u8],
+    src_w: u32,
+    src_h: u32,
+    dst: &mut [u8],
+    dst_w: u32,
+    dst_h: u32,
+    algorithm: u32,
+) -> i32 {
+    resize_slices_in_rect(src, src_w, src_h, dst, dst_w, dst_h, DstRect::full(dst_w, dst_h), false, algorithm)
+}
+
+/// Like resize_slices, but only computes and writes destination pixels inside `rect`
+/// Source sampling is unchanged, so pixels inside `rect` match a full resize exactly
+/// With `src_bottom_up` the source rows are stored bottom-up (first row is the bottom)
+#[allow(clippy::too_many_arguments)]
+fn resize_slices_in_rect(
+    src: &[u8],
+    src_w: u32,
+    src_h: u32,
+    dst: &mut [u8],
+    dst_w: u32,
+    dst_h: u32,
+    rect: DstRect,
+    src_bottom_up: bool,
+    algorithm: u32,
+) -> i32 {
+    let algorithm = match resolve_algorithm(algorithm, src_w, src_h, dst_w, dst_h) {
+        Ok(a) => a,
+        Err(code) => return code,
+    };
+    
+    match algorithm {
+        RESIZE_ALGO_NEAREST => nearest_with_thread_locals(src, src_w, src_h, dst, dst_w, dst_h, rect, src_bottom_up),
+        RESIZE_ALGO_LANCZOS => lanczos_with_thread_locals(src, src_w, src_h, dst, dst_w, dst_h, rect, src_bottom_up),
+        _ => bilinear_with_thread_locals(src, src_w, src_h, dst, dst_w, dst_h, rect, src_bottom_up),
+    }
+}
+
+/// Owned working memory of a RowStream, kept in thread-local storage between calls
+/// Nearest uses `x0_indices` as its X mapping; Lanczos keeps its ring in the `lanczos`
+/// temp of the stream's intermediate precision
+#[derive(Default)]
+struct RowStreamStorage {
+    lanczos: LanczosStorage,
+    x0_indices: Vec<usize>,
+    x1_indices: Vec<usize>,
+    fx_values: Vec<f32>,
+}
+
+/// Destination-row-at-a-time resampler shared by the streaming exports
+/// The LUTs / tap tables are built once per stream. Lanczos keeps a ring of the last
+/// LANCZOS_MAX_TAPS Pass-1 rows (every destination row's taps span at most that many
+/// consecutive source rows) instead of the dst_w x src_h intermediate, and computes each
+/// source row once when rows are requested top to bottom. The ring and the weights use
+/// the intermediate precision selected when the stream is built, so rows are
+/// bit-identical to those of the full resize with the same algorithm at any precision
+struct RowStream<'a> {
+    src: &'a [u8],
+    src_w: u32,
+    src_h: u32,
+    dst_w: u32,
+    dst_h: u32,
+    algorithm: u32,
+    precision: u32,
+    deterministic: bool,
+    storage: RowStreamStorage,
+    // Source rows ring_start..ring_end currently held in the Pass-1 ring
+    ring_start: u32,
+    ring_end: u32,
+}
+
+impl<'a> RowStream<'a> {
+    /// Build the tables for one resize; `algorithm` must already be resolved
+    fn new(src: &'a [u8], src_w: u32, src_h: u32, dst_w: u32, dst_h: u32, algorithm: u32) -> Self {
+        // Taken out of the thread-local so a sink that starts another streamed resize
+        // gets its own storage instead of a double borrow
+        let mut storage = ROW_STREAM_STORAGE.with(|cell| std::mem::take(&mut *cell.borrow_mut()));
+        let dst_w_usize = dst_w as usize;
+        let precision = separable::intermediate_precision();
+        
+        match algorithm {
+            RESIZE_ALGO_NEAREST => {
+                storage.x0_indices.resize(dst_w_usize, 0);
+                fill_nearest_lut(src_w, dst_w, &mut storage.x0_indices);
+            }
+            RESIZE_ALGO_LANCZOS => {
+                let ring_len = dst_w_usize * LANCZOS_MAX_TAPS * 4;
+                storage.lanczos.work(dst_w, dst_h, ring_len, precision);
+                let lanczos = &mut storage.lanczos;
+                let scale_x = src_w as f32 / dst_w as f32;
+                let scale_y = src_h as f32 / dst_h as f32;
+                fill_lanczos_taps(src_w, scale_x, &mut lanczos.x_counts, &mut lanczos.x_weights, &mut lanczos.x_indices);
+                fill_lanczos_taps(src_h, scale_y, &mut lanczos.y_counts, &mut lanczos.y_weights, &mut lanczos.y_indices);
+                lanczos.fill_fixed_weights();
+            }
+            _ => {
+                storage.x0_indices.resize(dst_w_usize, 0);
+                storage.x1_indices.resize(dst_w_usize, 0);
+                storage.fx_values.resize(dst_w_usize, 0.0);
+                fill_bilinear_lut(src_w, dst_w, &mut storage.x0_indices, &mut storage.x1_indices, &mut storage.fx_values);
+            }
+        }
+        
+        RowStream {
+            src,
+            src_w,
+            src_h,
+            dst_w,
+            dst_h,
+            algorithm,
+            precision,
+            deterministic: DETERMINISTIC_ACCUMULATION.with(|d| d.get()),
+            storage,
+            ring_start: 0,
+            ring_end: 0,
+        }
+    }
+    
+    /// Compute destination row `y` into `dst_row` (dst_w RGBA pixels)
+    fn write_row(&mut self, y: u32, dst_row: &mut [u8]) -> i32 {
+        let (src, src_w, src_h, dst_w, dst_h) = (self.src, self.src_w, self.src_h, self.dst_w, self.dst_h);
+        let storage = &self.storage;
+        
+        match self.algorithm {
+            RESIZE_ALGO_NEAREST => nearest_row(src, src_w, src_h, dst_h, y, false, 0..dst_w, &storage.x0_indices, dst_row),
+            RESIZE_ALGO_LANCZOS => self.lanczos_row(y, dst_row),
+            _ => {
+                let lut = BilinearLut {
+                    x0_indices: &storage.x0_indices,
+                    x1_indices: &storage.x1_indices,
+                    fx_values: &storage.fx_values,
+                    deterministic: self.deterministic,
+                };
+                bilinear_row(src, src_w, src_h, dst_h, y, false, 0..dst_w, &lut, dst_row)
+            }
+        }
+    }
+    
+    /// Lanczos row in the stream's precision (see stream_lanczos_row)
+    fn lanczos_row(&mut self, y: u32, dst_row: &mut [u8]) -> i32 {
+        let LanczosStorage {
+            x_weights,
+            x_indices,
+            x_counts,
+            y_weights,
+            y_indices,
+            y_counts,
+            temp,
+            temp_f64,
+            temp_fixed,
+            fixed_weights,
+        } = &mut self.storage.lanczos;
+        let stream = StreamRow {
+            src: self.src,
+            src_w: self.src_w,
+            src_h: self.src_h,
+            dst_w: self.dst_w,
+            ring_window: (&mut self.ring_start, &mut self.ring_end),
+        };
+        
+        match self.precision {
+            RESIZE_PRECISION_FIXED => {
+                let (x_fixed, y_fixed) = fixed_weights.split_at(x_counts.len() * LANCZOS_MAX_TAPS);
+                let x_taps = LanczosTaps { counts: x_counts, weights: x_fixed, indices: x_indices };
+                let y_taps = LanczosTaps { counts: y_counts, weights: y_fixed, indices: y_indices };
+                stream_lanczos_row(stream, &x_taps, &y_taps, temp_fixed, y, dst_row)
+            }
+            precision => {
+                let x_taps = LanczosTaps { counts: x_counts, weights: x_weights, indices: x_indices };
+                let y_taps = LanczosTaps { counts: y_counts, weights: y_weights, indices: y_indices };
+                if precision == RESIZE_PRECISION_F64 {
+                    stream_lanczos_row(stream, &x_taps, &y_taps, temp_f64, y, dst_row)
+                } else {
+                    stream_lanczos_row(stream, &x_taps, &y_taps, temp, y, dst_row)
+                }
+            }
+        }
+    }
+    
+    /// Return the working memory to the thread-local for the next stream
+    fn release(self) {
+        ROW_STREAM_STORAGE.with(|cell| *cell.borrow_mut() = self.storage);
+    }
+}
+
+/// Source, sizes and Pass-1 ring window (`ring_start`, `ring_end`) of a RowStream,
+/// borrowed apart from its tap tables for stream_lanczos_row
+struct StreamRow<'s> {
+    src: &'s [u8],
+    src_w: u32,
+    src_h: u32,
+    dst_w: u32,
+    ring_window: (&'s mut u32, &'s mut u32),
+}
+
+/// Lanczos row of a RowStream with `S` as the Pass-1 sample type: bring the source rows
+/// under row `y`'s taps into `ring` (LANCZOS_MAX_TAPS rows of `S`), then Pass 2
+fn stream_lanczos_row<S: LanczosSample>(
+    stream: StreamRow<'_>,
+    x_taps: &LanczosTaps<'_, S::Weight>,
+    y_taps: &LanczosTaps<'_, S::Weight>,
+    ring: &mut [S],
+    y: u32,
+    dst_row: &mut [u8],
+) -> i32 {
+    let StreamRow { src, src_w, src_h, dst_w, ring_window: (ring_start, ring_end) } = stream;
+    let row_len = dst_w as usize * 4;
+    
+    let (_, indices) = y_taps.get(y);
+    if let (Some(&first), Some(&last)) = (indices.first(), indices.last()) {
+        let (first, last) = (first as u32, last as u32);
+        // Rows before the ring or past a gap start a fresh window
+        if first < *ring_start || first > *ring_end {
+            *ring_start = first;
+            *ring_end = first;
+        }
+        for sy in *ring_end..=last {
+            let slot = sy as usize % LANCZOS_MAX_TAPS;
+            let ring_row = &mut ring[slot * row_len..(slot + 1) * row_len];
+            let code = lanczos_pass1_row(src, src_w, src_h, sy, false, 0..dst_w, x_taps, ring_row);
+            if code != RESIZE_OK {
+                return code;
+            }
+        }
+        *ring_end = (*ring_end).max(last + 1);
+        *ring_start = (*ring_start).max(ring_end.saturating_sub(LANCZOS_MAX_TAPS as u32));
+    }
+    
+    lanczos_pass2_row(ring, |sy| sy % LANCZOS_MAX_TAPS, dst_w, src_h, y, 0..dst_w, y_taps, dst_row);
+    RESIZE_OK
+}
+
+/// Resize with an explicit algorithm, validating raw pointers first
+/// Common dispatcher for the exported variants taking an `algorithm` parameter
+unsafe fn resize_with_algorithm(
+    src_ptr: *const u8,
+    src_w: u32,
+    src_h: u32,
+    dst_ptr: *mut u8,
+    dst_w: u32,
+    dst_h: u32,
+    algorithm: u32,
+) -> i32 {
+    let (src_size, dst_size) = match validate_params(src_ptr, src_w, src_h, dst_ptr, dst_w, dst_h) {
+        Ok(sizes) => sizes,
+        Err(code) => return code,
+    };
+    
+    let src = std::slice::from_raw_parts(src_ptr, src_size);
+    let dst = std::slice::from_raw_parts_mut(dst_ptr, dst_size);
+    resize_slices(src, src_w, src_h, dst, dst_w, dst_h, algorithm)
+}
+
+/// resize_with_algorithm as the first pass of an operation that post-processes `dst`
+/// Starts the operation's row tracking, but leaves the rows unreported: the caller
+/// marks them once its own pass has stored their final values
+unsafe fn resize_for_post_pass(
+    src_ptr: *const u8,
+    src_w: u32,
+    src_h: u32,
+    dst_ptr: *mut u8,
+    dst_w: u32,
+    dst_h: u32,
+    algorithm: u32,
+) -> i32 {
+    begin_resize();
+    intermediate_pass(|| resize_with_algorithm(src_ptr, src_w, src_h, dst_ptr, dst_w, dst_h, algorithm))
+}
+
+/// Resize and stream the output one row at a time
+/// Each destination row is computed into a thread-local row buffer and passed to
+/// `row_sink(row_index, row_ptr, row_len)` in top-to-bottom order, so the caller never
+/// needs a full destination frame. The taps are built once and Lanczos keeps only a
+/// ring of Pass-1 rows (see RowStream), so no full-size intermediate is allocated either.
+/// The rows are identical to those of the regular resize with `algorithm` at the current
+/// intermediate precision; `row_ptr` is only valid for the duration of the callback
+/// Returns error code: 0 = success, non-zero = error
+///
+/// # Safety
+/// src_ptr must point to a valid RGBA buffer of the given dimensions
+#[no_mangle]
+pub unsafe extern "C" fn resize_rgba_row_sink(
+    src_ptr: *const u8,
+    src_w: u32,
+    src_h: u32,
+    dst_w: u32,
+    dst_h: u32,
+    algorithm: u32,
+    row_sink: extern "C" fn(row_index: u32, row_ptr: *const u8, row_len: usize),
+) -> i32 {
+    let src_size = match validate_buffer(src_ptr, src_w, src_h) {
+        Ok(size) => size,
+        Err(code) => return code,
+    };
+
+    let dst_pixels = dst_w as u64 * dst_h as u64;
+    if dst_w == 0 || dst_h == 0 || dst_w > MAX_DIMENSION || dst_h > MAX_DIMENSION || dst_pixels > MAX_PIXELS {
+        set_last_error(RESIZE_ERR_INVALID_SIZE);
+        return RESIZE_ERR_INVALID_SIZE;
+    }
+
+    let algorithm = match resolve_algorithm(algorithm, src_w, src_h, dst_w, dst_h) {
+        Ok(a) => a,
+        Err(code) => return code,
+    };
+
+    let src = std::slice::from_raw_parts(src_ptr, src_size);
+
+    // Taken out of the thread-local so a sink that starts another streamed resize
+    // gets its own buffer instead of a double borrow
+    let mut row = ROW_SINK_BUFFER.with(|cell| std::mem::take(&mut *cell.borrow_mut()));
+    row.resize(dst_w as usize * 4, 0);
+
+    let mut stream = RowStream::new(src, src_w, src_h, dst_w, dst_h, algorithm);
+    let mut code = RESIZE_OK;
+    for y in 0..dst_h {
+        code = stream.write_row(y, &mut row);
+        if code != RESIZE_OK {
+            break;
+        }
+        row_sink(y, row.as_ptr(), row.len());
+        mark_row_completed(y);
+        if cancel_due(y, dst_h) {
+            code = RESIZE_ERR_CANCELLED;
+            break;
+        }
+    }
+    stream.release();
+
+    ROW_SINK_BUFFER.with(|cell| *cell.borrow_mut() = row);
+    code
+}
+
+/// Resize and stream the output one tile at a time
+/// The destination is split into `tile_w` x `tile_h` tiles (smaller at the right and
+/// bottom edges), visited row by row. Each band of `tile_h` destination rows is
+/// resampled once through the same RowStream as resize_rgba_row_sink (so the rows match
+/// the full resize with `algorithm` at the current intermediate precision and Lanczos
+/// keeps only its Pass-1 ring), then cut into tiles in a thread-local buffer. Each tile
+/// is passed to `tile_sink(tile_x, tile_y, ptr, w, h)`: the destination pixel position of
+/// its top-left corner, and `w` x `h` tightly packed RGBA pixels that are only valid for
+/// the duration of the callback
+/// Returns error code: 0 = success, non-zero = error
+///
+/// # Safety
+/// src_ptr must point to a valid RGBA buffer of the given dimensions
+#[no_mangle]
+pub unsafe extern "C" fn resize_rgba_tile_sink(
+    src_ptr: *const u8,
+    src_w: u32,
+    src_h: u32,
+    dst_w: u32,
+    dst_h: u32,
+    tile_w: u32,
+    tile_h: u32,
+    algorithm: u32,
+    tile_sink: extern "C" fn(tile_x: u32, tile_y: u32, ptr: *const u8, w: u32, h: u32),
+) -> i32 {
+    let src_size = match validate_buffer(src_ptr, src_w, src_h) {
+        Ok(size) => size,
+        Err(code) => return code,
+    };
+
+    let dst_pixels = dst_w as u64 * dst_h as u64;
+    if dst_w == 0 || dst_h == 0 || dst_w > MAX_DIMENSION || dst_h > MAX_DIMENSION || dst_pixels > MAX_PIXELS {
+        set_last_error(RESIZE_ERR_INVALID_SIZE);
+        return RESIZE_ERR_INVALID_SIZE;
+    }
+
+    if tile_w == 0 || tile_h == 0 {
+        set_last_error(RESIZE_ERR_INVALID_PARAM);
+        return RESIZE_ERR_INVALID_PARAM;
+    }
+
+    let algorithm = match resolve_algorithm(algorithm, src_w, src_h, dst_w, dst_h) {
+        Ok(a) => a,
+        Err(code) => return code,
+    };
+
+    let src = std::slice::from_raw_parts(src_ptr, src_size);
+    let (tile_w, tile_h) = (tile_w.min(dst_w), tile_h.min(dst_h));
+    let row_bytes = dst_w as usize * 4;
+    let band_len = row_bytes * tile_h as usize;
+
+    // Taken out of the thread-local for the same reentrancy reason as ROW_SINK_BUFFER
+    let mut buffer = TILE_SINK_BUFFER.with(|cell| std::mem::take(&mut *cell.borrow_mut()));
+    buffer.resize(band_len + tile_w as usize * tile_h as usize * 4, 0);
+    let (band, tile) = buffer.split_at_mut(band_len);
+
+    let mut stream = RowStream::new(src, src_w, src_h, dst_w, dst_h, algorithm);
+    let mut code = RESIZE_OK;
+    'bands: for y in (0..dst_h).step_by(tile_h as usize) {
+        let h = tile_h.min(dst_h - y);
+        // The band holds full-width rows starting at destination row y
+        for (band_y, row) in (y..y + h).zip(band.chunks_exact_mut(row_bytes)) {
+            code = stream.write_row(band_y, row);
+            if code != RESIZE_OK {
+                break 'bands;
+            }
+            if cancel_due(band_y, dst_h) {
+                code = RESIZE_ERR_CANCELLED;
+                break 'bands;
+            }
+        }
+
+        for x in (0..dst_w).step_by(tile_w as usize) {
+            let w = tile_w.min(dst_w - x);
+            let tile_row = w as usize * 4;
+            for (out, row) in tile.chunks_exact_mut(tile_row).zip(band.chunks_exact(row_bytes)).take(h as usize) {
+                out.copy_from_slice(&row[x as usize * 4..x as usize * 4 + tile_row]);
+            }
+            tile_sink(x, y, tile.as_ptr(), w, h);
+        }
+        // A band's rows are complete once all of its tiles have been delivered
+        mark_rows_completed(y + h);
+    }
+    stream.release();
+
+    TILE_SINK_BUFFER.with(|cell| *cell.borrow_mut() = buffer);
+    code
+}
+
+/// Produce a low-res preview and a full-res result from one call
+/// The full-size output is resized from the source first; when the preview is no larger
+/// than the full output on either axis it is derived from the full result (reusing that
+/// work) instead of resampling the source a second time.
+/// Returns error code: 0 = success, non-zero = error
+///
+/// # Safety
+/// src_ptr, preview_ptr and full_ptr must point to valid, non-overlapping RGBA buffers
+/// of their respective dimensions
+#[no_mangle]
+pub unsafe extern "C" fn resize_rgba_multi(
+    src_ptr: *const u8,
+    src_w: u32,
+    src_h: u32,
+    preview_ptr: *mut u8,
+    preview_w: u32,
+    preview_h: u32,
+    full_ptr: *mut u8,
+    full_w: u32,
+    full_h: u32,
+    algorithm: u32,
+) -> i32 {
+    // Validate every pairing up front so no output is written on bad input
+    if let Err(code) = validate_params(src_ptr, src_w, src_h, preview_ptr, preview_w, preview_h) {
+        return code;
+    }
+    if let Err(code) = validate_params(full_ptr, full_w, full_h, preview_ptr, preview_w, preview_h) {
+        return code;
+    }
+    
+    let code = resize_with_algorithm(src_ptr, src_w, src_h, full_ptr, full_w, full_h, algorithm);
+    if code != RESIZE_OK {
+        return code;
+    }
+    
+    if preview_w <= full_w && preview_h <= full_h {
+        resize_with_algorithm(full_ptr, full_w, full_h, preview_ptr, preview_w, preview_h, algorithm)
+    } else {
+        resize_with_algorithm(src_ptr, src_w, src_h, preview_ptr, preview_w, preview_h, algorithm)
+    }
+}
+
+/// One resize of a batch passed to resize_rgba_batch_budget
+/// On wasm32 this is eight 32-bit words: src_ptr, src_w, src_h, dst_ptr, dst_w, dst_h,
+/// algorithm, result. dst_w/dst_h are updated to the dimensions actually written and
+/// `result` receives the entry's error code
+#[repr(C)]
+pub struct ResizeBatchEntry {
+    pub src_ptr: *const u8,
+    pub src_w: u32,
+    pub src_h: u32,
+    pub dst_ptr: *mut u8,
+    pub dst_w: u32,
+    pub dst_h: u32,
+    pub algorithm: u32,
+    pub result: i32,
+}
+
+/// Resize a batch of images while keeping the total output under a pixel budget
+/// When the requested destination sizes of all `count` entries add up to more than
+/// `pixel_budget` pixels, every destination is shrunk by the same factor (preserving its
+/// aspect ratio, at least 1x1) until the total fits. The adjusted dimensions are written
+/// back into each entry and the output is packed at that width from the start of its
+/// dst_ptr. All entries are validated against their requested sizes before any pixel is
+/// written
+/// Returns error code: 0 = success, otherwise the first failing entry's code
+///
+/// # Safety
+/// entries_ptr must point to `count` writable, 4-byte aligned ResizeBatchEntry values,
+/// each describing valid, non-overlapping RGBA buffers of the requested dimensions
+#[no_mangle]
+pub unsafe extern "C" fn resize_rgba_batch_budget(
+    entries_ptr: *mut ResizeBatchEntry,
+    count: u32,
+    pixel_budget: u64,
+) -> i32 {
+    if entries_ptr.is_null() {
+        set_last_error(RESIZE_ERR_NULL_PTR);
+        return RESIZE_ERR_NULL_PTR;
+    }
+
+    if !(entries_ptr as usize).is_multiple_of(std::mem::align_of::<ResizeBatchEntry>()) {
+        set_last_error(RESIZE_ERR_ALIGNMENT);
+        return RESIZE_ERR_ALIGNMENT;
+    }
+
+    // Every destination needs at least one pixel
+    if pixel_budget < count as u64 {
+        set_last_error(RESIZE_ERR_INVALID_PARAM);
+        return RESIZE_ERR_INVALID_PARAM;
+    }
+
+    let entries = std::slice::from_raw_parts_mut(entries_ptr, count as usize);
+    for entry in entries.iter() {
+        if let Err(code) = validate_params(entry.src_ptr, entry.src_w, entry.src_h, entry.dst_ptr, entry.dst_w, entry.dst_h) {
+            return code;
+        }
+    }
+
+    let requested: Vec<(u32, u32)> = entries.iter().map(|e| (e.dst_w, e.dst_h)).collect();
+    let scaled = |factor: f64| -> Vec<(u32, u32)> {
+        requested
+            .iter()
+            .map(|&(w, h)| (((w as f64 * factor) as u32).max(1), ((h as f64 * factor) as u32).max(1)))
+            .collect()
+    };
+    let total = |dims: &[(u32, u32)]| dims.iter().map(|&(w, h)| w as u64 * h as u64).sum::<u64>();
+
+    let mut dims = requested.clone();
+    let requested_total = total(&dims);
+    if requested_total > pixel_budget {
+        let mut factor = (pixel_budget as f64 / requested_total as f64).sqrt();
+        dims = scaled(factor);
+        // Flooring keeps the total within budget except where a size was raised to 1
+        while total(&dims) > pixel_budget {
+            factor *= 0.99;
+            dims = scaled(factor);
+        }
+    }
+
+    let mut first_error = RESIZE_OK;
+    for (entry, &(w, h)) in entries.iter_mut().zip(&dims) {
+        entry.dst_w = w;
+        entry.dst_h = h;
+        entry.result = resize_with_algorithm(entry.src_ptr, entry.src_w, entry.src_h, entry.dst_ptr, w, h, entry.algorithm);
+        if first_error == RESIZE_OK {
+            first_error = entry.result;
+        }
+    }
+
+    if first_error != RESIZE_OK {
+        set_last_error(first_error);
+    }
+    first_error
+}
+
+/// Version-pinned automatic resize for golden tests and other reproducible callers
+/// Picks the algorithm with the frozen v1 heuristic (select_resize_algorithm_v1) so
+/// later tuning of resize_rgba's selection never changes which resampler runs here
+/// Returns error code: 0 = success, non-zero = error
+///
+/// # Safety
+/// src_ptr/dst_ptr must point to valid RGBA buffers of the given dimensions
+#[no_mangle]
+pub unsafe extern "C" fn resize_rgba_v1(
+    src_ptr: *const u8,
+    src_w: u32,
+    src_h: u32,
+    dst_ptr: *mut u8,
+    dst_w: u32,
+    dst_h: u32,
+) -> i32 {
+    let algorithm = select_resize_algorithm_v1(src_w, src_h, dst_w, dst_h);
+    resize_with_algorithm(src_ptr, src_w, src_h, dst_ptr, dst_w, dst_h, algorithm)
+}
+
+/// Resize that can refuse to upscale
+/// With `no_upscale` = 1, each axis whose requested size exceeds the source is clamped to
+/// the source size; when both axes end up at the source size the pixels are copied
+/// through unchanged. The dimensions actually written are stored to `out_dims` as
+/// [w, h], and the output is packed at that width from the start of `dst_ptr`.
+/// `no_upscale` = 0 resizes to the requested size as usual
+/// Returns error code: 0 = success, non-zero = error
+///
+/// # Safety
+/// src_ptr must point to a valid RGBA buffer of the given dimensions, dst_ptr to one
+/// of the requested dst_w x dst_h, and `out_dims` to 2 writable, 4-byte aligned u32 values
+#[no_mangle]
+pub unsafe extern "C" fn resize_rgba_capped(
+    src_ptr: *const u8,
+    src_w: u32,
+    src_h: u32,
+    dst_ptr: *mut u8,
+    dst_w: u32,
+    dst_h: u32,
+    algorithm: u32,
+    no_upscale: u32,
+    out_dims: *mut u32,
+) -> i32 {
+    let (src_size, dst_size) = match validate_params(src_ptr, src_w, src_h, dst_ptr, dst_w, dst_h) {
+        Ok(sizes) => sizes,
+        Err(code) => return code,
+    };
+
+    if out_dims.is_null() {
+        set_last_error(RESIZE_ERR_NULL_PTR);
+        return RESIZE_ERR_NULL_PTR;
+    }
+
+    if !(out_dims as usize).is_multiple_of(std::mem::align_of::<u32>()) {
+        set_last_error(RESIZE_ERR_ALIGNMENT);
+        return RESIZE_ERR_ALIGNMENT;
+    }
+
+    if no_upscale > 1 {
+        set_last_error(RESIZE_ERR_INVALID_PARAM);
+        return RESIZE_ERR_INVALID_PARAM;
+    }
+
+    let (used_w, used_h) = if no_upscale == 1 {
+        (dst_w.min(src_w), dst_h.min(src_h))
+    } else {
+        (dst_w, dst_h)
+    };
+
+    if let Err(code) = resolve_algorithm(algorithm, src_w, src_h, used_w, used_h) {
+        return code;
+    }
+
+    let src = std::slice::from_raw_parts(src_ptr, src_size);
+    let dst = std::slice::from_raw_parts_mut(dst_ptr, dst_size);
+
+    let code = if used_w == src_w && used_h == src_h {
+        dst[..src_size].copy_from_slice(src);
+        mark_rows_completed(used_h);
+        RESIZE_OK
+    } else {
+        let used_size = used_w as usize * used_h as usize * 4;
+        resize_slices(src, src_w, src_h, &mut dst[..used_size], used_w, used_h, algorithm)
+    };
+
+    if code == RESIZE_OK {
+        std::slice::from_raw_parts_mut(out_dims, 2).copy_from_slice(&[used_w, used_h]);
+    }
+    code
+}
+
+/// Resize with a selectable source row order
+/// `flip_input` = 1 reads the source bottom-up (BMP-style and GPU readback data, where
+/// the first stored row is the bottom of the image) by inverting the source row mapping
+/// of the chosen algorithm, so no separate flip pass is needed; the output is top-down.
+/// `flip_input` = 0 is a regular top-down resize
+/// Returns error code: 0 = success, non-zero = error
+///
+/// # Safety
+/// src_ptr/dst_ptr must point to valid RGBA buffers of the given dimensions
+#[no_mangle]
+pub unsafe extern "C" fn resize_rgba_row_order(
+    src_ptr: *const u8,
+    src_w: u32,
+    src_h: u32,
+    dst_ptr: *mut u8,
+    dst_w: u32,
+    dst_h: u32,
+    algorithm: u32,
+    flip_input: u32,
+) -> i32 {
+    let (src_size, dst_size) = match validate_params(src_ptr, src_w, src_h, dst_ptr, dst_w, dst_h) {
+        Ok(sizes) => sizes,
+        Err(code) => return code,
+    };
+
+    if flip_input > 1 {
+        set_last_error(RESIZE_ERR_INVALID_PARAM);
+        return RESIZE_ERR_INVALID_PARAM;
+    }
+
+    let src = std::slice::from_raw_parts(src_ptr, src_size);
+    let dst = std::slice::from_raw_parts_mut(dst_ptr, dst_size);
+    let rect = DstRect::full(dst_w, dst_h);
+    resize_slices_in_rect(src, src_w, src_h, dst, dst_w, dst_h, rect, flip_input == 1, algorithm)
+}
+
+/// Resize with a hard (stencil) alpha channel
+/// RGB and alpha are resized normally with `algorithm`, then each output alpha is
+/// snapped to 0 when below `threshold` and to 255 otherwise, so cutout edges stay
+/// crisp at the position where the interpolated coverage crosses the threshold
+/// Returns error code: 0 = success, non-zero = error
+///
+/// # Safety
+/// src_ptr/dst_ptr must point to valid RGBA buffers of the given dimensions
+#[no_mangle]
+pub unsafe extern "C" fn resize_rgba_binary_alpha(
+    src_ptr: *const u8,
+    src_w: u32,
+    src_h: u32,
+    dst_ptr: *mut u8,
+    dst_w: u32,
+    dst_h: u32,
+    threshold: u8,
+    algorithm: u32,
+) -> i32 {
+    let code = resize_for_post_pass(src_ptr, src_w, src_h, dst_ptr, dst_w, dst_h, algorithm);
+    if code != RESIZE_OK {
+        return code;
+    }
+
+    let dst = std::slice::from_raw_parts_mut(dst_ptr, dst_w as usize * dst_h as usize * 4);
+    for pixel in dst.chunks_exact_mut(4) {
+        pixel[3] = if pixel[3] < threshold { 0 } else { 255 };
+    }
+    mark_rows_completed(dst_h);
+
+    RESIZE_OK
+}
+
+/// Resize RGBA data that is already premultiplied by alpha
+/// The premultiplied channels are filtered directly with `algorithm` (the correct
+/// operation for premultiplied data) and the output stays premultiplied. Unlike the
+/// straight-alpha premultiplied paths nothing is multiplied by alpha again, so edges
+/// are not darkened. Kernel overshoot is clamped so every output color is <= its alpha
+/// Returns error code: 0 = success, non-zero = error
+///
+/// # Safety
+/// src_ptr/dst_ptr must point to valid RGBA buffers of the given dimensions
+#[no_mangle]
+pub unsafe extern "C" fn resize_rgba_assume_premultiplied(
+    src_ptr: *const u8,
+    src_w: u32,
+    src_h: u32,
+    dst_ptr: *mut u8,
+    dst_w: u32,
+    dst_h: u32,
+    algorithm: u32,
+) -> i32 {
+    let code = resize_for_post_pass(src_ptr, src_w, src_h, dst_ptr, dst_w, dst_h, algorithm);
+    if code != RESIZE_OK {
+        return code;
+    }
+
+    let dst = std::slice::from_raw_parts_mut(dst_ptr, dst_w as usize * dst_h as usize * 4);
+    for pixel in dst.chunks_exact_mut(4) {
+        let alpha = pixel[3];
+        for channel in &mut pixel[..3] {
+            *channel = (*channel).min(alpha);
+        }
+    }
+    mark_rows_completed(dst_h);
+
+    RESIZE_OK
+}
+
+/// Resize and clamp the output color to a legal range
+/// RGB is resized normally with `algorithm` and then clamped to [`low`, `high`] instead
+/// of [0, 255], e.g. 16..=235 to keep limited-range (studio-swing) video broadcast-legal
+/// when filter overshoot would leave it. Alpha is not a video level and keeps its full
+/// range. `low` must be below `high`
+/// Returns error code: 0 = success, non-zero = error
+///
+/// # Safety
+/// src_ptr/dst_ptr must point to valid RGBA buffers of the given dimensions
+#[no_mangle]
+pub unsafe extern "C" fn resize_rgba_clamp_range(
+    src_ptr: *const u8,
+    src_w: u32,
+    src_h: u32,
+    dst_ptr: *mut u8,
+    dst_w: u32,
+    dst_h: u32,
+    low: u8,
+    high: u8,
+    algorithm: u32,
+) -> i32 {
+    if low >= high {
+        set_last_error(RESIZE_ERR_INVALID_PARAM);
+        return RESIZE_ERR_INVALID_PARAM;
+    }
+
+    let code = resize_for_post_pass(src_ptr, src_w, src_h, dst_ptr, dst_w, dst_h, algorithm);
+    if code != RESIZE_OK {
+        return code;
+    }
+
+    let dst = std::slice::from_raw_parts_mut(dst_ptr, dst_w as usize * dst_h as usize * 4);
+    for pixel in dst.chunks_exact_mut(4) {
+        for channel in &mut pixel[..3] {
+            *channel = (*channel).clamp(low, high);
+        }
+    }
+    mark_rows_completed(dst_h);
+
+    RESIZE_OK
+}
+
+/// Resize into destination rows placed `dst_stride` bytes apart (negative = upwards)
+/// The image is resized once into a contiguous thread-local scratch frame and its rows
+/// are copied into place, so padding between rows is never written.
+/// Callers must have validated the whole strided extent and resolved `algorithm`
+#[allow(clippy::too_many_arguments)]
+unsafe fn resize_strided_rows(
+    src: &[u8],
+    src_w: u32,
+    src_h: u32,
+    dst_ptr: *mut u8,
+    dst_w: u32,
+    dst_h: u32,
+    dst_stride: isize,
+    algorithm: u32,
+) -> i32 {
+    let row_bytes = dst_w as usize * 4;
+    // Taken out of the thread-local so nested resizes cannot double-borrow it
+    let mut frame = STRIDED_FRAME.with(|cell| std::mem::take(&mut *cell.borrow_mut()));
+    frame.resize(row_bytes * dst_h as usize, 0);
+
+    let code = intermediate_pass(|| resize_slices(src, src_w, src_h, &mut frame, dst_w, dst_h, algorithm));
+    if code == RESIZE_OK {
+        for (y, row) in frame.chunks_exact(row_bytes).enumerate() {
+            let dst_row = std::slice::from_raw_parts_mut(dst_ptr.offset(y as isize * dst_stride), row_bytes);
+            dst_row.copy_from_slice(row);
+            mark_row_completed(y as u32);
+        }
+    }
+
+    STRIDED_FRAME.with(|cell| *cell.borrow_mut() = frame);
+    code
+}
+
+/// Resize into a destination with an explicit signed row stride in bytes
+/// Uses the same automatic algorithm selection as resize_rgba. Row `y` of the output is
+/// written at `dst_ptr + y * dst_stride`, so a negative stride with `dst_ptr` at the last
+/// row of the buffer writes the image bottom-to-top (mirrored vertically).
+/// `|dst_stride|` must be a multiple of 4 and at least `dst_w * 4`; padding between rows
+/// is left untouched. `dst_size` is the byte size of the destination buffer measured
+/// from its lowest address, and the whole strided extent must fit inside it and must
+/// not overlap the source
+/// Returns error code: 0 = success, non-zero = error
+///
+/// # Safety
+/// src_ptr must point to a valid RGBA buffer of the given dimensions and the
+/// destination buffer must be valid for `dst_size` bytes from its lowest address
+#[no_mangle]
+pub unsafe extern "C" fn resize_rgba_dst_stride(
+    src_ptr: *const u8,
+    src_w: u32,
+    src_h: u32,
+    dst_ptr: *mut u8,
+    dst_w: u32,
+    dst_h: u32,
+    dst_stride: isize,
+    dst_size: usize,
+) -> i32 {
+    let (src_size, row_bytes) = match validate_params(src_ptr, src_w, src_h, dst_ptr, dst_w, 1) {
+        Ok(sizes) => sizes,
+        Err(code) => return code,
+    };
+
+    let dst_pixels = dst_w as u64 * dst_h as u64;
+    if dst_h == 0 || dst_h > MAX_DIMENSION || dst_pixels > MAX_PIXELS {
+        set_last_error(RESIZE_ERR_INVALID_SIZE);
+        return RESIZE_ERR_INVALID_SIZE;
+    }
+
+    let stride_abs = dst_stride.unsigned_abs();
+    if stride_abs < row_bytes || !stride_abs.is_multiple_of(4) {
+        set_last_error(RESIZE_ERR_INVALID_PARAM);
+        return RESIZE_ERR_INVALID_PARAM;
+    }
+
+    // Byte span from the lowest written address to the end of the highest row
+    let span = match stride_abs
+        .checked_mul(dst_h as usize - 1)
+        .and_then(|x| x.checked_add(row_bytes))
+    {
+        Some(s) => s,
+        None => {
+            set_last_error(RESIZE_ERR_OVERFLOW);
+            return RESIZE_ERR_OVERFLOW;
+        }
+    };
+    if span > dst_size {
+        set_last_error(RESIZE_ERR_MEMORY);
+        return RESIZE_ERR_MEMORY;
+    }
+
+    // A negative stride starts at the last row, so the buffer begins below dst_ptr
+    let dst_start = if dst_stride < 0 {
+        match (dst_ptr as usize).checked_sub(span - row_bytes) {
+            Some(start) => start,
+            None => {
+                set_last_error(RESIZE_ERR_OVERFLOW);
+                return RESIZE_ERR_OVERFLOW;
+            }
+        }
+    } else {
+        dst_ptr as usize
+    };
+    let dst_end = match dst_start.checked_add(span) {
+        Some(end) => end,
+        None => {
+            set_last_error(RESIZE_ERR_OVERFLOW);
+            return RESIZE_ERR_OVERFLOW;
+        }
+    };
+
+    let src_start = src_ptr as usize;
+    let src_end = src_start.saturating_add(src_size);
+    if (src_start < dst_end) && (dst_start < src_end) {
+        set_last_error(RESIZE_ERR_OVERLAP);
+        return RESIZE_ERR_OVERLAP;
+    }
+
+    // Same selection as resize_rgba, so the rows match a contiguous resize exactly
+    let algorithm = select_resize_algorithm(src_w, src_h, dst_w, dst_h);
+
+    let src = std::slice::from_raw_parts(src_ptr, src_size);
+    resize_strided_rows(src, src_w, src_h, dst_ptr, dst_w, dst_h, dst_stride, algorithm)
+}
+
+/// Resize with rows padded to a caller-chosen byte alignment (e.g. 256 for WebGPU
+/// texture uploads)
+/// Each output row starts at a multiple of `dst_row_align` bytes from `dst_ptr`: the
+/// row stride is `dst_w * 4` rounded up to `dst_row_align`, so the destination holds
+/// `stride * dst_h` bytes. Padding bytes are left untouched. `dst_row_align` must be a
+/// power of two and at least 4 (one pixel); the image is resized once and its rows are
+/// copied into place
+/// Returns error code: 0 = success, non-zero = error
+///
+/// # Safety
+/// src_ptr must point to a valid RGBA buffer of the given dimensions and dst_ptr to
+/// `stride * dst_h` writable bytes that do not overlap the source
+#[no_mangle]
+pub unsafe extern "C" fn resize_rgba_aligned_stride(
+    src_ptr: *const u8,
+    src_w: u32,
+    src_h: u32,
+    dst_ptr: *mut u8,
+    dst_w: u32,
+    dst_h: u32,
+    dst_row_align: u32,
+    algorithm: u32,
+) -> i32 {
+    let (src_size, row_bytes) = match validate_params(src_ptr, src_w, src_h, dst_ptr, dst_w, 1) {
+        Ok(sizes) => sizes,
+        Err(code) => return code,
+    };
+
+    let dst_pixels = dst_w as u64 * dst_h as u64;
+    if dst_h == 0 || dst_h > MAX_DIMENSION || dst_pixels > MAX_PIXELS {
+        set_last_error(RESIZE_ERR_INVALID_SIZE);
+        return RESIZE_ERR_INVALID_SIZE;
+    }
+
+    if !dst_row_align.is_power_of_two() || dst_row_align < 4 {
+        set_last_error(RESIZE_ERR_INVALID_PARAM);
+        return RESIZE_ERR_INVALID_PARAM;
+    }
+
+    let stride = (row_bytes as u64).next_multiple_of(dst_row_align as u64);
+    let dst_size = match stride.checked_mul(dst_h as u64) {
+        Some(size) if size <= isize::MAX as u64 => size as usize,
+        _ => {
+            set_last_error(RESIZE_ERR_OVERFLOW);
+            return RESIZE_ERR_OVERFLOW;
+        }
+    };
+
+    let src_start = src_ptr as usize;
+    let src_end = src_start.saturating_add(src_size);
+    let dst_start = dst_ptr as usize;
+    let dst_end = dst_start.saturating_add(dst_size);
+    if (src_start < dst_end) && (dst_start < src_end) {
+        set_last_error(RESIZE_ERR_OVERLAP);
+        return RESIZE_ERR_OVERLAP;
+    }
+
+    let algorithm = match resolve_algorithm(algorithm, src_w, src_h, dst_w, dst_h) {
+        Ok(a) => a,
+        Err(code) => return code,
+    };
+
+    let src = std::slice::from_raw_parts(src_ptr, src_size);
+    resize_strided_rows(src, src_w, src_h, dst_ptr, dst_w, dst_h, stride as isize, algorithm)
+}
+
+// Fixed-point one (Q16) for the tap fractions of resize_rgba_ratio
+const RATIO_FRAC_ONE: u64 = 1 << 16;
+
+/// Greatest common divisor; resize_rgba_ratio reduces `num / den` with it first
+#[inline(always)]
+fn gcd(mut a: u32, mut b: u32) -> u32 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// One axis of resize_rgba_ratio: per destination coordinate, the two source taps and
+/// the Q16 weight of the second, computed with integer arithmetic only
+/// Destination center `d + 0.5` maps to source `(d + 0.5) * den / num - 0.5`; the Q16
+/// position is formed in i128 since `(2d + 1) * den << 16` can exceed i64 for large `den`
+fn ratio_axis(dst_size: u32, src_size: u32, num: u32, den: u32, algorithm: u32) -> Vec<(usize, usize, u64)> {
+    let last = src_size as i128 - 1;
+    (0..dst_size as i128)
+        .map(|d| {
+            // Doubled so the pixel center stays an integer: (2d + 1) * den / (2 * num)
+            let center = (2 * d + 1) * den as i128;
+            if algorithm == RESIZE_ALGO_NEAREST {
+                let i = (center / (2 * num as i128)).min(last) as usize;
+                return (i, i, 0);
+            }
+            let pos = (center << 16) / (2 * num as i128) - (1 << 15);
+            let (i0, frac) = match pos >> 16 {
+                _ if pos < 0 => (0, 0),
+                i if i >= last => (last, 0),
+                i => (i, pos & 0xFFFF),
+            };
+            (i0 as usize, (i0 + 1).min(last) as usize, frac as u64)
+        })
+        .collect()
+}
+
+/// Resize by an exact integer ratio with fixed-point coordinate mapping
+/// The scale is `num / den` (destination pixels per source pixel) instead of being
+/// inferred from the dimensions, and source positions are derived with integer math
+/// (Q16 fractions), so the output is bit-identical on every platform. Only nearest and
+/// bilinear (interpolated with integer weights) are supported; other algorithms
+/// return RESIZE_ERR_INVALID_PARAM. Each destination dimension must equal the source
+/// dimension times `num / den`, rounded either way
+/// Returns error code: 0 = success, non-zero = error
+///
+/// # Safety
+/// src_ptr/dst_ptr must point to valid RGBA buffers of the given dimensions
+#[no_mangle]
+pub unsafe extern "C" fn resize_rgba_ratio(
+    src_ptr: *const u8,
+    src_w: u32,
+    src_h: u32,
+    dst_ptr: *mut u8,
+    dst_w: u32,
+    dst_h: u32,
+    num: u32,
+    den: u32,
+    algorithm: u32,
+) -> i32 {
+    let (src_size, dst_size) = match validate_params(src_ptr, src_w, src_h, dst_ptr, dst_w, dst_h) {
+        Ok(sizes) => sizes,
+        Err(code) => return code,
+    };
+
+    // Consistent when dst * den is within one `den` of src * num
+    let consistent = |src: u32, dst: u32| (dst as u64 * den as u64).abs_diff(src as u64 * num as u64) < den as u64;
+    if num == 0
+        || den == 0
+        || !consistent(src_w, dst_w)
+        || !consistent(src_h, dst_h)
+        || (algorithm != RESIZE_ALGO_NEAREST && algorithm != RESIZE_ALGO_BILINEAR)
+    {
+        set_last_error(RESIZE_ERR_INVALID_PARAM);
+        return RESIZE_ERR_INVALID_PARAM;
+    }
+
+    // Equivalent ratios such as 2/4 and 1/2 map every pixel identically
+    let g = gcd(num, den);
+    let (num, den) = (num / g, den / g);
+
+    let src = std::slice::from_raw_parts(src_ptr, src_size);
+    let dst = std::slice::from_raw_parts_mut(dst_ptr, dst_size);
+    let x_taps = ratio_axis(dst_w, src_w, num, den, algorithm);
+    let y_taps = ratio_axis(dst_h, src_h, num, den, algorithm);
+    let src_row_bytes = src_w as usize * 4;
+
+    for (y, (dst_row, &(y0, y1, fy))) in dst.chunks_exact_mut(dst_w as usize * 4).zip(&y_taps).enumerate() {
+        let row0 = &src[y0 * src_row_bytes..(y0 + 1) * src_row_bytes];
+        let row1 = &src[y1 * src_row_bytes..(y1 + 1) * src_row_bytes];
+        for (out, &(x0, x1, fx)) in dst_row.chunks_exact_mut(4).zip(&x_taps) {
+            // Q32 weights of the four taps; they sum to exactly 1 << 32
+            let (gx, gy) = (RATIO_FRAC_ONE - fx, RATIO_FRAC_ONE - fy);
+            let weights = [gx * gy, fx * gy, gx * fy, fx * fy];
+            for c in 0..4 {
+                let taps = [row0[x0 * 4 + c], row0[x1 * 4 + c], row1[x0 * 4 + c], row1[x1 * 4 + c]];
+                let sum: u64 = taps.iter().zip(&weights).map(|(&t, &w)| t as u64 * w).sum();
+                out[c] = ((sum + (1 << 31)) >> 32) as u8;
+            }
+        }
+        mark_row_completed(y as u32);
+        if cancel_due(y as u32, dst_h) {
+            return RESIZE_ERR_CANCELLED;
+        }
+    }
+
+    RESIZE_OK
+}
+
+#[cfg(test)]
+mod tests;
+
 /* 
  * Empty your mind, be formless, shapeless, like water. 
  * Now you put water into a cup, it becomes the cup; Put it in a teapot, it becomes the teapot. 
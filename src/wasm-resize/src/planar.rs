@@ -0,0 +1,456 @@
+//! Planar (one byte per sample) resampling
+//! Resizes single image planes such as the Y, U and V planes of planar video
+//! formats, with optional sub-pixel phase offsets for chroma siting, and alpha masks
+
+#[cfg(not(any(target_arch = "wasm32", test)))]
+compile_error!("This module only supports wasm32 target");
+
+use std::cell::RefCell;
+use std::thread_local;
+
+use crate::separable::{algorithm_taps, AxisTaps};
+use crate::{
+    begin_resize, cancel_due, mark_row_completed, mark_rows_completed, resize_slices, resolve_algorithm,
+    set_last_error, validate_buffer, validate_intermediate, MAX_DIMENSION, MAX_PIXELS,
+    RESIZE_ALGO_BILINEAR, RESIZE_ERR_CANCELLED, RESIZE_ERR_INVALID_PARAM, RESIZE_ERR_INVALID_SIZE,
+    RESIZE_ERR_NULL_PTR, RESIZE_ERR_OVERLAP, RESIZE_OK, RESIZE_YUV_BT601_LIMITED,
+    RESIZE_YUV_BT709_FULL, RESIZE_YUV_BT709_LIMITED,
+};
+
+// Horizontal chroma phase for 4:2:0 with MPEG-2 siting: chroma samples sit on the even
+// luma columns rather than between them, a quarter chroma pixel left of the center mapping
+const CHROMA_420_PHASE_X: f32 = 0.25;
+
+// Step between adjacent source samples (8-bit levels) at which resize_alpha_edge_aware
+// treats a footprint as containing a hard edge rather than a soft ramp
+const ALPHA_HARD_STEP: i32 = 64;
+
+thread_local! {
+    // Decoded plane reused across resize_rle_mask calls
+    static RLE_SCRATCH: RefCell<Vec<u8>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Validate a single plane buffer and compute its size in bytes
+/// Like validate_buffer it marks the start of a new resize and applies the same
+/// null/dimension limits; planes need no alignment
+#[inline(always)]
+fn validate_plane(ptr: *const u8, w: u32, h: u32) -> Result<usize, i32> {
+    begin_resize();
+
+    if ptr.is_null() {
+        set_last_error(RESIZE_ERR_NULL_PTR);
+        return Err(RESIZE_ERR_NULL_PTR);
+    }
+
+    let pixels = w as u64 * h as u64;
+    if w == 0 || h == 0 || w > MAX_DIMENSION || h > MAX_DIMENSION || pixels > MAX_PIXELS {
+        set_last_error(RESIZE_ERR_INVALID_SIZE);
+        return Err(RESIZE_ERR_INVALID_SIZE);
+    }
+
+    set_last_error(RESIZE_OK);
+    Ok(pixels as usize)
+}
+
+/// Two-pass separable convolution of a single plane
+/// Returns dst_w * dst_h unclamped f32 samples in the input's value range, or
+/// RESIZE_ERR_CANCELLED when request_cancel stops either pass
+fn convolve_plane(
+    src: &[u8],
+    src_w: u32,
+    src_h: u32,
+    dst_w: u32,
+    dst_h: u32,
+    x_taps: &AxisTaps,
+    y_taps: &AxisTaps,
+) -> Result<Vec<f32>, i32> {
+    let src_w = src_w as usize;
+    let dst_w = dst_w as usize;
+
+    // ==================== Pass 1: Horizontal ====================
+    let mut temp = vec![0.0f32; dst_w * src_h as usize];
+    for (sy, (row, temp_row)) in src.chunks_exact(src_w).zip(temp.chunks_exact_mut(dst_w)).enumerate() {
+        for (x, out) in temp_row.iter_mut().enumerate() {
+            *out = x_taps.taps(x).map(|(sx, w)| row[sx] as f32 * w).sum();
+        }
+        if cancel_due(sy as u32, src_h) {
+            return Err(RESIZE_ERR_CANCELLED);
+        }
+    }
+
+    // ==================== Pass 2: Vertical ====================
+    let mut out = vec![0.0f32; dst_w * dst_h as usize];
+    for (y, out_row) in out.chunks_exact_mut(dst_w).enumerate() {
+        for (sy, w) in y_taps.taps(y) {
+            for (o, &t) in out_row.iter_mut().zip(&temp[sy * dst_w..(sy + 1) * dst_w]) {
+                *o += t * w;
+            }
+        }
+        if cancel_due(y as u32, dst_h) {
+            return Err(RESIZE_ERR_CANCELLED);
+        }
+    }
+
+    Ok(out)
+}
+
+/// Resize a single 8-bit plane with a sub-pixel sampling phase
+/// Every sample position is shifted by (`phase_x`, `phase_y`) source pixels relative
+/// to the regular pixel-center mapping of `algorithm`, e.g. to honor the chroma
+/// siting of 4:2:0 planes. A phase of (0, 0) samples like the regular resize
+/// Returns error code: 0 = success, non-zero = error
+///
+/// # Safety
+/// src_ptr must point to src_w * src_h readable bytes and dst_ptr to dst_w * dst_h
+/// writable bytes; the buffers must not overlap
+#[no_mangle]
+pub unsafe extern "C" fn resize_plane_phase(
+    src_ptr: *const u8,
+    src_w: u32,
+    src_h: u32,
+    dst_ptr: *mut u8,
+    dst_w: u32,
+    dst_h: u32,
+    phase_x: f32,
+    phase_y: f32,
+    algorithm: u32,
+) -> i32 {
+    let src_size = match validate_plane(src_ptr, src_w, src_h) {
+        Ok(size) => size,
+        Err(code) => return code,
+    };
+    let dst_size = match validate_plane(dst_ptr, dst_w, dst_h) {
+        Ok(size) => size,
+        Err(code) => return code,
+    };
+
+    let (src_start, dst_start) = (src_ptr as usize, dst_ptr as usize);
+    if src_start < dst_start.saturating_add(dst_size) && dst_start < src_start.saturating_add(src_size) {
+        set_last_error(RESIZE_ERR_OVERLAP);
+        return RESIZE_ERR_OVERLAP;
+    }
+
+    if !phase_x.is_finite() || !phase_y.is_finite() {
+        set_last_error(RESIZE_ERR_INVALID_PARAM);
+        return RESIZE_ERR_INVALID_PARAM;
+    }
+
+    let algorithm = match resolve_algorithm(algorithm, src_w, src_h, dst_w, dst_h) {
+        Ok(a) => a,
+        Err(code) => return code,
+    };
+
+    if let Err(code) = validate_intermediate(dst_w, src_h) {
+        return code;
+    }
+
+    let src = std::slice::from_raw_parts(src_ptr, src_size);
+    let dst = std::slice::from_raw_parts_mut(dst_ptr, dst_size);
+
+    let x_taps = algorithm_taps(src_w, dst_w, algorithm, phase_x);
+    let y_taps = algorithm_taps(src_h, dst_h, algorithm, phase_y);
+    let out = match convolve_plane(src, src_w, src_h, dst_w, dst_h, &x_taps, &y_taps) {
+        Ok(out) => out,
+        Err(code) => return code,
+    };
+    for (d, v) in dst.iter_mut().zip(out) {
+        *d = (v + 0.5).clamp(0.0, 255.0) as u8;
+    }
+    mark_rows_completed(dst_h);
+
+    RESIZE_OK
+}
+
+/// Resize a run-length encoded single-channel mask into an 8-bit plane
+/// The source is `rle_len` bytes of (count, value) pairs, each standing for `count`
+/// (1..=255) consecutive samples of `value` in row-major order; runs may cross rows.
+/// It is decoded into a scratch plane reused across calls and resized like
+/// resize_plane_phase with a zero phase. Odd lengths, zero counts and streams that
+/// don't decode to exactly src_w * src_h samples are rejected
+/// Returns error code: 0 = success, non-zero = error
+///
+/// # Safety
+/// rle_ptr must point to rle_len readable bytes and dst_ptr to dst_w * dst_h writable
+/// bytes; the buffers must not overlap
+#[no_mangle]
+pub unsafe extern "C" fn resize_rle_mask(
+    rle_ptr: *const u8,
+    rle_len: usize,
+    src_w: u32,
+    src_h: u32,
+    dst_ptr: *mut u8,
+    dst_w: u32,
+    dst_h: u32,
+    algorithm: u32,
+) -> i32 {
+    let src_size = match validate_plane(rle_ptr, src_w, src_h) {
+        Ok(size) => size,
+        Err(code) => return code,
+    };
+    let dst_size = match validate_plane(dst_ptr, dst_w, dst_h) {
+        Ok(size) => size,
+        Err(code) => return code,
+    };
+
+    let (rle_start, dst_start) = (rle_ptr as usize, dst_ptr as usize);
+    if rle_start < dst_start.saturating_add(dst_size) && dst_start < rle_start.saturating_add(rle_len) {
+        set_last_error(RESIZE_ERR_OVERLAP);
+        return RESIZE_ERR_OVERLAP;
+    }
+
+    let algorithm = match resolve_algorithm(algorithm, src_w, src_h, dst_w, dst_h) {
+        Ok(a) => a,
+        Err(code) => return code,
+    };
+
+    if let Err(code) = validate_intermediate(dst_w, src_h) {
+        return code;
+    }
+
+    if !rle_len.is_multiple_of(2) {
+        set_last_error(RESIZE_ERR_INVALID_PARAM);
+        return RESIZE_ERR_INVALID_PARAM;
+    }
+
+    let rle = std::slice::from_raw_parts(rle_ptr, rle_len);
+    RLE_SCRATCH.with(|scratch| {
+        let mut plane = scratch.borrow_mut();
+        plane.clear();
+        plane.reserve(src_size);
+
+        for run in rle.chunks_exact(2) {
+            let (count, value) = (run[0] as usize, run[1]);
+            let end = plane.len() + count;
+            if count == 0 || end > src_size {
+                set_last_error(RESIZE_ERR_INVALID_PARAM);
+                return RESIZE_ERR_INVALID_PARAM;
+            }
+            plane.resize(end, value);
+        }
+        if plane.len() != src_size {
+            set_last_error(RESIZE_ERR_INVALID_PARAM);
+            return RESIZE_ERR_INVALID_PARAM;
+        }
+
+        let dst = std::slice::from_raw_parts_mut(dst_ptr, dst_size);
+        let x_taps = algorithm_taps(src_w, dst_w, algorithm, 0.0);
+        let y_taps = algorithm_taps(src_h, dst_h, algorithm, 0.0);
+        let out = match convolve_plane(&plane, src_w, src_h, dst_w, dst_h, &x_taps, &y_taps) {
+            Ok(out) => out,
+            Err(code) => return code,
+        };
+        for (d, v) in dst.iter_mut().zip(out) {
+            *d = (v + 0.5).clamp(0.0, 255.0) as u8;
+        }
+        mark_rows_completed(dst_h);
+
+        RESIZE_OK
+    })
+}
+
+/// Source samples covered by destination coordinate `d` with their coverage (area sampling)
+fn area_span(d: u32, src_size: u32, dst_size: u32) -> Vec<(usize, f32)> {
+    let scale = src_size as f32 / dst_size as f32;
+    let start = d as f32 * scale;
+    let end = ((d + 1) as f32 * scale).min(src_size as f32);
+    (start.floor() as usize..end.ceil() as usize)
+        .map(|i| (i, end.min(i as f32 + 1.0) - start.max(i as f32)))
+        .filter(|&(_, coverage)| coverage > 0.0)
+        .collect()
+}
+
+/// Downscale an alpha mask (one byte per sample) keeping hard edges hard and ramps smooth
+/// Each destination sample covers its source footprint as in an area downscale. When
+/// two adjacent source samples inside the footprint differ by at least 64 levels (a
+/// hard edge) the footprint's minimum or maximum is written, whichever is closer to the
+/// area average, so cut-out edges stay crisp; otherwise (soft shadows and gradients)
+/// the coverage-weighted area average is written
+/// Returns error code: 0 = success, non-zero = error
+///
+/// # Safety
+/// src_ptr must point to src_w * src_h readable bytes and dst_ptr to dst_w * dst_h
+/// writable bytes; the buffers must not overlap
+#[no_mangle]
+pub unsafe extern "C" fn resize_alpha_edge_aware(
+    src_ptr: *const u8,
+    src_w: u32,
+    src_h: u32,
+    dst_ptr: *mut u8,
+    dst_w: u32,
+    dst_h: u32,
+) -> i32 {
+    let src_size = match validate_plane(src_ptr, src_w, src_h) {
+        Ok(size) => size,
+        Err(code) => return code,
+    };
+    let dst_size = match validate_plane(dst_ptr, dst_w, dst_h) {
+        Ok(size) => size,
+        Err(code) => return code,
+    };
+
+    let (src_start, dst_start) = (src_ptr as usize, dst_ptr as usize);
+    if src_start < dst_start.saturating_add(dst_size) && dst_start < src_start.saturating_add(src_size) {
+        set_last_error(RESIZE_ERR_OVERLAP);
+        return RESIZE_ERR_OVERLAP;
+    }
+
+    let src = std::slice::from_raw_parts(src_ptr, src_size);
+    let dst = std::slice::from_raw_parts_mut(dst_ptr, dst_size);
+    let w = src_w as usize;
+    let x_spans: Vec<_> = (0..dst_w).map(|d| area_span(d, src_w, dst_w)).collect();
+
+    for (dy, dst_row) in dst.chunks_exact_mut(dst_w as usize).enumerate() {
+        let y_span = area_span(dy as u32, src_h, dst_h);
+        for (out, x_span) in dst_row.iter_mut().zip(&x_spans) {
+            let (mut sum, mut area) = (0.0f32, 0.0f32);
+            let (mut min, mut max) = (u8::MAX, u8::MIN);
+            let mut hard = false;
+
+            for &(sy, wy) in &y_span {
+                for &(sx, wx) in x_span {
+                    let v = src[sy * w + sx];
+                    sum += v as f32 * wx * wy;
+                    area += wx * wy;
+                    min = min.min(v);
+                    max = max.max(v);
+
+                    // Steps to the left and upper neighbors inside the footprint
+                    let step = |n: u8| (v as i32 - n as i32).abs() >= ALPHA_HARD_STEP;
+                    hard |= sx > x_span[0].0 && step(src[sy * w + sx - 1]);
+                    hard |= sy > y_span[0].0 && step(src[(sy - 1) * w + sx]);
+                }
+            }
+
+            let average = sum / area;
+            *out = if !hard {
+                (average + 0.5) as u8
+            } else if average * 2.0 >= min as f32 + max as f32 {
+                max
+            } else {
+                min
+            };
+        }
+        mark_row_completed(dy as u32);
+        if cancel_due(dy as u32, dst_h) {
+            return RESIZE_ERR_CANCELLED;
+        }
+    }
+
+    RESIZE_OK
+}
+
+/// YUV -> RGB coefficients: (Cr -> R, Cb -> G, Cr -> G, Cb -> B)
+#[inline(always)]
+fn yuv_coefficients(matrix: u32) -> (f32, f32, f32, f32) {
+    match matrix {
+        RESIZE_YUV_BT709_LIMITED | RESIZE_YUV_BT709_FULL => (1.5748, 0.187324, 0.468124, 1.8556),
+        // BT.601, limited or full range
+        _ => (1.402, 0.344136, 0.714136, 1.772),
+    }
+}
+
+/// Convert planar YUV 4:2:0 to RGBA and resize it
+/// The chroma planes (src_w / 2 x src_h / 2) are bilinearly upsampled to full resolution
+/// with MPEG-2 siting, converted together with luma using `matrix` (0 = BT.601 limited
+/// range, 1 = BT.709 limited, 2 = BT.601 full range, 3 = BT.709 full), and the opaque
+/// RGBA frame is resized to the destination with `algorithm`.
+/// `src_w` and `src_h` must be even
+/// Returns error code: 0 = success, non-zero = error
+///
+/// # Safety
+/// y_ptr must point to src_w * src_h readable bytes, u_ptr/v_ptr to
+/// (src_w / 2) * (src_h / 2) readable bytes each, and dst_ptr to a valid RGBA buffer of
+/// dst_w x dst_h pixels not overlapping any plane
+#[no_mangle]
+pub unsafe extern "C" fn resize_yuv420_to_rgba(
+    y_ptr: *const u8,
+    u_ptr: *const u8,
+    v_ptr: *const u8,
+    src_w: u32,
+    src_h: u32,
+    dst_ptr: *mut u8,
+    dst_w: u32,
+    dst_h: u32,
+    matrix: u32,
+    algorithm: u32,
+) -> i32 {
+    if !src_w.is_multiple_of(2) || !src_h.is_multiple_of(2) {
+        set_last_error(RESIZE_ERR_INVALID_SIZE);
+        return RESIZE_ERR_INVALID_SIZE;
+    }
+
+    let (chroma_w, chroma_h) = (src_w / 2, src_h / 2);
+    let luma_size = match validate_plane(y_ptr, src_w, src_h) {
+        Ok(size) => size,
+        Err(code) => return code,
+    };
+    let mut chroma_size = 0;
+    for ptr in [u_ptr, v_ptr] {
+        chroma_size = match validate_plane(ptr, chroma_w, chroma_h) {
+            Ok(size) => size,
+            Err(code) => return code,
+        };
+    }
+    let dst_size = match validate_buffer(dst_ptr, dst_w, dst_h) {
+        Ok(size) => size,
+        Err(code) => return code,
+    };
+
+    let dst_start = dst_ptr as usize;
+    let dst_end = dst_start.saturating_add(dst_size);
+    let overlaps = |ptr: *const u8, size: usize| (ptr as usize) < dst_end && dst_start < (ptr as usize).saturating_add(size);
+    if overlaps(y_ptr, luma_size) || overlaps(u_ptr, chroma_size) || overlaps(v_ptr, chroma_size) {
+        set_last_error(RESIZE_ERR_OVERLAP);
+        return RESIZE_ERR_OVERLAP;
+    }
+
+    if matrix > RESIZE_YUV_BT709_FULL {
+        set_last_error(RESIZE_ERR_INVALID_PARAM);
+        return RESIZE_ERR_INVALID_PARAM;
+    }
+
+    if let Err(code) = resolve_algorithm(algorithm, src_w, src_h, dst_w, dst_h) {
+        return code;
+    }
+
+    let luma = std::slice::from_raw_parts(y_ptr, luma_size);
+    let u_plane = std::slice::from_raw_parts(u_ptr, chroma_size);
+    let v_plane = std::slice::from_raw_parts(v_ptr, chroma_size);
+    let dst = std::slice::from_raw_parts_mut(dst_ptr, dst_size);
+
+    let x_taps = algorithm_taps(chroma_w, src_w, RESIZE_ALGO_BILINEAR, CHROMA_420_PHASE_X);
+    let y_taps = algorithm_taps(chroma_h, src_h, RESIZE_ALGO_BILINEAR, 0.0);
+    let cb = match convolve_plane(u_plane, chroma_w, chroma_h, src_w, src_h, &x_taps, &y_taps) {
+        Ok(plane) => plane,
+        Err(code) => return code,
+    };
+    let cr = match convolve_plane(v_plane, chroma_w, chroma_h, src_w, src_h, &x_taps, &y_taps) {
+        Ok(plane) => plane,
+        Err(code) => return code,
+    };
+
+    // Normalize to Y in 0..255 and chroma centered on 0 with a +-127.5 swing
+    let limited = matches!(matrix, RESIZE_YUV_BT601_LIMITED | RESIZE_YUV_BT709_LIMITED);
+    let (y_offset, y_scale, c_scale) = if limited {
+        (16.0, 255.0 / 219.0, 255.0 / 224.0)
+    } else {
+        (0.0, 1.0, 1.0)
+    };
+    let (r_cr, g_cb, g_cr, b_cb) = yuv_coefficients(matrix);
+
+    let mut rgba = vec![0u8; luma_size * 4];
+    for (i, p) in rgba.chunks_exact_mut(4).enumerate() {
+        let y = (luma[i] as f32 - y_offset) * y_scale;
+        let u = (cb[i] - 128.0) * c_scale;
+        let v = (cr[i] - 128.0) * c_scale;
+        let to_u8 = |c: f32| (c + 0.5).clamp(0.0, 255.0) as u8;
+        p.copy_from_slice(&[to_u8(y + r_cr * v), to_u8(y - g_cb * u - g_cr * v), to_u8(y + b_cb * u), 255]);
+    }
+
+    if dst_w == src_w && dst_h == src_h {
+        dst.copy_from_slice(&rgba);
+        mark_rows_completed(dst_h);
+        return RESIZE_OK;
+    }
+    resize_slices(&rgba, src_w, src_h, dst, dst_w, dst_h, algorithm)
+}
@@ -0,0 +1,83 @@
+//! Phase timing for performance work (`timing` feature)
+//! Timestamps come from the host through the imported `now_ms` function, so builds
+//! with this feature must provide it in the `env` import object, e.g.
+//! `{ env: { now_ms: () => performance.now() } }`
+
+#[cfg(not(any(target_arch = "wasm32", test)))]
+compile_error!("This module only supports wasm32 target");
+
+use std::cell::Cell;
+use std::thread_local;
+
+use crate::{resize_slices, validate_out_ptr, validate_params, RESIZE_ALGO_LANCZOS, RESIZE_OK};
+
+// Phases timed in the Lanczos core: weight precompute, Pass 1, Pass 2
+const LANCZOS_PHASES: usize = 3;
+
+extern "C" {
+    /// Host clock in milliseconds (monotonic, any origin)
+    fn now_ms() -> f64;
+}
+
+thread_local! {
+    // Durations recorded by the most recent Lanczos core run
+    static LANCZOS_PHASE_MS: Cell<[f32; LANCZOS_PHASES]> = const { Cell::new([0.0; LANCZOS_PHASES]) };
+}
+
+/// Current host time in milliseconds
+#[inline(always)]
+pub(crate) fn phase_clock() -> f64 {
+    unsafe { now_ms() }
+}
+
+/// Store the phase boundaries `marks[0] ..= marks[3]` of a Lanczos core run
+pub(crate) fn record_lanczos_phases(marks: [f64; LANCZOS_PHASES + 1]) {
+    let mut phases = [0.0f32; LANCZOS_PHASES];
+    for (p, pair) in phases.iter_mut().zip(marks.windows(2)) {
+        *p = (pair[1] - pair[0]).max(0.0) as f32;
+    }
+    LANCZOS_PHASE_MS.with(|c| c.set(phases));
+}
+
+/// Lanczos resize that reports where the time went
+/// Runs the regular resize_rgba_lanczos path and writes three durations in
+/// milliseconds to `out_phase_ms`: weight/tap precompute, horizontal pass (Pass 1) and
+/// vertical pass (Pass 2), as measured with the host's `now_ms`
+/// Returns error code: 0 = success, non-zero = error
+///
+/// # Safety
+/// src_ptr/dst_ptr must point to valid RGBA buffers of the given dimensions and
+/// `out_phase_ms` to 3 writable, 4-byte aligned f32 values not overlapping either buffer
+#[no_mangle]
+pub unsafe extern "C" fn resize_rgba_lanczos_profiled(
+    src_ptr: *const u8,
+    src_w: u32,
+    src_h: u32,
+    dst_ptr: *mut u8,
+    dst_w: u32,
+    dst_h: u32,
+    out_phase_ms: *mut f32,
+) -> i32 {
+    let (src_size, dst_size) = match validate_params(src_ptr, src_w, src_h, dst_ptr, dst_w, dst_h) {
+        Ok(sizes) => sizes,
+        Err(code) => return code,
+    };
+
+    let buffers = [(src_ptr, src_size), (dst_ptr as *const u8, dst_size)];
+    if let Err(code) = validate_out_ptr(out_phase_ms, LANCZOS_PHASES, &buffers) {
+        return code;
+    }
+
+    LANCZOS_PHASE_MS.with(|c| c.set([0.0; LANCZOS_PHASES]));
+    let src = std::slice::from_raw_parts(src_ptr, src_size);
+    let dst = std::slice::from_raw_parts_mut(dst_ptr, dst_size);
+    let code = resize_slices(src, src_w, src_h, dst, dst_w, dst_h, RESIZE_ALGO_LANCZOS);
+    if code != RESIZE_OK {
+        return code;
+    }
+
+    let out = std::slice::from_raw_parts_mut(out_phase_ms, LANCZOS_PHASES);
+    out.copy_from_slice(&LANCZOS_PHASE_MS.with(|c| c.get()));
+
+    RESIZE_OK
+}
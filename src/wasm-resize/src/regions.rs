@@ -0,0 +1,1257 @@
+//! Region-aware resize variants
+//! Resizes that treat part of the source or destination specially:
+//! preserved borders, clip rectangles and similar sub-rectangle operations
+
+#[cfg(not(any(target_arch = "wasm32", test)))]
+compile_error!("This module only supports wasm32 target");
+
+use std::cell::{Cell, RefCell};
+use std::thread_local;
+
+use crate::gamma_simd::{init_gamma_luts, linear_to_srgb_fast, srgb_to_linear_lut};
+use crate::separable::{algorithm_taps, convolve};
+use crate::{
+    intermediate_pass, mark_row_completed, mark_rows_completed, resize_for_post_pass, resize_slices,
+    resize_slices_in_rect, resolve_algorithm, set_last_error, validate_buffer,
+    validate_intermediate, validate_params, DstRect, LANCZOS_A, MAX_DIMENSION, MAX_PIXELS,
+    RESIZE_ALGO_BILINEAR, RESIZE_ALGO_LANCZOS, RESIZE_ALGO_NEAREST, RESIZE_ERR_ALIGNMENT,
+    RESIZE_ERR_INVALID_PARAM, RESIZE_ERR_INVALID_SIZE, RESIZE_ERR_NULL_PTR, RESIZE_ERR_OVERLAP,
+    RESIZE_OK,
+};
+
+// Source tile edge of the coverage map used by resize_rgba_skip_transparent
+const COVERAGE_TILE: u32 = 8;
+// Destination tile edge: each tile is either skipped or resized as one clip rectangle
+const SKIP_TILE: u32 = 16;
+
+thread_local! {
+    // Destination pixels the last resize_rgba_skip_transparent call wrote without resampling
+    static SKIPPED_PIXELS: Cell<u32> = const { Cell::new(0) };
+    // Resized tile reused across resize_rgba_tile_fill calls
+    static TILE_CACHE: RefCell<Vec<u8>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Map a destination coordinate to a source coordinate so that the outer
+/// `dst_border` destination pixels on each side sample only the outer `src_border`
+/// source pixels, and the remaining interior maps onto the source interior
+/// All mappings use nearest sampling
+#[inline(always)]
+fn ring_source_coord(d: u32, dst_size: u32, dst_border: u32, src_size: u32, src_border: u32) -> u32 {
+    // Map [d0, d0 + dn) onto [s0, s0 + sn) with nearest (pixel-center) sampling
+    let map = |d: u32, d0: u32, dn: u32, s0: u32, sn: u32| -> u32 {
+        if dn == 0 || sn == 0 {
+            return s0.min(src_size - 1);
+        }
+        let t = ((d - d0) as f32 + 0.5) * sn as f32 / dn as f32;
+        (s0 + (t as u32).min(sn - 1)).min(src_size - 1)
+    };
+
+    if d < dst_border {
+        map(d, 0, dst_border, 0, src_border)
+    } else if d >= dst_size - dst_border {
+        map(d, dst_size - dst_border, dst_border, src_size - src_border, src_border)
+    } else {
+        map(d, dst_border, dst_size - 2 * dst_border, src_border, src_size - 2 * src_border)
+    }
+}
+
+/// Destination ring width corresponding to `border_px` source pixels on one axis
+/// At least 1 pixel when a border is requested, but never more than half the output
+#[inline(always)]
+fn scaled_border(border_px: u32, src_size: u32, dst_size: u32) -> u32 {
+    if border_px == 0 {
+        return 0;
+    }
+    let scaled = ((border_px as u64 * dst_size as u64 + src_size as u64 / 2) / src_size as u64).max(1);
+    scaled.min(dst_size as u64 / 2) as u32
+}
+
+/// Resize while keeping a crisp constant-color frame
+/// The image is resized normally with `algorithm`, then the outermost `border_px`
+/// source ring is copied into the corresponding (scaled) destination ring using
+/// nearest sampling, so the frame is never blended with interior pixels.
+/// `border_px` must be smaller than half the smaller source dimension
+/// Returns error code: 0 = success, non-zero = error
+///
+/// # Safety
+/// src_ptr/dst_ptr must point to valid RGBA buffers of the given dimensions
+#[no_mangle]
+pub unsafe extern "C" fn resize_rgba_preserve_border(
+    src_ptr: *const u8,
+    src_w: u32,
+    src_h: u32,
+    border_px: u32,
+    dst_ptr: *mut u8,
+    dst_w: u32,
+    dst_h: u32,
+    algorithm: u32,
+) -> i32 {
+    let (src_size, dst_size) = match validate_params(src_ptr, src_w, src_h, dst_ptr, dst_w, dst_h) {
+        Ok(sizes) => sizes,
+        Err(code) => return code,
+    };
+
+    if border_px.saturating_mul(2) >= src_w.min(src_h) {
+        set_last_error(RESIZE_ERR_INVALID_PARAM);
+        return RESIZE_ERR_INVALID_PARAM;
+    }
+
+    let code = resize_for_post_pass(src_ptr, src_w, src_h, dst_ptr, dst_w, dst_h, algorithm);
+    if code != RESIZE_OK {
+        return code;
+    }
+    if border_px == 0 {
+        mark_rows_completed(dst_h);
+        return RESIZE_OK;
+    }
+
+    let src = std::slice::from_raw_parts(src_ptr, src_size);
+    let dst = std::slice::from_raw_parts_mut(dst_ptr, dst_size);
+
+    let border_x = scaled_border(border_px, src_w, dst_w);
+    let border_y = scaled_border(border_px, src_h, dst_h);
+
+    for y in 0..dst_h {
+        let in_ring_y = y < border_y || y >= dst_h - border_y;
+        let sy = ring_source_coord(y, dst_h, border_y, src_h, border_px) as usize;
+        let dst_row = y as usize * dst_w as usize * 4;
+        let src_row = sy * src_w as usize * 4;
+
+        for x in 0..dst_w {
+            if !in_ring_y && x >= border_x && x < dst_w - border_x {
+                continue;
+            }
+            let sx = ring_source_coord(x, dst_w, border_x, src_w, border_px) as usize;
+            let s = src_row + sx * 4;
+            let d = dst_row + x as usize * 4;
+            dst[d..d + 4].copy_from_slice(&src[s..s + 4]);
+        }
+        mark_row_completed(y);
+    }
+
+    RESIZE_OK
+}
+
+/// Resize while keeping the source's four corner pixels exact (seamless tiles, nine-patch)
+/// The image is resized normally with `algorithm`, then each destination corner pixel is
+/// overwritten with the matching source corner, so corner colors never blend inward.
+/// When a destination dimension is 1 the corners share pixels and the later
+/// (right/bottom) corner wins
+/// Returns error code: 0 = success, non-zero = error
+///
+/// # Safety
+/// src_ptr/dst_ptr must point to valid RGBA buffers of the given dimensions
+#[no_mangle]
+pub unsafe extern "C" fn resize_rgba_keep_corners(
+    src_ptr: *const u8,
+    src_w: u32,
+    src_h: u32,
+    dst_ptr: *mut u8,
+    dst_w: u32,
+    dst_h: u32,
+    algorithm: u32,
+) -> i32 {
+    let (src_size, dst_size) = match validate_params(src_ptr, src_w, src_h, dst_ptr, dst_w, dst_h) {
+        Ok(sizes) => sizes,
+        Err(code) => return code,
+    };
+
+    let code = resize_for_post_pass(src_ptr, src_w, src_h, dst_ptr, dst_w, dst_h, algorithm);
+    if code != RESIZE_OK {
+        return code;
+    }
+
+    let src = std::slice::from_raw_parts(src_ptr, src_size);
+    let dst = std::slice::from_raw_parts_mut(dst_ptr, dst_size);
+
+    let corners = [(0, 0), (1, 0), (0, 1), (1, 1)];
+    for (cx, cy) in corners {
+        let s = ((cy * (src_h as usize - 1)) * src_w as usize + cx * (src_w as usize - 1)) * 4;
+        let d = ((cy * (dst_h as usize - 1)) * dst_w as usize + cx * (dst_w as usize - 1)) * 4;
+        dst[d..d + 4].copy_from_slice(&src[s..s + 4]);
+    }
+    mark_rows_completed(dst_h);
+
+    RESIZE_OK
+}
+
+/// Resize only the destination pixels inside a clip rectangle (e.g. an editor dirty region)
+/// Source sampling is unchanged - taps outside the clip are still read - so pixels inside
+/// the clip match a full resize exactly, while pixels outside keep their previous values.
+/// The clip must be non-empty and lie within the destination
+/// Returns error code: 0 = success, non-zero = error
+///
+/// # Safety
+/// src_ptr/dst_ptr must point to valid RGBA buffers of the given dimensions
+#[no_mangle]
+pub unsafe extern "C" fn resize_rgba_clip(
+    src_ptr: *const u8,
+    src_w: u32,
+    src_h: u32,
+    dst_ptr: *mut u8,
+    dst_w: u32,
+    dst_h: u32,
+    clip_x: u32,
+    clip_y: u32,
+    clip_w: u32,
+    clip_h: u32,
+    algorithm: u32,
+) -> i32 {
+    let (src_size, dst_size) = match validate_params(src_ptr, src_w, src_h, dst_ptr, dst_w, dst_h) {
+        Ok(sizes) => sizes,
+        Err(code) => return code,
+    };
+
+    let fits_x = clip_x.checked_add(clip_w).is_some_and(|end| end <= dst_w);
+    let fits_y = clip_y.checked_add(clip_h).is_some_and(|end| end <= dst_h);
+    if clip_w == 0 || clip_h == 0 || !fits_x || !fits_y {
+        set_last_error(RESIZE_ERR_INVALID_PARAM);
+        return RESIZE_ERR_INVALID_PARAM;
+    }
+
+    let src = std::slice::from_raw_parts(src_ptr, src_size);
+    let dst = std::slice::from_raw_parts_mut(dst_ptr, dst_size);
+    let rect = DstRect { x: clip_x, y: clip_y, w: clip_w, h: clip_h, origin_y: 0 };
+
+    resize_slices_in_rect(src, src_w, src_h, dst, dst_w, dst_h, rect, false, algorithm)
+}
+
+/// Resize with bilinear everywhere and Lanczos inside a region of interest
+/// The whole destination is resized with bilinear, then the ROI rectangle is resampled
+/// again with Lanczos from the same source mapping, so ROI pixels match a full Lanczos
+/// resize exactly and the rest match a full bilinear resize (e.g. a face in a video
+/// frame). The ROI must be non-empty and lie within the destination
+/// Returns error code: 0 = success, non-zero = error
+///
+/// # Safety
+/// src_ptr/dst_ptr must point to valid RGBA buffers of the given dimensions
+#[no_mangle]
+pub unsafe extern "C" fn resize_rgba_roi_quality(
+    src_ptr: *const u8,
+    src_w: u32,
+    src_h: u32,
+    dst_ptr: *mut u8,
+    dst_w: u32,
+    dst_h: u32,
+    roi_x: u32,
+    roi_y: u32,
+    roi_w: u32,
+    roi_h: u32,
+) -> i32 {
+    let (src_size, dst_size) = match validate_params(src_ptr, src_w, src_h, dst_ptr, dst_w, dst_h) {
+        Ok(sizes) => sizes,
+        Err(code) => return code,
+    };
+
+    let fits_x = roi_x.checked_add(roi_w).is_some_and(|end| end <= dst_w);
+    let fits_y = roi_y.checked_add(roi_h).is_some_and(|end| end <= dst_h);
+    if roi_w == 0 || roi_h == 0 || !fits_x || !fits_y {
+        set_last_error(RESIZE_ERR_INVALID_PARAM);
+        return RESIZE_ERR_INVALID_PARAM;
+    }
+
+    let src = std::slice::from_raw_parts(src_ptr, src_size);
+    let dst = std::slice::from_raw_parts_mut(dst_ptr, dst_size);
+    let code = intermediate_pass(|| resize_slices(src, src_w, src_h, dst, dst_w, dst_h, RESIZE_ALGO_BILINEAR));
+    if code != RESIZE_OK {
+        return code;
+    }
+
+    let rect = DstRect { x: roi_x, y: roi_y, w: roi_w, h: roi_h, origin_y: 0 };
+    let code = intermediate_pass(|| {
+        resize_slices_in_rect(src, src_w, src_h, dst, dst_w, dst_h, rect, false, RESIZE_ALGO_LANCZOS)
+    });
+    if code == RESIZE_OK {
+        mark_rows_completed(dst_h);
+    }
+    code
+}
+
+/// Find the tight bounding box of pixels whose alpha exceeds `alpha_threshold`
+/// Writes `[x, y, w, h]` to `out_box`; a fully transparent image reports the full
+/// image box so the result can always be passed straight to resize_rgba_crop
+/// Returns error code: 0 = success, non-zero = error
+///
+/// # Safety
+/// ptr must point to a valid RGBA buffer of the given dimensions;
+/// `out_box` must point to 4 writable, 4-byte aligned u32 values
+#[no_mangle]
+pub unsafe extern "C" fn autocrop_alpha_bounds(
+    ptr: *const u8,
+    w: u32,
+    h: u32,
+    alpha_threshold: u8,
+    out_box: *mut u32,
+) -> i32 {
+    let size = match validate_buffer(ptr, w, h) {
+        Ok(size) => size,
+        Err(code) => return code,
+    };
+
+    if out_box.is_null() {
+        set_last_error(RESIZE_ERR_NULL_PTR);
+        return RESIZE_ERR_NULL_PTR;
+    }
+
+    if !(out_box as usize).is_multiple_of(std::mem::align_of::<u32>()) {
+        set_last_error(RESIZE_ERR_ALIGNMENT);
+        return RESIZE_ERR_ALIGNMENT;
+    }
+
+    let pixels = std::slice::from_raw_parts(ptr, size);
+    let row_bytes = w as usize * 4;
+
+    let (mut min_x, mut min_y, mut max_x, mut max_y) = (w, h, 0u32, 0u32);
+
+    for (y, row) in pixels.chunks_exact(row_bytes).enumerate() {
+        let opaque = |p: &[u8]| p[3] > alpha_threshold;
+        // Only the outermost opaque pixel on each side of the row matters
+        let Some(first) = row.chunks_exact(4).position(opaque) else {
+            continue;
+        };
+        let last = row.chunks_exact(4).rposition(opaque).unwrap_or(first);
+
+        let y = y as u32;
+        min_x = min_x.min(first as u32);
+        max_x = max_x.max(last as u32);
+        min_y = min_y.min(y);
+        max_y = y;
+    }
+
+    let out = std::slice::from_raw_parts_mut(out_box, 4);
+    if min_x > max_x || min_y > max_y {
+        out.copy_from_slice(&[0, 0, w, h]);
+    } else {
+        out.copy_from_slice(&[min_x, min_y, max_x - min_x + 1, max_y - min_y + 1]);
+    }
+
+    set_last_error(RESIZE_OK);
+    RESIZE_OK
+}
+
+/// Resize a sub-rectangle of the source (e.g. the box from autocrop_alpha_bounds)
+/// The crop `[crop_x, crop_y, crop_w, crop_h]` must be non-empty and lie within the source;
+/// edge taps clamp to the crop, so pixels outside it never bleed into the output
+/// Returns error code: 0 = success, non-zero = error
+///
+/// # Safety
+/// src_ptr/dst_ptr must point to valid RGBA buffers of the given dimensions
+#[no_mangle]
+pub unsafe extern "C" fn resize_rgba_crop(
+    src_ptr: *const u8,
+    src_w: u32,
+    src_h: u32,
+    crop_x: u32,
+    crop_y: u32,
+    crop_w: u32,
+    crop_h: u32,
+    dst_ptr: *mut u8,
+    dst_w: u32,
+    dst_h: u32,
+    algorithm: u32,
+) -> i32 {
+    let (src_size, dst_size) = match validate_params(src_ptr, src_w, src_h, dst_ptr, dst_w, dst_h) {
+        Ok(sizes) => sizes,
+        Err(code) => return code,
+    };
+
+    let fits_x = crop_x.checked_add(crop_w).is_some_and(|end| end <= src_w);
+    let fits_y = crop_y.checked_add(crop_h).is_some_and(|end| end <= src_h);
+    if crop_w == 0 || crop_h == 0 || !fits_x || !fits_y {
+        set_last_error(RESIZE_ERR_INVALID_PARAM);
+        return RESIZE_ERR_INVALID_PARAM;
+    }
+
+    let src = std::slice::from_raw_parts(src_ptr, src_size);
+    let dst = std::slice::from_raw_parts_mut(dst_ptr, dst_size);
+    let start = (crop_y as usize * src_w as usize + crop_x as usize) * 4;
+
+    resize_strided(&src[start..], src_w as usize * 4, crop_w, crop_h, dst, dst_w, dst_h, algorithm)
+}
+
+/// Resize a `w` x `h` image whose rows start `stride` bytes apart in `src`
+/// `src` begins at the first pixel and only the `w * 4` bytes of each row are read,
+/// so the rows can be a window of a wider buffer
+#[allow(clippy::too_many_arguments)]
+fn resize_strided(
+    src: &[u8],
+    stride: usize,
+    w: u32,
+    h: u32,
+    dst: &mut [u8],
+    dst_w: u32,
+    dst_h: u32,
+    algorithm: u32,
+) -> i32 {
+    let row_len = w as usize * 4;
+
+    // Full-width windows are already contiguous
+    if stride == row_len {
+        return resize_slices(&src[..row_len * h as usize], w, h, dst, dst_w, dst_h, algorithm);
+    }
+
+    let mut packed = Vec::with_capacity(row_len * h as usize);
+    for y in 0..h as usize {
+        packed.extend_from_slice(&src[y * stride..y * stride + row_len]);
+    }
+
+    resize_slices(&packed, w, h, dst, dst_w, dst_h, algorithm)
+}
+
+/// Resize a sub-image of a larger buffer, confining every tap to the window
+/// `src_ptr` is the start of a `full_w` x `full_h` buffer (row stride `full_w * 4`) and
+/// `[win_x, win_y, win_w, win_h]` the non-empty window inside it. Edge taps clamp at the
+/// window's borders rather than the buffer's, and only the window's own pixels are
+/// ever read: the source slice starts at the window's first pixel and ends at its last
+/// Returns error code: 0 = success, non-zero = error
+///
+/// # Safety
+/// src_ptr must point to a valid RGBA buffer of full_w x full_h pixels and dst_ptr to a
+/// valid RGBA buffer of the destination dimensions
+#[no_mangle]
+pub unsafe extern "C" fn resize_rgba_window(
+    src_ptr: *const u8,
+    full_w: u32,
+    full_h: u32,
+    win_x: u32,
+    win_y: u32,
+    win_w: u32,
+    win_h: u32,
+    dst_ptr: *mut u8,
+    dst_w: u32,
+    dst_h: u32,
+    algorithm: u32,
+) -> i32 {
+    let (_, dst_size) = match validate_params(src_ptr, full_w, full_h, dst_ptr, dst_w, dst_h) {
+        Ok(sizes) => sizes,
+        Err(code) => return code,
+    };
+
+    let fits_x = win_x.checked_add(win_w).is_some_and(|end| end <= full_w);
+    let fits_y = win_y.checked_add(win_h).is_some_and(|end| end <= full_h);
+    if win_w == 0 || win_h == 0 || !fits_x || !fits_y {
+        set_last_error(RESIZE_ERR_INVALID_PARAM);
+        return RESIZE_ERR_INVALID_PARAM;
+    }
+
+    let stride = full_w as usize * 4;
+    let start = win_y as usize * stride + win_x as usize * 4;
+    let span = (win_h as usize - 1) * stride + win_w as usize * 4;
+    let window = std::slice::from_raw_parts(src_ptr.add(start), span);
+    let dst = std::slice::from_raw_parts_mut(dst_ptr, dst_size);
+
+    resize_strided(window, stride, win_w, win_h, dst, dst_w, dst_h, algorithm)
+}
+
+/// Strip black letterbox/pillarbox bars, then resize the remaining content
+/// Rows are trimmed from the top and bottom, then columns from the left and right, as
+/// long as every pixel in them has R, G and B below `black_threshold` (alpha is
+/// ignored). The remaining rectangle is resized like resize_rgba_crop; an image that is
+/// dark everywhere is resized whole
+/// Returns error code: 0 = success, non-zero = error
+///
+/// # Safety
+/// src_ptr/dst_ptr must point to valid RGBA buffers of the given dimensions
+#[no_mangle]
+pub unsafe extern "C" fn resize_rgba_deletterbox(
+    src_ptr: *const u8,
+    src_w: u32,
+    src_h: u32,
+    dst_ptr: *mut u8,
+    dst_w: u32,
+    dst_h: u32,
+    black_threshold: u8,
+    algorithm: u32,
+) -> i32 {
+    let (src_size, _) = match validate_params(src_ptr, src_w, src_h, dst_ptr, dst_w, dst_h) {
+        Ok(sizes) => sizes,
+        Err(code) => return code,
+    };
+
+    let src = std::slice::from_raw_parts(src_ptr, src_size);
+    let row_bytes = src_w as usize * 4;
+    let dark = |p: &[u8]| p[0] < black_threshold && p[1] < black_threshold && p[2] < black_threshold;
+    let dark_row = |y: u32| src[y as usize * row_bytes..(y as usize + 1) * row_bytes].chunks_exact(4).all(dark);
+
+    let Some(top) = (0..src_h).find(|&y| !dark_row(y)) else {
+        return resize_rgba_crop(src_ptr, src_w, src_h, 0, 0, src_w, src_h, dst_ptr, dst_w, dst_h, algorithm);
+    };
+    // A non-dark row exists, so the reverse scan stops at or below `top`
+    let bottom = (top..src_h).rev().find(|&y| !dark_row(y)).unwrap_or(top);
+
+    let dark_column = |x: u32| (top..=bottom).all(|y| dark(&src[y as usize * row_bytes + x as usize * 4..][..4]));
+    let left = (0..src_w).find(|&x| !dark_column(x)).unwrap_or(0);
+    let right = (left..src_w).rev().find(|&x| !dark_column(x)).unwrap_or(left);
+
+    resize_rgba_crop(src_ptr, src_w, src_h, left, top, right - left + 1, bottom - top + 1, dst_ptr, dst_w, dst_h, algorithm)
+}
+
+/// Center-crop to a square, resize and cut out a circle in one call (e.g. avatars)
+/// The largest centered square of the source is resized to `dst_size` x `dst_size`
+/// with the taps of `algorithm`, premultiplied so transparent source pixels don't
+/// darken edges, and the alpha is then multiplied by a circular mask inscribed in the
+/// output. The mask ramps linearly from opaque to transparent over `feather` pixels
+/// centered on the circle's edge; 0 gives a hard edge. Output is straight alpha
+/// Returns error code: 0 = success, non-zero = error
+///
+/// # Safety
+/// src_ptr must point to a valid RGBA buffer of the given dimensions and dst_ptr to a
+/// valid RGBA buffer of dst_size x dst_size pixels
+#[no_mangle]
+pub unsafe extern "C" fn resize_rgba_circle_avatar(
+    src_ptr: *const u8,
+    src_w: u32,
+    src_h: u32,
+    dst_ptr: *mut u8,
+    dst_size: u32,
+    feather: f32,
+    algorithm: u32,
+) -> i32 {
+    let (src_size, dst_len) = match validate_params(src_ptr, src_w, src_h, dst_ptr, dst_size, dst_size) {
+        Ok(sizes) => sizes,
+        Err(code) => return code,
+    };
+
+    if !feather.is_finite() || feather < 0.0 {
+        set_last_error(RESIZE_ERR_INVALID_PARAM);
+        return RESIZE_ERR_INVALID_PARAM;
+    }
+
+    let side = src_w.min(src_h);
+    let algorithm = match resolve_algorithm(algorithm, side, side, dst_size, dst_size) {
+        Ok(a) => a,
+        Err(code) => return code,
+    };
+
+    if let Err(code) = validate_intermediate(dst_size, side) {
+        return code;
+    }
+
+    let src = std::slice::from_raw_parts(src_ptr, src_size);
+    let dst = std::slice::from_raw_parts_mut(dst_ptr, dst_len);
+
+    let (crop_x, crop_y) = ((src_w - side) as usize / 2, (src_h - side) as usize / 2);
+    let src_stride = src_w as usize * 4;
+    let premultiplied: Vec<f32> = src
+        .chunks_exact(src_stride)
+        .skip(crop_y)
+        .take(side as usize)
+        .flat_map(|row| row[crop_x * 4..(crop_x + side as usize) * 4].chunks_exact(4))
+        .flat_map(|p| {
+            let a = p[3] as f32 / 255.0;
+            [p[0] as f32 * a, p[1] as f32 * a, p[2] as f32 * a, a]
+        })
+        .collect();
+
+    let taps = algorithm_taps(side, dst_size, algorithm, 0.0);
+    let out = match convolve(&premultiplied, side, side, dst_size, dst_size, &taps, &taps) {
+        Ok(out) => out,
+        Err(code) => return code,
+    };
+
+    let center = dst_size as f32 / 2.0;
+    let size = dst_size as usize;
+    for (i, (d, v)) in dst.chunks_exact_mut(4).zip(out.chunks_exact(4)).enumerate() {
+        let a = v[3].clamp(0.0, 1.0);
+        let dx = (i % size) as f32 + 0.5 - center;
+        let dy = (i / size) as f32 + 0.5 - center;
+        let inside = center - (dx * dx + dy * dy).sqrt();
+        let mask = if feather > 0.0 {
+            (inside / feather + 0.5).clamp(0.0, 1.0)
+        } else if inside >= 0.0 {
+            1.0
+        } else {
+            0.0
+        };
+
+        if a <= 0.0 || mask <= 0.0 {
+            d.fill(0);
+            continue;
+        }
+        for c in 0..3 {
+            d[c] = (v[c] / a).clamp(0.0, 255.0).round() as u8;
+        }
+        d[3] = (a * mask * 255.0 + 0.5) as u8;
+    }
+    mark_rows_completed(dst_size);
+
+    RESIZE_OK
+}
+
+/// Resize into a slot of a texture atlas and bleed the slot's edges outward
+/// The source is resized to `slot_w` x `slot_h` and written at (`slot_x`, `slot_y`);
+/// then the slot's edge pixels are replicated `bleed_px` pixels outward (corners take
+/// the corner pixel) so bilinear sampling at fractional UVs never picks up neighbours.
+/// The slot plus its bleed ring must lie within the atlas
+/// Returns error code: 0 = success, non-zero = error
+///
+/// # Safety
+/// src_ptr/atlas_ptr must point to valid RGBA buffers of the given dimensions
+#[no_mangle]
+pub unsafe extern "C" fn resize_rgba_atlas_slot(
+    src_ptr: *const u8,
+    src_w: u32,
+    src_h: u32,
+    atlas_ptr: *mut u8,
+    atlas_w: u32,
+    atlas_h: u32,
+    slot_x: u32,
+    slot_y: u32,
+    slot_w: u32,
+    slot_h: u32,
+    bleed_px: u32,
+    algorithm: u32,
+) -> i32 {
+    let (src_size, atlas_size) = match validate_params(src_ptr, src_w, src_h, atlas_ptr, atlas_w, atlas_h) {
+        Ok(sizes) => sizes,
+        Err(code) => return code,
+    };
+
+    // Slot plus bleed on both sides must fit: [slot - bleed, slot + size + bleed)
+    let fits = |pos: u32, size: u32, limit: u32| {
+        pos >= bleed_px
+            && pos
+                .checked_add(size)
+                .and_then(|end| end.checked_add(bleed_px))
+                .is_some_and(|end| end <= limit)
+    };
+    if slot_w == 0 || slot_h == 0 || !fits(slot_x, slot_w, atlas_w) || !fits(slot_y, slot_h, atlas_h) {
+        set_last_error(RESIZE_ERR_INVALID_PARAM);
+        return RESIZE_ERR_INVALID_PARAM;
+    }
+
+    let src = std::slice::from_raw_parts(src_ptr, src_size);
+    let atlas = std::slice::from_raw_parts_mut(atlas_ptr, atlas_size);
+
+    // The resamplers write contiguous rows; the slot is strided inside the atlas
+    let slot_stride = slot_w as usize * 4;
+    let mut slot = vec![0u8; slot_stride * slot_h as usize];
+    let code = intermediate_pass(|| resize_slices(src, src_w, src_h, &mut slot, slot_w, slot_h, algorithm));
+    if code != RESIZE_OK {
+        return code;
+    }
+
+    let atlas_stride = atlas_w as usize * 4;
+    let x_first = (slot_x - bleed_px) as usize;
+    let x_end = (slot_x + slot_w + bleed_px) as usize;
+
+    for ay in slot_y - bleed_px..slot_y + slot_h + bleed_px {
+        let sy = ay.clamp(slot_y, slot_y + slot_h - 1) - slot_y;
+        let slot_row = &slot[sy as usize * slot_stride..(sy as usize + 1) * slot_stride];
+        let atlas_row = &mut atlas[ay as usize * atlas_stride..(ay as usize + 1) * atlas_stride];
+
+        let left = slot_x as usize * 4;
+        let right = left + slot_stride;
+        atlas_row[left..right].copy_from_slice(slot_row);
+
+        for ax in x_first..slot_x as usize {
+            atlas_row[ax * 4..ax * 4 + 4].copy_from_slice(&slot_row[..4]);
+        }
+        for ax in slot_x as usize + slot_w as usize..x_end {
+            atlas_row[ax * 4..ax * 4 + 4].copy_from_slice(&slot_row[slot_stride - 4..]);
+        }
+        mark_row_completed(ay);
+    }
+
+    RESIZE_OK
+}
+
+/// Resize the source to one tile and repeat it across the destination
+/// The source is resized once to `tile_w` x `tile_h` with `algorithm` into a reused
+/// thread-local tile buffer, then copied row by row so the tiles start at the
+/// destination's top-left corner. Tiles at the right and bottom edges are clipped
+/// Returns error code: 0 = success, non-zero = error
+///
+/// # Safety
+/// src_ptr/dst_ptr must point to valid RGBA buffers of the given dimensions
+#[no_mangle]
+pub unsafe extern "C" fn resize_rgba_tile_fill(
+    src_ptr: *const u8,
+    src_w: u32,
+    src_h: u32,
+    tile_w: u32,
+    tile_h: u32,
+    dst_ptr: *mut u8,
+    dst_w: u32,
+    dst_h: u32,
+    algorithm: u32,
+) -> i32 {
+    let (src_size, dst_size) = match validate_params(src_ptr, src_w, src_h, dst_ptr, dst_w, dst_h) {
+        Ok(sizes) => sizes,
+        Err(code) => return code,
+    };
+
+    let tile_pixels = tile_w as u64 * tile_h as u64;
+    if tile_w == 0 || tile_h == 0 || tile_w > MAX_DIMENSION || tile_h > MAX_DIMENSION || tile_pixels > MAX_PIXELS {
+        set_last_error(RESIZE_ERR_INVALID_SIZE);
+        return RESIZE_ERR_INVALID_SIZE;
+    }
+
+    let src = std::slice::from_raw_parts(src_ptr, src_size);
+    let dst = std::slice::from_raw_parts_mut(dst_ptr, dst_size);
+
+    TILE_CACHE.with(|cell| {
+        let mut tile = cell.borrow_mut();
+        tile.resize(tile_pixels as usize * 4, 0);
+        let code = intermediate_pass(|| resize_slices(src, src_w, src_h, &mut tile, tile_w, tile_h, algorithm));
+        if code != RESIZE_OK {
+            return code;
+        }
+
+        let tile_stride = tile_w as usize * 4;
+        for (y, dst_row) in dst.chunks_exact_mut(dst_w as usize * 4).enumerate() {
+            let ty = y % tile_h as usize;
+            let tile_row = &tile[ty * tile_stride..(ty + 1) * tile_stride];
+            // The last chunk is shorter when the destination width isn't a tile multiple
+            for chunk in dst_row.chunks_mut(tile_stride) {
+                chunk.copy_from_slice(&tile_row[..chunk.len()]);
+            }
+            mark_row_completed(y as u32);
+        }
+
+        RESIZE_OK
+    })
+}
+
+/// One source image of a mosaic passed to resize_rgba_mosaic
+/// On wasm32 this is three 32-bit words: ptr, w, h
+#[repr(C)]
+pub struct MosaicSource {
+    pub ptr: *const u8,
+    pub w: u32,
+    pub h: u32,
+}
+
+/// Resize several images into the cells of a grid (contact sheets)
+/// Source `i` is resized to `cell_w` x `cell_h` with `algorithm` and placed in column
+/// `i % cols`, row `i / cols`, starting at the destination's top-left corner. The rest
+/// of the destination (unused cells and any margin right of or below the grid) is
+/// cleared to transparent. The grid must fit the destination, and every source is
+/// validated before any pixel is written
+/// Returns error code: 0 = success, non-zero = error
+///
+/// # Safety
+/// sources_ptr must point to `count` 4-byte aligned MosaicSource values, each describing
+/// a valid RGBA buffer, and dst_ptr to a valid RGBA buffer of the given dimensions
+/// overlapping none of them
+#[no_mangle]
+pub unsafe extern "C" fn resize_rgba_mosaic(
+    sources_ptr: *const MosaicSource,
+    count: u32,
+    cols: u32,
+    cell_w: u32,
+    cell_h: u32,
+    dst_ptr: *mut u8,
+    dst_w: u32,
+    dst_h: u32,
+    algorithm: u32,
+) -> i32 {
+    let dst_size = match validate_buffer(dst_ptr, dst_w, dst_h) {
+        Ok(size) => size,
+        Err(code) => return code,
+    };
+
+    if sources_ptr.is_null() {
+        set_last_error(RESIZE_ERR_NULL_PTR);
+        return RESIZE_ERR_NULL_PTR;
+    }
+
+    if !(sources_ptr as usize).is_multiple_of(std::mem::align_of::<MosaicSource>()) {
+        set_last_error(RESIZE_ERR_ALIGNMENT);
+        return RESIZE_ERR_ALIGNMENT;
+    }
+
+    if count == 0 || cols == 0 || cell_w == 0 || cell_h == 0 {
+        set_last_error(RESIZE_ERR_INVALID_PARAM);
+        return RESIZE_ERR_INVALID_PARAM;
+    }
+
+    let rows = count.div_ceil(cols);
+    let fits = |cells: u32, size: u32, limit: u32| cells as u64 * size as u64 <= limit as u64;
+    if !fits(cols.min(count), cell_w, dst_w) || !fits(rows, cell_h, dst_h) {
+        set_last_error(RESIZE_ERR_INVALID_PARAM);
+        return RESIZE_ERR_INVALID_PARAM;
+    }
+
+    let dst_start = dst_ptr as usize;
+    let dst_end = dst_start.saturating_add(dst_size);
+    let sources = std::slice::from_raw_parts(sources_ptr, count as usize);
+    for source in sources {
+        let size = match validate_buffer(source.ptr, source.w, source.h) {
+            Ok(size) => size,
+            Err(code) => return code,
+        };
+        let start = source.ptr as usize;
+        if start < dst_end && dst_start < start.saturating_add(size) {
+            set_last_error(RESIZE_ERR_OVERLAP);
+            return RESIZE_ERR_OVERLAP;
+        }
+    }
+
+    let dst = std::slice::from_raw_parts_mut(dst_ptr, dst_size);
+    dst.fill(0);
+
+    let cell_stride = cell_w as usize * 4;
+    let dst_stride = dst_w as usize * 4;
+    let mut cell = vec![0u8; cell_stride * cell_h as usize];
+
+    for (i, source) in sources.iter().enumerate() {
+        let src = std::slice::from_raw_parts(source.ptr, source.w as usize * source.h as usize * 4);
+        let code = intermediate_pass(|| resize_slices(src, source.w, source.h, &mut cell, cell_w, cell_h, algorithm));
+        if code != RESIZE_OK {
+            return code;
+        }
+
+        let left = (i % cols as usize) * cell_stride;
+        let top = (i / cols as usize) * cell_h as usize;
+        for (y, cell_row) in cell.chunks_exact(cell_stride).enumerate() {
+            let row = (top + y) * dst_stride + left;
+            dst[row..row + cell_stride].copy_from_slice(cell_row);
+        }
+    }
+    mark_rows_completed(dst_h);
+
+    RESIZE_OK
+}
+
+/// Resize the base image, then composite a watermark over the result
+/// The watermark is used at its own size (no resampling) and placed with its top-left
+/// corner at (`wm_x`, `wm_y`); it must lie entirely within the destination.
+/// Blending is straight-alpha "over" in linear light, with the watermark's alpha
+/// scaled by `wm_opacity` (clamped to [0, 1]); pixels outside the watermark are left
+/// exactly as the plain resize produced them
+/// Returns error code: 0 = success, non-zero = error
+///
+/// # Safety
+/// src_ptr/dst_ptr/wm_ptr must point to valid RGBA buffers of the given dimensions;
+/// wm_ptr must not overlap dst_ptr
+#[no_mangle]
+pub unsafe extern "C" fn resize_rgba_watermark(
+    src_ptr: *const u8,
+    src_w: u32,
+    src_h: u32,
+    dst_ptr: *mut u8,
+    dst_w: u32,
+    dst_h: u32,
+    wm_ptr: *const u8,
+    wm_w: u32,
+    wm_h: u32,
+    wm_x: u32,
+    wm_y: u32,
+    wm_opacity: f32,
+    algorithm: u32,
+) -> i32 {
+    let (src_size, dst_size) = match validate_params(src_ptr, src_w, src_h, dst_ptr, dst_w, dst_h) {
+        Ok(sizes) => sizes,
+        Err(code) => return code,
+    };
+    let wm_size = match validate_buffer(wm_ptr, wm_w, wm_h) {
+        Ok(size) => size,
+        Err(code) => return code,
+    };
+
+    // The watermark is read after dst is written, so it must not alias the destination
+    let (dst_start, wm_start) = (dst_ptr as usize, wm_ptr as usize);
+    if wm_start < dst_start.saturating_add(dst_size) && dst_start < wm_start.saturating_add(wm_size) {
+        set_last_error(RESIZE_ERR_OVERLAP);
+        return RESIZE_ERR_OVERLAP;
+    }
+
+    let fits_x = wm_x.checked_add(wm_w).is_some_and(|end| end <= dst_w);
+    let fits_y = wm_y.checked_add(wm_h).is_some_and(|end| end <= dst_h);
+    if !fits_x || !fits_y || !wm_opacity.is_finite() {
+        set_last_error(RESIZE_ERR_INVALID_PARAM);
+        return RESIZE_ERR_INVALID_PARAM;
+    }
+
+    let src = std::slice::from_raw_parts(src_ptr, src_size);
+    let dst = std::slice::from_raw_parts_mut(dst_ptr, dst_size);
+    let code = intermediate_pass(|| resize_slices(src, src_w, src_h, dst, dst_w, dst_h, algorithm));
+    if code != RESIZE_OK {
+        return code;
+    }
+
+    let wm = std::slice::from_raw_parts(wm_ptr, wm_size);
+    let opacity = wm_opacity.clamp(0.0, 1.0);
+    init_gamma_luts();
+
+    let dst_stride = dst_w as usize * 4;
+    let wm_stride = wm_w as usize * 4;
+    for (row, wm_row) in wm.chunks_exact(wm_stride).enumerate() {
+        let start = (wm_y as usize + row) * dst_stride + wm_x as usize * 4;
+        let dst_row = &mut dst[start..start + wm_stride];
+
+        for (d, w) in dst_row.chunks_exact_mut(4).zip(wm_row.chunks_exact(4)) {
+            blend_over_linear(d, w, opacity);
+        }
+    }
+    mark_rows_completed(dst_h);
+
+    RESIZE_OK
+}
+
+/// Straight-alpha "over" of one `top` pixel onto `base` in linear light
+/// `top`'s alpha is scaled by `opacity`; the gamma LUTs must be initialized
+#[inline(always)]
+fn blend_over_linear(base: &mut [u8], top: &[u8], opacity: f32) {
+    let top_a = top[3] as f32 / 255.0 * opacity;
+    if top_a <= 0.0 {
+        return;
+    }
+    let base_a = base[3] as f32 / 255.0;
+    let base_weight = base_a * (1.0 - top_a);
+    let out_a = top_a + base_weight;
+
+    for c in 0..3 {
+        let linear = (srgb_to_linear_lut(top[c]) * top_a
+            + srgb_to_linear_lut(base[c]) * base_weight)
+            / out_a;
+        base[c] = (linear_to_srgb_fast(linear) * 255.0 + 0.5) as u8;
+    }
+    base[3] = (out_a * 255.0 + 0.5) as u8;
+}
+
+/// Resize and composite the result over the destination's existing content
+/// The source is resized with `algorithm` into a temporary buffer and blended onto
+/// dst_ptr with straight-alpha "over" in linear light (the same blend as
+/// resize_rgba_watermark), so the destination is read as well as written. The source
+/// and destination must still not overlap
+/// Returns error code: 0 = success, non-zero = error
+///
+/// # Safety
+/// src_ptr/dst_ptr must point to valid RGBA buffers of the given dimensions and dst_ptr
+/// must hold initialized pixels
+#[no_mangle]
+pub unsafe extern "C" fn resize_rgba_over_dst(
+    src_ptr: *const u8,
+    src_w: u32,
+    src_h: u32,
+    dst_ptr: *mut u8,
+    dst_w: u32,
+    dst_h: u32,
+    algorithm: u32,
+) -> i32 {
+    let (src_size, dst_size) = match validate_params(src_ptr, src_w, src_h, dst_ptr, dst_w, dst_h) {
+        Ok(sizes) => sizes,
+        Err(code) => return code,
+    };
+
+    let src = std::slice::from_raw_parts(src_ptr, src_size);
+    let mut resized = vec![0u8; dst_size];
+    let code = intermediate_pass(|| resize_slices(src, src_w, src_h, &mut resized, dst_w, dst_h, algorithm));
+    if code != RESIZE_OK {
+        return code;
+    }
+
+    init_gamma_luts();
+    let dst = std::slice::from_raw_parts_mut(dst_ptr, dst_size);
+    for (d, top) in dst.chunks_exact_mut(4).zip(resized.chunks_exact(4)) {
+        blend_over_linear(d, top, 1.0);
+    }
+    mark_rows_completed(dst_h);
+
+    RESIZE_OK
+}
+
+/// Resize and draw a 1px grid on the source pixel boundaries (editor visualization)
+/// Integer upscales are resized with nearest so each source pixel stays a crisp block;
+/// anything else uses `algorithm`. A line is drawn at destination column
+/// `floor(i * dst_w / src_w)` for every source column boundary `i` in 0..src_w, plus
+/// the last column for the closing edge (rows likewise), blended over the output
+/// with (`grid_r`, `grid_g`, `grid_b`, `grid_a`) as in resize_rgba_watermark.
+/// Crossings are blended once
+/// Returns error code: 0 = success, non-zero = error
+///
+/// # Safety
+/// src_ptr/dst_ptr must point to valid RGBA buffers of the given dimensions
+#[no_mangle]
+pub unsafe extern "C" fn resize_rgba_grid_overlay(
+    src_ptr: *const u8,
+    src_w: u32,
+    src_h: u32,
+    dst_ptr: *mut u8,
+    dst_w: u32,
+    dst_h: u32,
+    grid_r: u8,
+    grid_g: u8,
+    grid_b: u8,
+    grid_a: u8,
+    algorithm: u32,
+) -> i32 {
+    let (src_size, dst_size) = match validate_params(src_ptr, src_w, src_h, dst_ptr, dst_w, dst_h) {
+        Ok(sizes) => sizes,
+        Err(code) => return code,
+    };
+
+    let integer_upscale = dst_w.is_multiple_of(src_w) && dst_h.is_multiple_of(src_h);
+    let algorithm = if integer_upscale { RESIZE_ALGO_NEAREST } else { algorithm };
+
+    let src = std::slice::from_raw_parts(src_ptr, src_size);
+    let dst = std::slice::from_raw_parts_mut(dst_ptr, dst_size);
+    let code = intermediate_pass(|| resize_slices(src, src_w, src_h, dst, dst_w, dst_h, algorithm));
+    if code != RESIZE_OK {
+        return code;
+    }
+
+    let lines = |src_size: u32, dst_size: u32| -> Vec<bool> {
+        let mut on = vec![false; dst_size as usize];
+        for i in 0..src_size as u64 {
+            on[(i * dst_size as u64 / src_size as u64) as usize] = true;
+        }
+        on[dst_size as usize - 1] = true;
+        on
+    };
+    let (columns, rows) = (lines(src_w, dst_w), lines(src_h, dst_h));
+
+    init_gamma_luts();
+    let grid = [grid_r, grid_g, grid_b, grid_a];
+    for (dst_row, &row_line) in dst.chunks_exact_mut(dst_w as usize * 4).zip(&rows) {
+        for (d, &column_line) in dst_row.chunks_exact_mut(4).zip(&columns) {
+            if row_line || column_line {
+                blend_over_linear(d, &grid, 1.0);
+            }
+        }
+    }
+    mark_rows_completed(dst_h);
+
+    RESIZE_OK
+}
+
+/// Coarse map of which COVERAGE_TILE-sized source tiles contain any non-empty pixel,
+/// stored as a summed-area table so any rectangle of tiles is checked in O(1)
+struct CoverageMap {
+    tiles_w: usize,
+    sums: Vec<u32>,
+}
+
+impl CoverageMap {
+    /// Build from an RGBA image; a pixel is empty when all four channels are 0
+    fn new(src: &[u8], w: u32, h: u32) -> Self {
+        let tiles_w = w.div_ceil(COVERAGE_TILE) as usize;
+        let tiles_h = h.div_ceil(COVERAGE_TILE) as usize;
+        let mut occupied = vec![0u32; tiles_w * tiles_h];
+
+        for (y, row) in src.chunks_exact(w as usize * 4).enumerate() {
+            let tile_row = &mut occupied[y / COVERAGE_TILE as usize * tiles_w..][..tiles_w];
+            for (tile, pixels) in tile_row.iter_mut().zip(row.chunks(COVERAGE_TILE as usize * 4)) {
+                if *tile == 0 && pixels.iter().any(|&v| v != 0) {
+                    *tile = 1;
+                }
+            }
+        }
+
+        // sums[(ty + 1) * (tiles_w + 1) + tx + 1] = occupied tiles in [0, tx] x [0, ty]
+        let stride = tiles_w + 1;
+        let mut sums = vec![0u32; stride * (tiles_h + 1)];
+        for ty in 0..tiles_h {
+            for tx in 0..tiles_w {
+                sums[(ty + 1) * stride + tx + 1] = occupied[ty * tiles_w + tx]
+                    + sums[ty * stride + tx + 1]
+                    + sums[(ty + 1) * stride + tx]
+                    - sums[ty * stride + tx];
+            }
+        }
+
+        CoverageMap { tiles_w, sums }
+    }
+
+    /// Whether the inclusive source pixel rectangle may contain a non-empty pixel
+    fn any_in(&self, x0: u32, y0: u32, x1: u32, y1: u32) -> bool {
+        let stride = self.tiles_w + 1;
+        let (tx0, tx1) = ((x0 / COVERAGE_TILE) as usize, (x1 / COVERAGE_TILE) as usize + 1);
+        let (ty0, ty1) = ((y0 / COVERAGE_TILE) as usize, (y1 / COVERAGE_TILE) as usize + 1);
+        let count = self.sums[ty1 * stride + tx1] + self.sums[ty0 * stride + tx0]
+            - self.sums[ty0 * stride + tx1]
+            - self.sums[ty1 * stride + tx0];
+        count > 0
+    }
+}
+
+/// Inclusive source range read by the resampling cores for destination coordinates
+/// `d0..=d1` on one axis. Mirrors their tap windows: nearest and bilinear read
+/// floor(c) and floor(c) + 1, Lanczos reads floor(c) - 2 ..= floor(c) + 3, where
+/// c = (d + 0.5) * scale - 0.5
+fn source_footprint(d0: u32, d1: u32, src_size: u32, dst_size: u32, algorithm: u32) -> (u32, u32) {
+    let scale = src_size as f32 / dst_size as f32;
+    let base = |d: u32| ((d as f32 + 0.5) * scale - 0.5).floor() as i64;
+    let (before, after) = if algorithm == RESIZE_ALGO_LANCZOS {
+        (LANCZOS_A as i64 - 1, LANCZOS_A as i64)
+    } else {
+        (0, 1)
+    };
+    let last = src_size as i64 - 1;
+    (
+        (base(d0) - before).clamp(0, last) as u32,
+        (base(d1) + after).clamp(0, last) as u32,
+    )
+}
+
+/// Resize that skips destination regions whose source footprint is empty
+/// With `skip_transparent` = 1 the destination is processed in SKIP_TILE tiles; a tile
+/// whose source footprint (every tap the algorithm would read) holds only transparent
+/// black pixels (all channels 0) is written as transparent black without resampling,
+/// checked against a coarse coverage map built once. The output is identical to the
+/// regular resize. Transparent pixels that still carry color are not treated as empty,
+/// because the regular path would blend that color. `skip_transparent` = 0 is a plain
+/// resize. get_transparent_skip_count reports how many pixels were skipped
+/// Returns error code: 0 = success, non-zero = error
+///
+/// # Safety
+/// src_ptr/dst_ptr must point to valid RGBA buffers of the given dimensions
+#[no_mangle]
+pub unsafe extern "C" fn resize_rgba_skip_transparent(
+    src_ptr: *const u8,
+    src_w: u32,
+    src_h: u32,
+    dst_ptr: *mut u8,
+    dst_w: u32,
+    dst_h: u32,
+    algorithm: u32,
+    skip_transparent: u32,
+) -> i32 {
+    let (src_size, dst_size) = match validate_params(src_ptr, src_w, src_h, dst_ptr, dst_w, dst_h) {
+        Ok(sizes) => sizes,
+        Err(code) => return code,
+    };
+
+    if skip_transparent > 1 {
+        set_last_error(RESIZE_ERR_INVALID_PARAM);
+        return RESIZE_ERR_INVALID_PARAM;
+    }
+
+    let algorithm = match resolve_algorithm(algorithm, src_w, src_h, dst_w, dst_h) {
+        Ok(a) => a,
+        Err(code) => return code,
+    };
+
+    SKIPPED_PIXELS.with(|c| c.set(0));
+    let src = std::slice::from_raw_parts(src_ptr, src_size);
+    let dst = std::slice::from_raw_parts_mut(dst_ptr, dst_size);
+
+    if skip_transparent == 0 {
+        return resize_slices(src, src_w, src_h, dst, dst_w, dst_h, algorithm);
+    }
+
+    let coverage = CoverageMap::new(src, src_w, src_h);
+    let dst_stride = dst_w as usize * 4;
+    let mut skipped = 0u32;
+
+    for band_y in (0..dst_h).step_by(SKIP_TILE as usize) {
+        let band_h = SKIP_TILE.min(dst_h - band_y);
+        let (sy0, sy1) = source_footprint(band_y, band_y + band_h - 1, src_h, dst_h, algorithm);
+
+        // Consecutive non-empty tiles are resized together as one rectangle;
+        // the trailing dst_w entry only flushes the last run
+        let mut run_start: Option<u32> = None;
+        for tile_x in (0..dst_w).step_by(SKIP_TILE as usize).chain([dst_w]) {
+            let empty = tile_x < dst_w && {
+                let tile_w = SKIP_TILE.min(dst_w - tile_x);
+                let (sx0, sx1) = source_footprint(tile_x, tile_x + tile_w - 1, src_w, dst_w, algorithm);
+                !coverage.any_in(sx0, sy0, sx1, sy1)
+            };
+
+            if tile_x < dst_w && !empty {
+                run_start.get_or_insert(tile_x);
+                continue;
+            }
+
+            if let Some(x) = run_start.take() {
+                let rect = DstRect { x, y: band_y, w: tile_x - x, h: band_h, origin_y: 0 };
+                let code = intermediate_pass(|| {
+                    resize_slices_in_rect(src, src_w, src_h, dst, dst_w, dst_h, rect, false, algorithm)
+                });
+                if code != RESIZE_OK {
+                    return code;
+                }
+            }
+
+            if empty {
+                let tile_w = SKIP_TILE.min(dst_w - tile_x);
+                for row in dst.chunks_exact_mut(dst_stride).skip(band_y as usize).take(band_h as usize) {
+                    row[tile_x as usize * 4..(tile_x + tile_w) as usize * 4].fill(0);
+                }
+                skipped += tile_w * band_h;
+            }
+        }
+        // Runs of one band share its rows, which are final once the band is done
+        mark_rows_completed(band_y + band_h);
+    }
+
+    SKIPPED_PIXELS.with(|c| c.set(skipped));
+    RESIZE_OK
+}
+
+/// Destination coordinates `d0..=d1` on one axis whose source footprint intersects the
+/// source range `s0..=s1`, or None when no destination pixel reads it
+fn dirty_dst_range(s0: u32, s1: u32, src_size: u32, dst_size: u32, algorithm: u32) -> Option<(u32, u32)> {
+    // Footprints move monotonically with d, so the hits form one contiguous run
+    let hit = |&d: &u32| {
+        let (lo, hi) = source_footprint(d, d, src_size, dst_size, algorithm);
+        lo <= s1 && hi >= s0
+    };
+    let first = (0..dst_size).find(hit)?;
+    let last = (first..dst_size).rev().find(hit)?;
+    Some((first, last))
+}
+
+/// Update a previous resize after part of the source changed
+/// Only destination pixels whose filter support overlaps the dirty source rectangle
+/// are recomputed (the dirty rectangle expanded by the tap window of `algorithm` and
+/// mapped to the destination); all other destination pixels are left untouched. If the
+/// destination held the resize of the old source, it then equals a full resize of the
+/// new one. The dirty rectangle must be non-empty and lie within the source
+/// Returns error code: 0 = success, non-zero = error
+///
+/// # Safety
+/// src_ptr/dst_ptr must point to valid RGBA buffers of the given dimensions
+#[no_mangle]
+pub unsafe extern "C" fn resize_rgba_update(
+    src_ptr: *const u8,
+    src_w: u32,
+    src_h: u32,
+    dst_ptr: *mut u8,
+    dst_w: u32,
+    dst_h: u32,
+    dirty_x: u32,
+    dirty_y: u32,
+    dirty_w: u32,
+    dirty_h: u32,
+    algorithm: u32,
+) -> i32 {
+    let (src_size, dst_size) = match validate_params(src_ptr, src_w, src_h, dst_ptr, dst_w, dst_h) {
+        Ok(sizes) => sizes,
+        Err(code) => return code,
+    };
+
+    let fits_x = dirty_x.checked_add(dirty_w).is_some_and(|end| end <= src_w);
+    let fits_y = dirty_y.checked_add(dirty_h).is_some_and(|end| end <= src_h);
+    if dirty_w == 0 || dirty_h == 0 || !fits_x || !fits_y {
+        set_last_error(RESIZE_ERR_INVALID_PARAM);
+        return RESIZE_ERR_INVALID_PARAM;
+    }
+
+    let algorithm = match resolve_algorithm(algorithm, src_w, src_h, dst_w, dst_h) {
+        Ok(a) => a,
+        Err(code) => return code,
+    };
+
+    let x_range = dirty_dst_range(dirty_x, dirty_x + dirty_w - 1, src_w, dst_w, algorithm);
+    let y_range = dirty_dst_range(dirty_y, dirty_y + dirty_h - 1, src_h, dst_h, algorithm);
+    let ((x0, x1), (y0, y1)) = match (x_range, y_range) {
+        (Some(x), Some(y)) => (x, y),
+        // A nearest downscale can skip the changed pixels entirely
+        _ => return RESIZE_OK,
+    };
+
+    let src = std::slice::from_raw_parts(src_ptr, src_size);
+    let dst = std::slice::from_raw_parts_mut(dst_ptr, dst_size);
+    let rect = DstRect { x: x0, y: y0, w: x1 - x0 + 1, h: y1 - y0 + 1, origin_y: 0 };
+    resize_slices_in_rect(src, src_w, src_h, dst, dst_w, dst_h, rect, false, algorithm)
+}
+
+/// Number of destination pixels the last resize_rgba_skip_transparent call wrote as
+/// transparent black without resampling (0 after a call with skipping disabled)
+#[no_mangle]
+pub extern "C" fn get_transparent_skip_count() -> u32 {
+    SKIPPED_PIXELS.with(|c| c.get())
+}
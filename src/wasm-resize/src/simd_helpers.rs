@@ -1,7 +1,7 @@
 //! SIMD optimization helpers for image resampling
 //! Provides batch processing functions using WASM SIMD128
 
-#[cfg(not(target_arch = "wasm32"))]
+#[cfg(not(any(target_arch = "wasm32", test)))]
 compile_error!("This module only supports wasm32 target");
 
 // SIMD batch processing functions
@@ -20,16 +20,6 @@ pub unsafe fn copy_4_pixels_simd(src: *const u8, dst: *mut u8) {
     v128_store(dst as *mut v128, data);
 }
 
-/// Copy 4 RGBA pixels (scalar fallback when SIMD not available)
-#[cfg(not(target_feature = "simd128"))]
-#[inline(always)]
-pub unsafe fn copy_4_pixels_simd(src: *const u8, dst: *mut u8) {
-    // Scalar fallback: copy 16 bytes
-    let src_slice = std::slice::from_raw_parts(src, 16);
-    let dst_slice = std::slice::from_raw_parts_mut(dst, 16);
-    dst_slice.copy_from_slice(src_slice);
-}
-
 /// Batch bilinear interpolation for 4 pixels using SIMD
 /// Processes 4 pixels in parallel for better performance
 /// 
@@ -86,10 +76,25 @@ pub unsafe fn bilinear_interp_4_pixels(
     p11: [u8; 4],
     fx: f32,
     fy: f32,
+) -> [u8; 4] {
+    bilinear_interp_4_pixels_scalar(p00, p10, p01, p11, fx, fy)
+}
+
+/// Bilinear interpolation with the scalar `a * (1 - t) + b * t` blend in every build
+/// The simd128 variant blends as `a + t * (b - a)`, which can differ in the last bit;
+/// this form gives the same bytes whether or not SIMD is enabled
+#[inline(always)]
+pub fn bilinear_interp_4_pixels_scalar(
+    p00: [u8; 4],
+    p10: [u8; 4],
+    p01: [u8; 4],
+    p11: [u8; 4],
+    fx: f32,
+    fy: f32,
 ) -> [u8; 4] {
     let lerp = |a: u8, b: u8, t: f32| -> u8 {
         let result = a as f32 * (1.0 - t) + b as f32 * t;
-        result.max(0.0).min(255.0) as u8
+        result.clamp(0.0, 255.0) as u8
     };
     
     let c0 = [
@@ -173,3 +178,101 @@ pub unsafe fn batch_copy_nearest(
     dst_slice.copy_from_slice(src_slice);
 }
 
+
+/// Horizontal prefix sum over 4-lane integer pixels (one u32 lane per channel)
+/// `out[0..4]` is zero and `out[(i + 1) * 4..(i + 2) * 4]` holds the sum of pixels 0..=i,
+/// so any run of whole pixels sums with a single subtraction. The running totals are
+/// u64, so the subtraction is exact however long the row (f32 totals drop low bits).
+/// `out` must hold at least `values.len() + 4` entries
+#[cfg(target_feature = "simd128")]
+#[inline(always)]
+pub fn prefix_sum_4_lanes(values: &[u32], out: &mut [u64]) {
+    use std::arch::wasm32::*;
+    
+    crate::mark_simd_used();
+    out[..4].fill(0);
+    let mut acc_lo = u64x2_splat(0);
+    let mut acc_hi = u64x2_splat(0);
+    for (v, o) in values.chunks_exact(4).zip(out[4..].chunks_exact_mut(4)) {
+        // SAFETY: `v` is 16 bytes and `o` 32 bytes; wasm v128 loads/stores allow any alignment
+        unsafe {
+            let lanes = v128_load(v.as_ptr() as *const v128);
+            acc_lo = i64x2_add(acc_lo, u64x2_extend_low_u32x4(lanes));
+            acc_hi = i64x2_add(acc_hi, u64x2_extend_high_u32x4(lanes));
+            v128_store(o.as_mut_ptr() as *mut v128, acc_lo);
+            v128_store(o.as_mut_ptr().add(2) as *mut v128, acc_hi);
+        }
+    }
+}
+
+/// Scalar fallback for prefix_sum_4_lanes
+#[cfg(not(target_feature = "simd128"))]
+#[inline(always)]
+pub fn prefix_sum_4_lanes(values: &[u32], out: &mut [u64]) {
+    out[..4].fill(0);
+    let mut acc = [0u64; 4];
+    for (v, o) in values.chunks_exact(4).zip(out[4..].chunks_exact_mut(4)) {
+        for (a, &x) in acc.iter_mut().zip(v) {
+            *a += x as u64;
+        }
+        o.copy_from_slice(&acc);
+    }
+}
+
+/// Sum 4-lane pixels over the fractional interval [start, end) in O(1)
+/// Whole pixels come from the prefix table (see prefix_sum_4_lanes) as an exact integer;
+/// the partially covered pixels at either end are then added with their coverage
+/// fraction, so the result is bit-identical to summing the taps one by one in that order.
+/// `end` must not exceed the pixel count
+#[inline(always)]
+pub fn box_sum_4_lanes(prefix: &[u64], values: &[u32], start: f32, end: f32) -> [f64; 4] {
+    let pixels = values.len() / 4;
+    let first = start.floor() as usize;
+    let last = end.floor() as usize;
+    let mut out = [0.0f64; 4];
+    
+    if first == last {
+        // Footprint inside a single source pixel (upscaling)
+        let i = first.min(pixels - 1) * 4;
+        for (o, &v) in out.iter_mut().zip(&values[i..i + 4]) {
+            *o = v as f64 * (end - start) as f64;
+        }
+        return out;
+    }
+    
+    let head = (first as f32 + 1.0 - start) as f64;
+    let tail = if last < pixels { (end - last as f32) as f64 } else { 0.0 };
+    for c in 0..4 {
+        let whole = prefix[last * 4 + c] - prefix[(first + 1) * 4 + c];
+        out[c] = whole as f64 + values[first * 4 + c] as f64 * head;
+        if tail > 0.0 {
+            out[c] += values[last * 4 + c] as f64 * tail;
+        }
+    }
+    out
+}
+
+/// Clamp four f32 channels to [0, 255] and truncate them to bytes
+/// Matches the scalar `v.max(0.0).min(255.0) as u8` exactly, NaN included (maps to 0);
+/// callers wanting round-to-nearest add 0.5 first
+#[cfg(target_feature = "simd128")]
+#[inline(always)]
+pub fn pack_f32x4_to_u8(v: [f32; 4]) -> [u8; 4] {
+    use std::arch::wasm32::*;
+    
+    crate::mark_simd_used();
+    let v = f32x4(v[0], v[1], v[2], v[3]);
+    // max/min propagate NaN, which trunc_sat then turns into 0
+    let clamped = f32x4_min(f32x4_max(v, f32x4_splat(0.0)), f32x4_splat(255.0));
+    let ints = i32x4_trunc_sat_f32x4(clamped);
+    let shorts = i16x8_narrow_i32x4(ints, ints);
+    let bytes = u8x16_narrow_i16x8(shorts, shorts);
+    (i32x4_extract_lane::<0>(bytes) as u32).to_le_bytes()
+}
+
+/// Scalar fallback for pack_f32x4_to_u8
+#[cfg(not(target_feature = "simd128"))]
+#[inline(always)]
+pub fn pack_f32x4_to_u8(v: [f32; 4]) -> [u8; 4] {
+    v.map(|c| c.clamp(0.0, 255.0) as u8)
+}
@@ -0,0 +1,110 @@
+//! Allocation-counting tests for warm_buffers, the scratch path and the batch free in lib.rs
+//! The counting allocator below is installed for the whole test binary; counts are per
+//! thread, so tests running in parallel do not disturb each other
+
+use crate::*;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+thread_local! {
+    // Allocations (including reallocations) made on this thread
+    static ALLOCATIONS: Cell<u64> = const { Cell::new(0) };
+    // Allocations made on this thread minus those freed on it
+    static LIVE: Cell<i64> = const { Cell::new(0) };
+}
+
+struct CountingAllocator;
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|c| c.set(c.get() + 1));
+        let _ = LIVE.try_with(|c| c.set(c.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        let _ = LIVE.try_with(|c| c.set(c.get() - 1));
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|c| c.set(c.get() + 1));
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn allocations() -> u64 {
+    ALLOCATIONS.with(|c| c.get())
+}
+
+fn live_allocations() -> i64 {
+    LIVE.with(|c| c.get())
+}
+
+#[test]
+fn warmed_resize_does_not_allocate() {
+    let src: Vec<u8> = (0..120 * 90 * 4).map(|i| (i * 7 % 251) as u8).collect();
+    let mut dst = vec![0u8; 70 * 60 * 4];
+    for algo in [RESIZE_ALGO_NEAREST, RESIZE_ALGO_BILINEAR, RESIZE_ALGO_LANCZOS] {
+        // A different size per algorithm so nothing is pre-grown by the previous iteration
+        let (dw, dh) = (50 - algo * 5, 40 - algo * 3);
+        assert_eq!(warm_buffers(120, 90, dw, dh, algo), 0);
+        let before = allocations();
+        let code = unsafe { resize_rgba_row_order(src.as_ptr(), 120, 90, dst.as_mut_ptr(), dw, dh, algo, 0) };
+        assert_eq!(code, 0);
+        assert_eq!(allocations(), before, "algo {algo}");
+    }
+    // Warming sizes the Pass-1 buffer of the selected intermediate precision
+    for (i, precision) in [RESIZE_PRECISION_F64, RESIZE_PRECISION_FIXED].into_iter().enumerate() {
+        let (dw, dh) = (30 - i as u32 * 4, 20 - i as u32 * 3);
+        assert_eq!(set_intermediate_precision(precision), 0);
+        assert_eq!(warm_buffers(120, 90, dw, dh, RESIZE_ALGO_LANCZOS), 0);
+        let before = allocations();
+        let code = unsafe { resize_rgba_lanczos(src.as_ptr(), 120, 90, dst.as_mut_ptr(), dw, dh) };
+        assert_eq!(code, 0);
+        assert_eq!(allocations(), before, "precision {precision}");
+    }
+    set_intermediate_precision(RESIZE_PRECISION_F32);
+    // Without warming, the first call at a new size grows the buffers
+    let before = allocations();
+    unsafe { assert_eq!(resize_rgba_lanczos(src.as_ptr(), 120, 90, dst.as_mut_ptr(), 70, 60), 0) };
+    assert!(allocations() > before);
+}
+
+#[test]
+fn scratch_lanczos_does_not_allocate() {
+    let src: Vec<u8> = (0..40 * 30 * 4).map(|i| (i * 13 % 251) as u8).collect();
+    for precision in [RESIZE_PRECISION_F32, RESIZE_PRECISION_FIXED, RESIZE_PRECISION_F64] {
+        assert_eq!(set_intermediate_precision(precision), 0);
+        let need = scratch_size_needed(40, 30, 23, 17, RESIZE_ALGO_LANCZOS);
+        // Offset by one usize, which on wasm32 leaves an f64 Pass-1 buffer to be padded
+        let mut scratch = vec![0u64; need / 8 + 2];
+        let scratch_ptr = unsafe { (scratch.as_mut_ptr() as *mut u8).add(std::mem::align_of::<usize>()) };
+        let mut a = vec![0u8; 23 * 17 * 4];
+        let mut b = a.clone();
+        let before = allocations();
+        let code =
+            unsafe { resize_rgba_scratch(src.as_ptr(), 40, 30, a.as_mut_ptr(), 23, 17, RESIZE_ALGO_LANCZOS, scratch_ptr, need) };
+        assert_eq!(code, 0);
+        assert_eq!(allocations(), before, "precision {precision}");
+        unsafe { assert_eq!(resize_rgba_lanczos(src.as_ptr(), 40, 30, b.as_mut_ptr(), 23, 17), 0) };
+        assert_eq!(a, b, "precision {precision}");
+    }
+    set_intermediate_precision(RESIZE_PRECISION_F32);
+}
+
+#[test]
+fn dealloc_many_frees_live_buffers() {
+    let live = live_allocations();
+    let sizes = [64usize, 0, 128, 32];
+    let ptrs = [alloc_memory(64), std::ptr::null_mut(), alloc_memory(128), alloc_memory(32)];
+    assert_eq!(live_allocations(), live + 3);
+    // dealloc_many takes u32 offsets, which only hold real pointers on wasm32; its free
+    // loop is fed full addresses here
+    let entries = ptrs.iter().map(|&p| p as usize).zip(sizes);
+    assert_eq!(unsafe { dealloc_entries(entries) }, 3);
+    assert_eq!(live_allocations(), live);
+}
@@ -0,0 +1,626 @@
+//! Tests for the measurement and analysis exports in analysis.rs
+
+use super::*;
+use crate::*;
+
+#[test]
+fn edge_map_peaks_on_the_step() {
+    let src = img(
+        40,
+        40,
+        |x, _| if x < 20 { [0, 0, 0, 255] } else { [255, 255, 255, 255] },
+    );
+    let mut dst = vec![0u8; 20 * 20 * 4];
+    let mut plain = dst.clone();
+    let mut edges = vec![0u8; 20 * 20];
+    unsafe {
+        assert_eq!(
+            resize_rgba_with_edges(src.as_ptr(), 40, 40, dst.as_mut_ptr(), edges.as_mut_ptr(), 20, 20, 1),
+            0
+        );
+        assert_eq!(
+            resize_with_algorithm(src.as_ptr(), 40, 40, plain.as_mut_ptr(), 20, 20, 1),
+            0
+        );
+    }
+    assert_eq!(dst, plain);
+    for row in edges.chunks_exact(20) {
+        assert!(row[9] > 128 || row[10] > 128, "{row:?}");
+        assert!(row[..7].iter().chain(&row[13..]).all(|&e| e < 4), "{row:?}");
+    }
+    let overlapping = dst.as_mut_ptr().wrapping_add(4);
+    let code = unsafe { resize_rgba_with_edges(src.as_ptr(), 40, 40, dst.as_mut_ptr(), overlapping, 20, 20, 1) };
+    assert_eq!(code, RESIZE_ERR_OVERLAP);
+}
+
+#[test]
+fn psnr_matches_closed_form() {
+    let a = img(16, 8, |x, y| [(x * 10) as u8, (y * 20) as u8, 50, 255]);
+    let mut b = a.clone();
+    let mut out = 0.0f32;
+    unsafe { assert_eq!(psnr_rgba(a.as_ptr(), a.as_ptr(), 16, 8, &mut out), 0) };
+    assert_eq!(out, 99.0);
+    b[0] += 1;
+    b[5] += 1;
+    unsafe { assert_eq!(psnr_rgba(a.as_ptr(), b.as_ptr(), 16, 8, &mut out), 0) };
+    let mse = 2.0 / (16.0 * 8.0 * 4.0);
+    let expected = 10.0 * (255.0f64 * 255.0 / mse).log10();
+    assert!((out as f64 - expected).abs() < 1e-3, "{out} vs {expected}");
+    unsafe {
+        assert_eq!(
+            psnr_rgba(a.as_ptr(), b.as_ptr(), 16, 8, std::ptr::null_mut()),
+            RESIZE_ERR_NULL_PTR
+        )
+    };
+}
+
+#[test]
+fn channel_stats_measure_source_in_linear_light() {
+    let solid = img(30, 20, |_, _| [200, 100, 50, 128]);
+    let mut dst = vec![0u8; 10 * 10 * 4];
+    let mut stats = [0.0f32; 8];
+    unsafe {
+        let code = resize_rgba_channel_stats(solid.as_ptr(), 30, 20, dst.as_mut_ptr(), 10, 10, 1, stats.as_mut_ptr());
+        assert_eq!(code, 0);
+    }
+    let expected = [
+        srgb_to_linear(200),
+        srgb_to_linear(100),
+        srgb_to_linear(50),
+        128.0 / 255.0,
+    ];
+    for c in 0..4 {
+        assert!((stats[c * 2] as f64 - expected[c]).abs() < 1e-4, "{stats:?}");
+        assert!(stats[c * 2 + 1] < 1e-3, "{stats:?}");
+    }
+    assert!(dst.chunks_exact(4).all(|p| p == [200, 100, 50, 128]));
+
+    // Red split evenly between black and white: mean 0.5, stddev 0.5 in linear light
+    let split = img(30, 20, |x, _| [if x < 15 { 0 } else { 255 }, 0, 0, 255]);
+    unsafe {
+        let code = resize_rgba_channel_stats(split.as_ptr(), 30, 20, dst.as_mut_ptr(), 10, 10, 1, stats.as_mut_ptr());
+        assert_eq!(code, 0);
+    }
+    assert!(
+        (stats[0] - 0.5).abs() < 1e-4 && (stats[1] - 0.5).abs() < 1e-4,
+        "{stats:?}"
+    );
+
+    let code = unsafe {
+        resize_rgba_channel_stats(
+            solid.as_ptr(),
+            30,
+            20,
+            dst.as_mut_ptr(),
+            10,
+            10,
+            1,
+            std::ptr::null_mut(),
+        )
+    };
+    assert_eq!(code, RESIZE_ERR_NULL_PTR);
+}
+
+#[test]
+fn phash_separates_similar_and_different_images() {
+    let scene = |x: u32, y: u32, w: u32, h: u32| {
+        let (fx, fy) = (x as f32 / w as f32, y as f32 / h as f32);
+        let spot = if (fx - 0.3).powi(2) + (fy - 0.6).powi(2) < 0.04 {
+            50.0
+        } else {
+            0.0
+        };
+        let v = ((fx * 6.0).sin() * (fy * 4.0).cos() * 0.5 + 0.5) * 200.0 + spot;
+        [v as u8, (v * 0.8) as u8, (255.0 - v) as u8, 255]
+    };
+    let original = img(200, 150, |x, y| scene(x, y, 200, 150));
+    // Mildly resized copy with deterministic "compression" noise
+    let similar = img(170, 128, |x, y| {
+        let mut p = scene(x, y, 170, 128);
+        let noise = ((x * 31 + y * 17) % 7) as i32 - 3;
+        for c in &mut p[..3] {
+            *c = (*c as i32 + noise).clamp(0, 255) as u8;
+        }
+        p
+    });
+    let different = img(200, 150, |x, y| {
+        let v = ((x / 20 + y / 15) % 2 * 200) as u8;
+        [v, 255 - v, (x + y) as u8, 255]
+    });
+    let mut hashes = [0u64; 3];
+    unsafe {
+        assert_eq!(phash_rgba(original.as_ptr(), 200, 150, &mut hashes[0]), 0);
+        assert_eq!(phash_rgba(similar.as_ptr(), 170, 128, &mut hashes[1]), 0);
+        assert_eq!(phash_rgba(different.as_ptr(), 200, 150, &mut hashes[2]), 0);
+        assert_eq!(
+            phash_rgba(original.as_ptr(), 200, 150, std::ptr::null_mut()),
+            RESIZE_ERR_NULL_PTR
+        );
+    }
+    let near = (hashes[0] ^ hashes[1]).count_ones();
+    let far = (hashes[0] ^ hashes[2]).count_ones();
+    assert!(near <= 6 && far >= 20, "near {near} far {far}");
+}
+
+#[test]
+fn compare_nearest_separates_flat_and_detailed_sources() {
+    let flat = img(40, 30, |_, _| [90, 160, 30, 255]);
+    let detail = img(40, 30, |x, y| {
+        [
+            (x * 37 % 256) as u8,
+            (y * 53 % 256) as u8,
+            ((x ^ y) * 9 % 256) as u8,
+            255,
+        ]
+    });
+    let mut dst = vec![0u8; 27 * 17 * 4];
+    let mut pct = -1.0f32;
+    unsafe {
+        for algo in [RESIZE_ALGO_BILINEAR, RESIZE_ALGO_LANCZOS] {
+            let code = resize_rgba_compare_nearest(flat.as_ptr(), 40, 30, dst.as_mut_ptr(), 27, 17, algo, &mut pct);
+            assert_eq!(code, 0);
+            assert!(pct < 0.5, "flat {algo}: {pct}");
+            let code = resize_rgba_compare_nearest(detail.as_ptr(), 40, 30, dst.as_mut_ptr(), 27, 17, algo, &mut pct);
+            assert_eq!(code, 0);
+            assert!(pct > 50.0, "detail {algo}: {pct}");
+        }
+        let code = resize_rgba_compare_nearest(
+            detail.as_ptr(),
+            40,
+            30,
+            dst.as_mut_ptr(),
+            27,
+            17,
+            RESIZE_ALGO_NEAREST,
+            &mut pct,
+        );
+        assert_eq!(code, 0);
+        assert_eq!(pct, 0.0);
+    }
+}
+
+#[test]
+fn out_pointers_are_validated() {
+    let src = img(40, 30, |x, y| [x as u8, y as u8, 0, 255]);
+    let mut dst = vec![0u8; 27 * 17 * 4];
+    let (sp, dp) = (src.as_ptr(), dst.as_mut_ptr());
+    unsafe {
+        let null = resize_rgba_compare_nearest(sp, 40, 30, dp, 27, 17, 2, std::ptr::null_mut());
+        assert_eq!(null, RESIZE_ERR_NULL_PTR);
+        let misaligned = resize_rgba_compare_nearest(sp, 40, 30, dp, 27, 17, 2, dp.add(1) as *mut f32);
+        assert_eq!(misaligned, RESIZE_ERR_ALIGNMENT);
+        let inside_dst = resize_rgba_compare_nearest(sp, 40, 30, dp, 27, 17, 2, dp.add(8) as *mut f32);
+        assert_eq!(inside_dst, RESIZE_ERR_OVERLAP);
+        // The last pixel of the source, right before its end
+        let tail = sp.add(src.len() - 4) as *mut f32;
+        assert_eq!(
+            resize_rgba_compare_nearest(sp, 40, 30, dp, 27, 17, 2, tail),
+            RESIZE_ERR_OVERLAP
+        );
+
+        let mut stats = vec![0f32; 8];
+        let stats_in_dst = dp.add(dst.len() - 16) as *mut f32;
+        assert_eq!(
+            resize_rgba_channel_stats(sp, 40, 30, dp, 27, 17, 2, stats_in_dst),
+            RESIZE_ERR_OVERLAP
+        );
+        assert_eq!(
+            resize_rgba_channel_stats(sp, 40, 30, dp, 27, 17, 2, stats.as_mut_ptr()),
+            0
+        );
+        assert_eq!(psnr_rgba(sp, sp, 40, 30, sp.add(64) as *mut f32), RESIZE_ERR_OVERLAP);
+        let hash_in_src = sp.add(sp.align_offset(8) + 8) as *mut u64;
+        assert_eq!(phash_rgba(sp, 40, 30, hash_in_src), RESIZE_ERR_OVERLAP);
+        assert_eq!(
+            phash_rgba(sp, 40, 30, (hash_in_src as *mut u8).add(4) as *mut u64),
+            RESIZE_ERR_ALIGNMENT
+        );
+    }
+}
+
+#[test]
+fn coverage_map_matches_resize_and_fades_at_borders() {
+    let src = img(20, 16, |x, y| [(x * 9) as u8, (y * 13) as u8, 7, 255]);
+    let (dw, dh) = (50u32, 40u32);
+    for algo in [RESIZE_ALGO_BILINEAR, RESIZE_ALGO_LANCZOS] {
+        let mut dst = vec![0u8; (dw * dh * 4) as usize];
+        let mut plain = dst.clone();
+        let mut cov = vec![0u8; (dw * dh) as usize];
+        unsafe {
+            let code = resize_rgba_coverage(src.as_ptr(), 20, 16, dst.as_mut_ptr(), cov.as_mut_ptr(), dw, dh, algo);
+            assert_eq!(code, 0);
+            assert_eq!(
+                resize_rgba_row_order(src.as_ptr(), 20, 16, plain.as_mut_ptr(), dw, dh, algo, 0),
+                0
+            );
+        }
+        assert_eq!(dst, plain);
+        assert_eq!(cov[(20 * dw + 25) as usize], 255);
+        let (corner, edge) = (cov[0], cov[(20 * dw) as usize]);
+        assert!(corner < edge && edge < 255, "algo {algo}: corner {corner} edge {edge}");
+        assert_eq!(cov[(dw * dh - 1) as usize], corner);
+    }
+
+    let mut dst = vec![0u8; 10 * 8 * 4];
+    let mut cov = vec![0u8; 80];
+    unsafe {
+        let code = resize_rgba_coverage(src.as_ptr(), 20, 16, dst.as_mut_ptr(), cov.as_mut_ptr(), 10, 8, 0);
+        assert_eq!(code, 0);
+    }
+    assert!(cov.iter().all(|&c| c == 255));
+
+    let dp = dst.as_mut_ptr();
+    unsafe {
+        let code = resize_rgba_coverage(src.as_ptr(), 20, 16, dp, dp.add(dst.len() - 40), 10, 8, 0);
+        assert_eq!(code, RESIZE_ERR_OVERLAP);
+    }
+}
+
+#[test]
+fn lanczos_coverage_is_share_of_dropped_weight() {
+    // One row at unchanged height, so the Y factor is exactly 1 and each coverage byte is
+    // the in-range share of the X window; precompute_lanczos_weights keeps exactly those taps
+    let (src_w, dst_w) = (20u32, 50u32);
+    let src = img(src_w, 1, |x, _| [(x * 9) as u8, 0, 0, 255]);
+    let mut dst = vec![0u8; dst_w as usize * 4];
+    let mut cov = vec![0u8; dst_w as usize];
+    unsafe {
+        let code = resize_rgba_coverage(src.as_ptr(), src_w, 1, dst.as_mut_ptr(), cov.as_mut_ptr(), dst_w, 1, 2);
+        assert_eq!(code, 0);
+    }
+    let scale = src_w as f32 / dst_w as f32;
+    for (d, &c) in cov.iter().enumerate() {
+        let mut weights = [0f32; LANCZOS_MAX_TAPS];
+        let mut indices = [0i32; LANCZOS_MAX_TAPS];
+        let n = precompute_lanczos_weights(d as f32, src_w, scale, LANCZOS_A, &mut weights, &mut indices);
+        let kept: f32 = weights[..n].iter().map(|w| w.abs()).sum();
+        let center = (d as f32 + 0.5) * scale - 0.5;
+        let base = center.floor() as i32;
+        let full: f32 = (base - 2..=base + 3)
+            .map(|i| lanczos_kernel((i as f32 - center) / scale, LANCZOS_A).abs())
+            .sum();
+        assert!(
+            (c as f32 - kept / full * 255.0).abs() <= 0.51,
+            "{d}: {c} vs {}",
+            kept / full * 255.0
+        );
+    }
+    assert!(cov[0] < 255 && cov[25] == 255);
+}
+
+#[test]
+fn ensemble_agrees_on_smooth_content_only() {
+    // Mid-tone gradient; the gamma path's output LUT is coarse in deep shadows
+    let smooth = img(64, 48, |x, y| [(100 + x * 2) as u8, (80 + y * 3) as u8, 128, 255]);
+    let busy = img(64, 48, |x, y| {
+        if (x + y) % 2 == 0 {
+            [0, 0, 0, 255]
+        } else {
+            [255, 255, 255, 255]
+        }
+    });
+    let mut dst = vec![0u8; 41 * 29 * 4];
+    let mut lanczos = dst.clone();
+    let (mut p_smooth, mut p_busy) = (-1.0f32, -1.0f32);
+    unsafe {
+        assert_eq!(
+            resize_rgba_ensemble(smooth.as_ptr(), 64, 48, dst.as_mut_ptr(), 41, 29, 8, &mut p_smooth),
+            0
+        );
+        assert_eq!(
+            resize_rgba_row_order(smooth.as_ptr(), 64, 48, lanczos.as_mut_ptr(), 41, 29, 2, 0),
+            0
+        );
+        assert_eq!(dst, lanczos);
+        assert_eq!(
+            resize_rgba_ensemble(busy.as_ptr(), 64, 48, dst.as_mut_ptr(), 41, 29, 8, &mut p_busy),
+            0
+        );
+
+        let dp = dst.as_mut_ptr();
+        let null = resize_rgba_ensemble(busy.as_ptr(), 64, 48, dp, 41, 29, 8, std::ptr::null_mut());
+        assert_eq!(null, RESIZE_ERR_NULL_PTR);
+        let in_dst = resize_rgba_ensemble(busy.as_ptr(), 64, 48, dp, 41, 29, 8, dp.add(4) as *mut f32);
+        assert_eq!(in_dst, RESIZE_ERR_OVERLAP);
+    }
+    assert!(p_smooth < 5.0 && p_busy > 50.0, "smooth {p_smooth} busy {p_busy}");
+}
+
+#[test]
+fn residual_adds_back_to_the_original() {
+    // Flat left half, fine stripes on the right
+    let src = img(64, 32, |x, _| match x {
+        0..=31 => [90, 140, 200, 255],
+        _ if x % 2 == 0 => [0, 0, 0, 255],
+        _ => [255, 255, 255, 255],
+    });
+    let mut residual = vec![0u8; src.len()];
+    let mut small = vec![0u8; 16 * 8 * 4];
+    let mut recon = vec![0u8; src.len()];
+    unsafe {
+        assert_eq!(
+            resize_residual_rgba(src.as_ptr(), 64, 32, 4, residual.as_mut_ptr(), 1),
+            0
+        );
+        assert_eq!(
+            resize_residual_rgba(src.as_ptr(), 64, 32, 0, residual.as_mut_ptr(), 1),
+            RESIZE_ERR_INVALID_PARAM
+        );
+        assert_eq!(
+            resize_rgba_row_order(src.as_ptr(), 64, 32, small.as_mut_ptr(), 16, 8, 1, 0),
+            0
+        );
+        assert_eq!(
+            resize_rgba_row_order(small.as_ptr(), 16, 8, recon.as_mut_ptr(), 64, 32, 1, 0),
+            0
+        );
+    }
+    let at = |x: usize, y: usize| (y * 64 + x) * 4;
+    for y in 0..32 {
+        for x in 0..24 {
+            assert_eq!(&residual[at(x, y)..at(x, y) + 4], &[128; 4], "({x},{y})");
+        }
+    }
+    assert!((40..64).any(|x| (residual[at(x, 10)] as i32 - 128).abs() > 60));
+    // Exact wherever the difference fits the biased byte
+    for ((&o, &r), &c) in src.iter().zip(&residual).zip(&recon) {
+        if (-128..=127).contains(&(o as i32 - c as i32)) {
+            assert_eq!(c as i32 + r as i32 - 128, o as i32);
+        }
+    }
+}
+
+#[test]
+fn weighted_average_color_favors_the_center() {
+    // Red 16x16 center on a 48x48 gray frame: red covers 1/9 of the pixels
+    let src = img(48, 48, |x, y| {
+        if (16..32).contains(&x) && (16..32).contains(&y) {
+            [220, 30, 30, 255]
+        } else {
+            [128, 128, 128, 255]
+        }
+    });
+    let mut out = [0u8; 4];
+    unsafe {
+        assert_eq!(
+            weighted_average_color_rgba(src.as_ptr(), 48, 48, 0.15, out.as_mut_ptr()),
+            0
+        )
+    };
+    let flat: Vec<u32> = (0..4)
+        .map(|c| src.chunks_exact(4).map(|p| p[c] as u32).sum::<u32>() / (48 * 48))
+        .collect();
+    assert!(
+        out[0] as u32 > flat[0] + 40 && (out[1] as u32) + 20 < flat[1],
+        "{out:?} vs {flat:?}"
+    );
+    assert_eq!(out[3], 255);
+
+    // Transparent pixels contribute no color
+    let half = img(8, 8, |x, _| if x < 4 { [0, 255, 0, 0] } else { [0, 0, 255, 255] });
+    unsafe {
+        assert_eq!(
+            weighted_average_color_rgba(half.as_ptr(), 8, 8, 10.0, out.as_mut_ptr()),
+            0
+        )
+    };
+    assert_eq!(&out[..3], &[0, 0, 255]);
+    assert!((out[3] as i32 - 128).abs() <= 1);
+
+    unsafe {
+        let code = weighted_average_color_rgba(half.as_ptr(), 8, 8, 0.0, out.as_mut_ptr());
+        assert_eq!(code, RESIZE_ERR_INVALID_PARAM);
+        let code = weighted_average_color_rgba(half.as_ptr(), 8, 8, 1.0, half.as_ptr().add(8) as *mut u8);
+        assert_eq!(code, RESIZE_ERR_OVERLAP);
+    }
+}
+
+#[test]
+fn palette_sidecar() {
+    // 60% blue, 30% orange, 10% noise, 200x100 so the palette sample is downscaled
+    let src = img(200, 100, |x, y| {
+        if x < 120 {
+            [20, 40, 200, 255]
+        } else if x < 180 {
+            [240, 140, 20, 255]
+        } else {
+            [(x * 13 % 256) as u8, (y * 7 % 256) as u8, 90, 255]
+        }
+    });
+    let mut dst = vec![0u8; 50 * 25 * 4];
+    let mut direct = dst.clone();
+    let mut pal = vec![9u8; 6 * 4];
+    unsafe {
+        assert_eq!(
+            resize_rgba_with_palette(src.as_ptr(), 200, 100, dst.as_mut_ptr(), 50, 25, pal.as_mut_ptr(), 6, 2),
+            0
+        );
+        assert_eq!(
+            resize_rgba_row_order(src.as_ptr(), 200, 100, direct.as_mut_ptr(), 50, 25, 2, 0),
+            0
+        );
+    }
+    assert_eq!(dst, direct);
+    assert_eq!(&pal[..4], &[20, 40, 200, 255]);
+    assert_eq!(&pal[4..8], &[240, 140, 20, 255]);
+    // Fewer colors than requested: zero tail; transparent pixels ignored
+    let two = img(8, 8, |x, _| {
+        if x < 2 {
+            [255, 0, 0, 0]
+        } else if x < 6 {
+            [1, 2, 3, 255]
+        } else {
+            [9, 9, 9, 128]
+        }
+    });
+    let mut pal = vec![9u8; 4 * 4];
+    unsafe {
+        assert_eq!(
+            resize_rgba_with_palette(two.as_ptr(), 8, 8, dst.as_mut_ptr(), 4, 4, pal.as_mut_ptr(), 4, 1),
+            0
+        );
+    }
+    assert_eq!(pal, [1, 2, 3, 255, 9, 9, 9, 128, 0, 0, 0, 0, 0, 0, 0, 0]);
+    let clear = [0u8; 64];
+    unsafe {
+        assert_eq!(
+            resize_rgba_with_palette(clear.as_ptr(), 4, 4, dst.as_mut_ptr(), 4, 4, pal.as_mut_ptr(), 4, 1),
+            0
+        );
+        assert_eq!(
+            resize_rgba_with_palette(clear.as_ptr(), 4, 4, dst.as_mut_ptr(), 4, 4, pal.as_mut_ptr(), 0, 1),
+            RESIZE_ERR_INVALID_PARAM
+        );
+        assert_eq!(
+            resize_rgba_with_palette(clear.as_ptr(), 4, 4, dst.as_mut_ptr(), 4, 4, dst.as_mut_ptr(), 4, 1),
+            RESIZE_ERR_OVERLAP
+        );
+    }
+    assert!(pal.iter().all(|&v| v == 0));
+}
+
+#[test]
+fn focus_stack() {
+    // Fine checker texture; `a` has it sharp on the left half and box-blurred on the right, `b` the reverse
+    let (w, h) = (80u32, 40u32);
+    let sharp = |x: u32, y: u32| if (x / 2 + y / 2).is_multiple_of(2) { 230u8 } else { 30 };
+    let soft = |x: u32, y: u32| {
+        let mut s = 0u32;
+        let mut n = 0u32;
+        for dy in 0..5 {
+            for dx in 0..5 {
+                let (sx, sy) = (x + dx, y + dy);
+                if sx >= 2 && sy >= 2 && sx - 2 < w && sy - 2 < h {
+                    s += sharp(sx - 2, sy - 2) as u32;
+                    n += 1;
+                }
+            }
+        }
+        (s / n) as u8
+    };
+    let a = img(w, h, |x, y| {
+        let v = if x < w / 2 { sharp(x, y) } else { soft(x, y) };
+        [v, v, v, 255]
+    });
+    let b = img(w, h, |x, y| {
+        let v = if x < w / 2 { soft(x, y) } else { sharp(x, y) };
+        [v, v, v, 255]
+    });
+    let (dw, dh) = (60u32, 30u32);
+    let mut ra = vec![0u8; (dw * dh * 4) as usize];
+    let mut rb = ra.clone();
+    let mut out = ra.clone();
+    unsafe {
+        assert_eq!(
+            resize_rgba_row_order(a.as_ptr(), w, h, ra.as_mut_ptr(), dw, dh, 1, 0),
+            0
+        );
+        assert_eq!(
+            resize_rgba_row_order(b.as_ptr(), w, h, rb.as_mut_ptr(), dw, dh, 1, 0),
+            0
+        );
+        assert_eq!(
+            resize_rgba_focus_stack(a.as_ptr(), b.as_ptr(), w, h, out.as_mut_ptr(), dw, dh, 1),
+            0
+        );
+    }
+    for y in 0..dh {
+        for x in 0..dw {
+            let i = ((y * dw + x) * 4) as usize;
+            if x < dw / 2 - 3 {
+                assert_eq!(out[i..i + 4], ra[i..i + 4], "{x},{y}");
+            }
+            if x > dw / 2 + 3 {
+                assert_eq!(out[i..i + 4], rb[i..i + 4], "{x},{y}");
+            }
+        }
+    }
+    unsafe {
+        assert_eq!(
+            resize_rgba_focus_stack(a.as_ptr(), a.as_ptr(), w, h, out.as_mut_ptr(), dw, dh, 1),
+            0
+        );
+        assert_eq!(out, ra);
+        assert_eq!(
+            resize_rgba_focus_stack(a.as_ptr(), std::ptr::null(), w, h, out.as_mut_ptr(), dw, dh, 1),
+            RESIZE_ERR_NULL_PTR
+        );
+        assert_eq!(
+            resize_rgba_focus_stack(a.as_ptr(), out.as_ptr(), w, h, out.as_mut_ptr(), dw, dh, 1),
+            RESIZE_ERR_OVERLAP
+        );
+    }
+}
+
+#[test]
+fn hashed_output() {
+    let src = img(37, 23, |x, y| [(x * 7) as u8, (y * 11) as u8, ((x ^ y) * 5) as u8, 255]);
+    let run = |dw: u32, dh: u32, algo: u32| {
+        let mut dst = vec![0u8; (dw * dh * 4) as usize];
+        let mut hash = 0u64;
+        unsafe {
+            assert_eq!(
+                resize_rgba_hashed(src.as_ptr(), 37, 23, dst.as_mut_ptr(), dw, dh, algo, &mut hash),
+                0
+            );
+        }
+        (hash, dst)
+    };
+    let (h1, d1) = run(20, 12, 2);
+    let (h2, d2) = run(20, 12, 2);
+    assert_eq!(h1, h2);
+    assert_eq!(d1, d2);
+    // FNV-1a over (w, h as little-endian u32) then the pixels
+    let reference = 20u32
+        .to_le_bytes()
+        .iter()
+        .chain(&12u32.to_le_bytes())
+        .chain(&d1)
+        .fold(0xcbf29ce484222325u64, |h, &b| {
+            (h ^ b as u64).wrapping_mul(0x100000001b3)
+        });
+    assert_eq!(h1, reference);
+    assert_ne!(run(20, 12, 1).0, h1);
+    assert_ne!(run(20, 12, 0).0, h1);
+    assert_ne!(run(21, 12, 2).0, h1);
+    assert_ne!(run(20, 11, 2).0, h1);
+    // Same bytes, transposed shape
+    let flat = img(4, 4, |_, _| [9, 9, 9, 255]);
+    let mut a = vec![0u8; 32];
+    let (mut ha, mut hb) = (0u64, 0u64);
+    unsafe {
+        assert_eq!(
+            resize_rgba_hashed(flat.as_ptr(), 4, 4, a.as_mut_ptr(), 2, 4, 1, &mut ha),
+            0
+        );
+        assert_eq!(
+            resize_rgba_hashed(flat.as_ptr(), 4, 4, a.as_mut_ptr(), 4, 2, 1, &mut hb),
+            0
+        );
+    }
+    assert_ne!(ha, hb);
+    let mut buf = [0u64; 2];
+    unsafe {
+        assert_eq!(
+            resize_rgba_hashed(flat.as_ptr(), 4, 4, a.as_mut_ptr(), 2, 2, 1, std::ptr::null_mut()),
+            RESIZE_ERR_NULL_PTR
+        );
+        assert_eq!(
+            resize_rgba_hashed(
+                flat.as_ptr(),
+                4,
+                4,
+                a.as_mut_ptr(),
+                2,
+                2,
+                1,
+                (buf.as_mut_ptr() as *mut u8).add(4) as *mut u64
+            ),
+            RESIZE_ERR_ALIGNMENT
+        );
+        assert_eq!(
+            resize_rgba_hashed(flat.as_ptr(), 4, 4, a.as_mut_ptr(), 2, 2, 1, a.as_mut_ptr() as *mut u64),
+            RESIZE_ERR_OVERLAP
+        );
+    }
+}
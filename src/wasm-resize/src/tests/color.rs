@@ -0,0 +1,307 @@
+//! Tests for the color adjustments in color.rs
+
+use super::*;
+use crate::*;
+
+/// Per-channel linear-light means over all pixels
+fn linear_means(pixels: &[u8]) -> [f64; 3] {
+    let mut sum = [0.0; 3];
+    for p in pixels.chunks_exact(4) {
+        for c in 0..3 {
+            sum[c] += srgb_to_linear(p[c]);
+        }
+    }
+    sum.map(|s| s / (pixels.len() / 4) as f64)
+}
+
+#[test]
+fn white_balance_equalizes_channel_means() {
+    let mut im = img(16, 16, |x, y| {
+        [(60 + x * 3) as u8, (70 + y * 2) as u8, 200, (255 - x) as u8]
+    });
+    let alpha: Vec<u8> = im.chunks_exact(4).map(|p| p[3]).collect();
+    unsafe { assert_eq!(auto_white_balance_rgba(im.as_mut_ptr(), 16, 16), 0) };
+    let m = linear_means(&im);
+    assert!(
+        (m[0] - m[2]).abs() / m[2] < 0.03 && (m[1] - m[2]).abs() / m[2] < 0.03,
+        "{m:?}"
+    );
+    assert!(im.chunks_exact(4).map(|p| p[3]).eq(alpha));
+}
+
+#[test]
+fn white_balance_leaves_gray_untouched() {
+    let original = img(8, 8, |x, y| {
+        let v = (x * 20 + y * 8) as u8;
+        [v, v, v, 255]
+    });
+    let mut im = original.clone();
+    unsafe { assert_eq!(auto_white_balance_rgba(im.as_mut_ptr(), 8, 8), 0) };
+    assert!(max_diff(&im, &original) <= 1);
+}
+
+#[test]
+fn color_matrix_identity_and_sepia() {
+    let src = img(37, 23, |x, y| {
+        [(x * 7) as u8, (y * 11) as u8, ((x * y) % 256) as u8, (x * 3 + y) as u8]
+    });
+    let identity = [1.0f32, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0];
+    for algo in [
+        RESIZE_ALGO_NEAREST,
+        RESIZE_ALGO_BILINEAR,
+        RESIZE_ALGO_LANCZOS,
+        RESIZE_ALGO_AUTO,
+    ] {
+        let mut matrixed = vec![0u8; 50 * 40 * 4];
+        let mut plain = matrixed.clone();
+        unsafe {
+            let code = resize_rgba_colormatrix(
+                src.as_ptr(),
+                37,
+                23,
+                matrixed.as_mut_ptr(),
+                50,
+                40,
+                identity.as_ptr(),
+                algo,
+            );
+            assert_eq!(code, 0);
+            assert_eq!(
+                resize_with_algorithm(src.as_ptr(), 37, 23, plain.as_mut_ptr(), 50, 40, algo),
+                0
+            );
+        }
+        assert_eq!(matrixed, plain, "algo {algo}");
+    }
+
+    let sepia = [
+        0.393f32, 0.769, 0.189, 0.0, 0.349, 0.686, 0.168, 0.0, 0.272, 0.534, 0.131, 0.0,
+    ];
+    let gray = img(8, 8, |_, _| [100, 100, 100, 200]);
+    let mut dst = vec![0u8; 4 * 4 * 4];
+    unsafe {
+        assert_eq!(
+            resize_rgba_colormatrix(gray.as_ptr(), 8, 8, dst.as_mut_ptr(), 4, 4, sepia.as_ptr(), 1),
+            0
+        )
+    };
+    let l = srgb_to_linear(100);
+    let expected: Vec<u8> = sepia
+        .chunks_exact(4)
+        .map(|m| linear_to_srgb(l * (m[0] + m[1] + m[2]) as f64))
+        .collect();
+    for p in dst.chunks_exact(4) {
+        assert!(max_diff(&p[..3], &expected) <= 1, "{p:?} vs {expected:?}");
+        assert_eq!(p[3], 200);
+    }
+
+    let bad = [f32::NAN; 12];
+    unsafe {
+        let code = resize_rgba_colormatrix(gray.as_ptr(), 8, 8, dst.as_mut_ptr(), 4, 4, bad.as_ptr(), 1);
+        assert_eq!(code, RESIZE_ERR_INVALID_PARAM);
+        let code = resize_rgba_colormatrix(gray.as_ptr(), 8, 8, dst.as_mut_ptr(), 4, 4, std::ptr::null(), 1);
+        assert_eq!(code, RESIZE_ERR_NULL_PTR);
+    }
+}
+
+#[test]
+fn dematte_recovers_foreground_over_key_color() {
+    // Opaque red, a 50% edge column keyed over green, then fully transparent matte
+    let src = img(12, 6, |x, _| match x {
+        0..=5 => [255, 0, 0, 255],
+        6 => [128, 128, 0, 128],
+        _ => [0, 255, 0, 0],
+    });
+    let mut dst = vec![0u8; 12 * 6 * 4];
+    unsafe {
+        assert_eq!(
+            resize_rgba_dematte(src.as_ptr(), 12, 6, dst.as_mut_ptr(), 12, 6, 0, 255, 0, 1),
+            0
+        )
+    };
+    let edge = &dst[6 * 4..7 * 4];
+    assert!(
+        edge[0] >= 250 && edge[1] <= 2 && edge[2] == 0 && (edge[3] as i32 - 128).abs() <= 1,
+        "{edge:?}"
+    );
+
+    // Downscaled, every visible pixel stays red; a plain resize lets the matte bleed in
+    let mut dst = vec![0u8; 5 * 3 * 4];
+    let mut plain = dst.clone();
+    unsafe {
+        assert_eq!(
+            resize_rgba_dematte(src.as_ptr(), 12, 6, dst.as_mut_ptr(), 5, 3, 0, 255, 0, 2),
+            0
+        );
+        assert_eq!(
+            resize_rgba_row_order(src.as_ptr(), 12, 6, plain.as_mut_ptr(), 5, 3, 2, 0),
+            0
+        );
+    }
+    for p in dst.chunks_exact(4).filter(|p| p[3] > 0) {
+        assert!(p[1] <= 3 && p[0] >= 250, "{p:?}");
+    }
+    assert!(plain.chunks_exact(4).any(|p| p[3] > 0 && p[1] > 40), "{plain:?}");
+}
+
+#[test]
+fn curves_apply_per_channel_luts_after_resize() {
+    let src = img(37, 23, |x, y| {
+        [(x * 7) as u8, (y * 11) as u8, ((x * y) % 256) as u8, (x * 3 + y) as u8]
+    });
+    let invert: Vec<u8> = (0..=255u8).map(|v| 255 - v).collect();
+    let null = std::ptr::null();
+    for algo in [RESIZE_ALGO_NEAREST, RESIZE_ALGO_BILINEAR, RESIZE_ALGO_LANCZOS] {
+        let mut dst = vec![0u8; 50 * 40 * 4];
+        let mut plain = dst.clone();
+        unsafe {
+            let code = resize_rgba_curves(
+                src.as_ptr(),
+                37,
+                23,
+                dst.as_mut_ptr(),
+                50,
+                40,
+                null,
+                invert.as_ptr(),
+                null,
+                algo,
+            );
+            assert_eq!(code, 0);
+            assert_eq!(
+                resize_rgba_row_order(src.as_ptr(), 37, 23, plain.as_mut_ptr(), 50, 40, algo, 0),
+                0
+            );
+        }
+        // Only green has a curve; null LUTs leave their channel unchanged
+        for (p, q) in dst.chunks_exact(4).zip(plain.chunks_exact(4)) {
+            assert_eq!([p[0], 255 - p[1], p[2], p[3]], [q[0], q[1], q[2], q[3]]);
+        }
+    }
+}
+
+#[test]
+fn alpha_bleed_spreads_one_ring_per_iteration() {
+    let mut pixels = img(11, 11, |x, y| {
+        if (x, y) == (5, 5) {
+            [200, 100, 50, 255]
+        } else {
+            [0, 0, 0, 0]
+        }
+    });
+    unsafe { assert_eq!(alpha_bleed_rgba(pixels.as_mut_ptr(), 11, 11, 3), 0) };
+    for y in 0..11i32 {
+        for x in 0..11i32 {
+            let p = &pixels[((y * 11 + x) * 4) as usize..][..4];
+            let expected = match (x - 5).abs().max((y - 5).abs()) {
+                0 => [200, 100, 50, 255],
+                1..=3 => [200, 100, 50, 0],
+                _ => [0, 0, 0, 0],
+            };
+            assert_eq!(p, expected, "({x},{y})");
+        }
+    }
+
+    // Enough iterations fill everything, then stop early
+    unsafe { assert_eq!(alpha_bleed_rgba(pixels.as_mut_ptr(), 11, 11, 100), 0) };
+    assert!(pixels.chunks_exact(4).all(|p| p[..3] == [200, 100, 50]));
+}
+
+/// f64 reference for the OKLab midpoint of two opaque sRGB colors, back in sRGB
+fn oklab_midpoint(a: [u8; 3], b: [u8; 3]) -> [i32; 3] {
+    let to_lab = |p: [u8; 3]| {
+        let [r, g, b] = p.map(srgb_to_linear);
+        let l = (0.4122214708 * r + 0.5363325363 * g + 0.0514459929 * b).cbrt();
+        let m = (0.2119034982 * r + 0.6806995451 * g + 0.1073969566 * b).cbrt();
+        let s = (0.0883024619 * r + 0.2817188376 * g + 0.6299787005 * b).cbrt();
+        [
+            0.2104542553 * l + 0.7936177850 * m - 0.0040720468 * s,
+            1.9779984951 * l - 2.4285922050 * m + 0.4505937099 * s,
+            0.0259040371 * l + 0.7827717662 * m - 0.8086757660 * s,
+        ]
+    };
+    let (a, b) = (to_lab(a), to_lab(b));
+    let [l, ca, cb] = [0, 1, 2].map(|i| (a[i] + b[i]) / 2.0);
+    let l3 = (l + 0.3963377774 * ca + 0.2158037573 * cb).powi(3);
+    let m3 = (l - 0.1055613458 * ca - 0.0638541728 * cb).powi(3);
+    let s3 = (l - 0.0894841775 * ca - 1.2914855480 * cb).powi(3);
+    [
+        4.0767416621 * l3 - 3.3077115913 * m3 + 0.2309699292 * s3,
+        -1.2684380046 * l3 + 2.6097574011 * m3 - 0.3413193965 * s3,
+        -0.0041960863 * l3 - 0.7034186147 * m3 + 1.7076147010 * s3,
+    ]
+    .map(|c| linear_to_srgb(c) as i32)
+}
+
+#[test]
+fn oklab_blends_blue_and_yellow_without_gray() {
+    let src = img(2, 1, |x, _| if x == 0 { [0, 0, 255, 255] } else { [255, 255, 0, 255] });
+    let mut oklab = vec![0u8; 12];
+    let mut rgb = oklab.clone();
+    unsafe {
+        assert_eq!(resize_rgba_oklab(src.as_ptr(), 2, 1, oklab.as_mut_ptr(), 3, 1, 1), 0);
+        assert_eq!(
+            resize_rgba_row_order(src.as_ptr(), 2, 1, rgb.as_mut_ptr(), 3, 1, 1, 0),
+            0
+        );
+    }
+    let mid = &oklab[4..8];
+    let reference = oklab_midpoint([0, 0, 255], [255, 255, 0]);
+    for c in 0..3 {
+        assert!((mid[c] as i32 - reference[c]).abs() <= 2, "{mid:?} vs {reference:?}");
+    }
+    assert_eq!(mid[3], 255);
+
+    // Not the muddy gray of sRGB interpolation: the channels stay apart and brighter
+    let spread = |p: &[u8]| *p[..3].iter().max().unwrap() as i32 - *p[..3].iter().min().unwrap() as i32;
+    let sum = |p: &[u8]| p[..3].iter().map(|&v| v as i32).sum::<i32>();
+    assert!(spread(&rgb[4..8]) <= 2);
+    assert!(
+        spread(mid) >= 30 && sum(mid) > sum(&rgb[4..8]),
+        "{mid:?} vs {:?}",
+        &rgb[4..8]
+    );
+    assert_eq!(&oklab[..4], &[0, 0, 255, 255]);
+    assert_eq!(&oklab[8..], &[255, 255, 0, 255]);
+
+    // Transparent pixels don't tint their neighbors
+    let faded = img(2, 1, |x, _| if x == 0 { [255, 0, 0, 0] } else { [0, 200, 0, 255] });
+    unsafe { assert_eq!(resize_rgba_oklab(faded.as_ptr(), 2, 1, oklab.as_mut_ptr(), 3, 1, 1), 0) };
+    assert!(
+        oklab[4] <= 1 && (oklab[5] as i32 - 200).abs() <= 1 && oklab[6] <= 1,
+        "{oklab:?}"
+    );
+}
+
+#[test]
+fn alpha_snap() {
+    let src = img(8, 4, |x, _| {
+        [
+            200,
+            100,
+            50,
+            match x {
+                0..=1 => 253,
+                2..=3 => 128,
+                4..=5 => 3,
+                _ => 255,
+            },
+        ]
+    });
+    let mut dst = vec![0u8; src.len()];
+    unsafe {
+        assert_eq!(
+            resize_rgba_alpha_snap(src.as_ptr(), 8, 4, dst.as_mut_ptr(), 8, 4, 250, 5, 0),
+            0
+        );
+    }
+    let alphas: Vec<u8> = dst.chunks_exact(4).take(8).map(|p| p[3]).collect();
+    assert_eq!(alphas, [255, 255, 128, 128, 0, 0, 255, 255]);
+    assert!(dst.chunks_exact(4).all(|p| p[..3] == [200, 100, 50]));
+    unsafe {
+        assert_eq!(
+            resize_rgba_alpha_snap(src.as_ptr(), 8, 4, dst.as_mut_ptr(), 8, 4, 10, 20, 0),
+            RESIZE_ERR_INVALID_PARAM
+        );
+    }
+}
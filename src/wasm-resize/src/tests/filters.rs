@@ -0,0 +1,283 @@
+//! Tests for the spatial post-filters in filters.rs
+
+use super::*;
+use crate::*;
+
+#[test]
+fn sharpen_soft_clip_keeps_highlight_gradient() {
+    // Soft ramps from grey into two near-white bands (little fine detail, so close to the
+    // full amount): the mask pushes the top of both ramps past white
+    let src = img(32, 16, |x, y| {
+        let top = if y < 8 { 254 } else { 250 };
+        let v = (150 + (x as i32 - 8) * 13).clamp(150, top) as u8;
+        [v, v, v, 255]
+    });
+    let mut hard = vec![0u8; 32 * 16 * 4];
+    let mut soft = hard.clone();
+    unsafe {
+        let (s, algo) = (src.as_ptr(), RESIZE_ALGO_NEAREST);
+        assert_eq!(
+            resize_rgba_adaptive_sharpen(s, 32, 16, hard.as_mut_ptr(), 32, 16, 4.0, 0, algo),
+            0
+        );
+        assert_eq!(
+            resize_rgba_adaptive_sharpen(s, 32, 16, soft.as_mut_ptr(), 32, 16, 4.0, 1, algo),
+            0
+        );
+        let code = resize_rgba_adaptive_sharpen(s, 32, 16, soft.as_mut_ptr(), 32, 16, 4.0, 2, algo);
+        assert_eq!(code, RESIZE_ERR_INVALID_PARAM);
+    }
+    let peak = |v: &[u8], y: usize| {
+        v[y * 32 * 4..(y + 1) * 32 * 4]
+            .chunks_exact(4)
+            .map(|p| p[0])
+            .max()
+            .unwrap()
+    };
+    // Clamping flattens both overshoots to white; the roll-off keeps them apart
+    assert_eq!((peak(&hard, 3), peak(&hard, 12)), (255, 255));
+    assert!(peak(&soft, 3) > peak(&soft, 12));
+    assert!(soft.chunks_exact(4).all(|p| p[3] == 255));
+}
+
+#[test]
+fn compress_prep_flattens_noise_and_keeps_hard_edges() {
+    // Left half: mid gray with +-4 checker noise; right half: hard 0/255 step at x = 24
+    let src = img(32, 16, |x, y| {
+        let v = match x {
+            0..=15 if (x + y) % 2 == 0 => 124,
+            0..=15 => 132,
+            16..=23 => 0,
+            _ => 255,
+        };
+        [v, v, v, 255]
+    });
+    let mut dst = vec![0u8; src.len()];
+    let mut plain = vec![0u8; src.len()];
+    unsafe {
+        assert_eq!(
+            resize_rgba_compress_prep(src.as_ptr(), 32, 16, dst.as_mut_ptr(), 32, 16, 1.0, 0),
+            0
+        );
+        assert_eq!(
+            resize_rgba_compress_prep(src.as_ptr(), 32, 16, plain.as_mut_ptr(), 32, 16, 0.0, 0),
+            0
+        );
+        for bad in [f32::NAN, 1.5] {
+            let code = resize_rgba_compress_prep(src.as_ptr(), 32, 16, plain.as_mut_ptr(), 32, 16, bad, 0);
+            assert_eq!(code, RESIZE_ERR_INVALID_PARAM);
+        }
+    }
+    // Strength 0 at the same size is a plain copy
+    assert_eq!(plain, src);
+
+    let px = |b: &[u8], x: usize, y: usize| b[(y * 32 + x) * 4] as i32;
+    let noise = |b: &[u8]| {
+        (1..15)
+            .flat_map(|y| (1..14).map(move |x| (px(b, x, y) - px(b, x + 1, y)).abs()))
+            .max()
+            .unwrap()
+    };
+    assert_eq!(noise(&src), 8);
+    assert!(noise(&dst) <= 2, "noise {}", noise(&dst));
+    for y in 0..16 {
+        assert_eq!(px(&dst, 24, y) - px(&dst, 23, y), 255);
+    }
+}
+
+#[test]
+fn adaptive_sharpen() {
+    // Left half: grain around mid-grey; right half: a soft vertical edge (8 px ramp)
+    let mut seed = 12345u32;
+    let noise: Vec<i32> = (0..64 * 32)
+        .map(|_| {
+            seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+            ((seed >> 16) % 61) as i32 - 30
+        })
+        .collect();
+    let src = img(64, 32, |x, y| {
+        let v = if x < 32 {
+            128 + noise[(y * 64 + x) as usize]
+        } else {
+            (32 + (x as i32 - 40) * 24).clamp(32, 224)
+        };
+        [v as u8, v as u8, v as u8, 255]
+    });
+    let (dw, dh) = (48u32, 24u32);
+    let mut plain = vec![0u8; (dw * dh * 4) as usize];
+    let mut sharp = plain.clone();
+    unsafe {
+        assert_eq!(
+            resize_rgba_row_order(src.as_ptr(), 64, 32, plain.as_mut_ptr(), dw, dh, 1, 0),
+            0
+        );
+        assert_eq!(
+            resize_rgba_adaptive_sharpen(src.as_ptr(), 64, 32, sharp.as_mut_ptr(), dw, dh, 0.0, 0, 1),
+            0
+        );
+    }
+    assert_eq!(plain, sharp);
+    unsafe {
+        assert_eq!(
+            resize_rgba_adaptive_sharpen(src.as_ptr(), 64, 32, sharp.as_mut_ptr(), dw, dh, 1.5, 0, 1),
+            0
+        );
+    }
+    // Effective gain over the interior of each half (away from the seam): change relative
+    // to the high-pass (pixel minus 3x3 binomial blur) of the plain resize
+    let gain = |xs: std::ops::Range<u32>| {
+        let (mut changed, mut detail) = (0.0f32, 0.0f32);
+        for y in 2..dh - 2 {
+            for x in xs.clone() {
+                let i = ((y * dw + x) * 4) as usize;
+                let mut blur = 0.0;
+                for dy in 0..3 {
+                    for dx in 0..3 {
+                        let w = [1.0, 2.0, 1.0][dy] * [1.0, 2.0, 1.0][dx];
+                        blur += w * plain[(((y + dy as u32 - 1) * dw + x + dx as u32 - 1) * 4) as usize] as f32;
+                    }
+                }
+                detail += (plain[i] as f32 - blur / 16.0).abs();
+                changed += (sharp[i] as i32 - plain[i] as i32).abs() as f32;
+                assert_eq!(sharp[i + 3], 255);
+            }
+        }
+        changed / detail
+    };
+    let (grain, edge) = (gain(2..20), gain(26..46));
+    assert!(edge > 2.0 * grain, "edge {edge} grain {grain}");
+    assert!(edge > 0.75, "edge {edge}");
+    let mut dst = vec![0u8; 4];
+    unsafe {
+        assert_eq!(
+            resize_rgba_adaptive_sharpen(src.as_ptr(), 64, 32, dst.as_mut_ptr(), 1, 1, -0.5, 0, 1),
+            RESIZE_ERR_INVALID_PARAM
+        );
+        assert_eq!(
+            resize_rgba_adaptive_sharpen(src.as_ptr(), 64, 32, dst.as_mut_ptr(), 1, 1, f32::NAN, 0, 1),
+            RESIZE_ERR_INVALID_PARAM
+        );
+    }
+}
+
+#[test]
+fn scrim() {
+    let src = img(60, 40, |x, y| {
+        [
+            (x * 4) as u8,
+            (y * 6) as u8,
+            if (x / 6 + y / 6) % 2 == 0 { 230 } else { 20 },
+            255,
+        ]
+    });
+    let (dw, dh) = (30u32, 20u32);
+    let mut plain = vec![0u8; (dw * dh * 4) as usize];
+    let mut dark = plain.clone();
+    let mut blurred = plain.clone();
+    unsafe {
+        assert_eq!(
+            resize_rgba_row_order(src.as_ptr(), 60, 40, plain.as_mut_ptr(), dw, dh, 1, 0),
+            0
+        );
+        assert_eq!(
+            resize_rgba_scrim(src.as_ptr(), 60, 40, dark.as_mut_ptr(), dw, dh, 0.0, 0.0, 1),
+            0
+        );
+        assert_eq!(dark, plain);
+        assert_eq!(
+            resize_rgba_scrim(src.as_ptr(), 60, 40, dark.as_mut_ptr(), dw, dh, 0.6, 0.0, 1),
+            0
+        );
+        assert_eq!(
+            resize_rgba_scrim(src.as_ptr(), 60, 40, blurred.as_mut_ptr(), dw, dh, 0.6, 2.0, 1),
+            0
+        );
+    }
+    // Every channel is the plain resize scaled by 0.4 in linear light
+    for (i, (&d, &p)) in dark.iter().zip(&plain).enumerate() {
+        if i % 4 == 3 {
+            assert_eq!(d, 255);
+            continue;
+        }
+        let expect = linear_to_srgb(srgb_to_linear(p) * 0.4);
+        assert!(d.abs_diff(expect) <= 1, "i {i}: {d} vs {expect}");
+        assert!(d <= p);
+    }
+    // Blur lowers the local (neighbor) gradient of the checker channel
+    let grad = |v: &[u8]| -> u32 {
+        let mut g = 0;
+        for y in 0..dh {
+            for x in 0..dw - 1 {
+                let i = ((y * dw + x) * 4 + 2) as usize;
+                g += (v[i] as i32 - v[i + 4] as i32).unsigned_abs();
+            }
+        }
+        g
+    };
+    assert!(
+        grad(&blurred) * 3 < grad(&dark),
+        "{} vs {}",
+        grad(&blurred),
+        grad(&dark)
+    );
+    let mut d1 = [0u8; 4];
+    unsafe {
+        for (darken, sigma) in [(-0.1f32, 0.0f32), (1.1, 0.0), (0.5, -1.0), (0.5, f32::NAN), (0.5, 65.0)] {
+            assert_eq!(
+                resize_rgba_scrim(src.as_ptr(), 60, 40, d1.as_mut_ptr(), 1, 1, darken, sigma, 1),
+                RESIZE_ERR_INVALID_PARAM
+            );
+        }
+    }
+}
+
+#[test]
+fn box_blur_integral() {
+    let (w, h) = (23u32, 17u32);
+    let src = img(w, h, |x, y| {
+        [
+            (x * 11) as u8,
+            (y * 15) as u8,
+            ((x * y) % 256) as u8,
+            (255 - x * 3) as u8,
+        ]
+    });
+    let naive = |r: u32| {
+        let mut out = src.clone();
+        for y in 0..h {
+            for x in 0..w {
+                let (mut sum, mut n) = ([0u64; 4], 0u64);
+                for sy in y.saturating_sub(r)..(y + r + 1).min(h) {
+                    for sx in x.saturating_sub(r)..(x + r + 1).min(w) {
+                        let i = ((sy * w + sx) * 4) as usize;
+                        for c in 0..4 {
+                            sum[c] += src[i + c] as u64;
+                        }
+                        n += 1;
+                    }
+                }
+                for c in 0..4 {
+                    out[((y * w + x) * 4) as usize + c] = ((sum[c] + n / 2) / n) as u8;
+                }
+            }
+        }
+        out
+    };
+    for r in [0u32, 1, 2, 5, 11, 22, 23, 30] {
+        let mut v = src.clone();
+        unsafe { assert_eq!(box_blur_rgba_integral(v.as_mut_ptr(), w, h, r), 0) };
+        assert_eq!(v, naive(r), "radius {r}");
+    }
+    // Huge radii must not overflow the window bounds: they blur to the global mean
+    for r in [u32::MAX, u32::MAX - 1, 1 << 31] {
+        let mut v = src.clone();
+        unsafe { assert_eq!(box_blur_rgba_integral(v.as_mut_ptr(), w, h, r), 0) };
+        assert_eq!(v, naive(w.max(h)), "radius {r}");
+    }
+    unsafe {
+        assert_eq!(
+            box_blur_rgba_integral(std::ptr::null_mut(), 4, 4, 1),
+            RESIZE_ERR_NULL_PTR
+        )
+    };
+}
@@ -0,0 +1,236 @@
+//! Tests for the packed and reduced-depth outputs in formats.rs
+
+use super::*;
+use crate::*;
+
+/// Expand an RGB565 value back to 8-bit channels by bit replication
+fn unpack_565(v: u16) -> [i32; 3] {
+    let (r, g, b) = ((v >> 11) as i32, ((v >> 5) & 63) as i32, (v & 31) as i32);
+    [(r << 3) | (r >> 2), (g << 2) | (g >> 4), (b << 3) | (b >> 2)]
+}
+
+#[test]
+fn rgb565_packs_primaries_and_round_trips_ramp() {
+    let src = img(4, 1, |x, _| {
+        [[255, 0, 0, 255], [0, 255, 0, 255], [0, 0, 255, 255], [255, 255, 255, 0]][x as usize]
+    });
+    let mut dst = vec![0u16; 4];
+    unsafe {
+        assert_eq!(
+            resize_rgba_to_rgb565(src.as_ptr(), 4, 1, dst.as_mut_ptr() as *mut u8, 4, 1, 0, 0),
+            0
+        )
+    };
+    let packed: Vec<u16> = dst.iter().map(|v| u16::from_le(*v)).collect();
+    assert_eq!(packed, [0xF800, 0x07E0, 0x001F, 0xFFFF]);
+
+    // A gray ramp through 5/6-bit quantization comes back within half a step
+    let ramp = img(256, 1, |x, _| [x as u8, x as u8, x as u8, 255]);
+    let mut dst = vec![0u16; 256];
+    unsafe {
+        assert_eq!(
+            resize_rgba_to_rgb565(ramp.as_ptr(), 256, 1, dst.as_mut_ptr() as *mut u8, 256, 1, 0, 0),
+            0
+        )
+    };
+    for (x, v) in dst.iter().enumerate() {
+        let [r, g, b] = unpack_565(u16::from_le(*v));
+        let x = x as i32;
+        assert!(
+            (r - x).abs() <= 5 && (g - x).abs() <= 3 && (b - x).abs() <= 5,
+            "{x}: {r} {g} {b}"
+        );
+    }
+}
+
+#[test]
+fn rgb565_dither_averages_to_input() {
+    let flat = img(16, 16, |_, _| [100, 100, 100, 255]);
+    let mut dst = vec![0u16; 256];
+    unsafe {
+        assert_eq!(
+            resize_rgba_to_rgb565(flat.as_ptr(), 16, 16, dst.as_mut_ptr() as *mut u8, 16, 16, 1, 1),
+            0
+        )
+    };
+    let reds: Vec<u16> = dst.iter().map(|v| u16::from_le(*v) >> 11).collect();
+    let mean = reds.iter().map(|&r| r as f32 * 255.0 / 31.0).sum::<f32>() / 256.0;
+    assert!((mean - 100.0).abs() < 1.0, "{mean}");
+    let levels: std::collections::BTreeSet<u16> = reds.into_iter().collect();
+    assert_eq!(levels.len(), 2);
+
+    unsafe {
+        let code = resize_rgba_to_rgb565(flat.as_ptr(), 16, 16, dst.as_mut_ptr() as *mut u8, 16, 16, 1, 2);
+        assert_eq!(code, RESIZE_ERR_INVALID_PARAM);
+    }
+}
+
+#[test]
+fn indexed_keeps_exact_colors_of_a_two_color_image() {
+    // 3/4 teal, 1/4 orange; a nearest resize keeps exactly the two colors
+    let (teal, orange) = ([0u8, 128, 128, 255], [255u8, 140, 0, 255]);
+    let src = img(16, 16, |x, _| if x < 12 { teal } else { orange });
+    let mut indices = vec![9u8; 8 * 8];
+    let mut palette = vec![7u8; 8];
+    unsafe {
+        let code = resize_rgba_to_indexed(
+            src.as_ptr(),
+            16,
+            16,
+            indices.as_mut_ptr(),
+            palette.as_mut_ptr(),
+            8,
+            8,
+            2,
+            0,
+        );
+        assert_eq!(code, 0);
+    }
+    let entries: Vec<&[u8]> = palette.chunks_exact(4).collect();
+    assert!(
+        entries.contains(&&teal[..]) && entries.contains(&&orange[..]),
+        "{palette:?}"
+    );
+    for (i, &index) in indices.iter().enumerate() {
+        let expected = if i % 8 < 6 { teal } else { orange };
+        assert_eq!(entries[index as usize], expected);
+    }
+}
+
+#[test]
+fn indexed_maps_each_pixel_to_its_nearest_entry() {
+    let grad = img(64, 4, |x, _| [(x * 4) as u8, 50, 255 - (x * 4) as u8, 255]);
+    let mut indices = vec![0u8; 32 * 2];
+    let mut palette = vec![0u8; 16 * 4];
+    let mut rgba = vec![0u8; 32 * 2 * 4];
+    let (ip, pp) = (indices.as_mut_ptr(), palette.as_mut_ptr());
+    unsafe {
+        assert_eq!(resize_rgba_to_indexed(grad.as_ptr(), 64, 4, ip, pp, 32, 2, 16, 1), 0);
+        assert_eq!(
+            resize_rgba_row_order(grad.as_ptr(), 64, 4, rgba.as_mut_ptr(), 32, 2, 1, 0),
+            0
+        );
+        assert_eq!(
+            resize_rgba_to_indexed(grad.as_ptr(), 64, 4, ip, pp, 32, 2, 1, 1),
+            RESIZE_ERR_INVALID_PARAM
+        );
+    }
+    let dist = |a: &[u8], b: &[u8]| {
+        a.iter()
+            .zip(b)
+            .map(|(&x, &y)| (x as i32 - y as i32).pow(2))
+            .sum::<i32>()
+    };
+    for (p, &i) in rgba.chunks_exact(4).zip(&indices) {
+        let best = palette.chunks_exact(4).map(|c| dist(c, p)).min().unwrap();
+        assert_eq!(dist(&palette[i as usize * 4..][..4], p), best);
+        assert!(best < 100, "{p:?}: {best}");
+    }
+}
+
+#[test]
+fn mask_sdf_circle() {
+    // Circle of radius 20 centered in 64x64, downscaled 4x
+    let src = img(64, 64, |x, y| {
+        let (dx, dy) = (x as f32 + 0.5 - 32.0, y as f32 + 0.5 - 32.0);
+        [255, 255, 255, if dx * dx + dy * dy <= 400.0 { 255 } else { 0 }]
+    });
+    let mut dst = vec![0u8; 16 * 16];
+    unsafe {
+        assert_eq!(
+            resize_mask_to_sdf(src.as_ptr(), 64, 64, dst.as_mut_ptr(), 16, 16, 8.0),
+            0
+        );
+    }
+    let at = |x: usize, y: usize| dst[y * 16 + x] as i32;
+    assert!(at(8, 8) > 128 && at(8, 8) == 255);
+    assert!(at(0, 0) < 128 && at(0, 0) == 0);
+    // Destination column 13 spans source x 52..56, centered 22 px from the circle center
+    // along the row through it: about 2 px outside
+    let e = at(13, 7);
+    assert!((e - 128).abs() <= 40 && e < 128, "edge {e}");
+    // Monotone from inside to outside along a row
+    assert!((8..15).all(|x| at(x, 8) >= at(x + 1, 8)));
+    unsafe {
+        assert_eq!(
+            resize_mask_to_sdf(src.as_ptr(), 64, 64, dst.as_mut_ptr(), 16, 16, 0.0),
+            RESIZE_ERR_INVALID_PARAM
+        );
+        assert_eq!(
+            resize_mask_to_sdf(src.as_ptr(), 64, 64, src.as_ptr() as *mut u8, 16, 16, 4.0),
+            RESIZE_ERR_OVERLAP
+        );
+    }
+}
+
+fn f16_to_f64(h: u16) -> f64 {
+    let s = if h & 0x8000 != 0 { -1.0 } else { 1.0 };
+    let e = ((h >> 10) & 0x1f) as i32;
+    let m = (h & 0x3ff) as f64;
+    if e == 0 {
+        s * m * 2f64.powi(-24)
+    } else {
+        s * (1.0 + m / 1024.0) * 2f64.powi(e - 15)
+    }
+}
+
+#[test]
+fn to_f16() {
+    for algo in 0..4u32 {
+        let white = img(9, 7, |_, _| [255, 255, 255, 255]);
+        let mut out = vec![0xffffu16; 5 * 4 * 4];
+        unsafe {
+            assert_eq!(
+                resize_rgba_to_f16(white.as_ptr(), 9, 7, out.as_mut_ptr(), 5, 4, algo),
+                0
+            );
+        }
+        assert!(out.iter().all(|&h| h == 0x3c00), "algo {algo}");
+        let clear = img(9, 7, |_, _| [0, 0, 0, 0]);
+        unsafe {
+            assert_eq!(
+                resize_rgba_to_f16(clear.as_ptr(), 9, 7, out.as_mut_ptr(), 5, 4, algo),
+                0
+            );
+        }
+        assert!(out.iter().all(|&h| h == 0), "algo {algo}");
+    }
+    // Same-size: every sRGB value round-trips to its linear value within half precision
+    let src = img(256, 2, |x, y| {
+        [
+            x as u8,
+            (255 - x) as u8,
+            (x / 2) as u8,
+            if y == 0 { 255 } else { x as u8 },
+        ]
+    });
+    let mut out = vec![0u16; 256 * 2 * 4];
+    unsafe {
+        assert_eq!(resize_rgba_to_f16(src.as_ptr(), 256, 2, out.as_mut_ptr(), 256, 2, 2), 0);
+    }
+    for x in 0..256usize {
+        let v = x as u8;
+        let expect = [srgb_to_linear(v), srgb_to_linear(255 - v), srgb_to_linear(v / 2), 1.0];
+        for c in 0..4 {
+            let got = f16_to_f64(out[x * 4 + c]);
+            assert!(
+                (got - expect[c]).abs() <= expect[c] * 1e-3 + 1e-5,
+                "x {x} c {c}: {got} vs {}",
+                expect[c]
+            );
+        }
+        let a = f16_to_f64(out[(256 + x) * 4 + 3]);
+        assert!((a - x as f64 / 255.0).abs() <= 1e-3, "alpha {x}: {a}");
+    }
+    unsafe {
+        let mut bytes = vec![0u8; 5 * 4 * 8 + 2];
+        assert_eq!(
+            resize_rgba_to_f16(src.as_ptr(), 256, 2, bytes.as_mut_ptr().add(1) as *mut u16, 5, 4, 1),
+            RESIZE_ERR_ALIGNMENT
+        );
+        assert_eq!(
+            resize_rgba_to_f16(src.as_ptr(), 256, 2, src.as_ptr() as *mut u16, 5, 4, 1),
+            RESIZE_ERR_OVERLAP
+        );
+    }
+}
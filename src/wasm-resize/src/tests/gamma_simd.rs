@@ -0,0 +1,554 @@
+//! Tests for the gamma-correct resamplers in gamma_simd.rs
+
+use super::*;
+use crate::*;
+
+/// Area-average reference in f64: premultiplied linear light over the exact footprint
+fn gamma_area_reference(src: &[u8], sw: u32, sh: u32, dw: u32, dh: u32) -> Vec<u8> {
+    let (sx, sy) = (sw as f64 / dw as f64, sh as f64 / dh as f64);
+    let cover = |i: u32, start: f64, end: f64| (end.min(i as f64 + 1.0) - start.max(i as f64)).max(0.0);
+    let mut out = Vec::with_capacity((dw * dh * 4) as usize);
+    for dy in 0..dh {
+        let (y0, y1) = (dy as f64 * sy, (dy + 1) as f64 * sy);
+        for dx in 0..dw {
+            let (x0, x1) = (dx as f64 * sx, (dx + 1) as f64 * sx);
+            let mut acc = [0.0f64; 5];
+            for y in y0.floor() as u32..(y1.ceil() as u32).min(sh) {
+                for x in x0.floor() as u32..(x1.ceil() as u32).min(sw) {
+                    let w = cover(y, y0, y1) * cover(x, x0, x1);
+                    let p = &src[((y * sw + x) * 4) as usize..][..4];
+                    let a = p[3] as f64 / 255.0;
+                    for c in 0..3 {
+                        acc[c] += srgb_to_linear(p[c]) * a * w;
+                    }
+                    acc[3] += a * w;
+                    acc[4] += w;
+                }
+            }
+            for c in 0..3 {
+                out.push(if acc[3] > 0.0 {
+                    linear_to_srgb(acc[c] / acc[3])
+                } else {
+                    0
+                });
+            }
+            out.push((acc[3] / acc[4] * 255.0 + 0.5) as u8);
+        }
+    }
+    out
+}
+
+#[test]
+fn gamma_area_checkerboard_averages_in_linear_light() {
+    let src = img(8, 8, |x, y| {
+        if (x + y) % 2 == 0 {
+            [0, 0, 0, 255]
+        } else {
+            [255, 255, 255, 255]
+        }
+    });
+    let mut dst = vec![0u8; 4];
+    unsafe { assert_eq!(resize_rgba_gamma_area(src.as_ptr(), 8, 8, dst.as_mut_ptr(), 1, 1), 0) };
+    assert_eq!(dst, [188, 188, 188, 255]);
+}
+
+#[test]
+fn gamma_area_matches_reference() {
+    for (sw, sh, dw, dh) in [
+        (97u32, 61u32, 13u32, 9u32),
+        (64, 64, 16, 16),
+        (9, 7, 4, 3),
+        (1000, 7, 3, 2),
+    ] {
+        let src = img(sw, sh, |x, y| {
+            [
+                (x * 37 + y) as u8,
+                ((y * 53) ^ x) as u8,
+                ((x * y) % 256) as u8,
+                ((x * 5 + y * 3) % 256) as u8,
+            ]
+        });
+        let mut dst = vec![0u8; (dw * dh * 4) as usize];
+        unsafe {
+            assert_eq!(
+                resize_rgba_gamma_area(src.as_ptr(), sw, sh, dst.as_mut_ptr(), dw, dh),
+                0
+            )
+        };
+        let reference = gamma_area_reference(&src, sw, sh, dw, dh);
+        assert!(max_diff(&dst, &reference) <= 1, "{sw}x{sh} -> {dw}x{dh}");
+    }
+}
+
+#[test]
+fn box_sum_matches_naive_tap_loop_bit_for_bit() {
+    use crate::simd_helpers::{box_sum_4_lanes, prefix_sum_4_lanes};
+
+    // Near-maximal fixed-point samples over a long row: an f32 running total would have
+    // lost the low bits long before the end
+    let pixels = 60_000usize;
+    let values: Vec<u32> = (0..pixels * 4)
+        .map(|i| 16_000_000 + ((i as u32).wrapping_mul(2_654_435_761) >> 12) % 711_425)
+        .collect();
+    let mut prefix = vec![0u64; (pixels + 1) * 4];
+    prefix_sum_4_lanes(&values, &mut prefix);
+
+    for (start, end) in [
+        (0.0f32, 1.0f32),
+        (0.25, 59_999.5),
+        (12.5, 13.75),
+        (100.3, 40_000.0),
+        (7.0, 7.5),
+        (59_000.2, 60_000.0),
+    ] {
+        let (first, last) = (start.floor() as usize, end.floor() as usize);
+        let mut naive = [0.0f64; 4];
+        for (c, out) in naive.iter_mut().enumerate() {
+            if first == last {
+                *out = values[first * 4 + c] as f64 * (end - start) as f64;
+                continue;
+            }
+            let whole: u64 = (first + 1..last).map(|i| values[i * 4 + c] as u64).sum();
+            *out = whole as f64 + values[first * 4 + c] as f64 * (first as f32 + 1.0 - start) as f64;
+            if last < pixels && end > last as f32 {
+                *out += values[last * 4 + c] as f64 * (end - last as f32) as f64;
+            }
+        }
+        let fast = box_sum_4_lanes(&prefix, &values, start, end);
+        assert_eq!(fast.map(f64::to_bits), naive.map(f64::to_bits), "[{start}, {end})");
+    }
+}
+
+#[test]
+fn mipchain_levels_and_offsets() {
+    let src = img(4, 4, |x, y| {
+        [
+            (x * 60) as u8,
+            (y * 70) as u8,
+            if (x + y) % 2 == 0 { 0 } else { 255 },
+            255,
+        ]
+    });
+    // One spare leading byte so the chain starts unaligned
+    let mut chain = vec![0u8; 85];
+    let (mut offsets, mut count) = ([0u32; 16], 0u32);
+    unsafe {
+        let dst = chain.as_mut_ptr().add(1);
+        assert_eq!(
+            generate_mipchain_rgba(src.as_ptr(), 4, 4, dst, 84, offsets.as_mut_ptr(), &mut count),
+            0
+        );
+        let code = generate_mipchain_rgba(src.as_ptr(), 4, 4, dst, 83, offsets.as_mut_ptr(), &mut count);
+        assert_eq!(code, RESIZE_ERR_MEMORY);
+    }
+    assert_eq!(count, 3);
+    assert_eq!(offsets[..3], [0, 64, 80]);
+    let chain = &chain[1..];
+    assert_eq!(chain[..64], src[..]);
+    let mut level1 = vec![0u8; 16];
+    let mut level2 = vec![0u8; 4];
+    unsafe {
+        assert_eq!(resize_rgba_gamma_area(src.as_ptr(), 4, 4, level1.as_mut_ptr(), 2, 2), 0);
+        assert_eq!(
+            resize_rgba_gamma_area(level1.as_ptr(), 2, 2, level2.as_mut_ptr(), 1, 1),
+            0
+        );
+    }
+    assert_eq!(chain[64..80], level1[..]);
+    assert_eq!(chain[80..84], level2[..]);
+
+    let src = img(13, 5, |x, y| [(x * 19) as u8, (y * 50) as u8, 3, 200]);
+    let mut chain = vec![0u8; 800];
+    unsafe {
+        let code = generate_mipchain_rgba(
+            src.as_ptr(),
+            13,
+            5,
+            chain.as_mut_ptr(),
+            800,
+            offsets.as_mut_ptr(),
+            &mut count,
+        );
+        assert_eq!(code, 0);
+    }
+    // 13x5, 6x2, 3x1, 1x1
+    assert_eq!(count, 4);
+    assert_eq!(offsets[..4], [0, 260, 308, 320]);
+}
+
+#[test]
+fn mipchain_rejects_overlapping_buffers() {
+    let mut buf = vec![0u32; 256];
+    let (mut offsets, mut count) = ([0u32; 16], 0u32);
+    unsafe {
+        let base = buf.as_mut_ptr() as *mut u8;
+        // Source inside the chain
+        let code = generate_mipchain_rgba(base.add(64), 4, 4, base, 84, offsets.as_mut_ptr(), &mut count);
+        assert_eq!(code, RESIZE_ERR_OVERLAP);
+        // Level offsets inside the chain
+        let src = img(4, 4, |_, _| [1, 2, 3, 4]);
+        let offsets_in_chain = base.add(40) as *mut u32;
+        let code = generate_mipchain_rgba(src.as_ptr(), 4, 4, base, 84, offsets_in_chain, &mut count);
+        assert_eq!(code, RESIZE_ERR_OVERLAP);
+        // Level count inside the chain
+        let count_in_chain = base.add(80) as *mut u32;
+        let code = generate_mipchain_rgba(src.as_ptr(), 4, 4, base, 84, offsets.as_mut_ptr(), count_in_chain);
+        assert_eq!(code, RESIZE_ERR_OVERLAP);
+    }
+}
+
+#[test]
+fn auto_alpha_premultiplies_only_varying_alpha() {
+    // Opaque red next to fully transparent green: the green must not bleed into the edge
+    let src = img(32, 8, |x, _| if x < 16 { [255, 0, 0, 255] } else { [0, 255, 0, 0] });
+    let mut range = [0u8; 2];
+    let mut dst = vec![0u8; 12 * 3 * 4];
+    unsafe {
+        assert_eq!(alpha_range(src.as_ptr(), 32, 8, range.as_mut_ptr()), 0);
+        assert_eq!(resize_rgba_auto_alpha(src.as_ptr(), 32, 8, dst.as_mut_ptr(), 12, 3), 0);
+    }
+    assert_eq!(range, [0, 255]);
+    assert!(dst.chunks_exact(4).all(|p| p[1] == 0 || p[3] == 0), "{:?}", &dst[..48]);
+
+    let opaque = img(32, 8, |x, y| [(x * 8) as u8, (y * 30) as u8, 77, 255]);
+    unsafe {
+        assert_eq!(alpha_range(opaque.as_ptr(), 32, 8, range.as_mut_ptr()), 0);
+        assert_eq!(
+            resize_rgba_auto_alpha(opaque.as_ptr(), 32, 8, dst.as_mut_ptr(), 12, 3),
+            0
+        );
+        assert_eq!(
+            alpha_range(opaque.as_ptr(), 32, 8, std::ptr::null_mut()),
+            RESIZE_ERR_NULL_PTR
+        );
+        assert_eq!(
+            alpha_range(opaque.as_ptr(), 32, 8, opaque.as_ptr().add(6) as *mut u8),
+            RESIZE_ERR_OVERLAP
+        );
+    }
+    assert_eq!(range, [255, 255]);
+    assert!(dst.chunks_exact(4).all(|p| p[3] == 255));
+}
+
+#[test]
+fn gamma_approx_switches_transfer_curve() {
+    let src = img(8, 8, |x, y| {
+        if (x + y) % 2 == 0 {
+            [0, 0, 0, 255]
+        } else {
+            [255, 255, 255, 255]
+        }
+    });
+    let run = || {
+        let mut dst = vec![0u8; 4];
+        unsafe { assert_eq!(resize_rgba_gamma_area(src.as_ptr(), 8, 8, dst.as_mut_ptr(), 1, 1), 0) };
+        dst
+    };
+    assert_eq!(run(), [188, 188, 188, 255]);
+    assert_eq!(set_gamma_approx(1), 0);
+    // 0.5 ^ (1 / 2.2) * 255 = 186.0
+    let pow22 = run();
+    assert_eq!(set_gamma_approx(7), RESIZE_ERR_INVALID_PARAM);
+    assert_eq!(set_gamma_approx(0), 0);
+    assert_eq!(pow22, [186, 186, 186, 255]);
+    // Switching back rebuilds the sRGB tables
+    assert_eq!(run(), [188, 188, 188, 255]);
+}
+
+#[test]
+fn cached_source_matches_direct_gamma_resizes() {
+    let src = img(40, 30, |x, y| {
+        [
+            (x * 6) as u8,
+            (y * 8) as u8,
+            ((x + y) * 3) as u8,
+            if x < 8 { 0 } else { 128 + y as u8 },
+        ]
+    });
+    let cache = unsafe { cache_source(src.as_ptr(), 40, 30) };
+    assert!(!cache.is_null());
+    // A zoom stack of sizes from one linearized source
+    for (dw, dh) in [(20u32, 15u32), (64, 48)] {
+        let mut cached = vec![0u8; (dw * dh * 4) as usize];
+        let mut direct = cached.clone();
+        unsafe {
+            assert_eq!(
+                resize_from_cache(cache, cached.as_mut_ptr(), dw, dh, RESIZE_ALGO_LANCZOS),
+                0
+            );
+            assert_eq!(
+                resize_rgba_auto_alpha(src.as_ptr(), 40, 30, direct.as_mut_ptr(), dw, dh),
+                0
+            );
+        }
+        assert_eq!(cached, direct);
+    }
+
+    // Bilinear agrees with the LUT-based gamma bilinear within rounding on opaque midtones
+    let opaque = img(40, 30, |x, y| [60 + x as u8 * 3, 80 + y as u8 * 4, 100, 255]);
+    let opaque_cache = unsafe { cache_source(opaque.as_ptr(), 40, 30) };
+    for (dw, dh) in [(20u32, 15u32), (64, 48)] {
+        let mut cached = vec![0u8; (dw * dh * 4) as usize];
+        let mut direct = cached.clone();
+        unsafe {
+            assert_eq!(
+                resize_from_cache(opaque_cache, cached.as_mut_ptr(), dw, dh, RESIZE_ALGO_BILINEAR),
+                0
+            );
+            assert_eq!(
+                resize_rgba_gamma_bilinear(opaque.as_ptr(), 40, 30, direct.as_mut_ptr(), dw, dh),
+                0
+            );
+        }
+        assert!(max_diff(&cached, &direct) <= 3);
+    }
+
+    unsafe {
+        free_cached_source(opaque_cache);
+        free_cached_source(cache);
+        free_cached_source(std::ptr::null_mut());
+        assert!(cache_source(std::ptr::null(), 4, 4).is_null());
+        let mut dst = [0u8; 16];
+        assert_eq!(
+            resize_from_cache(std::ptr::null(), dst.as_mut_ptr(), 2, 2, 1),
+            RESIZE_ERR_NULL_PTR
+        );
+    }
+}
+
+#[test]
+fn gamma_per_channel() {
+    // Black/white checkerboard downscaled 2x: each channel averages to 0.5 linear
+    let src = img(16, 16, |x, y| {
+        if (x + y) % 2 == 0 {
+            [0, 0, 0, 255]
+        } else {
+            [255, 255, 255, 255]
+        }
+    });
+    let mut dst = vec![0u8; 8 * 8 * 4];
+    unsafe {
+        assert_eq!(
+            resize_rgba_gamma_per_channel(src.as_ptr(), 16, 16, dst.as_mut_ptr(), 8, 8, 1.0, 2.2, 3.0),
+            0
+        );
+    }
+    let p = &dst[(3 * 8 + 3) * 4..(3 * 8 + 4) * 4];
+    let expect = |g: f64| (0.5f64.powf(1.0 / g) * 255.0).round() as i32;
+    for (c, g) in [1.0, 2.2, 3.0].into_iter().enumerate() {
+        assert!((p[c] as i32 - expect(g)).abs() <= 1, "{p:?}");
+    }
+    assert!(p[0] < p[1] && p[1] < p[2] && p[3] == 255);
+    // Same size round-trips (within float noise near black for steep gammas)
+    let grad = img(9, 7, |x, y| [(x * 28) as u8, (y * 40) as u8, ((x * y) * 5) as u8, 255]);
+    let mut same = vec![0u8; grad.len()];
+    unsafe {
+        assert_eq!(
+            resize_rgba_gamma_per_channel(grad.as_ptr(), 9, 7, same.as_mut_ptr(), 9, 7, 0.45, 1.8, 4.0),
+            0
+        );
+    }
+    assert!(same.iter().zip(&grad).all(|(a, b)| (*a as i32 - *b as i32).abs() <= 1));
+    unsafe {
+        assert_eq!(
+            resize_rgba_gamma_per_channel(src.as_ptr(), 16, 16, dst.as_mut_ptr(), 8, 8, 1.0, 0.0, 2.2),
+            RESIZE_ERR_INVALID_PARAM
+        );
+        assert_eq!(
+            resize_rgba_gamma_per_channel(src.as_ptr(), 16, 16, dst.as_mut_ptr(), 8, 8, f32::NAN, 1.0, 2.2),
+            RESIZE_ERR_INVALID_PARAM
+        );
+    }
+}
+
+#[test]
+fn text_aware() {
+    // White page with 1 px black strokes at an odd column and row, plus a soft gradient band
+    let src = img(64, 64, |x, y| {
+        if x == 21 || y == 33 {
+            [0, 0, 0, 255]
+        } else if y >= 48 {
+            let v = 100 + x as u8;
+            [v, v, v, 255]
+        } else {
+            [255, 255, 255, 255]
+        }
+    });
+    let mut text = vec![0u8; 32 * 32 * 4];
+    let mut area = text.clone();
+    unsafe {
+        assert_eq!(
+            resize_rgba_text_aware(src.as_ptr(), 64, 64, text.as_mut_ptr(), 32, 32),
+            0
+        );
+        assert_eq!(
+            resize_rgba_gamma_area(src.as_ptr(), 64, 64, area.as_mut_ptr(), 32, 32),
+            0
+        );
+    }
+    let at = |v: &[u8], x: usize, y: usize| v[(y * 32 + x) * 4];
+    for y in (0..24).filter(|&y| y != 16) {
+        assert!(at(&area, 10, y) > 150, "area fades the stroke: {}", at(&area, 10, y));
+        assert_eq!(at(&text, 10, y), 0, "y {y}");
+    }
+    for x in 0..32 {
+        if x != 10 {
+            assert_eq!(at(&text, x, 16), 0, "x {x}");
+            assert!(at(&area, x, 16) > 150);
+        }
+    }
+    // Smooth content and flat background match the plain area downscale
+    for y in (0..16).chain(24..32) {
+        for x in 0..32 {
+            if x == 10 || y == 16 {
+                continue;
+            }
+            let i = (y * 32 + x) * 4;
+            assert_eq!(text[i..i + 4], area[i..i + 4], "{x},{y}");
+        }
+    }
+    // Upscales fall back to the automatic resize
+    let mut up = vec![0u8; 80 * 80 * 4];
+    let mut auto = up.clone();
+    unsafe {
+        assert_eq!(resize_rgba_text_aware(src.as_ptr(), 64, 64, up.as_mut_ptr(), 80, 80), 0);
+        assert_eq!(
+            resize_rgba_row_order(src.as_ptr(), 64, 64, auto.as_mut_ptr(), 80, 80, 3, 0),
+            0
+        );
+    }
+    assert_eq!(up, auto);
+}
+
+#[test]
+fn text_aware_keeps_light_strokes_on_dark() {
+    // Dark-mode text: 1 px light strokes on a near-black page
+    let src = img(64, 64, |x, y| {
+        if x == 21 || y == 33 {
+            [240, 240, 240, 255]
+        } else {
+            [16, 16, 16, 255]
+        }
+    });
+    let mut text = vec![0u8; 32 * 32 * 4];
+    let mut area = text.clone();
+    unsafe {
+        assert_eq!(
+            resize_rgba_text_aware(src.as_ptr(), 64, 64, text.as_mut_ptr(), 32, 32),
+            0
+        );
+        assert_eq!(
+            resize_rgba_gamma_area(src.as_ptr(), 64, 64, area.as_mut_ptr(), 32, 32),
+            0
+        );
+    }
+    let at = |v: &[u8], x: usize, y: usize| v[(y * 32 + x) * 4];
+    for i in (0..32).filter(|&i| i != 10 && i != 16) {
+        assert!(at(&area, 10, i) < 200, "area fades the stroke: {}", at(&area, 10, i));
+        assert_eq!(at(&text, 10, i), 240, "column stroke at y {i}");
+        assert_eq!(at(&text, i, 16), 240, "row stroke at x {i}");
+        assert_eq!(at(&text, i, 5), 16, "background at x {i}");
+    }
+    // A lone light pixel in an otherwise dark footprint is the stroke
+    let dot = img(4, 4, |x, y| {
+        if (x, y) == (1, 2) {
+            [255, 255, 255, 255]
+        } else {
+            [0, 0, 0, 255]
+        }
+    });
+    let mut out = [0u8; 2 * 2 * 4];
+    unsafe { assert_eq!(resize_rgba_text_aware(dot.as_ptr(), 4, 4, out.as_mut_ptr(), 2, 2), 0) };
+    assert_eq!(out[8..12], [255, 255, 255, 255]);
+    assert_eq!(out[..8], [0, 0, 0, 255, 0, 0, 0, 255]);
+}
+
+/// Collapse a pyramid from build_laplacian_pyramid_rgba as its docs describe: upsample
+/// from the base upward and add each level's bytes minus 128
+fn collapse_laplacian(pyramid: &[u8], w: u32, h: u32) -> Vec<u8> {
+    let mut levels = vec![(w, h)];
+    while let Some(&(lw, lh)) = levels.last().filter(|&&l| l != (1, 1)) {
+        levels.push(((lw / 2).max(1), (lh / 2).max(1)));
+    }
+    let mut offsets = vec![0usize];
+    for &(lw, lh) in &levels[..levels.len() - 1] {
+        offsets.push(offsets.last().unwrap() + (lw * lh * 4) as usize);
+    }
+
+    let base = *offsets.last().unwrap();
+    let mut current = pyramid[base..base + 4].to_vec();
+    for i in (0..levels.len() - 1).rev() {
+        let ((lw, lh), (cw, ch)) = (levels[i], levels[i + 1]);
+        let mut up = vec![0u8; (lw * lh * 4) as usize];
+        assert_eq!(crate::gamma_simd::pyramid_upsample(&current, cw, ch, &mut up, lw, lh), 0);
+        for (u, &d) in up.iter_mut().zip(&pyramid[offsets[i]..]) {
+            *u = (*u as i32 + d as i32 - 128).clamp(0, 255) as u8;
+        }
+        current = up;
+    }
+    current
+}
+
+#[test]
+fn laplacian_pyramid() {
+    let src = img(37, 22, |x, y| {
+        [
+            (60 + x * 3) as u8,
+            (40 + y * 5) as u8,
+            (100 + (x + y) % 20) as u8,
+            (200 + x % 7) as u8,
+        ]
+    });
+    let mut pyramid = vec![0u8; 5000];
+    let mut offsets = [0u32; 16];
+    let mut count = 0u32;
+    let mut mip = vec![0u8; 5000];
+    let mut mip_offsets = [0u32; 16];
+    let mut mip_count = 0u32;
+    let (p, o) = (pyramid.as_mut_ptr(), offsets.as_mut_ptr());
+    unsafe {
+        assert_eq!(build_laplacian_pyramid_rgba(src.as_ptr(), 37, 22, p, 5000, o, &mut count), 0);
+        assert_eq!(
+            generate_mipchain_rgba(
+                src.as_ptr(),
+                37,
+                22,
+                mip.as_mut_ptr(),
+                5000,
+                mip_offsets.as_mut_ptr(),
+                &mut mip_count
+            ),
+            0
+        );
+    }
+    assert_eq!((count, offsets), (mip_count, mip_offsets));
+    let total = offsets[count as usize - 1] as usize + 4;
+
+    // The base is the smallest Gaussian; a smooth image's details sit close to 128
+    assert_eq!(pyramid[total - 4..total], mip[total - 4..total]);
+    assert!(pyramid[..37 * 22 * 4].iter().all(|&v| v.abs_diff(128) < 32));
+    assert!(max_diff(&collapse_laplacian(&pyramid, 37, 22), &src) <= 1);
+    unsafe {
+        assert_eq!(
+            build_laplacian_pyramid_rgba(src.as_ptr(), 37, 22, p, total - 1, o, &mut count),
+            RESIZE_ERR_MEMORY
+        );
+        assert_eq!(
+            build_laplacian_pyramid_rgba(src.as_ptr(), 37, 22, src.as_ptr() as *mut u8, 5000, o, &mut count),
+            RESIZE_ERR_OVERLAP
+        );
+    }
+
+    // A 1 px checkerboard has details beyond +-127, which clip to the ends of the range
+    let checker = img(16, 9, |x, y| {
+        if (x + y) % 2 == 0 {
+            [0, 0, 0, 255]
+        } else {
+            [255, 255, 255, 255]
+        }
+    });
+    unsafe {
+        assert_eq!(build_laplacian_pyramid_rgba(checker.as_ptr(), 16, 9, p, 5000, o, &mut count), 0);
+    }
+    assert!(pyramid[..16 * 9 * 4].iter().any(|&v| v == 0 || v == 255));
+}
@@ -0,0 +1,52 @@
+//! Tests for the floating-point (HDR) paths in hdr.rs
+
+use crate::*;
+
+/// 8x8 linear source whose red channel ramps from 0.25 to 7.95 across each row
+fn hdr_ramp() -> Vec<f32> {
+    (0..64u32)
+        .flat_map(|i| {
+            let v = 0.25 + (i % 8) as f32 * 1.1;
+            [v, v * 0.5, 0.1, 1.0]
+        })
+        .collect()
+}
+
+#[test]
+fn tone_curves_keep_highlights_distinct() {
+    let src = hdr_ramp();
+    let run = |tone: u32| {
+        let mut dst = vec![0u8; 4 * 4 * 4];
+        unsafe {
+            assert_eq!(
+                resize_rgbaf32_to_srgb_u8(src.as_ptr(), 8, 8, dst.as_mut_ptr(), 4, 4, tone, 1.0, 1),
+                0
+            )
+        };
+        dst
+    };
+    let red_row = |dst: &[u8]| (0..4).map(|x| dst[x * 4]).collect::<Vec<_>>();
+    let clip = run(RESIZE_TONE_CLIP);
+    // Clip saturates every highlight column; the tone curves keep them increasing
+    assert!(red_row(&clip)[1..].iter().all(|&v| v == 255));
+    assert!(clip.chunks_exact(4).all(|p| p[3] == 255));
+    for tone in [RESIZE_TONE_REINHARD, RESIZE_TONE_FILMIC] {
+        let row = red_row(&run(tone));
+        assert!(row.windows(2).all(|p| p[0] < p[1]), "tone {tone}: {row:?}");
+    }
+}
+
+#[test]
+fn hdr_rejects_bad_params_and_misaligned_source() {
+    let src = hdr_ramp();
+    let mut dst = vec![0u8; 64];
+    unsafe {
+        let code = resize_rgbaf32_to_srgb_u8(src.as_ptr(), 8, 8, dst.as_mut_ptr(), 4, 4, 3, 1.0, 1);
+        assert_eq!(code, RESIZE_ERR_INVALID_PARAM);
+        let code = resize_rgbaf32_to_srgb_u8(src.as_ptr(), 8, 8, dst.as_mut_ptr(), 4, 4, 1, f32::NAN, 1);
+        assert_eq!(code, RESIZE_ERR_INVALID_PARAM);
+        let misaligned = (src.as_ptr() as *const u8).wrapping_add(2) as *const f32;
+        let code = resize_rgbaf32_to_srgb_u8(misaligned, 4, 4, dst.as_mut_ptr(), 4, 4, 1, 1.0, 1);
+        assert_eq!(code, RESIZE_ERR_ALIGNMENT);
+    }
+}
@@ -0,0 +1,62 @@
+//! Unit tests, run on the host with `./test.sh` (or `cargo test --target <host triple>`)
+//! The exports are plain `extern "C"` functions, so the tests call them directly with
+//! pointers into Vec-backed images
+
+mod alloc;
+mod analysis;
+mod color;
+mod filters;
+mod formats;
+mod gamma_simd;
+mod hdr;
+mod planar;
+#[cfg(feature = "timing")]
+mod profiling;
+mod regions;
+mod resize;
+mod separable;
+mod streaming;
+mod transform;
+
+/// Build a w x h RGBA image from a per-pixel generator
+fn img(w: u32, h: u32, f: impl Fn(u32, u32) -> [u8; 4]) -> Vec<u8> {
+    let mut v = Vec::with_capacity(w as usize * h as usize * 4);
+    for y in 0..h {
+        for x in 0..w {
+            v.extend_from_slice(&f(x, y));
+        }
+    }
+    v
+}
+
+/// Exact sRGB decode of an 8-bit value, in f64
+fn srgb_to_linear(v: u8) -> f64 {
+    let s = v as f64 / 255.0;
+    if s <= 0.04045 {
+        s / 12.92
+    } else {
+        ((s + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Exact sRGB encode of a linear value to 8 bits, in f64
+fn linear_to_srgb(v: f64) -> u8 {
+    let v = v.clamp(0.0, 1.0);
+    let s = if v <= 0.0031308 {
+        v * 12.92
+    } else {
+        1.055 * v.powf(1.0 / 2.4) - 0.055
+    };
+    (s * 255.0 + 0.5) as u8
+}
+
+/// Largest per-byte difference between two equally sized buffers
+fn max_diff(a: &[u8], b: &[u8]) -> u8 {
+    assert_eq!(a.len(), b.len());
+    a.iter().zip(b).map(|(&x, &y)| x.abs_diff(y)).max().unwrap_or(0)
+}
+
+/// Error code recorded by the most recent call on this thread
+fn last_error() -> i32 {
+    crate::LAST_ERROR_CODE.with(|c| c.get())
+}
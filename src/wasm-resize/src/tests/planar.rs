@@ -0,0 +1,271 @@
+//! Tests for the single-plane and YUV paths in planar.rs
+
+use super::*;
+use crate::*;
+
+#[test]
+fn plane_phase_shifts_samples() {
+    // Horizontal ramp with slope 8 per pixel, resized 1:1
+    let (w, h) = (32u32, 4u32);
+    let src: Vec<u8> = (0..w * h).map(|i| ((i % w) * 8) as u8).collect();
+    let mut unshifted = vec![0u8; (w * h) as usize];
+    let mut shifted = unshifted.clone();
+    unsafe {
+        assert_eq!(
+            resize_plane_phase(src.as_ptr(), w, h, unshifted.as_mut_ptr(), w, h, 0.0, 0.0, 1),
+            0
+        );
+        assert_eq!(
+            resize_plane_phase(src.as_ptr(), w, h, shifted.as_mut_ptr(), w, h, 0.25, 0.0, 1),
+            0
+        );
+    }
+    assert_eq!(unshifted, src);
+    // A quarter-pixel shift samples a quarter of the slope further along
+    for x in 0..(w - 1) as usize {
+        assert_eq!(shifted[x], src[x] + 2, "x {x}");
+    }
+}
+
+#[test]
+fn plane_zero_phase_matches_rgba_resize() {
+    let (w, h) = (32u32, 4u32);
+    let src: Vec<u8> = (0..w * h).map(|i| ((i % w) * 8) as u8).collect();
+    let rgba: Vec<u8> = src.iter().flat_map(|&v| [v, v, v, v]).collect();
+    let (dw, dh) = (13u32, 9u32);
+    for algo in [RESIZE_ALGO_NEAREST, RESIZE_ALGO_BILINEAR, RESIZE_ALGO_LANCZOS] {
+        let mut plane = vec![0u8; (dw * dh) as usize];
+        let mut reference = vec![0u8; (dw * dh * 4) as usize];
+        unsafe {
+            assert_eq!(
+                resize_plane_phase(src.as_ptr(), w, h, plane.as_mut_ptr(), dw, dh, 0.0, 0.0, algo),
+                0
+            );
+            assert_eq!(
+                resize_with_algorithm(rgba.as_ptr(), w, h, reference.as_mut_ptr(), dw, dh, algo),
+                0
+            );
+        }
+        let red: Vec<u8> = reference.chunks_exact(4).map(|p| p[0]).collect();
+        assert!(max_diff(&plane, &red) <= 1, "algo {algo}");
+    }
+}
+
+#[test]
+fn cancelled_plane_resize_reports_no_stale_row() {
+    let src = vec![90u8; 40 * 30];
+    let mut dst = vec![0u8; 20 * 16];
+    unsafe {
+        assert_eq!(resize_plane_phase(src.as_ptr(), 40, 30, dst.as_mut_ptr(), 20, 16, 0.0, 0.0, 1), 0);
+        assert_eq!(get_last_completed_row(), 15);
+        request_cancel();
+        let code = resize_plane_phase(src.as_ptr(), 40, 30, dst.as_mut_ptr(), 20, 16, 0.0, 0.0, 1);
+        clear_cancel();
+        assert_eq!(code, RESIZE_ERR_CANCELLED);
+    }
+    assert_eq!(get_last_completed_row(), RESIZE_NO_ROW_COMPLETED);
+}
+
+#[test]
+fn yuv420_solid_colors_convert_per_matrix() {
+    let (w, h) = (16u32, 12u32);
+    let cases = [
+        (RESIZE_YUV_BT601_LIMITED, [81u8, 90, 240], [255u8, 0, 0]),
+        (RESIZE_YUV_BT709_LIMITED, [63, 102, 240], [255, 0, 0]),
+        (RESIZE_YUV_BT601_FULL, [76, 85, 255], [254, 0, 0]),
+        (RESIZE_YUV_BT601_LIMITED, [235, 128, 128], [255, 255, 255]),
+        (RESIZE_YUV_BT601_LIMITED, [16, 128, 128], [0, 0, 0]),
+    ];
+    for (matrix, yuv, rgb) in cases {
+        let y = vec![yuv[0]; (w * h) as usize];
+        let u = vec![yuv[1]; (w * h / 4) as usize];
+        let v = vec![yuv[2]; (w * h / 4) as usize];
+        for (dw, dh) in [(16u32, 12u32), (7, 5), (40, 30)] {
+            let mut dst = vec![0u8; (dw * dh * 4) as usize];
+            unsafe {
+                let code = resize_yuv420_to_rgba(
+                    y.as_ptr(),
+                    u.as_ptr(),
+                    v.as_ptr(),
+                    w,
+                    h,
+                    dst.as_mut_ptr(),
+                    dw,
+                    dh,
+                    matrix,
+                    3,
+                );
+                assert_eq!(code, 0);
+            }
+            for p in dst.chunks_exact(4) {
+                assert!(max_diff(&p[..3], &rgb) <= 2, "matrix {matrix}: {p:?} vs {rgb:?}");
+                assert_eq!(p[3], 255);
+            }
+        }
+    }
+}
+
+#[test]
+fn yuv420_identity_reports_every_row() {
+    let (w, h) = (16u32, 12u32);
+    let y = vec![81u8; (w * h) as usize];
+    let chroma = vec![128u8; (w * h / 4) as usize];
+    let mut dst = vec![0u8; (w * h * 4) as usize];
+    let code = unsafe {
+        resize_yuv420_to_rgba(
+            y.as_ptr(),
+            chroma.as_ptr(),
+            chroma.as_ptr(),
+            w,
+            h,
+            dst.as_mut_ptr(),
+            w,
+            h,
+            RESIZE_YUV_BT601_LIMITED,
+            3,
+        )
+    };
+    assert_eq!(code, 0);
+    assert_eq!(get_last_completed_row(), h - 1);
+}
+
+#[test]
+fn yuv420_rejects_odd_dimensions() {
+    let plane = [0u8; 15 * 12];
+    let mut dst = vec![0u8; 4 * 4 * 4];
+    let code = unsafe {
+        resize_yuv420_to_rgba(
+            plane.as_ptr(),
+            plane.as_ptr(),
+            plane.as_ptr(),
+            15,
+            12,
+            dst.as_mut_ptr(),
+            4,
+            4,
+            0,
+            1,
+        )
+    };
+    assert_eq!(code, RESIZE_ERR_INVALID_SIZE);
+}
+
+#[test]
+fn rle_mask() {
+    // 20x10 mask: vertical stripes 0/255 of width 3 and 2, plus a 7-sample run crossing rows
+    let (w, h) = (20u32, 10u32);
+    let mut decoded = Vec::new();
+    let mut rle = Vec::new();
+    for y in 0..h {
+        if y == 4 {
+            continue;
+        }
+        for (count, value) in [(3u8, 0u8), (2, 255), (3, 0), (2, 255), (3, 0), (2, 255), (5, 128)] {
+            if y == 3 && value == 128 {
+                // Row 3's tail and the whole of row 4 (20 samples) as one 25-sample run
+                rle.extend_from_slice(&[25, 128]);
+                decoded.extend(std::iter::repeat_n(128, 25));
+                continue;
+            }
+            rle.extend_from_slice(&[count, value]);
+            decoded.extend(std::iter::repeat_n(value, count as usize));
+        }
+    }
+    assert_eq!(decoded.len(), (w * h) as usize);
+    for algo in 0..3u32 {
+        for (dw, dh) in [(7u32, 5u32), (33, 17)] {
+            let mut a = vec![0u8; (dw * dh) as usize];
+            let mut b = a.clone();
+            unsafe {
+                assert_eq!(
+                    resize_rle_mask(rle.as_ptr(), rle.len(), w, h, a.as_mut_ptr(), dw, dh, algo),
+                    0
+                );
+                assert_eq!(
+                    resize_plane_phase(decoded.as_ptr(), w, h, b.as_mut_ptr(), dw, dh, 0.0, 0.0, algo),
+                    0
+                );
+            }
+            assert_eq!(a, b, "algo {algo} {dw}x{dh}");
+        }
+    }
+    let mut a = vec![0u8; 35];
+    unsafe {
+        assert_eq!(
+            resize_rle_mask(rle.as_ptr(), rle.len() - 2, w, h, a.as_mut_ptr(), 7, 5, 1),
+            RESIZE_ERR_INVALID_PARAM
+        );
+        assert_eq!(
+            resize_rle_mask(rle.as_ptr(), rle.len() - 1, w, h, a.as_mut_ptr(), 7, 5, 1),
+            RESIZE_ERR_INVALID_PARAM
+        );
+        assert_eq!(
+            resize_rle_mask(rle.as_ptr(), rle.len(), w, h - 1, a.as_mut_ptr(), 7, 5, 1),
+            RESIZE_ERR_INVALID_PARAM
+        );
+        assert_eq!(
+            resize_rle_mask([0u8, 5].as_ptr(), 2, 1, 1, a.as_mut_ptr(), 7, 5, 1),
+            RESIZE_ERR_INVALID_PARAM
+        );
+    }
+}
+
+#[test]
+fn alpha_edge_aware() {
+    // Left half: hard-edged cut-out (0 / 255, edges at odd positions); right half: soft ramp
+    let (w, h) = (64u32, 32u32);
+    let mask: Vec<u8> = (0..w * h)
+        .map(|i| {
+            let (x, y) = (i % w, i / w);
+            if x < 32 {
+                if x >= 13 && y >= 9 {
+                    255
+                } else {
+                    0
+                }
+            } else {
+                ((x - 32) * 8) as u8
+            }
+        })
+        .collect();
+    let (dw, dh) = (32u32, 16u32);
+    let mut out = vec![0u8; (dw * dh) as usize];
+    unsafe {
+        assert_eq!(
+            resize_alpha_edge_aware(mask.as_ptr(), w, h, out.as_mut_ptr(), dw, dh),
+            0
+        );
+    }
+    let area = |dx: u32, dy: u32| {
+        let s: u32 = [(0, 0), (1, 0), (0, 1), (1, 1)]
+            .iter()
+            .map(|&(ox, oy)| mask[((2 * dy + oy) * w + 2 * dx + ox) as usize] as u32)
+            .sum();
+        (s as f32 / 4.0 + 0.5) as u8
+    };
+    for dy in 0..dh {
+        for dx in 0..dw {
+            let v = out[(dy * dw + dx) as usize];
+            if dx < 16 {
+                assert!(v == 0 || v == 255, "hard edge blurred at {dx},{dy}: {v}");
+            } else {
+                assert_eq!(v, area(dx, dy), "ramp at {dx},{dy}");
+            }
+        }
+    }
+    // The straddled edge column would be grey with a plain area downscale
+    assert_eq!(area(6, 10), 128);
+    assert_eq!(out[(10 * dw + 6) as usize], 255);
+    // Ramp stays strictly increasing
+    assert!((17..32).all(|x| out[(3 * dw + x) as usize] > out[(3 * dw + x - 1) as usize]));
+    unsafe {
+        assert_eq!(
+            resize_alpha_edge_aware(mask.as_ptr(), w, h, mask.as_ptr() as *mut u8, dw, dh),
+            RESIZE_ERR_OVERLAP
+        );
+        assert_eq!(
+            resize_alpha_edge_aware(std::ptr::null(), w, h, out.as_mut_ptr(), dw, dh),
+            RESIZE_ERR_NULL_PTR
+        );
+    }
+}
@@ -0,0 +1,60 @@
+//! Tests for the phase timing in profiling.rs (`timing` feature)
+
+use super::*;
+use crate::*;
+
+use std::sync::OnceLock;
+use std::time::Instant;
+
+static START: OnceLock<Instant> = OnceLock::new();
+
+// Host stand-in for the `env.now_ms` import
+#[no_mangle]
+extern "C" fn now_ms() -> f64 {
+    START.get_or_init(Instant::now).elapsed().as_secs_f64() * 1000.0
+}
+
+#[test]
+fn lanczos_phases_add_up_to_the_call() {
+    let src: Vec<u8> = (0..1200 * 900 * 4).map(|i| (i * 31 % 251) as u8).collect();
+    let mut dst = vec![0u8; 500 * 400 * 4];
+    let mut reference = dst.clone();
+    let mut phases = [-1.0f32; 3];
+    let start = now_ms();
+    unsafe {
+        let code =
+            resize_rgba_lanczos_profiled(src.as_ptr(), 1200, 900, dst.as_mut_ptr(), 500, 400, phases.as_mut_ptr());
+        assert_eq!(code, 0);
+    }
+    let total = (now_ms() - start) as f32;
+    unsafe {
+        assert_eq!(
+            resize_rgba_lanczos(src.as_ptr(), 1200, 900, reference.as_mut_ptr(), 500, 400),
+            0
+        )
+    };
+    assert_eq!(dst, reference);
+    assert!(phases.iter().all(|&p| p >= 0.0));
+    assert!(phases[1] > 0.0 && phases[2] > 0.0, "{phases:?}");
+    let sum: f32 = phases.iter().sum();
+    assert!(sum <= total && sum > total * 0.5, "{sum} vs {total}");
+}
+
+#[test]
+fn lanczos_profiled_validates_phase_output() {
+    let src = img(16, 16, |x, y| [x as u8, y as u8, 0, 255]);
+    let mut dst = vec![0u8; 8 * 8 * 4];
+    let (sp, dp) = (src.as_ptr(), dst.as_mut_ptr());
+    unsafe {
+        let null = resize_rgba_lanczos_profiled(sp, 16, 16, dp, 8, 8, std::ptr::null_mut());
+        assert_eq!(null, RESIZE_ERR_NULL_PTR);
+        let misaligned = resize_rgba_lanczos_profiled(sp, 16, 16, dp, 8, 8, dp.add(2) as *mut f32);
+        assert_eq!(misaligned, RESIZE_ERR_ALIGNMENT);
+        // The 12 phase bytes would run into the start of the destination
+        let before_dst = dp.sub(8) as *mut f32;
+        assert_eq!(
+            resize_rgba_lanczos_profiled(sp, 16, 16, dp, 8, 8, before_dst),
+            RESIZE_ERR_OVERLAP
+        );
+    }
+}
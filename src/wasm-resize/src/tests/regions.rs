@@ -0,0 +1,833 @@
+//! Tests for the region-aware resize variants in regions.rs
+
+use super::*;
+use crate::*;
+
+#[test]
+fn preserve_border_keeps_frame_crisp() {
+    let src = img(20, 16, |x, y| {
+        if x == 0 || y == 0 || x == 19 || y == 15 {
+            [255, 0, 0, 255]
+        } else {
+            [(x * 10) as u8, 200, (y * 9) as u8, 255]
+        }
+    });
+    for (dw, dh) in [(10u32, 8u32), (40, 32), (7, 5)] {
+        for algo in [
+            RESIZE_ALGO_NEAREST,
+            RESIZE_ALGO_BILINEAR,
+            RESIZE_ALGO_LANCZOS,
+            RESIZE_ALGO_AUTO,
+        ] {
+            let mut dst = vec![0u8; (dw * dh * 4) as usize];
+            let mut plain = dst.clone();
+            unsafe {
+                assert_eq!(
+                    resize_rgba_preserve_border(src.as_ptr(), 20, 16, 1, dst.as_mut_ptr(), dw, dh, algo),
+                    0
+                );
+                assert_eq!(
+                    resize_with_algorithm(src.as_ptr(), 20, 16, plain.as_mut_ptr(), dw, dh, algo),
+                    0
+                );
+            }
+            // One source pixel scales to a ring of round(dst / src) pixels, at least 1
+            let (bx, by) = (((dw + 10) / 20).max(1), ((dh + 8) / 16).max(1));
+            for y in 0..dh {
+                for x in 0..dw {
+                    let i = ((y * dw + x) * 4) as usize;
+                    if x < bx || y < by || x >= dw - bx || y >= dh - by {
+                        assert_eq!(dst[i..i + 4], [255, 0, 0, 255], "{dw}x{dh} algo {algo} ({x}, {y})");
+                    } else {
+                        assert_eq!(dst[i..i + 4], plain[i..i + 4]);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn preserve_border_rejects_oversized_border() {
+    let src = img(8, 6, |_, _| [0, 0, 0, 255]);
+    let mut dst = vec![0u8; 4 * 3 * 4];
+    let code =
+        unsafe { resize_rgba_preserve_border(src.as_ptr(), 8, 6, 3, dst.as_mut_ptr(), 4, 3, RESIZE_ALGO_BILINEAR) };
+    assert_eq!(code, RESIZE_ERR_INVALID_PARAM);
+}
+
+#[test]
+fn clip_writes_only_inside_rectangle() {
+    let src = img(33, 21, |x, y| {
+        [(x * 7) as u8, (y * 11) as u8, ((x * y) % 256) as u8, (x + y * 5) as u8]
+    });
+    for algo in [RESIZE_ALGO_NEAREST, RESIZE_ALGO_BILINEAR, RESIZE_ALGO_LANCZOS] {
+        for (dw, dh) in [(50u32, 40u32), (16, 9)] {
+            let mut full = vec![0u8; (dw * dh * 4) as usize];
+            let mut clipped = vec![7u8; (dw * dh * 4) as usize];
+            unsafe {
+                assert_eq!(
+                    resize_with_algorithm(src.as_ptr(), 33, 21, full.as_mut_ptr(), dw, dh, algo),
+                    0
+                );
+                assert_eq!(
+                    resize_rgba_clip(src.as_ptr(), 33, 21, clipped.as_mut_ptr(), dw, dh, 3, 2, 5, 4, algo),
+                    0
+                );
+            }
+            for y in 0..dh {
+                for x in 0..dw {
+                    let i = ((y * dw + x) * 4) as usize;
+                    if (3..8).contains(&x) && (2..6).contains(&y) {
+                        assert_eq!(clipped[i..i + 4], full[i..i + 4], "algo {algo} ({x}, {y})");
+                    } else {
+                        assert_eq!(clipped[i..i + 4], [7, 7, 7, 7]);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn clip_nearest_leaves_pixels_right_of_rectangle() {
+    // Same-size 16-byte aligned buffers line every fourth source and destination pixel
+    // up for the SIMD nearest copy, which moves 4 pixels at a time
+    let src = img(16, 8, |x, y| [x as u8, y as u8, 100, 255]);
+    let len = src.len();
+    let mut src_buf = vec![0u8; len + 16];
+    let mut dst_buf = vec![0u8; len + 16];
+    let src_off = src_buf.as_ptr().align_offset(16);
+    let dst_off = dst_buf.as_ptr().align_offset(16);
+    src_buf[src_off..src_off + len].copy_from_slice(&src);
+    for (cx, cy, cw, ch) in [(1u32, 2u32, 4u32, 3u32), (12, 1, 2, 2), (0, 7, 13, 1)] {
+        let dst = &mut dst_buf[dst_off..dst_off + len];
+        dst.fill(7);
+        let code = unsafe {
+            resize_rgba_clip(
+                src_buf[src_off..].as_ptr(),
+                16,
+                8,
+                dst.as_mut_ptr(),
+                16,
+                8,
+                cx,
+                cy,
+                cw,
+                ch,
+                RESIZE_ALGO_NEAREST,
+            )
+        };
+        assert_eq!(code, 0);
+        for (i, px) in dst.chunks_exact(4).enumerate() {
+            let (x, y) = (i as u32 % 16, i as u32 / 16);
+            if (cx..cx + cw).contains(&x) && (cy..cy + ch).contains(&y) {
+                assert_eq!(px, &src[i * 4..i * 4 + 4]);
+            } else {
+                assert_eq!(px, [7, 7, 7, 7], "clip ({cx}, {cy}, {cw}, {ch}) pixel ({x}, {y})");
+            }
+        }
+    }
+}
+
+#[test]
+fn clip_rejects_rectangle_outside_destination() {
+    let src = img(8, 8, |_, _| [0, 0, 0, 255]);
+    let mut dst = vec![0u8; 4 * 4 * 4];
+    let code = unsafe {
+        resize_rgba_clip(
+            src.as_ptr(),
+            8,
+            8,
+            dst.as_mut_ptr(),
+            4,
+            4,
+            2,
+            2,
+            3,
+            1,
+            RESIZE_ALGO_BILINEAR,
+        )
+    };
+    assert_eq!(code, RESIZE_ERR_INVALID_PARAM);
+}
+
+#[test]
+fn autocrop_finds_alpha_bounds() {
+    let src = img(20, 16, |x, y| {
+        if (5..12).contains(&x) && (3..9).contains(&y) {
+            [x as u8 * 10, y as u8 * 10, 7, if x == 5 { 20 } else { 255 }]
+        } else {
+            [9, 9, 9, 0]
+        }
+    });
+    let mut bounds = [0u32; 4];
+    unsafe {
+        assert_eq!(autocrop_alpha_bounds(src.as_ptr(), 20, 16, 0, bounds.as_mut_ptr()), 0);
+        assert_eq!(bounds, [5, 3, 7, 6]);
+        assert_eq!(autocrop_alpha_bounds(src.as_ptr(), 20, 16, 50, bounds.as_mut_ptr()), 0);
+        assert_eq!(bounds, [6, 3, 6, 6]);
+        // Nothing above the threshold reports the full image
+        assert_eq!(autocrop_alpha_bounds(src.as_ptr(), 20, 16, 255, bounds.as_mut_ptr()), 0);
+        assert_eq!(bounds, [0, 0, 20, 16]);
+    }
+}
+
+#[test]
+fn crop_copies_sub_rectangle_at_same_size() {
+    let src = img(20, 16, |x, y| [x as u8 * 10, y as u8 * 10, 7, 255]);
+    let mut dst = vec![0u8; 7 * 6 * 4];
+    unsafe {
+        assert_eq!(
+            resize_rgba_crop(
+                src.as_ptr(),
+                20,
+                16,
+                5,
+                3,
+                7,
+                6,
+                dst.as_mut_ptr(),
+                7,
+                6,
+                RESIZE_ALGO_NEAREST
+            ),
+            0
+        );
+        let code = resize_rgba_crop(
+            src.as_ptr(),
+            20,
+            16,
+            15,
+            3,
+            7,
+            6,
+            dst.as_mut_ptr(),
+            7,
+            6,
+            RESIZE_ALGO_NEAREST,
+        );
+        assert_eq!(code, RESIZE_ERR_INVALID_PARAM);
+    }
+    for y in 0..6 {
+        for x in 0..7 {
+            let (d, s) = ((y * 7 + x) * 4, ((y + 3) * 20 + x + 5) * 4);
+            assert_eq!(dst[d..d + 4], src[s..s + 4]);
+        }
+    }
+}
+
+#[test]
+fn atlas_slot_resizes_and_bleeds_edges() {
+    let src = img(30, 20, |x, y| [(x * 8) as u8, (y * 12) as u8, 99, 255]);
+    let (aw, ah) = (40u32, 32u32);
+    let mut atlas = vec![7u8; (aw * ah * 4) as usize];
+    let mut plain = vec![0u8; 12 * 9 * 4];
+    unsafe {
+        let code = resize_rgba_atlas_slot(src.as_ptr(), 30, 20, atlas.as_mut_ptr(), aw, ah, 5, 6, 12, 9, 2, 1);
+        assert_eq!(code, 0);
+        assert_eq!(
+            resize_with_algorithm(src.as_ptr(), 30, 20, plain.as_mut_ptr(), 12, 9, RESIZE_ALGO_BILINEAR),
+            0
+        );
+        // The bleed ring would start left of the atlas
+        let code = resize_rgba_atlas_slot(src.as_ptr(), 30, 20, atlas.as_mut_ptr(), aw, ah, 1, 6, 12, 9, 2, 1);
+        assert_eq!(code, RESIZE_ERR_INVALID_PARAM);
+    }
+    let px = |x: u32, y: u32| &atlas[((y * aw + x) * 4) as usize..][..4];
+    // Slot at (5, 6) size 12x9 plus a 2 px ring: every ring pixel repeats the nearest slot pixel
+    for y in 4..17u32 {
+        for x in 3..19u32 {
+            let (sx, sy) = (x.clamp(5, 16) - 5, y.clamp(6, 14) - 6);
+            assert_eq!(px(x, y), &plain[((sy * 12 + sx) * 4) as usize..][..4], "({x}, {y})");
+        }
+    }
+    for (x, y) in [(2, 6), (5, 3), (19, 17), (0, 0), (39, 31)] {
+        assert_eq!(px(x, y), [7, 7, 7, 7]);
+    }
+}
+
+#[test]
+fn watermark_blends_in_linear_light_inside_its_rect() {
+    let (w, h) = (40u32, 30u32);
+    let src = img(w, h, |x, y| [(x * 6) as u8, (y * 8) as u8, 100, 255]);
+    // Left half opaque white, right half fully transparent
+    let wm = img(4, 3, |x, _| [255, 255, 255, if x < 2 { 255 } else { 0 }]);
+    let mut plain = vec![0u8; 20 * 15 * 4];
+    let mut out = plain.clone();
+    unsafe {
+        assert_eq!(
+            resize_with_algorithm(src.as_ptr(), w, h, plain.as_mut_ptr(), 20, 15, 1),
+            0
+        );
+        let code = resize_rgba_watermark(
+            src.as_ptr(),
+            w,
+            h,
+            out.as_mut_ptr(),
+            20,
+            15,
+            wm.as_ptr(),
+            4,
+            3,
+            17,
+            12,
+            0.5,
+            1,
+        );
+        assert_eq!(code, RESIZE_ERR_INVALID_PARAM);
+        let code = resize_rgba_watermark(
+            src.as_ptr(),
+            w,
+            h,
+            out.as_mut_ptr(),
+            20,
+            15,
+            wm.as_ptr(),
+            4,
+            3,
+            16,
+            12,
+            0.5,
+            1,
+        );
+        assert_eq!(code, 0);
+    }
+    for y in 0..15 {
+        for x in 0..20 {
+            let i = ((y * 20 + x) * 4) as usize;
+            if !(16..18).contains(&x) || y < 12 {
+                assert_eq!(out[i..i + 4], plain[i..i + 4], "({x}, {y})");
+                continue;
+            }
+            for c in 0..3 {
+                let expected = linear_to_srgb(0.5 + 0.5 * srgb_to_linear(plain[i + c]));
+                assert!(
+                    out[i + c].abs_diff(expected) <= 1,
+                    "({x}, {y}) {c}: {} vs {expected}",
+                    out[i + c]
+                );
+            }
+            assert_eq!(out[i + 3], 255);
+        }
+    }
+}
+
+#[test]
+fn watermark_rejects_overlap_with_destination() {
+    let src = img(40, 30, |x, y| [x as u8, y as u8, 0, 255]);
+    let mut dst = vec![0u8; 20 * 15 * 4];
+    let wm = dst.as_ptr().wrapping_add(4 * 20 * 4);
+    unsafe {
+        let code = resize_rgba_watermark(src.as_ptr(), 40, 30, dst.as_mut_ptr(), 20, 15, wm, 4, 3, 0, 0, 1.0, 1);
+        assert_eq!(code, RESIZE_ERR_OVERLAP);
+    }
+}
+
+#[test]
+fn skip_transparent_matches_full_resize() {
+    let (w, h) = (96u32, 80u32);
+    // Content only in the bottom-right quadrant; the rest is transparent black
+    let src = img(w, h, |x, y| {
+        if x >= 48 && y >= 40 {
+            [(x * 5) as u8, (y * 3) as u8, ((x ^ y) * 7) as u8, (100 + x) as u8]
+        } else {
+            [0, 0, 0, 0]
+        }
+    });
+    for algo in [
+        RESIZE_ALGO_NEAREST,
+        RESIZE_ALGO_BILINEAR,
+        RESIZE_ALGO_LANCZOS,
+        RESIZE_ALGO_AUTO,
+    ] {
+        for (dw, dh) in [(200u32, 170u32), (40, 33), (96, 80), (17, 90)] {
+            // Different fill patterns so skipped pixels must really be written
+            let mut full = vec![9u8; (dw * dh * 4) as usize];
+            let mut skipping = vec![7u8; (dw * dh * 4) as usize];
+            unsafe {
+                assert_eq!(
+                    resize_rgba_skip_transparent(src.as_ptr(), w, h, full.as_mut_ptr(), dw, dh, algo, 0),
+                    0
+                );
+                assert_eq!(get_transparent_skip_count(), 0);
+                let code = resize_rgba_skip_transparent(src.as_ptr(), w, h, skipping.as_mut_ptr(), dw, dh, algo, 1);
+                assert_eq!(code, 0);
+            }
+            assert_eq!(full, skipping, "algo {algo} {dw}x{dh}");
+            if dw >= 40 {
+                assert!(get_transparent_skip_count() > 0, "algo {algo} {dw}x{dh}");
+            }
+        }
+    }
+}
+
+#[test]
+fn tile_fill_repeats_resized_tile() {
+    // Unscaled 2x2 tile repeated across 5x5
+    let src = img(2, 2, |x, y| [(x * 100) as u8, (y * 100) as u8, 7, 255]);
+    let mut dst = vec![0u8; 5 * 5 * 4];
+    unsafe {
+        assert_eq!(
+            resize_rgba_tile_fill(src.as_ptr(), 2, 2, 2, 2, dst.as_mut_ptr(), 5, 5, 0),
+            0
+        )
+    };
+    for y in 0..5 {
+        for x in 0..5 {
+            let i = (y * 5 + x) * 4;
+            assert_eq!(
+                dst[i..i + 4],
+                [(x % 2 * 100) as u8, (y % 2 * 100) as u8, 7, 255],
+                "({x}, {y})"
+            );
+        }
+    }
+
+    // 6x6 source scaled to 3x2 tiles across 7x5, clipped at the right and bottom
+    let big = img(6, 6, |x, y| [(x * 40) as u8, (y * 40) as u8, 0, 255]);
+    let mut tile = vec![0u8; 3 * 2 * 4];
+    let mut dst = vec![0u8; 7 * 5 * 4];
+    unsafe {
+        assert_eq!(resize_with_algorithm(big.as_ptr(), 6, 6, tile.as_mut_ptr(), 3, 2, 2), 0);
+        assert_eq!(
+            resize_rgba_tile_fill(big.as_ptr(), 6, 6, 3, 2, dst.as_mut_ptr(), 7, 5, 2),
+            0
+        );
+        let code = resize_rgba_tile_fill(big.as_ptr(), 6, 6, 0, 2, dst.as_mut_ptr(), 7, 5, 2);
+        assert_eq!(code, RESIZE_ERR_INVALID_SIZE);
+    }
+    for y in 0..5 {
+        for x in 0..7 {
+            let (i, t) = ((y * 7 + x) * 4, ((y % 2) * 3 + x % 3) * 4);
+            assert_eq!(dst[i..i + 4], tile[t..t + 4], "({x}, {y})");
+        }
+    }
+}
+
+#[test]
+fn roi_quality_uses_lanczos_only_inside_the_region() {
+    let src = img(61, 45, |x, y| {
+        [
+            (x * 37 % 256) as u8,
+            (y * 53 % 256) as u8,
+            ((x ^ y) * 9 % 256) as u8,
+            (x * 3 + y) as u8,
+        ]
+    });
+    let (dw, dh) = (40u32, 31u32);
+    let mut dst = vec![0u8; (dw * dh * 4) as usize];
+    let mut bilinear = dst.clone();
+    let mut lanczos = dst.clone();
+    unsafe {
+        assert_eq!(
+            resize_rgba_roi_quality(src.as_ptr(), 61, 45, dst.as_mut_ptr(), dw, dh, 7, 5, 20, 12),
+            0
+        );
+        assert_eq!(
+            resize_rgba_row_order(src.as_ptr(), 61, 45, bilinear.as_mut_ptr(), dw, dh, 1, 0),
+            0
+        );
+        assert_eq!(
+            resize_rgba_row_order(src.as_ptr(), 61, 45, lanczos.as_mut_ptr(), dw, dh, 2, 0),
+            0
+        );
+        let code = resize_rgba_roi_quality(src.as_ptr(), 61, 45, dst.as_mut_ptr(), dw, dh, 30, 5, 11, 12);
+        assert_eq!(code, RESIZE_ERR_INVALID_PARAM);
+    }
+    for y in 0..dh {
+        for x in 0..dw {
+            let i = ((y * dw + x) * 4) as usize;
+            let inside = (7..27).contains(&x) && (5..17).contains(&y);
+            let expected = if inside {
+                &lanczos[i..i + 4]
+            } else {
+                &bilinear[i..i + 4]
+            };
+            assert_eq!(&dst[i..i + 4], expected, "({x},{y})");
+        }
+    }
+}
+
+#[test]
+fn update_rewrites_only_pixels_reached_by_the_dirty_rect() {
+    let before = img(61, 45, |x, y| {
+        [(x * 7) as u8, (y * 11) as u8, ((x * y) % 256) as u8, (x * 3 + y) as u8]
+    });
+    let mut after = before.clone();
+    for y in 20..24 {
+        for x in 30..35 {
+            after[(y * 61 + x) * 4..][..4].copy_from_slice(&[255, 0, 255, 255]);
+        }
+    }
+    let (dw, dh) = (40u32, 31u32);
+    for algo in [RESIZE_ALGO_NEAREST, RESIZE_ALGO_BILINEAR, RESIZE_ALGO_LANCZOS] {
+        let mut prev = vec![0u8; (dw * dh * 4) as usize];
+        let mut full = prev.clone();
+        unsafe {
+            assert_eq!(
+                resize_rgba_row_order(before.as_ptr(), 61, 45, prev.as_mut_ptr(), dw, dh, algo, 0),
+                0
+            );
+            assert_eq!(
+                resize_rgba_row_order(after.as_ptr(), 61, 45, full.as_mut_ptr(), dw, dh, algo, 0),
+                0
+            );
+        }
+        // A pixel far from the dirty rect is left alone even if it holds something else
+        let mut updated = prev.clone();
+        updated[..4].copy_from_slice(&[1, 2, 3, 4]);
+        unsafe {
+            let code = resize_rgba_update(after.as_ptr(), 61, 45, updated.as_mut_ptr(), dw, dh, 30, 20, 5, 4, algo);
+            assert_eq!(code, 0);
+        }
+        assert_eq!(&updated[..4], &[1, 2, 3, 4], "algo {algo}: far pixel was rewritten");
+        updated[..4].copy_from_slice(&prev[..4]);
+        assert_eq!(updated, full, "algo {algo}");
+        for (i, (p, q)) in prev.chunks_exact(4).zip(updated.chunks_exact(4)).enumerate() {
+            if p != q {
+                let (x, y) = (i as u32 % dw, i as u32 / dw);
+                assert!(
+                    (17..=25).contains(&x) && (11..=18).contains(&y),
+                    "algo {algo} ({x},{y})"
+                );
+            }
+        }
+    }
+
+    let mut dst = vec![0u8; (dw * dh * 4) as usize];
+    unsafe {
+        let code = resize_rgba_update(after.as_ptr(), 61, 45, dst.as_mut_ptr(), dw, dh, 58, 0, 4, 1, 1);
+        assert_eq!(code, RESIZE_ERR_INVALID_PARAM);
+    }
+}
+
+#[test]
+fn circle_avatar_masks_center_crop_with_feathered_edge() {
+    // 60x40 source: red in the centered 40x40 square, blue in the side bars
+    let src = img(60, 40, |x, _| {
+        if (10..50).contains(&x) {
+            [255, 0, 0, 255]
+        } else {
+            [0, 0, 255, 255]
+        }
+    });
+    let mut dst = vec![0u8; 32 * 32 * 4];
+    unsafe {
+        assert_eq!(
+            resize_rgba_circle_avatar(src.as_ptr(), 60, 40, dst.as_mut_ptr(), 32, 1.5, 1),
+            0
+        )
+    };
+    let px = |x: usize, y: usize| &dst[(y * 32 + x) * 4..][..4];
+    assert_eq!(px(0, 0)[3], 0);
+    assert_eq!(px(31, 31)[3], 0);
+    assert_eq!(px(16, 16), &[255, 0, 0, 255]);
+    // The edge pixel on the horizontal center line is partially covered
+    let edge = px(0, 16)[3];
+    assert!(edge > 0 && edge < 255, "edge {edge}");
+    // The side bars are cropped away
+    assert!(dst.chunks_exact(4).all(|p| p[3] == 0 || p[2] == 0));
+
+    let mut hard = vec![0u8; 32 * 32 * 4];
+    unsafe {
+        assert_eq!(
+            resize_rgba_circle_avatar(src.as_ptr(), 60, 40, hard.as_mut_ptr(), 32, 0.0, 1),
+            0
+        );
+        assert!(hard.chunks_exact(4).all(|p| p[3] == 0 || p[3] == 255));
+        let code = resize_rgba_circle_avatar(src.as_ptr(), 60, 40, hard.as_mut_ptr(), 32, -1.0, 1);
+        assert_eq!(code, RESIZE_ERR_INVALID_PARAM);
+    }
+}
+
+#[test]
+fn deletterbox_crops_dark_bars_before_resizing() {
+    // 10px near-black bars above and below a 40x30 picture
+    let src = img(40, 50, |x, y| {
+        if (10..40).contains(&y) {
+            [(x * 6) as u8, (y * 5) as u8, 90, 255]
+        } else {
+            [3, 5, 2, 255]
+        }
+    });
+    let inner = img(40, 30, |x, y| [(x * 6) as u8, ((y + 10) * 5) as u8, 90, 255]);
+    let mut cropped = vec![0u8; 20 * 15 * 4];
+    let mut expected = cropped.clone();
+    unsafe {
+        assert_eq!(
+            resize_rgba_deletterbox(src.as_ptr(), 40, 50, cropped.as_mut_ptr(), 20, 15, 16, 1),
+            0
+        );
+        assert_eq!(
+            resize_rgba_row_order(inner.as_ptr(), 40, 30, expected.as_mut_ptr(), 20, 15, 1, 0),
+            0
+        );
+    }
+    assert_eq!(cropped, expected);
+
+    // A threshold below the bar level keeps the bars
+    unsafe {
+        assert_eq!(
+            resize_rgba_deletterbox(src.as_ptr(), 40, 50, cropped.as_mut_ptr(), 20, 15, 2, 1),
+            0
+        )
+    };
+    assert_ne!(cropped, expected);
+
+    // An all-dark image is resized whole
+    let black = img(8, 8, |_, _| [0, 0, 0, 255]);
+    let mut dst = vec![0u8; 4 * 4 * 4];
+    unsafe {
+        assert_eq!(
+            resize_rgba_deletterbox(black.as_ptr(), 8, 8, dst.as_mut_ptr(), 4, 4, 16, 1),
+            0
+        )
+    };
+    assert!(dst.chunks_exact(4).all(|p| p == [0, 0, 0, 255]));
+}
+
+#[test]
+fn over_dst_composites_in_linear_light() {
+    let src = img(8, 8, |x, _| if x < 4 { [255, 0, 0, 128] } else { [0, 0, 0, 0] });
+    let mut dst = img(4, 4, |_, _| [0, 0, 255, 255]);
+    unsafe { assert_eq!(resize_rgba_over_dst(src.as_ptr(), 8, 8, dst.as_mut_ptr(), 4, 4, 0), 0) };
+    for row in dst.chunks_exact(16) {
+        // ~50% red over blue in linear light: both channels near sRGB 188
+        let p = &row[..4];
+        assert!(
+            (p[0] as i32 - 188).abs() <= 1 && p[1] == 0 && (p[2] as i32 - 188).abs() <= 1 && p[3] == 255,
+            "{p:?}"
+        );
+        assert_eq!(&row[4..8], p);
+        // Fully transparent source leaves the backdrop alone
+        assert_eq!(&row[8..16], &[0, 0, 255, 255, 0, 0, 255, 255]);
+    }
+
+    let mut buf = vec![0u8; 256];
+    let bp = buf.as_mut_ptr();
+    unsafe { assert_eq!(resize_rgba_over_dst(bp, 4, 4, bp.add(16), 2, 2, 0), RESIZE_ERR_OVERLAP) };
+}
+
+#[test]
+fn window_resize_never_reads_outside_the_window() {
+    // 10x8 gray window at (5, 4) inside a 24x20 red buffer
+    let full = img(24, 20, |x, y| {
+        if (5..15).contains(&x) && (4..12).contains(&y) {
+            [100, 100 + (x - 5) as u8, 100, 255]
+        } else {
+            [255, 0, 0, 255]
+        }
+    });
+    let window = img(10, 8, |x, _| [100, 100 + x as u8, 100, 255]);
+    for algo in [RESIZE_ALGO_NEAREST, RESIZE_ALGO_BILINEAR, RESIZE_ALGO_LANCZOS] {
+        for (dw, dh) in [(23u32, 17u32), (4, 3)] {
+            let mut dst = vec![0u8; (dw * dh * 4) as usize];
+            let mut expected = dst.clone();
+            unsafe {
+                let code = resize_rgba_window(full.as_ptr(), 24, 20, 5, 4, 10, 8, dst.as_mut_ptr(), dw, dh, algo);
+                assert_eq!(code, 0);
+                let code = resize_rgba_row_order(window.as_ptr(), 10, 8, expected.as_mut_ptr(), dw, dh, algo, 0);
+                assert_eq!(code, 0);
+            }
+            assert_eq!(dst, expected, "algo {algo} {dw}x{dh}");
+            assert!(dst.chunks_exact(4).all(|p| p[0] == 100 && p[2] == 100), "red bled in");
+        }
+    }
+
+    let mut dst = vec![0u8; 6 * 6 * 4];
+    let dp = dst.as_mut_ptr();
+    unsafe {
+        // Window past the right edge, and an empty window
+        let code = resize_rgba_window(full.as_ptr(), 24, 20, 15, 4, 10, 8, dp, 6, 6, 1);
+        assert_eq!(code, RESIZE_ERR_INVALID_PARAM);
+        assert_eq!(
+            resize_rgba_window(full.as_ptr(), 24, 20, 0, 0, 0, 8, dp, 6, 6, 1),
+            RESIZE_ERR_INVALID_PARAM
+        );
+    }
+}
+
+#[test]
+fn grid_overlay() {
+    let src = img(2, 2, |x, y| {
+        [if x == 0 { 200 } else { 40 }, if y == 0 { 90 } else { 160 }, 30, 255]
+    });
+    let mut dst = vec![0u8; 8 * 8 * 4];
+    // Algorithm LANCZOS is overridden: integer upscales stay crisp
+    unsafe {
+        assert_eq!(
+            resize_rgba_grid_overlay(src.as_ptr(), 2, 2, dst.as_mut_ptr(), 8, 8, 255, 255, 255, 64, 2),
+            0
+        );
+    }
+    let blend = |p: &[u8]| -> Vec<i32> {
+        let a = 64.0 / 255.0;
+        p[..3]
+            .iter()
+            .map(|&v| linear_to_srgb(a + srgb_to_linear(v) * (1.0 - a)) as i32)
+            .collect()
+    };
+    let lines = [0usize, 4, 7];
+    for y in 0..8usize {
+        for x in 0..8usize {
+            let o = (y * 8 + x) * 4;
+            let s = ((y / 4) * 2 + x / 4) * 4;
+            let got = &dst[o..o + 4];
+            if lines.contains(&x) || lines.contains(&y) {
+                let want = blend(&src[s..s + 4]);
+                for c in 0..3 {
+                    assert!((got[c] as i32 - want[c]).abs() <= 1, "({x},{y}) {got:?} vs {want:?}");
+                }
+                assert_eq!(got[3], 255);
+            } else {
+                assert_eq!(got, &src[s..s + 4], "({x},{y})");
+            }
+        }
+    }
+}
+
+#[test]
+fn keep_corners() {
+    let (w, h) = (31u32, 23u32);
+    let src = img(w, h, |x, y| match (x, y) {
+        (0, 0) => [255, 0, 0, 255],
+        (30, 0) => [0, 255, 0, 200],
+        (0, 22) => [0, 0, 255, 100],
+        (30, 22) => [250, 250, 10, 255],
+        _ => [(x * 8) as u8, (y * 11) as u8, 90, 255],
+    });
+    let px = |v: &[u8], w: u32, x: u32, y: u32| {
+        let i = ((y * w + x) * 4) as usize;
+        [v[i], v[i + 1], v[i + 2], v[i + 3]]
+    };
+    for algo in 0..4u32 {
+        for (dw, dh) in [(12u32, 9u32), (70, 50), (1, 1), (5, 1)] {
+            let mut a = vec![0u8; (dw * dh * 4) as usize];
+            let mut b = a.clone();
+            unsafe {
+                assert_eq!(
+                    resize_rgba_keep_corners(src.as_ptr(), w, h, a.as_mut_ptr(), dw, dh, algo),
+                    0
+                );
+                assert_eq!(
+                    resize_rgba_row_order(src.as_ptr(), w, h, b.as_mut_ptr(), dw, dh, algo, 0),
+                    0
+                );
+            }
+            let corners = [(0, 0), (dw - 1, 0), (0, dh - 1), (dw - 1, dh - 1)];
+            let src_corners = [(0, 0), (w - 1, 0), (0, h - 1), (w - 1, h - 1)];
+            if dw > 1 && dh > 1 {
+                for (&(dx, dy), &(sx, sy)) in corners.iter().zip(src_corners.iter()) {
+                    assert_eq!(
+                        px(&a, dw, dx, dy),
+                        px(&src, w, sx, sy),
+                        "algo {algo} {dw}x{dh} corner {dx},{dy}"
+                    );
+                }
+            }
+            for y in 0..dh {
+                for x in 0..dw {
+                    if !corners.contains(&(x, y)) {
+                        assert_eq!(px(&a, dw, x, y), px(&b, dw, x, y));
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn mosaic() {
+    let a = img(20, 10, |x, _| [200, (x * 10) as u8, 0, 255]);
+    let b = img(7, 9, |_, y| [0, 100, (y * 25) as u8, 255]);
+    let c = img(30, 30, |x, y| [(x * 8) as u8, (y * 8) as u8, 50, 128]);
+    let sources = [
+        MosaicSource {
+            ptr: a.as_ptr(),
+            w: 20,
+            h: 10,
+        },
+        MosaicSource {
+            ptr: b.as_ptr(),
+            w: 7,
+            h: 9,
+        },
+        MosaicSource {
+            ptr: c.as_ptr(),
+            w: 30,
+            h: 30,
+        },
+    ];
+    let (cw, ch, dw, dh) = (8u32, 6u32, 17u32, 13u32);
+    let mut dst = vec![0xabu8; (dw * dh * 4) as usize];
+    unsafe {
+        assert_eq!(
+            resize_rgba_mosaic(sources.as_ptr(), 3, 2, cw, ch, dst.as_mut_ptr(), dw, dh, 1),
+            0
+        );
+    }
+    let expect: Vec<Vec<u8>> = [(&a, 20u32, 10u32), (&b, 7, 9), (&c, 30, 30)]
+        .iter()
+        .map(|&(s, w, h)| {
+            let mut out = vec![0u8; (cw * ch * 4) as usize];
+            unsafe {
+                assert_eq!(
+                    resize_rgba_row_order(s.as_ptr(), w, h, out.as_mut_ptr(), cw, ch, 1, 0),
+                    0
+                );
+            }
+            out
+        })
+        .collect();
+    for y in 0..dh {
+        for x in 0..dw {
+            let i = ((y * dw + x) * 4) as usize;
+            let (col, row) = (x / cw, y / ch);
+            let cell = (row * 2 + col) as usize;
+            let got = &dst[i..i + 4];
+            if col < 2 && row < 2 && cell < 3 {
+                let j = (((y % ch) * cw + x % cw) * 4) as usize;
+                assert_eq!(got, &expect[cell][j..j + 4], "{x},{y}");
+            } else {
+                assert_eq!(got, [0, 0, 0, 0], "{x},{y}");
+            }
+        }
+    }
+    unsafe {
+        assert_eq!(
+            resize_rgba_mosaic(sources.as_ptr(), 3, 2, 9, ch, dst.as_mut_ptr(), dw, dh, 1),
+            RESIZE_ERR_INVALID_PARAM
+        );
+        assert_eq!(
+            resize_rgba_mosaic(sources.as_ptr(), 3, 2, cw, 7, dst.as_mut_ptr(), dw, dh, 1),
+            RESIZE_ERR_INVALID_PARAM
+        );
+        assert_eq!(
+            resize_rgba_mosaic(sources.as_ptr(), 3, 0, cw, ch, dst.as_mut_ptr(), dw, dh, 1),
+            RESIZE_ERR_INVALID_PARAM
+        );
+        assert_eq!(
+            resize_rgba_mosaic(sources.as_ptr(), 0, 2, cw, ch, dst.as_mut_ptr(), dw, dh, 1),
+            RESIZE_ERR_INVALID_PARAM
+        );
+        // A single row only needs room for the occupied columns
+        assert_eq!(
+            resize_rgba_mosaic(sources.as_ptr(), 2, 5, cw, ch, dst.as_mut_ptr(), dw, dh, 1),
+            0
+        );
+        assert_eq!(
+            resize_rgba_mosaic(std::ptr::null(), 3, 2, cw, ch, dst.as_mut_ptr(), dw, dh, 1),
+            RESIZE_ERR_NULL_PTR
+        );
+        let bad = [MosaicSource {
+            ptr: dst.as_ptr(),
+            w: 2,
+            h: 2,
+        }];
+        assert_eq!(
+            resize_rgba_mosaic(bad.as_ptr(), 1, 1, cw, ch, dst.as_mut_ptr(), dw, dh, 1),
+            RESIZE_ERR_OVERLAP
+        );
+    }
+}
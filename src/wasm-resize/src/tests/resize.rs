@@ -0,0 +1,1027 @@
+//! Tests for the core entry points in lib.rs
+
+use super::*;
+use crate::*;
+
+#[test]
+fn scratch_matches_thread_local_paths() {
+    let src = img(37, 23, |x, y| {
+        [(x * 7) as u8, (y * 11) as u8, ((x * y) % 256) as u8, 255]
+    });
+    for algo in [RESIZE_ALGO_NEAREST, RESIZE_ALGO_BILINEAR, RESIZE_ALGO_LANCZOS] {
+        for (dw, dh) in [(50u32, 40u32), (13, 7), (37, 23)] {
+            let need = scratch_size_needed(37, 23, dw, dh, algo);
+            assert!(need > 0);
+            // usize-backed so the index LUTs are aligned on 64-bit hosts too
+            let mut scratch = vec![0usize; need / std::mem::size_of::<usize>() + 1];
+            let mut a = vec![0u8; (dw * dh * 4) as usize];
+            let mut b = a.clone();
+            unsafe {
+                let scratch_ptr = scratch.as_mut_ptr() as *mut u8;
+                assert_eq!(
+                    resize_rgba_scratch(src.as_ptr(), 37, 23, a.as_mut_ptr(), dw, dh, algo, scratch_ptr, need),
+                    0
+                );
+                assert_eq!(
+                    resize_with_algorithm(src.as_ptr(), 37, 23, b.as_mut_ptr(), dw, dh, algo),
+                    0
+                );
+                assert_eq!(
+                    resize_rgba_scratch(
+                        src.as_ptr(),
+                        37,
+                        23,
+                        a.as_mut_ptr(),
+                        dw,
+                        dh,
+                        algo,
+                        scratch_ptr,
+                        need - 4
+                    ),
+                    RESIZE_ERR_MEMORY
+                );
+            }
+            assert_eq!(a, b, "algo {algo} {dw}x{dh}");
+        }
+    }
+
+    // Only 4-byte aligned on hosts where usize is wider
+    let mut scratch = vec![0usize; 4096];
+    let mut dst = vec![0u8; 13 * 7 * 4];
+    unsafe {
+        let misaligned = (scratch.as_mut_ptr() as *mut u8).add(std::mem::align_of::<usize>() / 2);
+        let code = resize_rgba_scratch(
+            src.as_ptr(),
+            37,
+            23,
+            dst.as_mut_ptr(),
+            13,
+            7,
+            RESIZE_ALGO_BILINEAR,
+            misaligned,
+            16000,
+        );
+        assert_eq!(code, RESIZE_ERR_ALIGNMENT);
+    }
+}
+
+#[test]
+fn scratch_size_rejects_invalid_dimensions() {
+    assert_eq!(scratch_size_needed(0, 10, 10, 10, RESIZE_ALGO_BILINEAR), 0);
+    assert_eq!(scratch_size_needed(10, 10, 65536, 1, RESIZE_ALGO_BILINEAR), 0);
+    assert_eq!(scratch_size_needed(65535, 65535, 10, 10, RESIZE_ALGO_LANCZOS), 0);
+    assert_eq!(scratch_size_needed(10, 10, 65535, 65535, RESIZE_ALGO_NEAREST), 0);
+    assert!(scratch_size_needed(65535, 4096, 10, 10, RESIZE_ALGO_LANCZOS) > 0);
+}
+
+#[test]
+fn multi_derives_preview_from_full_output() {
+    let src = img(64, 48, |x, y| [(x * 4) as u8, (y * 5) as u8, (x ^ y) as u8, 255]);
+    let mut full = vec![0u8; 32 * 24 * 4];
+    let mut preview = vec![0u8; 8 * 6 * 4];
+    let mut expected_full = full.clone();
+    let mut expected_preview = preview.clone();
+    unsafe {
+        let (s, f, p) = (src.as_ptr(), full.as_mut_ptr(), preview.as_mut_ptr());
+        assert_eq!(resize_rgba_multi(s, 64, 48, p, 8, 6, f, 32, 24, RESIZE_ALGO_LANCZOS), 0);
+        assert_eq!(
+            resize_with_algorithm(s, 64, 48, expected_full.as_mut_ptr(), 32, 24, RESIZE_ALGO_LANCZOS),
+            0
+        );
+        let ef = expected_full.as_ptr();
+        assert_eq!(
+            resize_with_algorithm(ef, 32, 24, expected_preview.as_mut_ptr(), 8, 6, RESIZE_ALGO_LANCZOS),
+            0
+        );
+    }
+    assert_eq!(full, expected_full);
+    assert_eq!(preview, expected_preview);
+}
+
+#[test]
+fn multi_larger_preview_resamples_source() {
+    let src = img(20, 20, |x, y| [(x * 12) as u8, (y * 12) as u8, 90, 255]);
+    let mut full = vec![0u8; 10 * 10 * 4];
+    let mut preview = vec![0u8; 15 * 15 * 4];
+    let mut expected = preview.clone();
+    unsafe {
+        let (s, f, p) = (src.as_ptr(), full.as_mut_ptr(), preview.as_mut_ptr());
+        assert_eq!(
+            resize_rgba_multi(s, 20, 20, p, 15, 15, f, 10, 10, RESIZE_ALGO_BILINEAR),
+            0
+        );
+        assert_eq!(
+            resize_with_algorithm(s, 20, 20, expected.as_mut_ptr(), 15, 15, RESIZE_ALGO_BILINEAR),
+            0
+        );
+    }
+    assert_eq!(preview, expected);
+}
+
+#[test]
+fn multi_rejects_overlapping_outputs() {
+    let src = img(16, 16, |x, y| [x as u8, y as u8, 0, 255]);
+    let mut out = vec![0u8; 8 * 8 * 4 + 4 * 4 * 4];
+    let full = out.as_mut_ptr();
+    let preview = unsafe { full.add(8 * 4) };
+    let code = unsafe { resize_rgba_multi(src.as_ptr(), 16, 16, preview, 4, 4, full, 8, 8, RESIZE_ALGO_BILINEAR) };
+    assert_eq!(code, RESIZE_ERR_OVERLAP);
+}
+
+#[test]
+fn dealloc_many_validates_and_skips_empty_entries() {
+    let ptrs = [0u32; 3];
+    let sizes = [16usize, 0, 8];
+    unsafe {
+        assert_eq!(dealloc_many(std::ptr::null(), sizes.as_ptr(), 3), 0);
+        assert_eq!(last_error(), RESIZE_ERR_NULL_PTR);
+        assert_eq!(dealloc_many(ptrs.as_ptr(), sizes.as_ptr(), 3), 0);
+        assert_eq!(last_error(), RESIZE_OK);
+    }
+}
+
+/// Run one of the effective_scale* exports and return the two factors
+fn scales(f: impl FnOnce(*mut f32) -> i32) -> [f32; 2] {
+    let mut out = [0.0f32; 2];
+    assert_eq!(f(out.as_mut_ptr()), 0);
+    out
+}
+
+#[test]
+fn effective_scale_reports_per_axis_factors() {
+    unsafe {
+        assert_eq!(scales(|o| effective_scale(1920, 1080, 640, 360, o)), [3.0, 3.0]);
+        assert_eq!(scales(|o| effective_scale(100, 50, 40, 20, o)), [2.5, 2.5]);
+        assert_eq!(scales(|o| effective_scale(30, 10, 60, 4, o)), [0.5, 2.5]);
+        let mut out = [0.0f32; 2];
+        assert_eq!(effective_scale(0, 10, 5, 5, out.as_mut_ptr()), RESIZE_ERR_INVALID_SIZE);
+        assert_eq!(effective_scale(10, 10, 5, 5, std::ptr::null_mut()), RESIZE_ERR_NULL_PTR);
+    }
+}
+
+#[test]
+fn effective_scale_contain_and_cover() {
+    unsafe {
+        // 1920x1080 in 500x500: contain is width-limited (500x281), cover height-limited (889x500)
+        assert_eq!(
+            scales(|o| effective_scale_contain(1920, 1080, 500, 500, o)),
+            [3.84, 1080.0 / 281.0]
+        );
+        assert_eq!(
+            scales(|o| effective_scale_cover(1920, 1080, 500, 500, o)),
+            [1920.0 / 889.0, 2.16]
+        );
+        // Exact fits agree on both axes
+        assert_eq!(scales(|o| effective_scale_contain(400, 300, 200, 1000, o)), [2.0, 2.0]);
+        assert_eq!(scales(|o| effective_scale_cover(400, 300, 100, 150, o)), [2.0, 2.0]);
+        // Tiny side never rounds to 0
+        assert_eq!(scales(|o| effective_scale_contain(1000, 1, 10, 10, o)), [100.0, 1.0]);
+    }
+}
+
+#[test]
+fn effective_scale_par_keeps_display_aspect() {
+    unsafe {
+        // Anamorphic 720x576 with 64:45 pixels displays as 1024x576
+        assert_eq!(
+            scales(|o| effective_scale_par(720, 576, 64, 45, 1024, o)),
+            [720.0 / 1024.0, 1.0]
+        );
+        // Square pixels reduce to a plain aspect-preserving resize
+        assert_eq!(scales(|o| effective_scale_par(300, 200, 1, 1, 150, o)), [2.0, 2.0]);
+        let mut out = [0.0f32; 2];
+        assert_eq!(
+            effective_scale_par(300, 200, 0, 1, 150, out.as_mut_ptr()),
+            RESIZE_ERR_INVALID_PARAM
+        );
+    }
+}
+
+extern "C" fn tent(x: f32) -> f32 {
+    (1.0 - x.abs()).max(0.0)
+}
+
+#[test]
+fn oversized_intermediate_fails_cleanly() {
+    // Tall source to wide destination: both pass MAX_PIXELS, the 65535 x 65535 Pass-1 buffer does not
+    let src = vec![128u8; 4 * 65535 * 4];
+    let mut dst = vec![0u8; 65535 * 4 * 4];
+    unsafe {
+        assert_eq!(
+            resize_rgba_lanczos(src.as_ptr(), 4, 65535, dst.as_mut_ptr(), 65535, 4),
+            RESIZE_ERR_MEMORY
+        );
+        let code = resize_rgba_custom_kernel(src.as_ptr(), 4, 65535, dst.as_mut_ptr(), 65535, 4, 1.0, tent);
+        assert_eq!(code, RESIZE_ERR_MEMORY);
+
+        // The limit is configurable per thread; 0 restores the default
+        assert_eq!(set_max_intermediate_pixels(100), 0);
+        assert_eq!(
+            resize_rgba_lanczos(src.as_ptr(), 4, 20, dst.as_mut_ptr(), 6, 17),
+            RESIZE_ERR_MEMORY
+        );
+        assert_eq!(resize_rgba_lanczos(src.as_ptr(), 4, 20, dst.as_mut_ptr(), 5, 17), 0);
+        assert_eq!(set_max_intermediate_pixels(0), 0);
+        assert_eq!(resize_rgba_lanczos(src.as_ptr(), 4, 20, dst.as_mut_ptr(), 6, 17), 0);
+    }
+}
+
+#[test]
+fn v1_selection_is_pinned() {
+    const N: u32 = RESIZE_ALGO_NEAREST;
+    const B: u32 = RESIZE_ALGO_BILINEAR;
+    const L: u32 = RESIZE_ALGO_LANCZOS;
+    // Hard-coded from the v1 rules, so any change to the frozen selection fails here
+    let table = [
+        // Upscale, identity and upscale on one axis only
+        ((100u32, 100u32), (200u32, 200u32), L),
+        ((100, 100), (100, 100), L),
+        ((5000, 4000), (6000, 5000), L),
+        ((100, 50), (400, 25), L),
+        ((100, 50), (400, 5), N),
+        // Below 1 MP: Lanczos up to 4x, bilinear up to 8x
+        ((100, 100), (25, 25), L),
+        ((100, 100), (24, 24), B),
+        ((100, 100), (13, 100), B),
+        ((100, 100), (12, 100), N),
+        ((1000, 999), (250, 250), L),
+        ((1000, 999), (200, 200), B),
+        // From exactly 1 MP: Lanczos up to 2x, bilinear up to 4x
+        ((1000, 1000), (500, 500), L),
+        ((1000, 1000), (250, 250), B),
+        ((1000, 1000), (200, 200), N),
+        ((4000, 2499), (2000, 1250), L),
+        ((4000, 2499), (1000, 625), B),
+        // From exactly 10 MP: bilinear up to 2x, never Lanczos when downscaling
+        ((4000, 2500), (2000, 1250), B),
+        ((4000, 2500), (4000, 2499), B),
+        ((4000, 2500), (1000, 625), N),
+    ];
+    for ((sw, sh), (dw, dh), expected) in table {
+        assert_eq!(
+            select_resize_algorithm_v1(sw, sh, dw, dh),
+            expected,
+            "{sw}x{sh} -> {dw}x{dh}"
+        );
+    }
+
+    // resize_rgba_v1 resamples with the selected algorithm
+    let src = img(100, 100, |x, y| {
+        [(x * 7) as u8, (y * 11) as u8, ((x * y) % 256) as u8, 255]
+    });
+    for ((dw, dh), algo) in [((200u32, 200u32), L), ((24, 24), B), ((12, 100), N)] {
+        let mut pinned = vec![0u8; (dw * dh * 4) as usize];
+        let mut direct = pinned.clone();
+        unsafe {
+            assert_eq!(resize_rgba_v1(src.as_ptr(), 100, 100, pinned.as_mut_ptr(), dw, dh), 0);
+            assert_eq!(
+                resize_with_algorithm(src.as_ptr(), 100, 100, direct.as_mut_ptr(), dw, dh, algo),
+                0
+            );
+        }
+        assert_eq!(pinned, direct, "{dw}x{dh}");
+    }
+}
+
+#[test]
+fn nearest_keyed_clears_only_key_pixels() {
+    let src = img(8, 8, |x, y| {
+        if (x + y) % 3 == 0 {
+            [255, 0, 255, 255]
+        } else {
+            [x as u8 * 20, y as u8 * 20, 5, 255]
+        }
+    });
+    let mut keyed = vec![0u8; 20 * 20 * 4];
+    let mut plain = keyed.clone();
+    unsafe {
+        assert_eq!(
+            resize_rgba_nearest_keyed(src.as_ptr(), 8, 8, keyed.as_mut_ptr(), 20, 20, 255, 0, 255),
+            0
+        );
+        assert_eq!(resize_rgba_nearest(src.as_ptr(), 8, 8, plain.as_mut_ptr(), 20, 20), 0);
+    }
+    let mut cleared = 0;
+    for (k, p) in keyed.chunks_exact(4).zip(plain.chunks_exact(4)) {
+        if p[..3] == [255, 0, 255] {
+            assert_eq!(k, [0, 0, 0, 0]);
+            cleared += 1;
+        } else {
+            assert_eq!(k, p);
+        }
+    }
+    assert!(cleared > 0);
+}
+
+#[test]
+fn capped_clamps_upscaled_axes() {
+    let src = img(20, 10, |x, y| [(x * 9) as u8, (y * 20) as u8, 7, 200]);
+    let mut dst = vec![0u8; 40 * 30 * 4];
+    let mut dims = [0u32; 2];
+    unsafe {
+        assert_eq!(
+            resize_rgba_capped(src.as_ptr(), 20, 10, dst.as_mut_ptr(), 40, 30, 2, 1, dims.as_mut_ptr()),
+            0
+        );
+    }
+    // Both axes clamp to the source: a straight copy packed at the source width
+    assert_eq!(dims, [20, 10]);
+    assert_eq!(dst[..src.len()], src[..]);
+
+    let mut reference = vec![0u8; 20 * 5 * 4];
+    unsafe {
+        assert_eq!(
+            resize_rgba_capped(src.as_ptr(), 20, 10, dst.as_mut_ptr(), 40, 5, 1, 1, dims.as_mut_ptr()),
+            0
+        );
+        assert_eq!(dims, [20, 5]);
+        assert_eq!(
+            resize_with_algorithm(src.as_ptr(), 20, 10, reference.as_mut_ptr(), 20, 5, 1),
+            0
+        );
+        assert_eq!(dst[..reference.len()], reference[..]);
+        assert_eq!(
+            resize_rgba_capped(src.as_ptr(), 20, 10, dst.as_mut_ptr(), 40, 30, 1, 0, dims.as_mut_ptr()),
+            0
+        );
+        assert_eq!(dims, [40, 30]);
+        let code = resize_rgba_capped(src.as_ptr(), 20, 10, dst.as_mut_ptr(), 40, 30, 1, 2, dims.as_mut_ptr());
+        assert_eq!(code, RESIZE_ERR_INVALID_PARAM);
+    }
+}
+
+#[test]
+fn bottom_up_source_matches_top_down() {
+    let (w, h) = (31u32, 17u32);
+    let top_down = img(w, h, |x, y| {
+        [(x * 8) as u8, (y * 15) as u8, ((x * y) % 256) as u8, (200 + y) as u8]
+    });
+    let bottom_up: Vec<u8> = top_down.chunks_exact(w as usize * 4).rev().flatten().copied().collect();
+    for algo in [
+        RESIZE_ALGO_NEAREST,
+        RESIZE_ALGO_BILINEAR,
+        RESIZE_ALGO_LANCZOS,
+        RESIZE_ALGO_AUTO,
+    ] {
+        for (dw, dh) in [(50u32, 40u32), (13, 7), (31, 17)] {
+            let mut a = vec![0u8; (dw * dh * 4) as usize];
+            let mut b = a.clone();
+            unsafe {
+                assert_eq!(
+                    resize_rgba_row_order(top_down.as_ptr(), w, h, a.as_mut_ptr(), dw, dh, algo, 0),
+                    0
+                );
+                assert_eq!(
+                    resize_rgba_row_order(bottom_up.as_ptr(), w, h, b.as_mut_ptr(), dw, dh, algo, 1),
+                    0
+                );
+            }
+            assert_eq!(a, b, "algo {algo} {dw}x{dh}");
+        }
+    }
+    let mut dst = vec![0u8; 4 * 4 * 4];
+    let code = unsafe { resize_rgba_row_order(top_down.as_ptr(), w, h, dst.as_mut_ptr(), 4, 4, 1, 2) };
+    assert_eq!(code, RESIZE_ERR_INVALID_PARAM);
+}
+
+#[test]
+fn binary_alpha_snaps_at_threshold() {
+    // Soft horizontal alpha ramp 0..255 over 16 px, upscaled 4x
+    let src = img(16, 4, |x, _| [200, 100, 50, (x * 17) as u8]);
+    let mut snapped = vec![0u8; 64 * 16 * 4];
+    let mut plain = snapped.clone();
+    unsafe {
+        assert_eq!(
+            resize_rgba_binary_alpha(src.as_ptr(), 16, 4, snapped.as_mut_ptr(), 64, 16, 128, 1),
+            0
+        );
+        assert_eq!(
+            resize_with_algorithm(src.as_ptr(), 16, 4, plain.as_mut_ptr(), 64, 16, 1),
+            0
+        );
+    }
+    for (s, p) in snapped.chunks_exact(4).zip(plain.chunks_exact(4)) {
+        assert_eq!(s[..3], p[..3]);
+        assert_eq!(s[3], if p[3] < 128 { 0 } else { 255 });
+    }
+}
+
+#[test]
+fn assume_premultiplied_does_not_darken_edges() {
+    // Opaque red fading out, stored premultiplied: r == a
+    let src = img(16, 8, |x, _| {
+        let a = (x * 17) as u8;
+        [a, 0, 0, a]
+    });
+    for algo in [RESIZE_ALGO_NEAREST, RESIZE_ALGO_BILINEAR, RESIZE_ALGO_LANCZOS] {
+        let mut premul = vec![0u8; 40 * 20 * 4];
+        let mut straight = premul.clone();
+        unsafe {
+            assert_eq!(
+                resize_rgba_assume_premultiplied(src.as_ptr(), 16, 8, premul.as_mut_ptr(), 40, 20, algo),
+                0
+            );
+            assert_eq!(
+                resize_rgba_auto_alpha(src.as_ptr(), 16, 8, straight.as_mut_ptr(), 40, 20),
+                0
+            );
+        }
+        let mut darker = 0;
+        for (p, s) in premul.chunks_exact(4).zip(straight.chunks_exact(4)) {
+            assert!(p[0] <= p[3] && p[1] == 0 && p[2] == 0, "{p:?}");
+            assert!(p[0].abs_diff(p[3]) <= 1, "{p:?}");
+            // Composited over black, the straight-alpha path multiplies r by alpha again
+            let straight_over_black = s[0] as u32 * s[3] as u32 / 255;
+            if straight_over_black + 8 < p[0] as u32 {
+                darker += 1;
+            }
+        }
+        assert!(darker > 100, "algo {algo}: {darker}");
+    }
+}
+
+#[test]
+fn dst_stride_mirrors_and_pads_rows() {
+    let src = img(37, 23, |x, y| {
+        [(x * 7) as u8, (y * 11) as u8, ((x * y) % 256) as u8, (x * 3 + y) as u8]
+    });
+    for (dw, dh) in [(50u32, 40u32), (13, 7), (37, 23), (120, 90)] {
+        let row = (dw * 4) as usize;
+        let size = row * dh as usize;
+        let mut normal = vec![0u8; size];
+        unsafe { assert_eq!(resize_rgba(src.as_ptr(), 37, 23, normal.as_mut_ptr(), dw, dh), 0) };
+
+        // Negative stride from the last row writes the image bottom-to-top
+        let mut mirrored = vec![0u8; size];
+        let last = mirrored.as_mut_ptr().wrapping_add(size - row);
+        unsafe {
+            assert_eq!(
+                resize_rgba_dst_stride(src.as_ptr(), 37, 23, last, dw, dh, -(row as isize), size),
+                0
+            )
+        };
+        let flipped: Vec<u8> = normal.chunks_exact(row).rev().flatten().copied().collect();
+        assert_eq!(mirrored, flipped, "{dw}x{dh}");
+
+        // A padded positive stride matches row by row and leaves the padding alone
+        let pad = row + 8;
+        let mut padded = vec![0xAAu8; pad * dh as usize];
+        unsafe {
+            let code = resize_rgba_dst_stride(
+                src.as_ptr(),
+                37,
+                23,
+                padded.as_mut_ptr(),
+                dw,
+                dh,
+                pad as isize,
+                pad * dh as usize,
+            );
+            assert_eq!(code, 0);
+        }
+        for (y, padded_row) in padded.chunks_exact(pad).enumerate() {
+            assert_eq!(padded_row[..row], normal[y * row..(y + 1) * row]);
+            assert!(padded_row[row..].iter().all(|&b| b == 0xAA));
+        }
+
+        unsafe {
+            let code = resize_rgba_dst_stride(src.as_ptr(), 37, 23, last, dw, dh, -(row as isize), size - 4);
+            assert_eq!(code, RESIZE_ERR_MEMORY);
+            let code = resize_rgba_dst_stride(src.as_ptr(), 37, 23, last, dw, dh, -(row as isize) + 4, size);
+            assert_eq!(code, RESIZE_ERR_INVALID_PARAM);
+        }
+    }
+}
+
+#[test]
+fn dst_stride_rejects_extent_reaching_into_source() {
+    let src = img(37, 23, |x, y| [x as u8, y as u8, 0, 255]);
+    let mut both = vec![0u32; 37 * 23 + 13 * 7];
+    let base = both.as_mut_ptr() as *mut u8;
+    unsafe {
+        std::ptr::copy_nonoverlapping(src.as_ptr(), base, src.len());
+        let dst_last = base.add(37 * 23 * 4 + 13 * 4 * 6);
+        assert_eq!(
+            resize_rgba_dst_stride(base, 37, 23, dst_last, 13, 7, -(13 * 4), 13 * 7 * 4),
+            0
+        );
+        let code = resize_rgba_dst_stride(base, 37, 23, dst_last, 13, 7, -(13 * 4 * 2), 13 * 13 * 4);
+        assert_eq!(code, RESIZE_ERR_OVERLAP);
+    }
+}
+
+#[test]
+fn last_completed_row_tracks_final_rows() {
+    use std::cell::RefCell;
+    thread_local! { static SEEN: RefCell<Vec<(u32, u32)>> = const { RefCell::new(Vec::new()) }; }
+    extern "C" fn sink(y: u32, _p: *const u8, _l: usize) {
+        SEEN.with(|s| s.borrow_mut().push((y, get_last_completed_row())));
+    }
+    let src = img(37, 23, |x, y| [(x * 7) as u8, (y * 11) as u8, 3, 255]);
+    for algo in 0..4u32 {
+        let mut d = vec![0u8; 50 * 40 * 4];
+        unsafe {
+            assert_eq!(
+                resize_rgba_row_order(src.as_ptr(), 37, 23, d.as_mut_ptr(), 50, 40, algo, 0),
+                0
+            )
+        };
+        assert_eq!(get_last_completed_row(), 39);
+        SEEN.with(|s| s.borrow_mut().clear());
+        unsafe { assert_eq!(resize_rgba_row_sink(src.as_ptr(), 37, 23, 20, 9, algo, sink), 0) };
+        // The sink sees each row before it is marked, in order and exactly once
+        SEEN.with(|s| {
+            let seen = s.borrow();
+            assert_eq!(seen.len(), 9);
+            assert!(seen
+                .iter()
+                .enumerate()
+                .all(|(i, &(y, last))| y == i as u32 && last == y.wrapping_sub(1)));
+        });
+        assert_eq!(get_last_completed_row(), 8);
+    }
+    let mut d = vec![0u8; 16 * 9 * 4];
+    unsafe {
+        assert_eq!(resize_rgba_gamma_area(src.as_ptr(), 37, 23, d.as_mut_ptr(), 16, 9), 0);
+        assert_eq!(get_last_completed_row(), 8);
+        assert_eq!(
+            resize_rgba_lanczos_overshoot(src.as_ptr(), 37, 23, d.as_mut_ptr(), 16, 9, 1),
+            0
+        );
+        assert_eq!(get_last_completed_row(), 8);
+        assert_eq!(
+            resize_rgba_weighted(src.as_ptr(), 37, 23, src.as_ptr(), d.as_mut_ptr(), 16, 9),
+            0
+        );
+        assert_eq!(get_last_completed_row(), 8);
+        assert_eq!(
+            resize_rgba_clamp_range(src.as_ptr(), 37, 23, d.as_mut_ptr(), 16, 9, 16, 235, 2),
+            0
+        );
+        assert_eq!(get_last_completed_row(), 8);
+        // Rejected before any row is written
+        assert_eq!(
+            resize_rgba(src.as_ptr(), 37, 23, d.as_mut_ptr(), 16, 0),
+            RESIZE_ERR_INVALID_SIZE
+        );
+    }
+    assert_eq!(get_last_completed_row(), RESIZE_NO_ROW_COMPLETED);
+}
+
+#[test]
+fn cancelled_intermediate_pass_reports_no_rows() {
+    let src = img(40, 80, |x, y| [(x * 5) as u8, (y * 3) as u8, 200, 255]);
+    let mut full = vec![0u8; 40 * 64 * 4];
+    let mut d = full.clone();
+    unsafe {
+        assert_eq!(
+            resize_rgba_row_order(src.as_ptr(), 40, 80, full.as_mut_ptr(), 40, 64, 1, 0),
+            0
+        );
+        request_cancel();
+        // A plain resize stops after a polled row; everything up to it is final
+        assert_eq!(
+            resize_rgba_row_order(src.as_ptr(), 40, 80, d.as_mut_ptr(), 40, 64, 1, 0),
+            RESIZE_ERR_CANCELLED
+        );
+        let last = get_last_completed_row();
+        assert!(last < 63);
+        let done = (last as usize + 1) * 40 * 4;
+        assert_eq!(d[..done], full[..done]);
+        // The clamp never ran, so none of the resized rows hold final output
+        let code = resize_rgba_clamp_range(src.as_ptr(), 40, 80, d.as_mut_ptr(), 40, 64, 16, 235, 1);
+        assert_eq!(code, RESIZE_ERR_CANCELLED);
+        assert_eq!(get_last_completed_row(), RESIZE_NO_ROW_COMPLETED);
+        clear_cancel();
+    }
+}
+
+#[test]
+fn ratio_half_matches_exact_box_average() {
+    let src = img(40, 30, |x, y| {
+        [
+            (x * 37 % 256) as u8,
+            (y * 53 % 256) as u8,
+            ((x ^ y) * 9 % 256) as u8,
+            (x * 3 + y) as u8,
+        ]
+    });
+    let mut dst = vec![0u8; 20 * 15 * 4];
+    unsafe {
+        assert_eq!(
+            resize_rgba_ratio(src.as_ptr(), 40, 30, dst.as_mut_ptr(), 20, 15, 1, 2, 1),
+            0
+        )
+    };
+    let s = |x: usize, y: usize, c: usize| src[(y * 40 + x) * 4 + c] as u32;
+    for y in 0..15usize {
+        for x in 0..20usize {
+            for c in 0..4 {
+                let sum =
+                    s(2 * x, 2 * y, c) + s(2 * x + 1, 2 * y, c) + s(2 * x, 2 * y + 1, c) + s(2 * x + 1, 2 * y + 1, c);
+                assert_eq!(dst[(y * 20 + x) * 4 + c] as u32, (sum + 2) / 4, "({x},{y}) {c}");
+            }
+        }
+    }
+
+    // Nearest 2x upscale duplicates pixels
+    let mut up = vec![0u8; 80 * 60 * 4];
+    unsafe {
+        assert_eq!(
+            resize_rgba_ratio(src.as_ptr(), 40, 30, up.as_mut_ptr(), 80, 60, 2, 1, 0),
+            0
+        )
+    };
+    for y in 0..60usize {
+        for x in 0..80usize {
+            assert_eq!(&up[(y * 80 + x) * 4..][..4], &src[((y / 2) * 40 + x / 2) * 4..][..4]);
+        }
+    }
+}
+
+#[test]
+fn ratio_is_stable_and_validates_params() {
+    let src = img(40, 30, |x, y| {
+        [(x * 37 % 256) as u8, (y * 53 % 256) as u8, (x * 3 + y) as u8, 255]
+    });
+    let mut a = vec![0u8; 28 * 21 * 4];
+    let mut b = a.clone();
+    unsafe {
+        for algo in [RESIZE_ALGO_NEAREST, RESIZE_ALGO_BILINEAR] {
+            assert_eq!(
+                resize_rgba_ratio(src.as_ptr(), 40, 30, a.as_mut_ptr(), 28, 21, 7, 10, algo),
+                0
+            );
+            assert_eq!(
+                resize_rgba_ratio(src.as_ptr(), 40, 30, b.as_mut_ptr(), 28, 21, 7, 10, algo),
+                0
+            );
+            assert_eq!(a, b);
+            // An unreduced ratio maps identically
+            assert_eq!(
+                resize_rgba_ratio(src.as_ptr(), 40, 30, b.as_mut_ptr(), 28, 21, 700, 1000, algo),
+                0
+            );
+            assert_eq!(a, b);
+        }
+        let mut up = vec![0u8; 100 * 75 * 4];
+        assert_eq!(
+            resize_rgba_ratio(src.as_ptr(), 40, 30, up.as_mut_ptr(), 100, 75, 5, 2, 1),
+            0
+        );
+        for (num, den, algo) in [(1, 2, 1), (7, 0, 1), (0, 7, 1), (7, 10, 2)] {
+            let code = resize_rgba_ratio(src.as_ptr(), 40, 30, a.as_mut_ptr(), 28, 21, num, den, algo);
+            assert_eq!(code, RESIZE_ERR_INVALID_PARAM, "{num}/{den} algo {algo}");
+        }
+    }
+}
+
+#[test]
+fn ratio_with_large_coprime_terms_does_not_overflow() {
+    // 2147483647 / 4294967291 are both prime, so nothing reduces and the Q16 source
+    // position of the last columns exceeds i64
+    let (num, den) = (2_147_483_647u32, 4_294_967_291u32);
+    let src = img(40000, 1, |x, _| [(x % 251) as u8, (x / 157) as u8, 0, 255]);
+    let mut dst = vec![0u8; 20000 * 4];
+    let mut half = dst.clone();
+    unsafe {
+        assert_eq!(
+            resize_rgba_ratio(src.as_ptr(), 40000, 1, dst.as_mut_ptr(), 20000, 1, num, den, 1),
+            0
+        );
+        assert_eq!(
+            resize_rgba_ratio(src.as_ptr(), 40000, 1, half.as_mut_ptr(), 20000, 1, 1, 2, 1),
+            0
+        );
+    }
+    assert!(max_diff(&dst, &half) <= 1);
+}
+
+#[test]
+fn clamp_range_bounds_lanczos_overshoot() {
+    // Full-range black/white edge plus a thin line, upscaled with Lanczos
+    let src = img(16, 8, |x, _| {
+        if x < 8 || x == 12 {
+            [0, 0, 0, 255]
+        } else {
+            [255, 255, 255, 255]
+        }
+    });
+    let mut dst = vec![0u8; 53 * 8 * 4];
+    let mut plain = dst.clone();
+    unsafe {
+        assert_eq!(
+            resize_rgba_clamp_range(src.as_ptr(), 16, 8, dst.as_mut_ptr(), 53, 8, 16, 235, 2),
+            0
+        );
+        assert_eq!(
+            resize_rgba_row_order(src.as_ptr(), 16, 8, plain.as_mut_ptr(), 53, 8, 2, 0),
+            0
+        );
+    }
+    assert!(plain.chunks_exact(4).any(|p| p[0] < 16) && plain.chunks_exact(4).any(|p| p[0] > 235));
+    for p in dst.chunks_exact(4) {
+        assert!(p[..3].iter().all(|&c| (16..=235).contains(&c)) && p[3] == 255, "{p:?}");
+    }
+
+    unsafe {
+        let code = resize_rgba_clamp_range(src.as_ptr(), 16, 8, dst.as_mut_ptr(), 53, 8, 200, 200, 2);
+        assert_eq!(code, RESIZE_ERR_INVALID_PARAM);
+    }
+}
+
+#[test]
+fn aligned_stride_places_rows_at_aligned_offsets() {
+    let src = img(37, 23, |x, y| {
+        [(x * 7) as u8, (y * 11) as u8, ((x * y) % 256) as u8, (x * 3 + y) as u8]
+    });
+    // Rows over 256 bytes (odd widths past 64 px) round up to the next multiple: 100 px -> 512
+    let cases = [
+        (13u32, 9u32, 256u32, 2u32, 256usize),
+        (100, 7, 256, 1, 512),
+        (65, 4, 256, 2, 512),
+        (70, 40, 512, 1, 512),
+        (64, 5, 256, 0, 256),
+        (13, 3, 4, 1, 52),
+        (13, 3, 32, 1, 64),
+    ];
+    for (dw, dh, align, algo, stride) in cases {
+        let row = (dw * 4) as usize;
+        let mut packed = vec![0u8; row * dh as usize];
+        // u32-backed so the destination is 4-byte aligned
+        let mut buf = vec![0xAAAA_AAAAu32; stride * dh as usize / 4];
+        let dp = buf.as_mut_ptr() as *mut u8;
+        unsafe {
+            assert_eq!(
+                resize_rgba_row_order(src.as_ptr(), 37, 23, packed.as_mut_ptr(), dw, dh, algo, 0),
+                0
+            );
+            assert_eq!(
+                resize_rgba_aligned_stride(src.as_ptr(), 37, 23, dp, dw, dh, align, algo),
+                0
+            );
+        }
+        let bytes: Vec<u8> = buf.iter().flat_map(|v| v.to_ne_bytes()).collect();
+        for y in 0..dh as usize {
+            assert_eq!(&bytes[y * stride..y * stride + row], &packed[y * row..(y + 1) * row]);
+            assert!(bytes[y * stride + row..(y + 1) * stride].iter().all(|&b| b == 0xAA));
+        }
+    }
+
+    let mut buf = vec![0u32; 256];
+    let dp = buf.as_mut_ptr() as *mut u8;
+    unsafe {
+        // Not a power of two, and powers of two below one pixel
+        for align in [100, 2, 1, 0] {
+            assert_eq!(
+                resize_rgba_aligned_stride(src.as_ptr(), 37, 23, dp, 13, 2, align, 1),
+                RESIZE_ERR_INVALID_PARAM
+            );
+        }
+    }
+}
+
+#[test]
+fn uniform_source_fills_destination_directly() {
+    let color = [12u8, 200, 77, 130];
+    let big = img(1500, 1000, |_, _| color);
+    for (dw, dh) in [(3000u32, 2000u32), (15, 10), (1499, 1001), (1, 1)] {
+        let mut dst = vec![0u8; (dw * dh * 4) as usize];
+        unsafe { assert_eq!(resize_rgba(big.as_ptr(), 1500, 1000, dst.as_mut_ptr(), dw, dh), 0) };
+        assert!(dst.chunks_exact(4).all(|p| p == color));
+        assert_eq!(get_last_completed_row(), dh - 1);
+    }
+
+    // Small uniform sources agree with the full resampling paths
+    let small = img(37, 23, |_, _| color);
+    for algo in [RESIZE_ALGO_NEAREST, RESIZE_ALGO_BILINEAR, RESIZE_ALGO_LANCZOS] {
+        for (dw, dh) in [(50u32, 40u32), (13, 7)] {
+            let mut dst = vec![0u8; (dw * dh * 4) as usize];
+            unsafe {
+                assert_eq!(
+                    resize_rgba_row_order(small.as_ptr(), 37, 23, dst.as_mut_ptr(), dw, dh, algo, 0),
+                    0
+                )
+            };
+            assert!(dst.chunks_exact(4).all(|p| p == color), "algo {algo}");
+        }
+    }
+
+    // One differing pixel at the very end takes the regular path
+    let mut almost = img(61, 45, |_, _| color);
+    let n = almost.len();
+    almost[n - 4..].copy_from_slice(&[255, 0, 0, 255]);
+    let mut dst = vec![0u8; 120 * 90 * 4];
+    unsafe { assert_eq!(resize_rgba(almost.as_ptr(), 61, 45, dst.as_mut_ptr(), 120, 90), 0) };
+    assert_eq!(&dst[..4], &color);
+    assert!(dst[dst.len() - 4..] != color);
+}
+
+#[test]
+fn batch_budget_scales_entries_to_fit() {
+    let a = img(80, 60, |x, y| [x as u8, y as u8, 9, 255]);
+    let b = img(50, 50, |x, y| [y as u8, x as u8, 200, 255]);
+    let mut dst_a = vec![0u8; 400 * 300 * 4];
+    let mut dst_b = vec![0u8; 200 * 200 * 4];
+    let entry = |src: &[u8], w, h, dst: &mut [u8], dst_w, dst_h| ResizeBatchEntry {
+        src_ptr: src.as_ptr(),
+        src_w: w,
+        src_h: h,
+        dst_ptr: dst.as_mut_ptr(),
+        dst_w,
+        dst_h,
+        algorithm: RESIZE_ALGO_BILINEAR,
+        result: -1,
+    };
+    let mut entries = [
+        entry(&a, 80, 60, &mut dst_a, 400, 300),
+        entry(&b, 50, 50, &mut dst_b, 200, 200),
+    ];
+
+    // 160000 requested pixels against a budget of 40000: roughly half on each axis
+    unsafe { assert_eq!(resize_rgba_batch_budget(entries.as_mut_ptr(), 2, 40_000), 0) };
+    let total: u64 = entries.iter().map(|e| e.dst_w as u64 * e.dst_h as u64).sum();
+    assert!(total <= 40_000 && total > 38_000, "total {total}");
+    assert_eq!((entries[0].dst_w, entries[0].dst_h), (200, 150));
+    assert_eq!((entries[1].dst_w, entries[1].dst_h), (100, 100));
+    assert!(entries.iter().all(|e| e.result == 0));
+    let mut expected = vec![0u8; 100 * 100 * 4];
+    unsafe {
+        assert_eq!(
+            resize_rgba_row_order(b.as_ptr(), 50, 50, expected.as_mut_ptr(), 100, 100, 1, 0),
+            0
+        )
+    };
+    assert_eq!(&dst_b[..expected.len()], &expected[..]);
+
+    // Within budget the sizes are kept; an impossible budget is rejected
+    unsafe {
+        assert_eq!(resize_rgba_batch_budget(entries.as_mut_ptr(), 2, 1_000_000), 0);
+        assert_eq!((entries[0].dst_w, entries[1].dst_w), (200, 100));
+        assert_eq!(
+            resize_rgba_batch_budget(entries.as_mut_ptr(), 2, 1),
+            RESIZE_ERR_INVALID_PARAM
+        );
+    }
+}
+
+#[cfg(not(target_feature = "simd128"))]
+#[test]
+fn simd_flag_scalar_build() {
+    let src = img(16, 16, |x, y| [x as u8, y as u8, 0, 255]);
+    let mut dst = vec![0u8; 32 * 32 * 4];
+    unsafe {
+        assert_eq!(resize_rgba_nearest(src.as_ptr(), 16, 16, dst.as_mut_ptr(), 32, 32), 0);
+        assert_eq!(last_resize_used_simd(), 0);
+        assert_eq!(resize_rgba_lanczos(src.as_ptr(), 16, 16, dst.as_mut_ptr(), 32, 32), 0);
+        assert_eq!(last_resize_used_simd(), 0);
+        assert_eq!(resize_rgba_gamma_area(src.as_ptr(), 16, 16, dst.as_mut_ptr(), 8, 8), 0);
+        assert_eq!(last_resize_used_simd(), 0);
+    }
+}
+
+#[cfg(target_feature = "simd128")]
+#[test]
+fn simd_flag_follows_alignment() {
+    let src = img(16, 16, |x, y| [x as u8, y as u8, 0, 255]);
+    let dst_len = 32 * 32 * 4;
+    let mut src_buf = vec![0u8; src.len() + 17];
+    let mut dst_buf = vec![0u8; dst_len + 17];
+    let src_off = src_buf.as_ptr().align_offset(16);
+    let dst_off = dst_buf.as_ptr().align_offset(16);
+
+    // Nearest copies through SIMD only when both rows are 16-byte aligned; one byte
+    // off, no source pixel lines up and every copy falls back to scalar
+    for (shift, expected) in [(0, 1), (1, 0)] {
+        let s = &mut src_buf[src_off + shift..][..src.len()];
+        s.copy_from_slice(&src);
+        let d = &mut dst_buf[dst_off + shift..][..dst_len];
+        unsafe {
+            assert_eq!(resize_rgba_nearest(s.as_ptr(), 16, 16, d.as_mut_ptr(), 32, 32), 0);
+        }
+        assert_eq!(last_resize_used_simd(), expected, "shift {shift}");
+    }
+
+    // The Lanczos and gamma output stages pack through SIMD whatever the alignment
+    let s = &src_buf[src_off + 1..][..src.len()];
+    let d = &mut dst_buf[dst_off + 1..][..dst_len];
+    unsafe {
+        assert_eq!(resize_rgba_lanczos(s.as_ptr(), 16, 16, d.as_mut_ptr(), 32, 32), 0);
+        assert_eq!(last_resize_used_simd(), 1);
+        assert_eq!(resize_rgba_gamma_area(s.as_ptr(), 16, 16, d.as_mut_ptr(), 8, 8), 0);
+        assert_eq!(last_resize_used_simd(), 1);
+    }
+}
+
+#[test]
+fn deterministic_accumulation_flag() {
+    // Host builds are scalar, so both modes must take the same blend
+    let src = img(97, 61, |x, y| {
+        [
+            (x.wrapping_mul(2654435761u32.wrapping_mul(y + 1)) >> 13) as u8,
+            (x * 3) as u8,
+            (y * 5) as u8,
+            255,
+        ]
+    });
+    let mut a = vec![0u8; 131 * 89 * 4];
+    let mut b = a.clone();
+    unsafe {
+        assert_eq!(set_deterministic_accumulation(1), 0);
+        assert_eq!(
+            resize_rgba_row_order(src.as_ptr(), 97, 61, a.as_mut_ptr(), 131, 89, 1, 0),
+            0
+        );
+        assert_eq!(set_deterministic_accumulation(0), 0);
+        assert_eq!(
+            resize_rgba_row_order(src.as_ptr(), 97, 61, b.as_mut_ptr(), 131, 89, 1, 0),
+            0
+        );
+    }
+    assert_eq!(a, b);
+    assert_eq!(set_deterministic_accumulation(2), RESIZE_ERR_INVALID_PARAM);
+}
+
+extern "C" fn cancel_at_row_10(y: u32, _p: *const u8, _len: usize) {
+    CANCEL_SEEN.with(|c| c.set(y));
+    if y == 10 {
+        request_cancel();
+    }
+}
+thread_local! { static CANCEL_SEEN: std::cell::Cell<u32> = const { std::cell::Cell::new(0) }; }
+
+#[test]
+fn cancel_flag() {
+    let src = img(50, 40, |x, y| [(x * 5) as u8, (y * 6) as u8, 7, 255]);
+    for algo in 0..3u32 {
+        clear_cancel();
+        unsafe {
+            assert_eq!(
+                resize_rgba_row_sink(src.as_ptr(), 50, 40, 60, 100, algo, cancel_at_row_10),
+                RESIZE_ERR_CANCELLED
+            );
+        }
+        // Stopped at the next poll after the sink raised the flag
+        assert_eq!(CANCEL_SEEN.with(|c| c.get()), 31);
+        assert_eq!(get_last_completed_row(), 31);
+        let msg = unsafe { std::ffi::CStr::from_ptr(get_last_error() as *const _) };
+        assert_eq!(msg.to_str().unwrap(), "Cancelled");
+        // Sticky until cleared; the first row is polled, so short resizes stop too
+        let mut dst = vec![0u8; 60 * 100 * 4];
+        unsafe {
+            assert_eq!(
+                resize_rgba_row_order(src.as_ptr(), 50, 40, dst.as_mut_ptr(), 60, 100, algo, 0),
+                RESIZE_ERR_CANCELLED
+            );
+            assert_eq!(
+                resize_rgba_row_order(src.as_ptr(), 50, 40, dst.as_mut_ptr(), 60, 3, algo, 0),
+                RESIZE_ERR_CANCELLED
+            );
+        }
+        clear_cancel();
+        unsafe {
+            assert_eq!(
+                resize_rgba_row_order(src.as_ptr(), 50, 40, dst.as_mut_ptr(), 60, 100, algo, 0),
+                0
+            )
+        };
+    }
+}
+
+#[test]
+fn cancel_reaches_every_row_loop() {
+    let src = img(50, 40, |x, y| [(x * 5) as u8, (y * 6) as u8, 7, 255]);
+    let mut dst = vec![0u8; 20 * 16 * 4];
+    let d = dst.as_mut_ptr();
+    let s = src.as_ptr();
+    request_cancel();
+    unsafe {
+        // Lanczos stops in Pass 1, before any destination row exists
+        assert_eq!(resize_rgba_lanczos(s, 50, 40, d, 20, 16), RESIZE_ERR_CANCELLED);
+        assert_eq!(get_last_completed_row(), RESIZE_NO_ROW_COMPLETED);
+        assert_eq!(resize_rgba_ratio(s, 50, 40, d, 20, 16, 2, 5, 1), RESIZE_ERR_CANCELLED);
+        assert_eq!(resize_rgba_gamma_bilinear(s, 50, 40, d, 20, 16), RESIZE_ERR_CANCELLED);
+        assert_eq!(resize_rgba_gamma_area(s, 50, 40, d, 20, 16), RESIZE_ERR_CANCELLED);
+        assert_eq!(resize_rgba_auto_alpha(s, 50, 40, d, 20, 16), RESIZE_ERR_CANCELLED);
+        assert_eq!(resize_rgba_fixedpoint(s, 50, 40, d, 20, 16, 2), RESIZE_ERR_CANCELLED);
+        assert_eq!(
+            resize_rgba_lanczos_blur(s, 50, 40, d, 20, 16, 1.5),
+            RESIZE_ERR_CANCELLED
+        );
+        assert_eq!(resize_rgba_flipped(s, 50, 40, d, 20, 16, 1, 1, 2), RESIZE_ERR_CANCELLED);
+        assert_eq!(resize_rgba_nearest(s, 50, 40, d, 20, 1), RESIZE_ERR_CANCELLED);
+        // Transforms and single-plane paths
+        assert_eq!(resize_rotate_rgba(s, 50, 40, d, 20, 16, 0.3, 2), RESIZE_ERR_CANCELLED);
+        assert_eq!(get_last_completed_row(), 0);
+        assert_eq!(resize_transpose_rgba(s, 50, 40, d, 20, 16, 2), RESIZE_ERR_CANCELLED);
+        assert_eq!(get_last_completed_row(), RESIZE_NO_ROW_COMPLETED);
+        let map = vec![1.5f32; 20 * 16 * 2];
+        assert_eq!(resize_rgba_warp(s, 50, 40, d, 20, 16, map.as_ptr(), 1), RESIZE_ERR_CANCELLED);
+        let mut rotated = vec![0u8; 40 * 50 * 4];
+        assert_eq!(rotate_rgba_90(s, 50, 40, rotated.as_mut_ptr()), RESIZE_ERR_CANCELLED);
+        assert_eq!(resize_plane_phase(s, 200, 40, d, 80, 16, 0.25, 0.0, 1), RESIZE_ERR_CANCELLED);
+        assert_eq!(resize_alpha_edge_aware(s, 200, 40, d, 80, 16), RESIZE_ERR_CANCELLED);
+    }
+    clear_cancel();
+    unsafe { assert_eq!(resize_rgba_gamma_area(s, 50, 40, d, 20, 16), 0) };
+}
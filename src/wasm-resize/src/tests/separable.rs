@@ -0,0 +1,678 @@
+//! Tests for the separable-kernel resamplers in separable.rs
+
+use super::*;
+use crate::*;
+
+extern "C" fn tent(x: f32) -> f32 {
+    (1.0 - x.abs()).max(0.0)
+}
+
+extern "C" fn boxed(x: f32) -> f32 {
+    if x.abs() < 0.5 {
+        1.0
+    } else {
+        0.0
+    }
+}
+
+#[test]
+fn custom_tent_kernel_matches_bilinear() {
+    let src = img(10, 8, |x, y| [(x * 25) as u8, (y * 30) as u8, (x * y * 3) as u8, 255]);
+    let mut custom = vec![0u8; 20 * 16 * 4];
+    let mut bilinear = custom.clone();
+    unsafe {
+        assert_eq!(
+            resize_rgba_custom_kernel(src.as_ptr(), 10, 8, custom.as_mut_ptr(), 20, 16, 1.0, tent),
+            0
+        );
+        assert_eq!(
+            resize_with_algorithm(src.as_ptr(), 10, 8, bilinear.as_mut_ptr(), 20, 16, RESIZE_ALGO_BILINEAR),
+            0
+        );
+    }
+    assert!(max_diff(&custom, &bilinear) <= 1);
+}
+
+#[test]
+fn custom_box_kernel_averages_pairs() {
+    let src = img(8, 2, |x, _| [(x * 20) as u8, 100, 200, 255]);
+    let mut dst = vec![0u8; 4 * 4];
+    unsafe {
+        assert_eq!(
+            resize_rgba_custom_kernel(src.as_ptr(), 8, 2, dst.as_mut_ptr(), 4, 1, 0.5, boxed),
+            0
+        )
+    };
+    for (x, p) in dst.chunks_exact(4).enumerate() {
+        assert_eq!(p, [(x * 40 + 10) as u8, 100, 200, 255]);
+    }
+}
+
+#[test]
+fn custom_kernel_rejects_bad_support() {
+    let src = img(4, 4, |_, _| [1, 2, 3, 255]);
+    let mut dst = vec![0u8; 2 * 2 * 4];
+    for support in [0.0, -1.0, 9.0, f32::NAN, f32::INFINITY] {
+        let code = unsafe { resize_rgba_custom_kernel(src.as_ptr(), 4, 4, dst.as_mut_ptr(), 2, 2, support, tent) };
+        assert_eq!(code, RESIZE_ERR_INVALID_PARAM, "support {support}");
+    }
+}
+
+#[test]
+fn normalmap_output_stays_unit_length() {
+    // Left texel points along +X, right texel along +Z
+    let src = [255u8, 128, 128, 255, 128, 128, 255, 0];
+    let mut dst = vec![0u8; 8 * 2 * 4];
+    unsafe { assert_eq!(resize_normalmap_rgba(src.as_ptr(), 2, 1, dst.as_mut_ptr(), 8, 2), 0) };
+    for p in dst.chunks_exact(4) {
+        let v: Vec<f32> = p[..3].iter().map(|&c| c as f32 / 255.0 * 2.0 - 1.0).collect();
+        let len = (v[0] * v[0] + v[1] * v[1] + v[2] * v[2]).sqrt();
+        assert!((len - 1.0).abs() < 0.02, "{p:?} has length {len}");
+    }
+    // Alpha is interpolated as a plain value, so it ramps monotonically from 255 to 0
+    let alpha: Vec<u8> = dst[..8 * 4].chunks_exact(4).map(|p| p[3]).collect();
+    assert!(alpha.windows(2).all(|w| w[0] >= w[1]) && alpha[0] > alpha[7]);
+}
+
+/// Mean absolute deviation of the red channel from `target`
+fn red_deviation(pixels: &[u8], target: f32) -> f32 {
+    let red: Vec<f32> = pixels.chunks_exact(4).map(|p| p[0] as f32).collect();
+    red.iter().map(|r| (r - target).abs()).sum::<f32>() / red.len() as f32
+}
+
+#[test]
+fn prefilter_bilinear_suppresses_aliasing() {
+    // Stripes finer than the output grid should average out to a flat 255 / period
+    let (mut filtered_total, mut plain_total) = (0.0, 0.0);
+    for period in [2u32, 3, 5] {
+        for ratio in [2u32, 3, 4] {
+            let n = 60 * ratio;
+            let src = img(n, 4, |x, _| {
+                if x % period == 0 {
+                    [255, 255, 255, 255]
+                } else {
+                    [0, 0, 0, 255]
+                }
+            });
+            let mut filtered = vec![0u8; 60 * 4 * 4];
+            let mut plain = filtered.clone();
+            unsafe {
+                assert_eq!(
+                    resize_rgba_prefilter_bilinear(src.as_ptr(), n, 4, filtered.as_mut_ptr(), 60, 4),
+                    0
+                );
+                assert_eq!(
+                    resize_with_algorithm(src.as_ptr(), n, 4, plain.as_mut_ptr(), 60, 4, RESIZE_ALGO_BILINEAR),
+                    0
+                );
+            }
+            let target = 255.0 / period as f32;
+            let (f, p) = (red_deviation(&filtered, target), red_deviation(&plain, target));
+            filtered_total += f;
+            plain_total += p;
+        }
+    }
+    assert!(filtered_total < plain_total * 0.5, "{filtered_total} vs {plain_total}");
+}
+
+#[test]
+fn prefilter_bilinear_keeps_flat_color() {
+    let src = img(90, 60, |_, _| [12, 130, 250, 200]);
+    let mut dst = vec![0u8; 30 * 20 * 4];
+    unsafe {
+        assert_eq!(
+            resize_rgba_prefilter_bilinear(src.as_ptr(), 90, 60, dst.as_mut_ptr(), 30, 20),
+            0
+        )
+    };
+    assert!(dst.chunks_exact(4).all(|p| p == [12, 130, 250, 200]));
+}
+
+extern "C" fn lanczos3(x: f32) -> f32 {
+    if x.abs() >= 3.0 {
+        0.0
+    } else if x == 0.0 {
+        1.0
+    } else {
+        let p = std::f32::consts::PI * x;
+        (p.sin() / p) * ((p / 3.0).sin() / (p / 3.0))
+    }
+}
+
+#[test]
+fn intermediate_precision_modes_agree() {
+    let src = img(53, 41, |x, y| {
+        [
+            (x * 37 + y) as u8,
+            ((y * 53) ^ x) as u8,
+            if (x / 4 + y / 4) % 2 == 0 { 0 } else { 255 },
+            255,
+        ]
+    });
+    for (dw, dh) in [(20u32, 15u32), (120, 90)] {
+        let mut out = vec![vec![0u8; (dw * dh * 4) as usize]; 3];
+        for mode in [RESIZE_PRECISION_F32, RESIZE_PRECISION_FIXED, RESIZE_PRECISION_F64] {
+            assert_eq!(set_intermediate_precision(mode), 0);
+            let dst = out[mode as usize].as_mut_ptr();
+            unsafe {
+                assert_eq!(
+                    resize_rgba_custom_kernel(src.as_ptr(), 53, 41, dst, dw, dh, 3.0, lanczos3),
+                    0
+                )
+            };
+        }
+        set_intermediate_precision(RESIZE_PRECISION_F32);
+        assert!(max_diff(&out[0], &out[2]) <= 1, "f64 vs f32 at {dw}x{dh}");
+        assert!(max_diff(&out[0], &out[1]) <= 2, "fixed vs f32 at {dw}x{dh}");
+    }
+    assert_eq!(set_intermediate_precision(3), RESIZE_ERR_INVALID_PARAM);
+}
+
+/// resize_rgba_lanczos in f64: same taps, anti-ringing clamps and truncation
+fn lanczos_reference(src: &[u8], sw: u32, sh: u32, dw: u32, dh: u32) -> Vec<u8> {
+    let taps = |src_size: u32, dst_size: u32| -> Vec<Vec<(f32, usize)>> {
+        (0..dst_size)
+            .map(|d| {
+                let (mut w, mut i) = ([0.0f32; LANCZOS_MAX_TAPS], [0i32; LANCZOS_MAX_TAPS]);
+                let scale = src_size as f32 / dst_size as f32;
+                let n = precompute_lanczos_weights(d as f32, src_size, scale, LANCZOS_A, &mut w, &mut i);
+                (0..n).map(|k| (w[k], i[k] as usize)).collect()
+            })
+            .collect()
+    };
+    let filter = |taps: &[(f32, usize)], sample: &dyn Fn(usize) -> [f64; 4]| -> [f64; 4] {
+        let (mut sums, mut weight_sum) = ([0.0f64; 4], 0.0f64);
+        let (mut mins, mut maxs) = ([255.0f64; 4], [0.0f64; 4]);
+        for &(w, i) in taps {
+            let v = sample(i);
+            for c in 0..4 {
+                sums[c] += v[c] * w as f64;
+                mins[c] = mins[c].min(v[c]);
+                maxs[c] = maxs[c].max(v[c]);
+            }
+            weight_sum += w as f64;
+        }
+        std::array::from_fn(|c| (sums[c] / weight_sum).max(mins[c]).min(maxs[c]))
+    };
+    let (x_taps, y_taps) = (taps(sw, dw), taps(sh, dh));
+    let pixel = |x: usize, y: usize| -> [f64; 4] {
+        let p = (y * sw as usize + x) * 4;
+        std::array::from_fn(|c| src[p + c] as f64)
+    };
+    let temp: Vec<Vec<[f64; 4]>> = (0..sh as usize)
+        .map(|y| x_taps.iter().map(|t| filter(t, &|x| pixel(x, y))).collect())
+        .collect();
+    let mut out = Vec::with_capacity((dw * dh * 4) as usize);
+    for t in &y_taps {
+        out.extend((0..dw as usize).flat_map(|x| filter(t, &|y| temp[y][x]).map(|v| v.clamp(0.0, 255.0) as u8)));
+    }
+    out
+}
+
+#[test]
+fn lanczos_core_honors_intermediate_precision() {
+    let src = img(400, 300, |x, y| {
+        [
+            (x * 37 + y * 11) as u8,
+            ((y * 53) ^ (x * 7)) as u8,
+            if (x / 3 + y / 5) % 2 == 0 { 10 } else { 245 },
+            (200 + (x * y) % 56) as u8,
+        ]
+    });
+    let (mut f32_total, mut fixed_total) = (0, 0);
+    for (dw, dh) in [(23u32, 17u32), (150, 110), (600, 100)] {
+        let reference = lanczos_reference(&src, 400, 300, dw, dh);
+        let mut out = vec![vec![0u8; (dw * dh * 4) as usize]; 3];
+        for mode in [RESIZE_PRECISION_F32, RESIZE_PRECISION_FIXED, RESIZE_PRECISION_F64] {
+            assert_eq!(set_intermediate_precision(mode), 0);
+            let dst = out[mode as usize].as_mut_ptr();
+            unsafe { assert_eq!(resize_rgba_lanczos(src.as_ptr(), 400, 300, dst, dw, dh), 0) };
+        }
+        set_intermediate_precision(RESIZE_PRECISION_F32);
+
+        // f64 reproduces the f64 reference; f32 rounding and the Q6 fixed-point
+        // intermediate move further away from it, in that order
+        let error = |out: &[u8]| {
+            out.iter()
+                .zip(&reference)
+                .map(|(&a, &b)| a.abs_diff(b) as u32)
+                .sum::<u32>()
+        };
+        let (f32_error, fixed_error) = (error(&out[0]), error(&out[1]));
+        assert_eq!(out[2], reference, "f64 at {dw}x{dh}");
+        assert!(max_diff(&out[0], &reference) <= 1, "f32 at {dw}x{dh}");
+        assert!(max_diff(&out[1], &reference) <= 2, "fixed at {dw}x{dh}");
+        assert!(f32_error < fixed_error, "{f32_error} vs {fixed_error} at {dw}x{dh}");
+        f32_total += f32_error;
+        fixed_total += fixed_error;
+    }
+    assert!(0 < f32_total && f32_total < fixed_total);
+}
+
+#[test]
+fn lanczos_soft_clip_keeps_highlight_gradient() {
+    // Dark-to-bright step into a near-white plateau: Lanczos rings well past 255
+    let src = img(16, 2, |x, _| {
+        let v = if x < 8 {
+            10
+        } else if x < 12 {
+            250
+        } else {
+            245
+        };
+        [v, v, v, 255]
+    });
+    let mut hard = vec![0u8; 64 * 2 * 4];
+    let mut soft = hard.clone();
+    unsafe {
+        assert_eq!(
+            resize_rgba_lanczos_overshoot(src.as_ptr(), 16, 2, hard.as_mut_ptr(), 64, 2, 0),
+            0
+        );
+        assert_eq!(
+            resize_rgba_lanczos_overshoot(src.as_ptr(), 16, 2, soft.as_mut_ptr(), 64, 2, 1),
+            0
+        );
+        let code = resize_rgba_lanczos_overshoot(src.as_ptr(), 16, 2, soft.as_mut_ptr(), 64, 2, 2);
+        assert_eq!(code, RESIZE_ERR_INVALID_PARAM);
+    }
+    // Hard clipping flattens the ringing into a run of 255s; the roll-off keeps it below
+    // white and graded
+    let at_white = hard[..64 * 4].chunks_exact(4).filter(|p| p[0] == 255).count();
+    let soft_peak = soft[..64 * 4].chunks_exact(4).map(|p| p[0]).max().unwrap();
+    let at_soft_peak = soft[..64 * 4].chunks_exact(4).filter(|p| p[0] == soft_peak).count();
+    assert!(at_white >= 3, "{at_white} pixels at 255");
+    assert!(
+        soft_peak < 255 && at_soft_peak < at_white,
+        "peak {soft_peak} x{at_soft_peak}"
+    );
+    // Flat areas away from the edge are unchanged apart from the knee
+    assert!(soft[..4 * 4].chunks_exact(4).all(|p| p[0].abs_diff(10) <= 1));
+}
+
+#[test]
+fn soft_clip_curve_is_monotonic_and_bounded() {
+    use crate::separable::soft_clip_value;
+
+    assert_eq!(soft_clip_value(-0.5), 0.0);
+    assert_eq!(soft_clip_value(0.25), 0.25);
+    assert_eq!(soft_clip_value(0.9), 0.9);
+    let samples: Vec<f32> = (0..400).map(|i| soft_clip_value(0.8 + i as f32 * 0.005)).collect();
+    assert!(samples.windows(2).all(|w| w[1] >= w[0]));
+    assert!(samples.iter().all(|&v| v <= 1.0));
+    assert!(soft_clip_value(1.2) > soft_clip_value(1.05));
+}
+
+#[test]
+fn weighted_salient_pixel_dominates_footprint() {
+    let src = img(16, 16, |x, y| {
+        if x == 5 && y == 5 {
+            [255, 0, 0, 255]
+        } else {
+            [0, 0, 255, 255]
+        }
+    });
+    let mask: Vec<u8> = (0..256).map(|i| if i == 5 * 16 + 5 { 255 } else { 0 }).collect();
+    let zero = vec![0u8; 256];
+    let mut weighted = vec![0u8; 4 * 4 * 4];
+    let mut flat = weighted.clone();
+    let mut plain = weighted.clone();
+    unsafe {
+        assert_eq!(
+            resize_rgba_weighted(src.as_ptr(), 16, 16, mask.as_ptr(), weighted.as_mut_ptr(), 4, 4),
+            0
+        );
+        assert_eq!(
+            resize_rgba_weighted(src.as_ptr(), 16, 16, zero.as_ptr(), flat.as_mut_ptr(), 4, 4),
+            0
+        );
+        assert_eq!(
+            resize_rgba_lanczos_overshoot(src.as_ptr(), 16, 16, plain.as_mut_ptr(), 4, 4, 0),
+            0
+        );
+        assert_eq!(
+            resize_rgba_weighted(src.as_ptr(), 16, 16, std::ptr::null(), flat.as_mut_ptr(), 4, 4),
+            RESIZE_ERR_NULL_PTR
+        );
+    }
+    // An all-zero mask is a uniform weight, i.e. the unweighted Lanczos-3 result
+    assert_eq!(flat, plain);
+    // The single red pixel outweighs the 15 blue ones around it in its output pixel
+    let i = (4 + 1) * 4;
+    assert!(weighted[i] > 200 && weighted[i + 2] < 50, "{:?}", &weighted[i..i + 4]);
+    assert!(flat[i] < 50);
+}
+
+#[test]
+fn pack_f32x4_clamps_and_truncates() {
+    use crate::simd_helpers::pack_f32x4_to_u8;
+
+    let values = [
+        -1e9,
+        -0.5,
+        0.0,
+        0.49,
+        0.99,
+        1.0,
+        127.5,
+        254.99,
+        255.0,
+        255.5,
+        1e9,
+        f32::NAN,
+    ];
+    for chunk in values.windows(4) {
+        let v = [chunk[0], chunk[1], chunk[2], chunk[3]];
+        // The scalar reference stage this replaced; NaN truncates to 0
+        let expected = v.map(|c| c.clamp(0.0, 255.0) as u8);
+        assert_eq!(pack_f32x4_to_u8(v), expected, "{v:?}");
+    }
+    assert_eq!(
+        pack_f32x4_to_u8([f32::INFINITY, f32::NEG_INFINITY, 3.7, 200.2]),
+        [255, 0, 3, 200]
+    );
+}
+
+#[test]
+fn seeded_dither_is_reproducible_and_reduces_banding() {
+    // Very shallow gradient: 4 source columns spanning 100..103, upscaled 64x
+    let src = img(4, 4, |x, _| [100 + x as u8, 100 + x as u8, 100 + x as u8, 255]);
+    let (dw, dh) = (256u32, 64u32);
+    let run = |seed: u32| {
+        let mut dst = vec![0u8; (dw * dh * 4) as usize];
+        unsafe {
+            assert_eq!(
+                resize_rgba_dithered_seeded(src.as_ptr(), 4, 4, dst.as_mut_ptr(), dw, dh, 1, seed),
+                0
+            )
+        };
+        dst
+    };
+    let a = run(7);
+    let b = run(8);
+    assert_eq!(a, run(7));
+    assert_ne!(a, b);
+    let mut plain = vec![0u8; (dw * dh * 4) as usize];
+    unsafe {
+        assert_eq!(
+            resize_with_algorithm(src.as_ptr(), 4, 4, plain.as_mut_ptr(), dw, dh, 1),
+            0
+        )
+    };
+    // Column means track the true ramp much more closely with dither
+    let column_error = |d: &[u8]| {
+        let mut err = 0.0f64;
+        for x in 0..dw as usize {
+            let mean = (0..dh as usize)
+                .map(|y| d[(y * dw as usize + x) * 4] as f64)
+                .sum::<f64>()
+                / dh as f64;
+            let sx = ((x as f64 + 0.5) * 4.0 / dw as f64 - 0.5).clamp(0.0, 3.0);
+            err += (mean - (100.0 + sx)).abs();
+        }
+        err / dw as f64
+    };
+    let (ep, ea, eb) = (column_error(&plain), column_error(&a), column_error(&b));
+    assert!(ea < ep * 0.6 && eb < ep * 0.6, "plain {ep:.3} seeded {ea:.3} {eb:.3}");
+    assert!(a.chunks_exact(4).all(|p| p[3] == 255));
+}
+
+#[test]
+fn describe_sample_reports_taps_and_weights() {
+    let mut indices = [0u32; 8];
+    let mut weights = [0f32; 8];
+    let (ip, wp) = (indices.as_mut_ptr(), weights.as_mut_ptr());
+    unsafe {
+        // 8x8 -> 4x4 bilinear: destination (1, 2) averages source (2..4, 4..6)
+        assert_eq!(describe_sample(8, 8, 4, 4, 1, 2, RESIZE_ALGO_BILINEAR, ip, wp, 8), 4);
+        assert_eq!(&indices[..4], &[34, 35, 42, 43]);
+        assert!(weights[..4].iter().all(|&v| (v - 0.25).abs() < 1e-6));
+        assert_eq!(describe_sample(8, 8, 4, 4, 1, 2, RESIZE_ALGO_NEAREST, ip, wp, 8), 1);
+
+        // A null / zero-capacity query reports the tap count, which then fits exactly
+        let null = std::ptr::null_mut();
+        let n = describe_sample(8, 8, 4, 4, 1, 2, RESIZE_ALGO_LANCZOS, null, null as *mut f32, 0);
+        assert!(n > 4);
+        let mut all_indices = vec![0u32; n as usize];
+        let mut all_weights = vec![0f32; n as usize];
+        let (aip, awp) = (all_indices.as_mut_ptr(), all_weights.as_mut_ptr());
+        assert_eq!(
+            describe_sample(8, 8, 4, 4, 1, 2, RESIZE_ALGO_LANCZOS, aip, awp, n as u32),
+            n
+        );
+        assert!((all_weights.iter().sum::<f32>() - 1.0).abs() < 1e-4);
+
+        // Outside the destination
+        assert_eq!(describe_sample(8, 8, 4, 4, 4, 0, RESIZE_ALGO_BILINEAR, ip, wp, 8), 0);
+    }
+}
+
+#[test]
+fn lanczos_blur_widens_the_kernel() {
+    let src = img(64, 16, |x, _| {
+        if x < 32 {
+            [20, 20, 20, 255]
+        } else {
+            [230, 230, 230, 255]
+        }
+    });
+    let mut normal = vec![0u8; 32 * 8 * 4];
+    let mut blur1 = normal.clone();
+    let mut blur2 = normal.clone();
+    unsafe {
+        assert_eq!(resize_rgba_lanczos(src.as_ptr(), 64, 16, normal.as_mut_ptr(), 32, 8), 0);
+        assert_eq!(
+            resize_rgba_lanczos_blur(src.as_ptr(), 64, 16, blur1.as_mut_ptr(), 32, 8, 1.0),
+            0
+        );
+        for bad in [0.5, f32::INFINITY] {
+            let code = resize_rgba_lanczos_blur(src.as_ptr(), 64, 16, blur2.as_mut_ptr(), 32, 8, bad);
+            assert_eq!(code, RESIZE_ERR_INVALID_PARAM);
+        }
+        assert_eq!(
+            resize_rgba_lanczos_blur(src.as_ptr(), 64, 16, blur2.as_mut_ptr(), 32, 8, 2.0),
+            0
+        );
+    }
+    // Blur 1 is the regular Lanczos; blur 2 spreads the step over more pixels
+    assert_eq!(blur1, normal);
+    let max_step = |b: &[u8]| {
+        (0..31)
+            .map(|x| (b[(4 * 32 + x + 1) * 4] as i32 - b[(4 * 32 + x) * 4] as i32).abs())
+            .max()
+    };
+    let (sharp, soft) = (max_step(&blur1).unwrap(), max_step(&blur2).unwrap());
+    assert!(soft * 3 < sharp * 2, "{soft} vs {sharp}");
+}
+
+#[test]
+fn fixedpoint_is_deterministic_and_close_to_float() {
+    let src = img(61, 47, |x, y| {
+        [
+            (x * 4) as u8,
+            (y * 5) as u8,
+            ((x * 3 + y * 2) / 2 + 30) as u8,
+            200 + (x / 2) as u8,
+        ]
+    });
+    for (dw, dh) in [(20u32, 15u32), (30, 47), (90, 70)] {
+        let mut a = vec![0u8; (dw * dh * 4) as usize];
+        let mut b = a.clone();
+        let mut reference = a.clone();
+        unsafe {
+            assert_eq!(
+                resize_rgba_fixedpoint(src.as_ptr(), 61, 47, a.as_mut_ptr(), dw, dh, 2),
+                0
+            );
+            assert_eq!(
+                resize_rgba_fixedpoint(src.as_ptr(), 61, 47, b.as_mut_ptr(), dw, dh, 2),
+                0
+            );
+            assert_eq!(
+                resize_rgba_lanczos(src.as_ptr(), 61, 47, reference.as_mut_ptr(), dw, dh),
+                0
+            );
+        }
+        assert_eq!(a, b);
+        assert!(max_diff(&a, &reference) <= 2, "{dw}x{dh}: {}", max_diff(&a, &reference));
+        for algo in [RESIZE_ALGO_NEAREST, RESIZE_ALGO_BILINEAR] {
+            unsafe {
+                assert_eq!(
+                    resize_rgba_fixedpoint(src.as_ptr(), 61, 47, a.as_mut_ptr(), dw, dh, algo),
+                    0
+                );
+                assert_eq!(
+                    resize_rgba_row_order(src.as_ptr(), 61, 47, reference.as_mut_ptr(), dw, dh, algo, 0),
+                    0
+                );
+            }
+            assert!(max_diff(&a, &reference) <= 2, "{dw}x{dh} algo {algo}");
+        }
+    }
+}
+
+#[test]
+fn monotonic_keeps_gradients_monotonic() {
+    // Strictly increasing luminance: slow ramp, steep step, slow ramp
+    let level = |x: u32| -> u8 {
+        match x {
+            0..=29 => 20 + x as u8,
+            30..=33 => 50 + (x - 30) as u8 * 40,
+            _ => 200 + (x - 34) as u8,
+        }
+    };
+    let src = img(64, 4, |x, _| [level(x), level(x), level(x), 255]);
+    let nondecreasing = |row: &[u8]| {
+        row.chunks_exact(4)
+            .zip(row.chunks_exact(4).skip(1))
+            .all(|(a, b)| b[0] >= a[0])
+    };
+    for dw in [21u32, 37, 150] {
+        let mut mono = vec![0u8; (dw * 3 * 4) as usize];
+        let mut lanczos = mono.clone();
+        unsafe {
+            assert_eq!(resize_rgba_monotonic(src.as_ptr(), 64, 4, mono.as_mut_ptr(), dw, 3), 0);
+            assert_eq!(
+                resize_rgba_lanczos_overshoot(src.as_ptr(), 64, 4, lanczos.as_mut_ptr(), dw, 3, 0),
+                0
+            );
+        }
+        assert!(mono.chunks_exact(dw as usize * 4).all(nondecreasing), "dw {dw}");
+        assert!(!nondecreasing(&lanczos[..dw as usize * 4]), "lanczos monotonic at {dw}");
+        assert!(mono.chunks_exact(4).all(|p| (20..=229).contains(&p[0]) && p[3] == 255));
+    }
+
+    // Same size is a copy
+    let mut same = vec![0u8; src.len()];
+    unsafe { assert_eq!(resize_rgba_monotonic(src.as_ptr(), 64, 4, same.as_mut_ptr(), 64, 4), 0) };
+    assert_eq!(same, src);
+}
+
+#[test]
+fn kernel_evaluator() {
+    let lanczos = |x: f32, a: f32| -> f32 {
+        if x.abs() >= a {
+            return 0.0;
+        }
+        if x == 0.0 {
+            return 1.0;
+        }
+        let (p, q) = (std::f32::consts::PI * x, std::f32::consts::PI * x / a);
+        (p.sin() / p) * (q.sin() / q)
+    };
+    for &x in &[0.0f32, 0.25, -0.5, 1.0, 1.7, -2.3, 2.99, 3.0, 4.5] {
+        assert_eq!(eval_kernel(RESIZE_KERNEL_LANCZOS, x, 3.0), lanczos(x, 3.0), "x {x}");
+        assert_eq!(eval_kernel(RESIZE_KERNEL_LANCZOS, x, 2.0), lanczos(x, 2.0), "x {x}");
+    }
+    assert_eq!(eval_kernel(RESIZE_KERNEL_TRIANGLE, 0.0, 0.0), 1.0);
+    assert_eq!(eval_kernel(RESIZE_KERNEL_TRIANGLE, 1.0, 0.0), 0.0);
+    assert_eq!(eval_kernel(RESIZE_KERNEL_TRIANGLE, -1.0, 0.0), 0.0);
+    assert_eq!(eval_kernel(RESIZE_KERNEL_TRIANGLE, 0.25, 0.0), 0.75);
+    // Catmull-Rom interpolates (1 at 0, 0 at the integers); Mitchell B = 0 is Catmull-Rom
+    assert_eq!(eval_kernel(RESIZE_KERNEL_CATMULL_ROM, 0.0, 0.0), 1.0);
+    assert_eq!(eval_kernel(RESIZE_KERNEL_CATMULL_ROM, 1.0, 0.0), 0.0);
+    assert_eq!(eval_kernel(RESIZE_KERNEL_CATMULL_ROM, 2.0, 0.0), 0.0);
+    assert!((eval_kernel(RESIZE_KERNEL_CATMULL_ROM, 1.5, 0.0) + 0.0625).abs() < 1e-6);
+    assert_eq!(
+        eval_kernel(RESIZE_KERNEL_MITCHELL, 0.7, 0.0),
+        eval_kernel(RESIZE_KERNEL_CATMULL_ROM, 0.7, 0.0)
+    );
+    assert!((eval_kernel(RESIZE_KERNEL_MITCHELL, 0.0, 1.0 / 3.0) - 8.0 / 9.0).abs() < 1e-6);
+    assert!((eval_kernel(RESIZE_KERNEL_MITCHELL, 1.0, 1.0 / 3.0) - 1.0 / 18.0).abs() < 1e-6);
+    // Cubic weights at integer offsets partition unity
+    for b in [0.0f32, 1.0 / 3.0, 1.0] {
+        let sum: f32 = (-2..=2)
+            .map(|i| eval_kernel(RESIZE_KERNEL_MITCHELL, i as f32 + 0.3, b))
+            .sum();
+        assert!((sum - 1.0).abs() < 1e-5, "b {b} sum {sum}");
+    }
+    assert_eq!(eval_kernel(RESIZE_KERNEL_GAUSSIAN, 0.0, 0.8), 1.0);
+    assert!((eval_kernel(RESIZE_KERNEL_GAUSSIAN, 2.0, 2.0) - (-0.5f32).exp()).abs() < 1e-6);
+    assert_eq!(last_error(), RESIZE_OK);
+    for (kind, param) in [
+        (0u32, 0.0f32),
+        (0, 9.0),
+        (0, f32::NAN),
+        (3, 1.5),
+        (3, -0.1),
+        (4, 0.0),
+        (4, f32::INFINITY),
+        (5, 1.0),
+    ] {
+        assert!(eval_kernel(kind, 0.5, param).is_nan(), "kind {kind} param {param}");
+        assert_eq!(last_error(), RESIZE_ERR_INVALID_PARAM);
+    }
+}
+
+#[test]
+fn bicubic_limited() {
+    // Vertical step 64 -> 192, upscaled 4x: Catmull-Rom overshoots on both sides
+    let src = img(16, 4, |x, _| {
+        let v = if x < 8 { 64 } else { 192 };
+        [v, v, v, 255]
+    });
+    let (dw, dh) = (64u32, 4u32);
+    let run = |limit: f32| {
+        let mut dst = vec![0u8; (dw * dh * 4) as usize];
+        unsafe {
+            assert_eq!(
+                resize_rgba_bicubic_limited(src.as_ptr(), 16, 4, dst.as_mut_ptr(), dw, dh, limit),
+                0
+            );
+        }
+        assert!(dst.chunks_exact(4).all(|p| p[3] == 255));
+        let max = dst.chunks_exact(4).map(|p| p[0]).max().unwrap();
+        let min = dst.chunks_exact(4).map(|p| p[0]).min().unwrap();
+        (min, max)
+    };
+    assert_eq!(run(0.0), (64, 192));
+    let free = run(1.0);
+    assert!(free.1 > 196 && free.0 < 60, "{free:?}");
+    let mut last = 192;
+    for limit in [0.005f32, 0.01, 0.02, 0.03, 0.05] {
+        let (min, max) = run(limit);
+        let cap = 128.0 * limit;
+        assert!(
+            max as f32 <= 192.0 + cap + 0.5 && min as f32 >= 64.0 - cap - 0.5,
+            "limit {limit}: {min} {max}"
+        );
+        assert!(max >= last, "limit {limit}");
+        // Below the natural overshoot the cap is reached
+        if 192.0 + cap < free.1 as f32 {
+            assert!(max as f32 >= 192.0 + cap - 0.5, "limit {limit}: {max}");
+        }
+        last = max;
+    }
+    assert_eq!(run(10.0), free);
+    let mut d1 = [0u8; 4];
+    unsafe {
+        assert_eq!(
+            resize_rgba_bicubic_limited(src.as_ptr(), 16, 4, d1.as_mut_ptr(), 1, 1, -0.1),
+            RESIZE_ERR_INVALID_PARAM
+        );
+        assert_eq!(
+            resize_rgba_bicubic_limited(src.as_ptr(), 16, 4, d1.as_mut_ptr(), 1, 1, f32::INFINITY),
+            RESIZE_ERR_INVALID_PARAM
+        );
+    }
+}
@@ -0,0 +1,172 @@
+//! Tests for the streaming exports (row and tile sinks) in lib.rs
+
+use super::*;
+use crate::*;
+use std::cell::RefCell;
+
+thread_local! {
+    // Rows delivered to collect_row, in call order
+    static ROWS: RefCell<Vec<(u32, Vec<u8>)>> = const { RefCell::new(Vec::new()) };
+    // Frame reassembled by collect_tile: pixels, frame width, tiles delivered
+    static TILE_FRAME: RefCell<(Vec<u8>, u32, u32)> = const { RefCell::new((Vec::new(), 0, 0)) };
+}
+
+extern "C" fn collect_row(y: u32, ptr: *const u8, len: usize) {
+    let row = unsafe { std::slice::from_raw_parts(ptr, len) }.to_vec();
+    ROWS.with(|r| r.borrow_mut().push((y, row)));
+}
+
+extern "C" fn collect_tile(tile_x: u32, tile_y: u32, ptr: *const u8, w: u32, h: u32) {
+    let tile = unsafe { std::slice::from_raw_parts(ptr, (w * h * 4) as usize) };
+    TILE_FRAME.with(|f| {
+        let (frame, frame_w, count) = &mut *f.borrow_mut();
+        *count += 1;
+        for (r, row) in tile.chunks_exact(w as usize * 4).enumerate() {
+            let start = ((tile_y as usize + r) * *frame_w as usize + tile_x as usize) * 4;
+            frame[start..start + row.len()].copy_from_slice(row);
+        }
+    });
+}
+
+#[test]
+fn row_sink_matches_full_resize() {
+    let (w, h) = (37u32, 23u32);
+    let src = img(w, h, |x, y| {
+        [(x * 7) as u8, (y * 11) as u8, ((x * y) % 256) as u8, (x * 3 + y) as u8]
+    });
+    let algos = [
+        RESIZE_ALGO_NEAREST,
+        RESIZE_ALGO_BILINEAR,
+        RESIZE_ALGO_LANCZOS,
+        RESIZE_ALGO_AUTO,
+    ];
+    // Upscale, downscale past the 6-row ring, identity and a tall squash
+    for (dw, dh) in [(50u32, 40u32), (13, 7), (37, 23), (9, 2), (60, 100)] {
+        for algo in algos {
+            let mut full = vec![0u8; (dw * dh * 4) as usize];
+            ROWS.with(|r| r.borrow_mut().clear());
+            unsafe {
+                assert_eq!(
+                    resize_with_algorithm(src.as_ptr(), w, h, full.as_mut_ptr(), dw, dh, algo),
+                    0
+                );
+                assert_eq!(resize_rgba_row_sink(src.as_ptr(), w, h, dw, dh, algo, collect_row), 0);
+            }
+            let rows = ROWS.with(|r| r.take());
+            assert_eq!(rows.len(), dh as usize);
+            let mut streamed = Vec::new();
+            for (i, (y, row)) in rows.into_iter().enumerate() {
+                assert_eq!(y as usize, i);
+                streamed.extend(row);
+            }
+            assert_eq!(streamed, full, "algo {algo} {dw}x{dh}");
+        }
+    }
+}
+
+#[test]
+fn row_sink_follows_intermediate_precision() {
+    let (w, h) = (37u32, 23u32);
+    let src = img(w, h, |x, y| [(x * 7) as u8, (y * 11) as u8, ((x * y) % 256) as u8, 200]);
+    for mode in [RESIZE_PRECISION_FIXED, RESIZE_PRECISION_F64] {
+        assert_eq!(set_intermediate_precision(mode), 0);
+        for (dw, dh) in [(50u32, 40u32), (13, 7)] {
+            let mut full = vec![0u8; (dw * dh * 4) as usize];
+            ROWS.with(|r| r.borrow_mut().clear());
+            unsafe {
+                assert_eq!(resize_rgba_lanczos(src.as_ptr(), w, h, full.as_mut_ptr(), dw, dh), 0);
+                assert_eq!(
+                    resize_rgba_row_sink(src.as_ptr(), w, h, dw, dh, RESIZE_ALGO_LANCZOS, collect_row),
+                    0
+                );
+            }
+            let streamed: Vec<u8> = ROWS.with(|r| r.take()).into_iter().flat_map(|(_, row)| row).collect();
+            assert_eq!(streamed, full, "precision {mode} {dw}x{dh}");
+        }
+    }
+    set_intermediate_precision(RESIZE_PRECISION_F32);
+}
+
+#[test]
+fn row_sink_needs_no_full_intermediate() {
+    let src = img(64, 48, |x, y| [(x * 4) as u8, (y * 5) as u8, 7, 255]);
+    let mut full = vec![0u8; 40 * 30 * 4];
+    unsafe {
+        assert_eq!(resize_rgba_lanczos(src.as_ptr(), 64, 48, full.as_mut_ptr(), 40, 30), 0);
+    }
+    // The plain Lanczos needs 40 x 48 intermediate pixels; the stream holds 40 x 6
+    set_max_intermediate_pixels(40 * 6);
+    ROWS.with(|r| r.borrow_mut().clear());
+    let code = unsafe { resize_rgba_row_sink(src.as_ptr(), 64, 48, 40, 30, RESIZE_ALGO_LANCZOS, collect_row) };
+    set_max_intermediate_pixels(0);
+    assert_eq!(code, 0);
+    let streamed: Vec<u8> = ROWS.with(|r| r.take()).into_iter().flat_map(|(_, row)| row).collect();
+    assert_eq!(streamed, full);
+}
+
+#[test]
+fn tile_sink_reassembles_full_resize() {
+    let src = img(53, 41, |x, y| {
+        [(x * 5) as u8, (y * 6) as u8, (x ^ y) as u8 * 3, 255 - y as u8]
+    });
+    for algo in [RESIZE_ALGO_NEAREST, RESIZE_ALGO_BILINEAR, RESIZE_ALGO_LANCZOS] {
+        for (dw, dh, tw, th) in [(80u32, 60u32, 16u32, 16u32), (23, 17, 7, 5), (30, 30, 64, 64)] {
+            TILE_FRAME.with(|f| *f.borrow_mut() = (vec![0u8; (dw * dh * 4) as usize], dw, 0));
+            let mut full = vec![0u8; (dw * dh * 4) as usize];
+            unsafe {
+                assert_eq!(
+                    resize_rgba_tile_sink(src.as_ptr(), 53, 41, dw, dh, tw, th, algo, collect_tile),
+                    0
+                );
+                assert_eq!(
+                    resize_rgba_row_order(src.as_ptr(), 53, 41, full.as_mut_ptr(), dw, dh, algo, 0),
+                    0
+                );
+            }
+            let (frame, _, count) = TILE_FRAME.with(|f| f.take());
+            assert_eq!(frame, full, "algo {algo} {dw}x{dh}");
+            assert_eq!(count, dw.div_ceil(tw.min(dw)) * dh.div_ceil(th.min(dh)));
+        }
+    }
+
+    let code = unsafe { resize_rgba_tile_sink(src.as_ptr(), 53, 41, 10, 10, 0, 4, 1, collect_tile) };
+    assert_eq!(code, RESIZE_ERR_INVALID_PARAM);
+}
+
+#[test]
+fn tile_sink_follows_intermediate_precision() {
+    let src = img(53, 41, |x, y| [(x * 5) as u8, (y * 6) as u8, (x ^ y) as u8 * 3, 255 - y as u8]);
+    for mode in [RESIZE_PRECISION_FIXED, RESIZE_PRECISION_F64] {
+        assert_eq!(set_intermediate_precision(mode), 0);
+        for (dw, dh, tw, th) in [(80u32, 60u32, 16u32, 16u32), (23, 17, 7, 5)] {
+            TILE_FRAME.with(|f| *f.borrow_mut() = (vec![0u8; (dw * dh * 4) as usize], dw, 0));
+            let mut full = vec![0u8; (dw * dh * 4) as usize];
+            unsafe {
+                assert_eq!(
+                    resize_rgba_tile_sink(src.as_ptr(), 53, 41, dw, dh, tw, th, RESIZE_ALGO_LANCZOS, collect_tile),
+                    0
+                );
+                assert_eq!(resize_rgba_lanczos(src.as_ptr(), 53, 41, full.as_mut_ptr(), dw, dh), 0);
+            }
+            let (frame, _, _) = TILE_FRAME.with(|f| f.take());
+            assert_eq!(frame, full, "precision {mode} {dw}x{dh}");
+        }
+    }
+    set_intermediate_precision(RESIZE_PRECISION_F32);
+}
+
+#[test]
+fn tile_sink_needs_no_full_intermediate() {
+    let src = img(64, 48, |x, y| [(x * 4) as u8, (y * 5) as u8, 7, 255]);
+    let mut full = vec![0u8; 40 * 30 * 4];
+    unsafe {
+        assert_eq!(resize_rgba_lanczos(src.as_ptr(), 64, 48, full.as_mut_ptr(), 40, 30), 0);
+    }
+    // Bands go through the same row stream as the row sink: a 40 x 6 Pass-1 ring
+    set_max_intermediate_pixels(40 * 6);
+    TILE_FRAME.with(|f| *f.borrow_mut() = (vec![0u8; full.len()], 40, 0));
+    let code = unsafe { resize_rgba_tile_sink(src.as_ptr(), 64, 48, 40, 30, 16, 8, RESIZE_ALGO_LANCZOS, collect_tile) };
+    set_max_intermediate_pixels(0);
+    assert_eq!(code, 0);
+    assert_eq!(TILE_FRAME.with(|f| f.take()).0, full);
+}
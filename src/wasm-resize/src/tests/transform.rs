@@ -0,0 +1,277 @@
+//! Tests for the rotation, transpose and flip variants in transform.rs
+
+use super::*;
+use crate::*;
+
+#[test]
+fn rotate_90_is_exact_permutation() {
+    let src = img(7, 3, |x, y| [(x * 30) as u8, (y * 80) as u8, 1, 255]);
+    let mut dst = vec![0u8; 7 * 3 * 4];
+    unsafe {
+        assert_eq!(rotate_rgba_90(src.as_ptr(), 7, 3, dst.as_mut_ptr()), 0);
+    }
+    // dst is 3 x 7: dst(x, y) = src(y, 2 - x)
+    for y in 0..7 {
+        for x in 0..3 {
+            let d = ((y * 3 + x) * 4) as usize;
+            let s = (((2 - x) * 7 + y) * 4) as usize;
+            assert_eq!(dst[d..d + 4], src[s..s + 4], "({x}, {y})");
+        }
+    }
+}
+
+#[test]
+fn rotate_quarter_turn_matches_rotate_90() {
+    let n = 21u32;
+    let src = img(n, n, |x, y| {
+        if (8..13).contains(&x) || (5..9).contains(&y) {
+            [200, (x * 10) as u8, (y * 10) as u8, 255]
+        } else {
+            [0, 0, 40, 128]
+        }
+    });
+    let mut r90 = vec![0u8; (n * n * 4) as usize];
+    unsafe {
+        assert_eq!(rotate_rgba_90(src.as_ptr(), n, n, r90.as_mut_ptr()), 0);
+    }
+    for algo in [RESIZE_ALGO_NEAREST, RESIZE_ALGO_BILINEAR, RESIZE_ALGO_LANCZOS] {
+        let mut out = vec![0u8; (n * n * 4) as usize];
+        unsafe {
+            assert_eq!(
+                resize_rotate_rgba(
+                    src.as_ptr(),
+                    n,
+                    n,
+                    out.as_mut_ptr(),
+                    n,
+                    n,
+                    std::f32::consts::FRAC_PI_2,
+                    algo
+                ),
+                0
+            );
+        }
+        // Pixel centers map onto pixel centers, so every kernel lands on single taps
+        assert_eq!(out, r90, "algo {algo}");
+    }
+}
+
+#[test]
+fn rotate_rejects_non_finite_angle() {
+    let src = img(21, 21, |x, y| [x as u8, y as u8, 0, 255]);
+    let mut dst = vec![0u8; 30 * 30 * 4];
+    unsafe {
+        assert_eq!(
+            resize_rotate_rgba(src.as_ptr(), 21, 21, dst.as_mut_ptr(), 30, 30, f32::NAN, 1),
+            RESIZE_ERR_INVALID_PARAM
+        );
+        assert_eq!(
+            resize_rotate_rgba(src.as_ptr(), 21, 21, dst.as_mut_ptr(), 30, 30, 0.5, 1),
+            0
+        );
+    }
+    // The corners map outside the source and stay transparent
+    assert_eq!(dst[3], 0);
+    assert_eq!(dst[((15 * 30 + 15) * 4 + 3) as usize], 255);
+}
+
+#[test]
+fn transpose_matches_resize_of_transposed_source() {
+    let (sw, sh) = (23u32, 11u32);
+    let src = img(sw, sh, |x, y| {
+        [
+            (x * 11) as u8,
+            (y * 23) as u8,
+            if (x / 3 + y / 2) % 2 == 0 { 20 } else { 230 },
+            (255 - x * 3) as u8,
+        ]
+    });
+    let transposed = img(sh, sw, |x, y| {
+        let i = ((x * sw + y) * 4) as usize;
+        [src[i], src[i + 1], src[i + 2], src[i + 3]]
+    });
+    for algo in [RESIZE_ALGO_NEAREST, RESIZE_ALGO_BILINEAR, RESIZE_ALGO_LANCZOS] {
+        for (dw, dh) in [(30u32, 50u32), (5, 9), (11, 23), (7, 40)] {
+            let mut fused = vec![0u8; (dw * dh * 4) as usize];
+            let mut reference = fused.clone();
+            unsafe {
+                assert_eq!(
+                    resize_transpose_rgba(src.as_ptr(), sw, sh, fused.as_mut_ptr(), dw, dh, algo),
+                    0
+                );
+                assert_eq!(
+                    resize_with_algorithm(transposed.as_ptr(), sh, sw, reference.as_mut_ptr(), dw, dh, algo),
+                    0
+                );
+            }
+            // Nearest is a pure gather; the filtered paths swap the pass order, which only
+            // moves the intermediate rounding
+            let tolerance = if algo == RESIZE_ALGO_NEAREST { 0 } else { 2 };
+            let diff = max_diff(&fused, &reference);
+            assert!(diff <= tolerance, "algo {algo} {dw}x{dh}: {diff}");
+        }
+    }
+}
+
+#[test]
+fn flipped_resize_equals_resize_then_flip() {
+    let src = img(37, 23, |x, y| {
+        [(x * 7) as u8, (y * 11) as u8, (x * y) as u8, 255 - x as u8]
+    });
+    for algo in [RESIZE_ALGO_NEAREST, RESIZE_ALGO_BILINEAR, RESIZE_ALGO_LANCZOS] {
+        for (dw, dh) in [(50u32, 40u32), (13, 7), (1, 5)] {
+            let (w, row) = (dw as usize, dw as usize * 4);
+            let mut plain = vec![0u8; (dw * dh * 4) as usize];
+            let mut flipped = [plain.clone(), plain.clone(), plain.clone(), plain.clone()];
+            unsafe {
+                assert_eq!(
+                    resize_rgba_row_order(src.as_ptr(), 37, 23, plain.as_mut_ptr(), dw, dh, algo, 0),
+                    0
+                );
+                for (i, out) in flipped.iter_mut().enumerate() {
+                    let (flip_h, flip_v) = (i as u32 & 1, i as u32 >> 1);
+                    let code =
+                        resize_rgba_flipped(src.as_ptr(), 37, 23, out.as_mut_ptr(), dw, dh, flip_h, flip_v, algo);
+                    assert_eq!(code, 0);
+                }
+            }
+            let mirrored = |flip_h: bool, flip_v: bool| -> Vec<u8> {
+                (0..dh as usize)
+                    .flat_map(|y| (0..w).map(move |x| (y, x)))
+                    .flat_map(|(y, x)| {
+                        let (sx, sy) = (
+                            if flip_h { w - 1 - x } else { x },
+                            if flip_v { dh as usize - 1 - y } else { y },
+                        );
+                        plain[sy * row + sx * 4..][..4].to_vec()
+                    })
+                    .collect()
+            };
+            assert_eq!(flipped[0], plain, "algo {algo} {dw}x{dh}");
+            assert_eq!(flipped[1], mirrored(true, false), "algo {algo} {dw}x{dh} h");
+            assert_eq!(flipped[2], mirrored(false, true), "algo {algo} {dw}x{dh} v");
+            assert_eq!(flipped[3], mirrored(true, true), "algo {algo} {dw}x{dh} hv");
+        }
+    }
+
+    let mut dst = vec![0u8; 16];
+    unsafe {
+        let code = resize_rgba_flipped(src.as_ptr(), 37, 23, dst.as_mut_ptr(), 2, 2, 2, 0, 1);
+        assert_eq!(code, RESIZE_ERR_INVALID_PARAM);
+    }
+}
+
+#[test]
+fn flipped_resize_follows_intermediate_precision() {
+    let src = img(37, 23, |x, y| [(x * 7) as u8, (y * 11) as u8, (x * y) as u8, 255 - x as u8]);
+    let (dw, dh) = (50usize, 40usize);
+    let mut plain = vec![0u8; dw * dh * 4];
+    let mut flipped = plain.clone();
+    assert_eq!(set_intermediate_precision(RESIZE_PRECISION_FIXED), 0);
+    unsafe {
+        assert_eq!(resize_rgba_lanczos(src.as_ptr(), 37, 23, plain.as_mut_ptr(), 50, 40), 0);
+        let code = resize_rgba_flipped(src.as_ptr(), 37, 23, flipped.as_mut_ptr(), 50, 40, 1, 1, RESIZE_ALGO_LANCZOS);
+        assert_eq!(code, 0);
+    }
+    set_intermediate_precision(RESIZE_PRECISION_F32);
+    assert_eq!(get_last_completed_row(), dh as u32 - 1);
+    let mirrored: Vec<u8> = plain
+        .chunks_exact(dw * 4)
+        .rev()
+        .flat_map(|row| row.chunks_exact(4).rev().flatten())
+        .copied()
+        .collect();
+    assert_eq!(flipped, mirrored);
+}
+
+#[test]
+fn warp_samples_source_at_mapped_coordinates() {
+    let src = img(40, 30, |x, y| [(x * 6) as u8, (y * 8) as u8, ((x + y) * 3) as u8, 255]);
+    let (dw, dh) = (20u32, 15u32);
+    let map_of = |f: &dyn Fn(u32, u32) -> (f32, f32)| -> Vec<f32> {
+        (0..dh)
+            .flat_map(|y| (0..dw).map(move |x| (x, y)))
+            .flat_map(|(x, y)| <[f32; 2]>::from(f(x, y)))
+            .collect()
+    };
+
+    // The regular resize mapping reproduces the resize
+    let identity = map_of(&|x, y| ((x as f32 + 0.5) * 2.0 - 0.5, (y as f32 + 0.5) * 2.0 - 0.5));
+    for algo in [RESIZE_ALGO_NEAREST, RESIZE_ALGO_BILINEAR] {
+        let mut warped = vec![0u8; (dw * dh * 4) as usize];
+        let mut resized = warped.clone();
+        unsafe {
+            let code = resize_rgba_warp(
+                src.as_ptr(),
+                40,
+                30,
+                warped.as_mut_ptr(),
+                dw,
+                dh,
+                identity.as_ptr(),
+                algo,
+            );
+            assert_eq!(code, 0);
+            assert_eq!(
+                resize_rgba_row_order(src.as_ptr(), 40, 30, resized.as_mut_ptr(), dw, dh, algo, 0),
+                0
+            );
+        }
+        assert!(max_diff(&warped, &resized) <= 1, "algo {algo}");
+    }
+
+    // Shifted 3 source pixels right: column x samples source column x - 3, left edge transparent
+    let shift = map_of(&|x, y| (x as f32 - 3.0, y as f32));
+    let mut warped = vec![0u8; (dw * dh * 4) as usize];
+    unsafe {
+        assert_eq!(
+            resize_rgba_warp(src.as_ptr(), 40, 30, warped.as_mut_ptr(), dw, dh, shift.as_ptr(), 0),
+            0
+        )
+    };
+    for y in 0..dh {
+        for x in 0..dw {
+            let o = ((y * dw + x) * 4) as usize;
+            let expected = if x < 3 {
+                &[0u8; 4][..]
+            } else {
+                &src[((y * 40 + x - 3) * 4) as usize..][..4]
+            };
+            assert_eq!(&warped[o..o + 4], expected, "({x},{y})");
+        }
+    }
+
+    // Non-finite and far-away entries are transparent, Lanczos included
+    let bad = map_of(&|x, _| if x % 2 == 0 { (f32::NAN, 1.0) } else { (1e30, -1e30) });
+    warped.fill(7);
+    unsafe {
+        assert_eq!(
+            resize_rgba_warp(src.as_ptr(), 40, 30, warped.as_mut_ptr(), dw, dh, bad.as_ptr(), 2),
+            0
+        )
+    };
+    assert!(warped.iter().all(|&v| v == 0));
+}
+
+#[test]
+fn warp_validates_the_map_pointer() {
+    let src = img(40, 30, |x, y| [x as u8, y as u8, 0, 255]);
+    let map = vec![0f32; 20 * 15 * 2];
+    let mut dst = vec![0u8; 20 * 15 * 4];
+    let (sp, dp) = (src.as_ptr(), dst.as_mut_ptr());
+    unsafe {
+        assert_eq!(
+            resize_rgba_warp(sp, 40, 30, dp, 20, 15, std::ptr::null(), 1),
+            RESIZE_ERR_NULL_PTR
+        );
+        let misaligned = (map.as_ptr() as *const u8).add(1) as *const f32;
+        assert_eq!(
+            resize_rgba_warp(sp, 40, 30, dp, 20, 15, misaligned, 1),
+            RESIZE_ERR_ALIGNMENT
+        );
+        assert_eq!(
+            resize_rgba_warp(sp, 40, 30, dp, 20, 15, dp as *const f32, 1),
+            RESIZE_ERR_OVERLAP
+        );
+    }
+}
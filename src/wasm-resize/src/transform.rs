@@ -0,0 +1,443 @@
+//! Geometric transforms combined with resampling
+//! Rotations (exact quarter turns and arbitrary angles), transposes, flips and
+//! coordinate-map warps sampled directly from the source, without materializing the
+//! transformed image
+
+#[cfg(not(any(target_arch = "wasm32", test)))]
+compile_error!("This module only supports wasm32 target");
+
+use crate::separable::algorithm_taps;
+use crate::{
+    cancel_due, intermediate_pass, lanczos_kernel, mark_row_completed, mark_rows_completed,
+    resize_slices, resolve_algorithm, set_last_error, validate_intermediate, validate_params,
+    RESIZE_ALGO_BILINEAR, RESIZE_ALGO_LANCZOS, RESIZE_ALGO_NEAREST, RESIZE_ERR_ALIGNMENT,
+    RESIZE_ERR_CANCELLED, RESIZE_ERR_INVALID_PARAM, RESIZE_ERR_INVALID_SIZE, RESIZE_ERR_NULL_PTR,
+    RESIZE_ERR_OVERLAP, RESIZE_OK,
+};
+
+/// Rotate an image 90 degrees clockwise (exact pixel permutation, no resampling)
+/// The destination is `src_h` x `src_w`: dst(x, y) = src(y, src_h - 1 - x)
+/// Returns error code: 0 = success, non-zero = error
+///
+/// # Safety
+/// src_ptr must point to a valid RGBA buffer of src_w x src_h pixels and dst_ptr
+/// to one of src_h x src_w pixels
+#[no_mangle]
+pub unsafe extern "C" fn rotate_rgba_90(src_ptr: *const u8, src_w: u32, src_h: u32, dst_ptr: *mut u8) -> i32 {
+    let (src_size, dst_size) = match validate_params(src_ptr, src_w, src_h, dst_ptr, src_h, src_w) {
+        Ok(sizes) => sizes,
+        Err(code) => return code,
+    };
+
+    let src = std::slice::from_raw_parts(src_ptr, src_size);
+    let dst = std::slice::from_raw_parts_mut(dst_ptr, dst_size);
+    let dst_w = src_h as usize;
+
+    for (y, dst_row) in dst.chunks_exact_mut(dst_w * 4).enumerate() {
+        for (x, out) in dst_row.chunks_exact_mut(4).enumerate() {
+            let s = ((src_h as usize - 1 - x) * src_w as usize + y) * 4;
+            out.copy_from_slice(&src[s..s + 4]);
+        }
+        mark_row_completed(y as u32);
+        if cancel_due(y as u32, src_w) {
+            return RESIZE_ERR_CANCELLED;
+        }
+    }
+
+    RESIZE_OK
+}
+
+/// Premultiplied accumulator for one output pixel
+/// Samples outside the source contribute transparent black, so edges fade out
+/// smoothly instead of dragging dark fringes into the color channels
+#[derive(Default)]
+struct PremulAcc {
+    rgb: [f32; 3],
+    alpha: f32,
+}
+
+impl PremulAcc {
+    #[inline(always)]
+    fn add(&mut self, p: &[u8], w: f32) {
+        let wa = w * p[3] as f32;
+        self.rgb[0] += p[0] as f32 * wa;
+        self.rgb[1] += p[1] as f32 * wa;
+        self.rgb[2] += p[2] as f32 * wa;
+        self.alpha += wa;
+    }
+
+    /// Un-premultiply into an RGBA pixel; `weight_sum` is the total kernel weight
+    #[inline(always)]
+    fn store(&self, weight_sum: f32, out: &mut [u8]) {
+        if self.alpha <= 1e-6 || weight_sum.abs() <= 1e-6 {
+            out.fill(0);
+            return;
+        }
+        for (o, &c) in out.iter_mut().zip(&self.rgb) {
+            *o = (c / self.alpha + 0.5).clamp(0.0, 255.0) as u8;
+        }
+        out[3] = (self.alpha / weight_sum + 0.5).clamp(0.0, 255.0) as u8;
+    }
+}
+
+/// Resize and rotate by an arbitrary angle in one resampling pass
+/// The source is stretched onto the destination size and rotated `angle_rad` clockwise
+/// (screen orientation) about the image center. Each destination pixel is mapped back
+/// through the inverse transform and sampled with `algorithm` (nearest, bilinear or a
+/// 6x6 Lanczos-3 window; AUTO picks as for resize_rgba_scratch). Regions that map
+/// outside the source are transparent. The sampling kernel is not widened, so strong
+/// downscales should be resized first
+/// Returns error code: 0 = success, non-zero = error
+///
+/// # Safety
+/// src_ptr/dst_ptr must point to valid RGBA buffers of the given dimensions
+#[no_mangle]
+pub unsafe extern "C" fn resize_rotate_rgba(
+    src_ptr: *const u8,
+    src_w: u32,
+    src_h: u32,
+    dst_ptr: *mut u8,
+    dst_w: u32,
+    dst_h: u32,
+    angle_rad: f32,
+    algorithm: u32,
+) -> i32 {
+    let (src_size, dst_size) = match validate_params(src_ptr, src_w, src_h, dst_ptr, dst_w, dst_h) {
+        Ok(sizes) => sizes,
+        Err(code) => return code,
+    };
+
+    if !angle_rad.is_finite() {
+        set_last_error(RESIZE_ERR_INVALID_PARAM);
+        return RESIZE_ERR_INVALID_PARAM;
+    }
+
+    let algorithm = match resolve_algorithm(algorithm, src_w, src_h, dst_w, dst_h) {
+        Ok(a) => a,
+        Err(code) => return code,
+    };
+
+    if src_w > i32::MAX as u32 || src_h > i32::MAX as u32 {
+        set_last_error(RESIZE_ERR_INVALID_SIZE);
+        return RESIZE_ERR_INVALID_SIZE;
+    }
+
+    let src = std::slice::from_raw_parts(src_ptr, src_size);
+    let dst = std::slice::from_raw_parts_mut(dst_ptr, dst_size);
+
+    // Inverse map: p = S^-1 * R(-angle) * (q - c_dst) + c_src, in continuous pixel coordinates
+    let (sin, cos) = angle_rad.sin_cos();
+    let inv_scale_x = src_w as f32 / dst_w as f32;
+    let inv_scale_y = src_h as f32 / dst_h as f32;
+    let (dst_cx, dst_cy) = (dst_w as f32 * 0.5, dst_h as f32 * 0.5);
+    let (src_cx, src_cy) = (src_w as f32 * 0.5, src_h as f32 * 0.5);
+
+    for (dy, dst_row) in dst.chunks_exact_mut(dst_w as usize * 4).enumerate() {
+        let v = dy as f32 + 0.5 - dst_cy;
+        for (dx, out) in dst_row.chunks_exact_mut(4).enumerate() {
+            let u = dx as f32 + 0.5 - dst_cx;
+            // Source sample position relative to pixel centers
+            let sx = (cos * u + sin * v) * inv_scale_x + src_cx - 0.5;
+            let sy = (cos * v - sin * u) * inv_scale_y + src_cy - 0.5;
+            sample_point(src, src_w, src_h, sx, sy, algorithm, out);
+        }
+        mark_row_completed(dy as u32);
+        if cancel_due(dy as u32, dst_h) {
+            return RESIZE_ERR_CANCELLED;
+        }
+    }
+
+    RESIZE_OK
+}
+
+/// Sample the source at (`sx`, `sy`) in pixel-center coordinates ((0, 0) is the center
+/// of the top-left pixel) with a concrete `algorithm`: nearest, bilinear or a 6x6
+/// Lanczos-3 window, accumulated premultiplied. Taps outside the source are
+/// transparent. The caller keeps the position within a few pixels of the source
+#[inline(always)]
+fn sample_point(src: &[u8], src_w: u32, src_h: u32, sx: f32, sy: f32, algorithm: u32, out: &mut [u8]) {
+    let pixel = |x: i32, y: i32| -> Option<&[u8]> {
+        if x < 0 || y < 0 || x >= src_w as i32 || y >= src_h as i32 {
+            return None;
+        }
+        let s = (y as usize * src_w as usize + x as usize) * 4;
+        Some(&src[s..s + 4])
+    };
+
+    match algorithm {
+        RESIZE_ALGO_NEAREST => match pixel(sx.round() as i32, sy.round() as i32) {
+            Some(p) => out.copy_from_slice(p),
+            None => out.fill(0),
+        },
+        RESIZE_ALGO_BILINEAR => {
+            let (x0, y0) = (sx.floor(), sy.floor());
+            let (fx, fy) = (sx - x0, sy - y0);
+            let (x0, y0) = (x0 as i32, y0 as i32);
+            let mut acc = PremulAcc::default();
+            for (oy, wy) in [(0, 1.0 - fy), (1, fy)] {
+                for (ox, wx) in [(0, 1.0 - fx), (1, fx)] {
+                    if let Some(p) = pixel(x0 + ox, y0 + oy) {
+                        acc.add(p, wx * wy);
+                    }
+                }
+            }
+            acc.store(1.0, out);
+        }
+        _ => {
+            let (cx, cy) = (sx.floor() as i32, sy.floor() as i32);
+            let mut acc = PremulAcc::default();
+            let mut weight_sum = 0.0f32;
+            for ty in cy - 2..=cy + 3 {
+                let wy = lanczos_kernel(ty as f32 - sy, 3.0);
+                for tx in cx - 2..=cx + 3 {
+                    let w = wy * lanczos_kernel(tx as f32 - sx, 3.0);
+                    weight_sum += w;
+                    if let Some(p) = pixel(tx, ty) {
+                        acc.add(p, w);
+                    }
+                }
+            }
+            acc.store(weight_sum, out);
+        }
+    }
+}
+
+/// Resample the source through a per-pixel coordinate map (lens correction, warps)
+/// `map_ptr` holds dst_w * dst_h (x, y) pairs, row-major, giving the source position
+/// each destination pixel samples, in source pixel-center coordinates: (0, 0) is the
+/// center of the top-left pixel, so `((dx + 0.5) * src_w / dst_w - 0.5, ...)` is a
+/// plain resize. Positions are sampled with `algorithm` as in resize_rotate_rgba;
+/// taps outside the source, and non-finite map entries, are transparent
+/// Returns error code: 0 = success, non-zero = error
+///
+/// # Safety
+/// src_ptr/dst_ptr must point to valid RGBA buffers of the given dimensions and map_ptr
+/// to dst_w * dst_h * 2 readable, 4-byte aligned f32 values not overlapping dst
+#[no_mangle]
+pub unsafe extern "C" fn resize_rgba_warp(
+    src_ptr: *const u8,
+    src_w: u32,
+    src_h: u32,
+    dst_ptr: *mut u8,
+    dst_w: u32,
+    dst_h: u32,
+    map_ptr: *const f32,
+    algorithm: u32,
+) -> i32 {
+    let (src_size, dst_size) = match validate_params(src_ptr, src_w, src_h, dst_ptr, dst_w, dst_h) {
+        Ok(sizes) => sizes,
+        Err(code) => return code,
+    };
+
+    if map_ptr.is_null() {
+        set_last_error(RESIZE_ERR_NULL_PTR);
+        return RESIZE_ERR_NULL_PTR;
+    }
+
+    if !(map_ptr as usize).is_multiple_of(std::mem::align_of::<f32>()) {
+        set_last_error(RESIZE_ERR_ALIGNMENT);
+        return RESIZE_ERR_ALIGNMENT;
+    }
+
+    // Two f32 (8 bytes) per destination pixel
+    let map_len = dst_size / 2;
+    let map_start = map_ptr as usize;
+    let map_end = map_start.saturating_add(dst_size * 2);
+    if (dst_ptr as usize) < map_end && map_start < (dst_ptr as usize).saturating_add(dst_size) {
+        set_last_error(RESIZE_ERR_OVERLAP);
+        return RESIZE_ERR_OVERLAP;
+    }
+
+    let algorithm = match resolve_algorithm(algorithm, src_w, src_h, dst_w, dst_h) {
+        Ok(a) => a,
+        Err(code) => return code,
+    };
+
+    let src = std::slice::from_raw_parts(src_ptr, src_size);
+    let dst = std::slice::from_raw_parts_mut(dst_ptr, dst_size);
+    let map = std::slice::from_raw_parts(map_ptr, map_len);
+
+    // Beyond this margin every tap of every kernel misses the source
+    let reach = |v: f32, size: u32| v > -4.0 && v < size as f32 + 3.0;
+    let rows = dst.chunks_exact_mut(dst_w as usize * 4).zip(map.chunks_exact(dst_w as usize * 2));
+    for (y, (dst_row, map_row)) in rows.enumerate() {
+        for (out, pos) in dst_row.chunks_exact_mut(4).zip(map_row.chunks_exact(2)) {
+            let (sx, sy) = (pos[0], pos[1]);
+            if reach(sx, src_w) && reach(sy, src_h) {
+                sample_point(src, src_w, src_h, sx, sy, algorithm, out);
+            } else {
+                out.fill(0);
+            }
+        }
+        mark_row_completed(y as u32);
+        if cancel_due(y as u32, dst_h) {
+            return RESIZE_ERR_CANCELLED;
+        }
+    }
+
+    RESIZE_OK
+}
+
+/// Weighted sum of 4-channel samples; with `anti_ring` the result is clamped to the
+/// range of the contributing samples, as the Lanczos path does in each pass
+#[inline(always)]
+fn accumulate<'a>(samples: impl Iterator<Item = (&'a [f32], f32)>, anti_ring: bool) -> [f32; 4] {
+    let mut acc = [0.0f32; 4];
+    let mut lo = [f32::MAX; 4];
+    let mut hi = [f32::MIN; 4];
+    for (p, w) in samples {
+        for c in 0..4 {
+            acc[c] += p[c] * w;
+            lo[c] = lo[c].min(p[c]);
+            hi[c] = hi[c].max(p[c]);
+        }
+    }
+    if anti_ring {
+        for c in 0..4 {
+            acc[c] = acc[c].clamp(lo[c], hi[c]);
+        }
+    }
+    acc
+}
+
+/// Transpose and resize in one pass
+/// The source is treated as transposed (source column `c` becomes row `c` of a
+/// `src_h` x `src_w` image) and resized to `dst_w` x `dst_h` with `algorithm`, matching
+/// a transpose followed by the regular resize. Pass 1 runs along contiguous source rows
+/// (they become destination columns) and Pass 2 along the intermediate rows, so neither
+/// pass walks the source column-wise
+/// Returns error code: 0 = success, non-zero = error
+///
+/// # Safety
+/// src_ptr/dst_ptr must point to valid RGBA buffers of the given dimensions
+#[no_mangle]
+pub unsafe extern "C" fn resize_transpose_rgba(
+    src_ptr: *const u8,
+    src_w: u32,
+    src_h: u32,
+    dst_ptr: *mut u8,
+    dst_w: u32,
+    dst_h: u32,
+    algorithm: u32,
+) -> i32 {
+    let (src_size, dst_size) = match validate_params(src_ptr, src_w, src_h, dst_ptr, dst_w, dst_h) {
+        Ok(sizes) => sizes,
+        Err(code) => return code,
+    };
+
+    // Transposed source: src_h wide, src_w tall
+    let algorithm = match resolve_algorithm(algorithm, src_h, src_w, dst_w, dst_h) {
+        Ok(a) => a,
+        Err(code) => return code,
+    };
+
+    if let Err(code) = validate_intermediate(dst_h, src_h) {
+        return code;
+    }
+
+    let src = std::slice::from_raw_parts(src_ptr, src_size);
+    let dst = std::slice::from_raw_parts_mut(dst_ptr, dst_size);
+
+    // Destination x walks source rows, destination y walks source columns
+    let x_taps = algorithm_taps(src_h, dst_w, algorithm, 0.0);
+    let y_taps = algorithm_taps(src_w, dst_h, algorithm, 0.0);
+    let anti_ring = algorithm == RESIZE_ALGO_LANCZOS;
+
+    let src_row_len = src_w as usize * 4;
+    let temp_row_len = src_h as usize * 4;
+    let src_f: Vec<f32> = src.iter().map(|&v| v as f32).collect();
+
+    // ==================== Pass 1: along source rows ====================
+    // temp[y][u] = resampled source row u at destination row y
+    let mut temp = vec![0.0f32; dst_h as usize * temp_row_len];
+    for (u, row) in src_f.chunks_exact(src_row_len).enumerate() {
+        for y in 0..dst_h as usize {
+            let acc = accumulate(y_taps.taps(y).map(|(v, w)| (&row[v * 4..v * 4 + 4], w)), anti_ring);
+            temp[y * temp_row_len + u * 4..y * temp_row_len + u * 4 + 4].copy_from_slice(&acc);
+        }
+        if cancel_due(u as u32, src_h) {
+            return RESIZE_ERR_CANCELLED;
+        }
+    }
+
+    // ==================== Pass 2: along intermediate rows ====================
+    let dst_rows = dst.chunks_exact_mut(dst_w as usize * 4);
+    for (y, (temp_row, dst_row)) in temp.chunks_exact(temp_row_len).zip(dst_rows).enumerate() {
+        for (x, out) in dst_row.chunks_exact_mut(4).enumerate() {
+            let acc = accumulate(x_taps.taps(x).map(|(u, w)| (&temp_row[u * 4..u * 4 + 4], w)), anti_ring);
+            for (o, v) in out.iter_mut().zip(acc) {
+                *o = (v + 0.5).clamp(0.0, 255.0) as u8;
+            }
+        }
+        mark_row_completed(y as u32);
+        if cancel_due(y as u32, dst_h) {
+            return RESIZE_ERR_CANCELLED;
+        }
+    }
+
+    RESIZE_OK
+}
+
+/// Resize and mirror the image horizontally and/or vertically
+/// Every flag combination runs the regular resize with `algorithm` (at the current
+/// intermediate precision) and then mirrors the destination in place, so the result
+/// equals a regular resize followed by the flips; with both flags 0 it is the regular
+/// resize
+/// Returns error code: 0 = success, non-zero = error
+///
+/// # Safety
+/// src_ptr/dst_ptr must point to valid RGBA buffers of the given dimensions
+#[no_mangle]
+pub unsafe extern "C" fn resize_rgba_flipped(
+    src_ptr: *const u8,
+    src_w: u32,
+    src_h: u32,
+    dst_ptr: *mut u8,
+    dst_w: u32,
+    dst_h: u32,
+    flip_h: u32,
+    flip_v: u32,
+    algorithm: u32,
+) -> i32 {
+    let (src_size, dst_size) = match validate_params(src_ptr, src_w, src_h, dst_ptr, dst_w, dst_h) {
+        Ok(sizes) => sizes,
+        Err(code) => return code,
+    };
+
+    if flip_h > 1 || flip_v > 1 {
+        set_last_error(RESIZE_ERR_INVALID_PARAM);
+        return RESIZE_ERR_INVALID_PARAM;
+    }
+
+    let src = std::slice::from_raw_parts(src_ptr, src_size);
+    let dst = std::slice::from_raw_parts_mut(dst_ptr, dst_size);
+    if flip_h == 0 && flip_v == 0 {
+        return resize_slices(src, src_w, src_h, dst, dst_w, dst_h, algorithm);
+    }
+
+    // Rows only hold their final value once mirrored, so the resize reports none of them
+    let code = intermediate_pass(|| resize_slices(src, src_w, src_h, dst, dst_w, dst_h, algorithm));
+    if code != RESIZE_OK {
+        return code;
+    }
+
+    let (w, row_bytes) = (dst_w as usize, dst_w as usize * 4);
+    if flip_h == 1 {
+        for row in dst.chunks_exact_mut(row_bytes) {
+            for x in 0..w / 2 {
+                let (left, right) = row.split_at_mut((w - 1 - x) * 4);
+                left[x * 4..x * 4 + 4].swap_with_slice(&mut right[..4]);
+            }
+        }
+    }
+    if flip_v == 1 {
+        let h = dst_h as usize;
+        for y in 0..h / 2 {
+            let (top, bottom) = dst.split_at_mut((h - 1 - y) * row_bytes);
+            top[y * row_bytes..(y + 1) * row_bytes].swap_with_slice(&mut bottom[..row_bytes]);
+        }
+    }
+
+    mark_rows_completed(dst_h);
+    RESIZE_OK
+}
@@ -0,0 +1,8 @@
+@echo off
+REM Run the unit tests on the host (Windows)
+REM The crate builds for wasm32 by default (.cargo\config.toml), so the tests are compiled
+REM for the host target instead; extra arguments are passed on to cargo test
+
+for /f "tokens=2" %%i in ('rustc -vV ^| findstr /b "host:"') do set HOST_TARGET=%%i
+
+cargo test --target %HOST_TARGET% %*
@@ -0,0 +1,10 @@
+#!/bin/bash
+# Run the unit tests on the host
+# The crate builds for wasm32 by default (.cargo/config.toml), so the tests are compiled
+# for the host target instead; extra arguments are passed on to `cargo test`
+
+set -e
+
+HOST_TARGET=$(rustc -vV | sed -n 's/^host: //p')
+
+cargo test --target "$HOST_TARGET" "$@"